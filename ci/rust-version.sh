@@ -13,8 +13,8 @@
 #   $ source ci/rust-version.sh
 #
 
-stable_version=1.36.0
-nightly_version=2019-07-19
+stable_version=1.39.0
+nightly_version=2019-11-07
 
 export rust_stable="$stable_version"
 export rust_stable_docker_image=solanalabs/rust:"$stable_version"
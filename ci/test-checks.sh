@@ -17,6 +17,8 @@ _ cargo +"$rust_stable" fmt --all -- --check
 _ cargo +"$rust_stable" clippy --version
 _ cargo +"$rust_stable" clippy --all --exclude solana-sdk-c -- --deny=warnings
 _ cargo +"$rust_stable" clippy --manifest-path sdk-c/Cargo.toml -- --deny=warnings
+# The kvstore Blocktree backend is only built with its feature
+_ cargo +"$rust_stable" clippy --manifest-path core/Cargo.toml --features=kvstore -- --deny=warnings
 
 _ cargo +"$rust_stable" audit --version
 _ cargo +"$rust_stable" audit
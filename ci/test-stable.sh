@@ -34,6 +34,12 @@ test-stable)
 
   _ cargo +"$rust_stable" build --all ${V:+--verbose}
   _ cargo +"$rust_stable" test --all ${V:+--verbose} -- --nocapture
+
+  # Run the blocktree tests against the kvstore backend as well, along with the
+  # kvstore's own tests
+  _ cargo +"$rust_stable" test --manifest-path=kvstore/Cargo.toml ${V:+--verbose} -- --nocapture
+  _ cargo +"$rust_stable" test --manifest-path=core/Cargo.toml ${V:+--verbose} \
+    --features=kvstore blocktree -- --nocapture
   ;;
 test-stable-perf)
   echo "Executing $testName"
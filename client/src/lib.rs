@@ -2,6 +2,7 @@ pub mod client_error;
 mod generic_rpc_client_request;
 pub mod mock_rpc_client_request;
 pub mod perf_utils;
+pub mod quic_client;
 pub mod rpc_client;
 pub mod rpc_client_request;
 pub mod rpc_request;
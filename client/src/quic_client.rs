@@ -0,0 +1,131 @@
+//! The `quic_client` module sends transactions to the QUIC port of a TPU, one
+//! unidirectional stream per transaction.  A connection to each TPU is opened on first use
+//! and reused until it fails.  The TPU's QUIC address is its `tpu_quic` in gossip.
+
+use futures::TryFutureExt;
+use log::*;
+use quinn::{ClientConfig, ClientConfigBuilder, Connection, Endpoint, NewConnection};
+use std::collections::HashMap;
+use std::io;
+use std::net::{SocketAddr, UdpSocket};
+use std::sync::{Arc, Mutex};
+use tokio::runtime::{self, Runtime};
+
+/// The name the TPU's self-signed certificate is issued to
+pub const QUIC_SERVER_NAME: &str = "solana-tpu";
+
+fn to_io_error<E: std::fmt::Display>(e: E) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, e.to_string())
+}
+
+// A TPU's certificate is self-signed and says nothing about which validator it belongs to,
+// so there is nothing to verify.  QUIC is used for its flow control and address validation,
+// transactions carry their own signatures.
+struct SkipServerVerification;
+
+impl rustls::ServerCertVerifier for SkipServerVerification {
+    fn verify_server_cert(
+        &self,
+        _roots: &rustls::RootCertStore,
+        _presented_certs: &[rustls::Certificate],
+        _dns_name: webpki::DNSNameRef,
+        _ocsp_response: &[u8],
+    ) -> Result<rustls::ServerCertVerified, rustls::TLSError> {
+        Ok(rustls::ServerCertVerified::assertion())
+    }
+}
+
+fn new_client_config() -> ClientConfig {
+    let mut config = ClientConfigBuilder::default().build();
+    Arc::get_mut(&mut config.crypto)
+        .unwrap()
+        .dangerous()
+        .set_certificate_verifier(Arc::new(SkipServerVerification));
+    config
+}
+
+type Connections = Arc<Mutex<HashMap<SocketAddr, Connection>>>;
+
+pub struct QuicClient {
+    // Drives the endpoint and its connections between sends
+    runtime: Runtime,
+    endpoint: Endpoint,
+    connections: Connections,
+}
+
+impl QuicClient {
+    /// Create a QuicClient that sends from `socket`
+    pub fn new(socket: UdpSocket) -> io::Result<Self> {
+        let runtime = runtime::Builder::new()
+            .threaded_scheduler()
+            .core_threads(1)
+            .enable_all()
+            .thread_name("solana-quic-client")
+            .build()?;
+        let mut builder = Endpoint::builder();
+        builder.default_client_config(new_client_config());
+        let (driver, endpoint, _incoming) = runtime
+            .enter(|| builder.with_socket(socket))
+            .map_err(to_io_error)?;
+        runtime.spawn(driver.unwrap_or_else(|e| warn!("QUIC client endpoint failed: {}", e)));
+        Ok(Self {
+            runtime,
+            endpoint,
+            connections: Connections::default(),
+        })
+    }
+
+    async fn connect(
+        endpoint: &Endpoint,
+        connections: &Connections,
+        addr: SocketAddr,
+    ) -> io::Result<Connection> {
+        let connection = connections.lock().unwrap().get(&addr).cloned();
+        if let Some(connection) = connection {
+            return Ok(connection);
+        }
+        let NewConnection {
+            driver, connection, ..
+        } = endpoint
+            .connect(&addr, QUIC_SERVER_NAME)
+            .map_err(to_io_error)?
+            .await
+            .map_err(to_io_error)?;
+        let driver_connections = connections.clone();
+        tokio::spawn(driver.unwrap_or_else(move |e| {
+            debug!("QUIC connection to {} closed: {}", addr, e);
+            driver_connections.lock().unwrap().remove(&addr);
+        }));
+        connections.lock().unwrap().insert(addr, connection.clone());
+        Ok(connection)
+    }
+
+    async fn send_stream(
+        endpoint: &Endpoint,
+        connections: &Connections,
+        data: Vec<u8>,
+        addr: SocketAddr,
+    ) -> io::Result<()> {
+        let connection = Self::connect(endpoint, connections, addr).await?;
+        let mut stream = connection.open_uni().await.map_err(to_io_error)?;
+        stream.write_all(&data).await.map_err(to_io_error)?;
+        stream.finish().await.map_err(to_io_error)
+    }
+
+    /// Send `data` to the QUIC port at `addr` on a new stream, blocking until the stream is
+    /// finished.  Blocks longer while the connection already has as many streams open as
+    /// the TPU allows.
+    pub fn send(&self, data: Vec<u8>, addr: &SocketAddr) -> io::Result<()> {
+        let endpoint = self.endpoint.clone();
+        let connections = self.connections.clone();
+        let addr = *addr;
+        let send = self.runtime.spawn(async move {
+            let result = Self::send_stream(&endpoint, &connections, data, addr).await;
+            if result.is_err() {
+                connections.lock().unwrap().remove(&addr);
+            }
+            result
+        });
+        futures::executor::block_on(send).map_err(to_io_error)?
+    }
+}
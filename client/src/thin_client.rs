@@ -3,6 +3,7 @@
 //! messages to the network directly. The binary encoding of its messages are
 //! unstable and may change in future releases.
 
+use crate::quic_client::QuicClient;
 use crate::rpc_client::RpcClient;
 use bincode::{serialize_into, serialized_size};
 use log::*;
@@ -110,6 +111,8 @@ pub struct ThinClient {
     transactions_addrs: Vec<SocketAddr>,
     rpc_clients: Vec<RpcClient>,
     optimizer: ClientOptimizer,
+    // The client and the TPU's QUIC address to send transactions to, if sending over QUIC
+    quic_client: Option<(QuicClient, SocketAddr)>,
 }
 
 impl ThinClient {
@@ -137,6 +140,21 @@ impl ThinClient {
         Self::new_from_client(transactions_addr, transactions_socket, rpc_client)
     }
 
+    /// Create a new ThinClient that will interface with the Rpc at `rpc_addr` using TCP
+    /// and send transactions to the Tpu's QUIC address `quic_addr` over `transactions_socket`.
+    /// `transactions_addr` is the Tpu's UDP address.
+    pub fn new_with_quic(
+        rpc_addr: SocketAddr,
+        transactions_addr: SocketAddr,
+        quic_addr: SocketAddr,
+        transactions_socket: UdpSocket,
+    ) -> io::Result<Self> {
+        let quic_client = QuicClient::new(transactions_socket.try_clone()?)?;
+        let mut thin_client = Self::new(rpc_addr, transactions_addr, transactions_socket);
+        thin_client.quic_client = Some((quic_client, quic_addr));
+        Ok(thin_client)
+    }
+
     fn new_from_client(
         transactions_addr: SocketAddr,
         transactions_socket: UdpSocket,
@@ -147,6 +165,7 @@ impl ThinClient {
             transactions_addrs: vec![transactions_addr],
             rpc_clients: vec![rpc_client],
             optimizer: ClientOptimizer::new(0),
+            quic_client: None,
         }
     }
 
@@ -165,6 +184,7 @@ impl ThinClient {
             transactions_socket,
             rpc_clients,
             optimizer: ClientOptimizer::new(rpc_len),
+            quic_client: None,
         }
     }
 
@@ -176,6 +196,16 @@ impl ThinClient {
         &self.rpc_clients[self.optimizer.best()]
     }
 
+    fn send_transaction_data(&self, data: Vec<u8>) -> io::Result<()> {
+        match &self.quic_client {
+            Some((quic_client, quic_addr)) => quic_client.send(data, quic_addr),
+            None => self
+                .transactions_socket
+                .send_to(&data, &self.transactions_addr())
+                .map(|_| ()),
+        }
+    }
+
     /// Retry a sending a signed Transaction to the server for processing.
     pub fn retry_transfer_until_confirmed(
         &self,
@@ -217,8 +247,7 @@ impl ThinClient {
             while now.elapsed().as_secs() < wait_time as u64 {
                 if num_confirmed == 0 {
                     // Send the transaction if there has been no confirmation (e.g. the first time)
-                    self.transactions_socket
-                        .send_to(&buf[..], &self.transactions_addr())?;
+                    self.send_transaction_data(buf.clone())?;
                 }
 
                 if let Ok(confirmed_blocks) = self.poll_for_signature_confirmation(
@@ -420,8 +449,7 @@ impl AsyncClient for ThinClient {
         serialize_into(&mut wr, &transaction)
             .expect("serialize Transaction in pub fn transfer_signed");
         assert!(buf.len() < PACKET_DATA_SIZE);
-        self.transactions_socket
-            .send_to(&buf[..], &self.transactions_addr())?;
+        self.send_transaction_data(buf)?;
         Ok(transaction.signatures[0])
     }
     fn async_send_message(
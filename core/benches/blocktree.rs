@@ -186,7 +186,9 @@ fn bench_insert_data_blob_big(bench: &mut Bencher) {
     bench.iter(move || {
         for blob in shared_blobs.iter_mut() {
             let index = blob.read().unwrap().index();
-            blocktree.write_shared_blobs(vec![blob.clone()]).unwrap();
+            blocktree
+                .write_shared_blobs(vec![blob.clone()], None)
+                .unwrap();
             blob.write().unwrap().set_index(index + num_entries as u64);
         }
     });
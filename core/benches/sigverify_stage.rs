@@ -7,6 +7,7 @@ use crossbeam_channel::unbounded;
 use log::*;
 use rand::{thread_rng, Rng};
 use solana::packet::to_packets_chunked;
+use solana::packet_qos::PacketQos;
 use solana::service::Service;
 use solana::sigverify_stage::SigVerifyStage;
 use solana::test_tx::test_tx;
@@ -15,6 +16,7 @@ use solana_sdk::signature::{Keypair, KeypairUtil};
 use solana_sdk::system_transaction;
 use solana_sdk::timing::duration_as_ms;
 use std::sync::mpsc::channel;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 use test::Bencher;
 
@@ -24,7 +26,12 @@ fn bench_sigverify_stage(bencher: &mut Bencher) {
     let (packet_s, packet_r) = channel();
     let (verified_s, verified_r) = unbounded();
     let sigverify_disabled = false;
-    let stage = SigVerifyStage::new(packet_r, sigverify_disabled, verified_s);
+    let stage = SigVerifyStage::new(
+        packet_r,
+        sigverify_disabled,
+        verified_s,
+        &Arc::new(PacketQos::default()),
+    );
 
     let now = Instant::now();
     let len = 4096;
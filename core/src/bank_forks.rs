@@ -7,6 +7,7 @@ use solana_measure::measure::Measure;
 use solana_metrics::inc_new_counter_info;
 use solana_runtime::bank::Bank;
 use solana_runtime::status_cache::MAX_CACHE_ENTRIES;
+use solana_sdk::hash::Hash;
 use solana_sdk::timing;
 use std::collections::{HashMap, HashSet};
 use std::ops::Index;
@@ -19,6 +20,11 @@ pub struct SnapshotConfig {
     snapshot_path: PathBuf,
     snapshot_package_output_path: PathBuf,
     snapshot_interval_slots: usize,
+    // Snapshots taken within this many slots of the last full snapshot are incremental
+    full_snapshot_interval_slots: usize,
+    // The cluster's hash for the bank of the snapshot to be loaded, snapshots aren't loaded
+    // without it
+    expected_bank_hash: Option<Hash>,
 }
 
 impl SnapshotConfig {
@@ -26,11 +32,14 @@ impl SnapshotConfig {
         snapshot_path: PathBuf,
         snapshot_package_output_path: PathBuf,
         snapshot_interval_slots: usize,
+        full_snapshot_interval_slots: usize,
     ) -> Self {
         Self {
             snapshot_path,
             snapshot_package_output_path,
             snapshot_interval_slots,
+            full_snapshot_interval_slots,
+            expected_bank_hash: None,
         }
     }
 
@@ -45,6 +54,18 @@ impl SnapshotConfig {
     pub fn snapshot_interval_slots(&self) -> usize {
         self.snapshot_interval_slots
     }
+
+    pub fn full_snapshot_interval_slots(&self) -> usize {
+        self.full_snapshot_interval_slots
+    }
+
+    pub fn expected_bank_hash(&self) -> Option<Hash> {
+        self.expected_bank_hash
+    }
+
+    pub fn set_expected_bank_hash(&mut self, expected_bank_hash: Option<Hash>) {
+        self.expected_bank_hash = expected_bank_hash;
+    }
 }
 
 pub struct BankForks {
@@ -53,6 +74,7 @@ pub struct BankForks {
     root: u64,
     snapshot_config: Option<SnapshotConfig>,
     slots_since_snapshot: Vec<u64>,
+    last_full_snapshot_slot: Option<u64>,
     confidence: HashMap<u64, Confidence>,
 }
 
@@ -62,6 +84,8 @@ pub struct Confidence {
     epoch_stakes: u64,
     lockouts: u64,
     stake_weighted_lockouts: u128,
+    // Hash of the version of the slot voted on by a supermajority of stake in gossip
+    optimistically_confirmed_hash: Option<Hash>,
 }
 
 impl Confidence {
@@ -71,6 +95,7 @@ impl Confidence {
             epoch_stakes,
             lockouts,
             stake_weighted_lockouts: 0,
+            optimistically_confirmed_hash: None,
         }
     }
     pub fn new_with_stake_weighted(
@@ -84,8 +109,24 @@ impl Confidence {
             epoch_stakes,
             lockouts,
             stake_weighted_lockouts,
+            optimistically_confirmed_hash: None,
         }
     }
+    pub fn fork_stakes(&self) -> u64 {
+        self.fork_stakes
+    }
+    pub fn epoch_stakes(&self) -> u64 {
+        self.epoch_stakes
+    }
+    pub fn lockouts(&self) -> u64 {
+        self.lockouts
+    }
+    pub fn stake_weighted_lockouts(&self) -> u128 {
+        self.stake_weighted_lockouts
+    }
+    pub fn optimistically_confirmed_hash(&self) -> Option<Hash> {
+        self.optimistically_confirmed_hash
+    }
 }
 
 impl Index<u64> for BankForks {
@@ -106,6 +147,7 @@ impl BankForks {
             root: 0,
             snapshot_config: None,
             slots_since_snapshot: vec![bank_slot],
+            last_full_snapshot_slot: None,
             confidence: HashMap::new(),
         }
     }
@@ -139,6 +181,10 @@ impl BankForks {
         descendants
     }
 
+    pub fn banks(&self) -> &HashMap<u64, Arc<Bank>> {
+        &self.banks
+    }
+
     pub fn frozen_banks(&self) -> HashMap<u64, Arc<Bank>> {
         self.banks
             .iter()
@@ -182,6 +228,7 @@ impl BankForks {
             working_bank,
             snapshot_config: None,
             slots_since_snapshot: rooted_path,
+            last_full_snapshot_slot: None,
             confidence: HashMap::new(),
         }
     }
@@ -240,16 +287,30 @@ impl BankForks {
                 .expect("Called package_snapshot without a snapshot configuration");
             if root - self.slots_since_snapshot[0] >= config.snapshot_interval_slots as u64 {
                 let mut snapshot_time = Measure::start("total-snapshot-ms");
+                let full_snapshot_slot = self.last_full_snapshot_slot.filter(|full_slot| {
+                    root - full_slot < config.full_snapshot_interval_slots as u64
+                });
+                let tar_output_file = if full_snapshot_slot.is_some() {
+                    snapshot_utils::get_incremental_snapshot_tar_path(
+                        &config.snapshot_package_output_path,
+                    )
+                } else {
+                    snapshot_utils::get_snapshot_tar_path(&config.snapshot_package_output_path)
+                };
                 let r = self.generate_snapshot(
                     root,
                     &self.slots_since_snapshot[1..],
                     snapshot_package_sender.as_ref().unwrap(),
-                    snapshot_utils::get_snapshot_tar_path(&config.snapshot_package_output_path),
+                    tar_output_file,
+                    full_snapshot_slot,
                 );
                 if r.is_err() {
                     warn!("Error generating snapshot for bank: {}, err: {:?}", root, r);
                 } else {
                     self.slots_since_snapshot = vec![root];
+                    if full_snapshot_slot.is_none() {
+                        self.last_full_snapshot_slot = Some(root);
+                    }
                 }
 
                 // Cleanup outdated snapshots
@@ -298,6 +359,7 @@ impl BankForks {
         slots_since_snapshot: &[u64],
         snapshot_package_sender: &SnapshotPackageSender,
         tar_output_file: P,
+        full_snapshot_slot: Option<u64>,
     ) -> Result<()> {
         let config = self.snapshot_config.as_ref().unwrap();
 
@@ -314,12 +376,22 @@ impl BankForks {
         // the status cache of anything older is thrown away by the bank in
         // status_cache.prune_roots()
         let start = slot_snapshot_paths.len().saturating_sub(MAX_CACHE_ENTRIES);
-        let package = snapshot_utils::package_snapshot(
-            &bank,
-            &slot_snapshot_paths[start..],
-            tar_output_file,
-            &config.snapshot_path,
-        )?;
+        let package = if let Some(full_snapshot_slot) = full_snapshot_slot {
+            snapshot_utils::package_incremental_snapshot(
+                &bank,
+                full_snapshot_slot,
+                &slot_snapshot_paths[start..],
+                tar_output_file,
+                &config.snapshot_path,
+            )?
+        } else {
+            snapshot_utils::package_snapshot(
+                &bank,
+                &slot_snapshot_paths[start..],
+                tar_output_file,
+                &config.snapshot_path,
+            )?
+        };
 
         // Send the package to the packaging thread
         snapshot_package_sender.send(package)?;
@@ -329,10 +401,16 @@ impl BankForks {
 
     fn prune_non_root(&mut self, root: u64) {
         let descendants = self.descendants();
+        let banks = &self.banks;
+        // Slots past the root that haven't been replayed yet may still carry an optimistic
+        // confirmation observed in gossip
+        self.confidence.retain(|slot, _| {
+            slot == &root
+                || descendants[&root].contains(slot)
+                || (*slot > root && !banks.contains_key(slot))
+        });
         self.banks
             .retain(|slot, _| slot == &root || descendants[&root].contains(slot));
-        self.confidence
-            .retain(|slot, _| slot == &root || descendants[&root].contains(slot));
     }
 
     pub fn cache_fork_confidence(
@@ -359,13 +437,33 @@ impl BankForks {
                 entry.stake_weighted_lockouts = stake_weighted_lockouts;
             })
             .or_insert(Confidence {
-                fork_stakes: 0,
-                epoch_stakes: 0,
-                lockouts: 0,
                 stake_weighted_lockouts,
+                ..Confidence::default()
             });
     }
 
+    /// Record that a supermajority of stake voted for `hash` at `slot` in gossip, whether or
+    /// not the slot has been replayed locally
+    pub fn set_optimistically_confirmed(&mut self, slot: u64, hash: Hash) {
+        self.confidence
+            .entry(slot)
+            .or_insert_with(Confidence::default)
+            .optimistically_confirmed_hash = Some(hash);
+    }
+
+    /// Returns true if the local bank at `slot` is frozen with the hash that was
+    /// optimistically confirmed
+    pub fn is_optimistically_confirmed(&self, slot: u64) -> bool {
+        let confirmed_hash = self
+            .confidence
+            .get(&slot)
+            .and_then(Confidence::optimistically_confirmed_hash);
+        match (self.banks.get(&slot), confirmed_hash) {
+            (Some(bank), Some(hash)) => bank.is_frozen() && bank.hash() == hash,
+            _ => false,
+        }
+    }
+
     pub fn get_fork_confidence(&self, fork: u64) -> Option<&Confidence> {
         self.confidence.get(&fork)
     }
@@ -479,6 +577,7 @@ mod tests {
                 epoch_stakes: 12,
                 lockouts: 13,
                 stake_weighted_lockouts: 0,
+                optimistically_confirmed_hash: None,
             }
         );
         // Ensure that {fork_stakes, epoch_stakes, lockouts} and stake_weighted_lockouts
@@ -491,6 +590,7 @@ mod tests {
                 epoch_stakes: 12,
                 lockouts: 13,
                 stake_weighted_lockouts: 20,
+                optimistically_confirmed_hash: None,
             }
         );
         bank_forks.cache_fork_confidence(fork, 21, 22, 23);
@@ -504,17 +604,58 @@ mod tests {
         );
     }
 
-    fn restore_from_snapshot(old_bank_forks: &BankForks, account_paths: String) {
+    #[test]
+    fn test_bank_forks_optimistic_confirmation() {
+        let GenesisBlockInfo { genesis_block, .. } = create_genesis_block(10_000);
+        let bank = Bank::new(&genesis_block);
+        let mut bank_forks = BankForks::new(0, bank);
+        let bank1 = Bank::new_from_parent(&bank_forks[0], &Pubkey::default(), 1);
+        bank_forks.insert(bank1);
+
+        // A confirmation for a slot that hasn't been replayed is kept
+        bank_forks.set_optimistically_confirmed(2, Hash::default());
+        assert!(!bank_forks.is_optimistically_confirmed(2));
+        assert_eq!(
+            bank_forks
+                .get_fork_confidence(2)
+                .unwrap()
+                .optimistically_confirmed_hash(),
+            Some(Hash::default())
+        );
+
+        // Only the confirmed version of a frozen bank counts
+        bank_forks.set_optimistically_confirmed(1, Hash::new(&[1; 32]));
+        bank_forks[1].freeze();
+        assert!(!bank_forks.is_optimistically_confirmed(1));
+        let hash = bank_forks[1].hash();
+        bank_forks.set_optimistically_confirmed(1, hash);
+        assert!(bank_forks.is_optimistically_confirmed(1));
+
+        bank_forks.set_root(1, &None);
+        assert!(bank_forks.is_optimistically_confirmed(1));
+        assert!(bank_forks.get_fork_confidence(2).is_some());
+    }
+
+    fn restore_from_snapshot(old_bank_forks: &BankForks, account_paths: String, incremental: bool) {
         let (snapshot_path, snapshot_package_output_path) = old_bank_forks
             .snapshot_config
             .as_ref()
             .map(|c| (&c.snapshot_path, &c.snapshot_package_output_path))
             .unwrap();
 
+        let incremental_snapshot_tar =
+            snapshot_utils::get_incremental_snapshot_tar_path(snapshot_package_output_path);
+        let mut snapshot_config = old_bank_forks.snapshot_config.clone().unwrap();
+        snapshot_config.set_expected_bank_hash(Some(old_bank_forks[old_bank_forks.root()].hash()));
         let deserialized_bank = snapshot_utils::bank_from_archive(
             account_paths,
-            old_bank_forks.snapshot_config.as_ref().unwrap(),
+            &snapshot_config,
             snapshot_utils::get_snapshot_tar_path(snapshot_package_output_path),
+            if incremental {
+                Some(incremental_snapshot_tar.as_path())
+            } else {
+                None
+            },
         )
         .unwrap();
 
@@ -578,6 +719,7 @@ mod tests {
         restore_from_snapshot(
             bank_forks,
             accounts_dir.path().to_str().unwrap().to_string(),
+            false,
         );
     }
 
@@ -602,6 +744,94 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_bank_forks_incremental_snapshot() {
+        solana_logger::setup();
+        let mut snapshot_test_config = setup_snapshot_test(1);
+
+        let bank_forks = &mut snapshot_test_config.bank_forks;
+        let accounts_dir = &snapshot_test_config.accounts_dir;
+        let snapshot_config = &snapshot_test_config.snapshot_config;
+        let mint_keypair = &snapshot_test_config.genesis_block_info.mint_keypair;
+
+        // create 1 new account in each bank, take a full snapshot of bank 2 and an
+        // incremental snapshot of bank 4 on top of it
+        let (full_slot, last_slot) = (2, 4);
+        for slot in 0..last_slot {
+            let bank = Bank::new_from_parent(&bank_forks[slot], &Pubkey::default(), slot + 1);
+            let key1 = Keypair::new().pubkey();
+            let tx = system_transaction::create_user_account(
+                &mint_keypair,
+                &key1,
+                1,
+                bank.last_blockhash(),
+            );
+            assert_eq!(bank.process_transaction(&tx), Ok(()));
+            bank.freeze();
+            let bank = bank_forks.insert(bank);
+            bank_forks.set_root(bank.slot(), &None);
+            snapshot_utils::add_snapshot(&snapshot_config.snapshot_path, &bank, &[]).unwrap();
+
+            let slot_snapshot_paths =
+                snapshot_utils::get_snapshot_paths(&snapshot_config.snapshot_path);
+            if bank.slot() == full_slot {
+                let snapshot_package = snapshot_utils::package_snapshot(
+                    &bank,
+                    &slot_snapshot_paths,
+                    snapshot_utils::get_snapshot_tar_path(
+                        &snapshot_config.snapshot_package_output_path,
+                    ),
+                    &snapshot_config.snapshot_path,
+                )
+                .unwrap();
+                assert!(!snapshot_package.is_incremental());
+                SnapshotPackagerService::package_snapshots(&snapshot_package).unwrap();
+            } else if bank.slot() == last_slot {
+                let snapshot_package = snapshot_utils::package_incremental_snapshot(
+                    &bank,
+                    full_slot,
+                    &slot_snapshot_paths,
+                    snapshot_utils::get_incremental_snapshot_tar_path(
+                        &snapshot_config.snapshot_package_output_path,
+                    ),
+                    &snapshot_config.snapshot_path,
+                )
+                .unwrap();
+                assert_eq!(snapshot_package.full_snapshot_slot(), Some(full_slot));
+                SnapshotPackagerService::package_snapshots(&snapshot_package).unwrap();
+
+                // an incremental snapshot layered on another full snapshot is rejected
+                let mismatched_tar = snapshot_config
+                    .snapshot_package_output_path
+                    .join("mismatched-incremental-snapshot.tar.bz2");
+                let snapshot_package = snapshot_utils::package_incremental_snapshot(
+                    &bank,
+                    full_slot - 1,
+                    &slot_snapshot_paths,
+                    &mismatched_tar,
+                    &snapshot_config.snapshot_path,
+                )
+                .unwrap();
+                SnapshotPackagerService::package_snapshots(&snapshot_package).unwrap();
+                assert!(snapshot_utils::bank_from_archive(
+                    accounts_dir.path().to_str().unwrap().to_string(),
+                    snapshot_config,
+                    snapshot_utils::get_snapshot_tar_path(
+                        &snapshot_config.snapshot_package_output_path,
+                    ),
+                    Some(mismatched_tar.as_path()),
+                )
+                .is_err());
+            }
+        }
+
+        restore_from_snapshot(
+            bank_forks,
+            accounts_dir.path().to_str().unwrap().to_string(),
+            true,
+        );
+    }
+
     fn goto_end_of_slot(bank: &mut Bank) {
         let mut tick_hash = bank.last_blockhash();
         loop {
@@ -719,6 +949,7 @@ mod tests {
                     snapshot_config
                         .snapshot_package_output_path
                         .join(slot.to_string()),
+                    None,
                 )
                 .unwrap();
 
@@ -850,6 +1081,7 @@ mod tests {
             PathBuf::from(snapshot_dir.path()),
             PathBuf::from(snapshot_output_path.path()),
             snapshot_interval,
+            snapshot_interval,
         );
         bank_forks.set_snapshot_config(snapshot_config.clone());
         SnapshotTestConfig {
@@ -3,6 +3,7 @@
 //! access read to a persistent file-based ledger.
 use crate::entry::Entry;
 use crate::erasure::{ErasureConfig, Session};
+use crate::leader_schedule_cache::LeaderScheduleCache;
 use crate::packet::{Blob, SharedBlob, BLOB_HEADER_SIZE};
 use crate::result::{Error, Result};
 
@@ -20,7 +21,9 @@ use solana_metrics::{datapoint_error, datapoint_info};
 
 use solana_sdk::genesis_block::GenesisBlock;
 use solana_sdk::hash::Hash;
-use solana_sdk::signature::{Keypair, KeypairUtil};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, KeypairUtil, Signable};
+use solana_sdk::transaction;
 
 use std::borrow::{Borrow, Cow};
 use std::cell::RefCell;
@@ -75,6 +78,7 @@ pub type CompletedSlotsReceiver = Receiver<Vec<u64>>;
 pub enum BlocktreeError {
     BlobForIndexExists,
     InvalidBlobData(Box<bincode::ErrorKind>),
+    #[cfg(not(feature = "kvstore"))]
     RocksDb(rocksdb::Error),
     #[cfg(feature = "kvstore")]
     KvsDb(kvstore::Error),
@@ -84,15 +88,18 @@ pub enum BlocktreeError {
 // ledger window
 pub struct Blocktree {
     db: Arc<Database>,
+    blocktree_path: PathBuf,
     meta_cf: LedgerColumn<cf::SlotMeta>,
     data_cf: LedgerColumn<cf::Data>,
     dead_slots_cf: LedgerColumn<cf::DeadSlots>,
+    duplicate_slots_cf: LedgerColumn<cf::DuplicateSlots>,
     erasure_cf: LedgerColumn<cf::Coding>,
     erasure_meta_cf: LedgerColumn<cf::ErasureMeta>,
     orphans_cf: LedgerColumn<cf::Orphans>,
     index_cf: LedgerColumn<cf::Index>,
     _data_shred_cf: LedgerColumn<cf::ShredData>,
     _code_shred_cf: LedgerColumn<cf::ShredCode>,
+    transaction_status_cf: LedgerColumn<cf::TransactionStatus>,
     batch_processor: Arc<RwLock<BatchProcessor>>,
     pub new_blobs_signals: Vec<SyncSender<bool>>,
     pub completed_slots_senders: Vec<SyncSender<Vec<u64>>>,
@@ -104,6 +111,8 @@ pub const META_CF: &str = "meta";
 pub const DATA_CF: &str = "data";
 // Column family for slots that have been marked as dead
 pub const DEAD_SLOTS_CF: &str = "dead_slots";
+// Column family for proofs that a slot's leader produced more than one version of it
+pub const DUPLICATE_SLOTS_CF: &str = "duplicate_slots";
 // Column family for erasure data
 pub const ERASURE_CF: &str = "erasure";
 pub const ERASURE_META_CF: &str = "erasure_meta";
@@ -117,6 +126,8 @@ pub const INDEX_CF: &str = "index";
 pub const DATA_SHRED_CF: &str = "data_shred";
 /// Column family for Code Shreds
 pub const CODE_SHRED_CF: &str = "code_shred";
+/// Column family for the statuses of transactions in rooted slots
+pub const TRANSACTION_STATUS_CF: &str = "transaction_status";
 
 impl Blocktree {
     /// Opens a Ledger in directory, provides "infinite" window of blobs
@@ -138,6 +149,9 @@ impl Blocktree {
         // Create the dead slots column family
         let dead_slots_cf = db.column();
 
+        // Create the duplicate slots column family
+        let duplicate_slots_cf = db.column();
+
         // Create the erasure column family
         let erasure_cf = db.column();
 
@@ -152,19 +166,25 @@ impl Blocktree {
         let data_shred_cf = db.column();
         let code_shred_cf = db.column();
 
+        // Create the transaction status column family
+        let transaction_status_cf = db.column();
+
         let db = Arc::new(db);
 
         Ok(Blocktree {
             db,
+            blocktree_path,
             meta_cf,
             data_cf,
             dead_slots_cf,
+            duplicate_slots_cf,
             erasure_cf,
             erasure_meta_cf,
             orphans_cf,
             index_cf,
             _data_shred_cf: data_shred_cf,
             _code_shred_cf: code_shred_cf,
+            transaction_status_cf,
             new_blobs_signals: vec![],
             batch_processor,
             completed_slots_senders: vec![],
@@ -195,6 +215,13 @@ impl Blocktree {
         self.meta_cf.get(slot)
     }
 
+    /// Returns the number of bytes the database occupies on disk.  Space freed by
+    /// `purge_slots` is only returned once the purged slots are compacted, see
+    /// `compact_storage()`.
+    pub fn storage_size(&self) -> Result<u64> {
+        Ok(fs_extra::dir::get_size(&self.blocktree_path)?)
+    }
+
     pub fn is_full(&self, slot: u64) -> bool {
         if let Ok(meta) = self.meta_cf.get(slot) {
             if let Some(meta) = meta {
@@ -214,14 +241,14 @@ impl Blocktree {
         let mut batch_end = to_slot
             .unwrap_or(from_slot + PURGE_BATCH_SIZE)
             .min(from_slot + PURGE_BATCH_SIZE);
-        while from_slot < batch_end {
+        while from_slot <= batch_end {
             if let Ok(end) = self.run_purge_batch(from_slot, batch_end) {
                 // no more slots to iter or reached the upper bound
                 if end {
                     break;
                 } else {
                     // update the next batch bounds
-                    from_slot = batch_end;
+                    from_slot = batch_end + 1;
                     batch_end = to_slot
                         .unwrap_or(batch_end + PURGE_BATCH_SIZE)
                         .min(batch_end + PURGE_BATCH_SIZE);
@@ -230,6 +257,26 @@ impl Blocktree {
         }
     }
 
+    /// Compact every column over slots `from_slot` through `to_slot`, returning the disk space
+    /// held by slots purged from that range
+    pub fn compact_storage(&self, from_slot: Slot, to_slot: Slot) -> Result<()> {
+        self.meta_cf.compact_range(from_slot, to_slot)?;
+        self.data_cf.compact_range(from_slot, to_slot)?;
+        self.dead_slots_cf.compact_range(from_slot, to_slot)?;
+        self.duplicate_slots_cf.compact_range(from_slot, to_slot)?;
+        self.erasure_cf.compact_range(from_slot, to_slot)?;
+        self.erasure_meta_cf.compact_range(from_slot, to_slot)?;
+        self.orphans_cf.compact_range(from_slot, to_slot)?;
+        self.index_cf.compact_range(from_slot, to_slot)?;
+        self._data_shred_cf.compact_range(from_slot, to_slot)?;
+        self._code_shred_cf.compact_range(from_slot, to_slot)?;
+        self.transaction_status_cf
+            .compact_range(from_slot, to_slot)?;
+        self.db
+            .column::<cf::Root>()
+            .compact_range(from_slot, to_slot)
+    }
+
     // Returns whether or not all iterators have reached their end
     fn run_purge_batch(&self, from_slot: Slot, batch_end: Slot) -> Result<bool> {
         let mut end = true;
@@ -331,6 +378,33 @@ impl Blocktree {
                     false
                 }
             };
+            end &= match self
+                .duplicate_slots_cf
+                .delete_slot(&mut write_batch, from_slot, batch_end)
+            {
+                Ok(finished) => finished,
+                Err(e) => {
+                    error!(
+                        "Error: {:?} while deleting duplicate_slots_cf for slot {:?}",
+                        e, from_slot
+                    );
+                    false
+                }
+            };
+            end &=
+                match self
+                    .transaction_status_cf
+                    .delete_slot(&mut write_batch, from_slot, batch_end)
+                {
+                    Ok(finished) => finished,
+                    Err(e) => {
+                        error!(
+                            "Error: {:?} while deleting transaction_status_cf for slot {:?}",
+                            e, from_slot
+                        );
+                        false
+                    }
+                };
             let roots_cf = self.db.column::<cf::Root>();
             end &= match roots_cf.delete_slot(&mut write_batch, from_slot, batch_end) {
                 Ok(finished) => finished,
@@ -384,8 +458,13 @@ impl Blocktree {
         Ok(slot_iterator.take_while(move |((blob_slot, _), _)| *blob_slot == slot))
     }
 
-    /// Use this function to write data blobs to blocktree
-    pub fn write_shared_blobs<I>(&self, shared_blobs: I) -> Result<()>
+    /// Use this function to write data blobs to blocktree.  See
+    /// `insert_data_blobs_with_leader_schedule()` for `leader_schedule`
+    pub fn write_shared_blobs<I>(
+        &self,
+        shared_blobs: I,
+        leader_schedule: Option<&LeaderScheduleCache>,
+    ) -> Result<()>
     where
         I: IntoIterator,
         I::Item: Borrow<SharedBlob>,
@@ -399,7 +478,7 @@ impl Blocktree {
 
         let blobs = r_blobs.iter().map(|s| &**s);
 
-        self.insert_data_blobs(blobs)
+        self.insert_data_blobs_with_leader_schedule(blobs, leader_schedule)
     }
 
     pub fn write_blobs<I>(&self, blobs: I) -> Result<()>
@@ -464,7 +543,55 @@ impl Blocktree {
         self.write_blobs(&blobs)
     }
 
+    /// Write the entries of a single slot chained to `parent_slot`, which unlike
+    /// `write_entries` need not be the slot right before it
+    pub fn write_slot_entries<I>(
+        &self,
+        slot: u64,
+        parent_slot: u64,
+        is_full_slot: bool,
+        entries: I,
+    ) -> Result<()>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<Entry>,
+    {
+        let mut blobs: Vec<Blob> = entries
+            .into_iter()
+            .enumerate()
+            .map(|(index, entry)| {
+                let mut b = entry.borrow().to_blob();
+                b.set_index(index as u64);
+                b.set_slot(slot);
+                b.set_parent(parent_slot);
+                b
+            })
+            .collect();
+        if is_full_slot {
+            if let Some(b) = blobs.last_mut() {
+                b.set_is_last_in_slot();
+            }
+        }
+
+        self.write_blobs(&blobs)
+    }
+
     pub fn insert_data_blobs<I>(&self, new_blobs: I) -> Result<()>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<Blob>,
+    {
+        self.insert_data_blobs_with_leader_schedule(new_blobs, None)
+    }
+
+    /// Insert data blobs, recording a proof against the leader of a slot if two of them
+    /// conflict.  The leader is looked up in `leader_schedule`, no proof is recorded without
+    /// one.
+    pub fn insert_data_blobs_with_leader_schedule<I>(
+        &self,
+        new_blobs: I,
+        leader_schedule: Option<&LeaderScheduleCache>,
+    ) -> Result<()>
     where
         I: IntoIterator,
         I::Item: Borrow<Blob>,
@@ -535,6 +662,7 @@ impl Blocktree {
                 &mut index_working_set,
                 &mut prev_inserted_blob_datas,
                 &mut write_batch,
+                leader_schedule,
             )?;
         } else {
             insert_data_blob_batch(
@@ -544,6 +672,7 @@ impl Blocktree {
                 &mut index_working_set,
                 &mut prev_inserted_blob_datas,
                 &mut write_batch,
+                leader_schedule,
             )?;
         }
 
@@ -1049,6 +1178,54 @@ impl Blocktree {
         self.dead_slots_cf.put(slot, &true)
     }
 
+    /// The proof that the leader of `slot` produced more than one version of it, if one has
+    /// been found
+    pub fn get_duplicate_slot(&self, slot: u64) -> Option<DuplicateSlotProof> {
+        self.duplicate_slots_cf
+            .get(slot)
+            .expect("fetch from DuplicateSlots column family failed")
+    }
+
+    pub fn has_duplicate_slot(&self, slot: u64) -> bool {
+        self.get_duplicate_slot(slot).is_some()
+    }
+
+    /// Store a proof that the leader of `slot` produced more than one version of it, keeping
+    /// the first proof stored for the slot
+    pub fn store_duplicate_slot(&self, slot: u64, proof: &DuplicateSlotProof) -> Result<()> {
+        if !self.has_duplicate_slot(slot) {
+            self.duplicate_slots_cf.put(slot, proof)?;
+        }
+        Ok(())
+    }
+
+    /// The status of the `index`th transaction in `slot`, if one has been recorded
+    pub fn get_transaction_status(
+        &self,
+        slot: u64,
+        index: u64,
+    ) -> Result<Option<transaction::Result<()>>> {
+        self.transaction_status_cf.get((slot, index))
+    }
+
+    /// Record the status of each transaction in `slot`, in the order they appear in the slot.
+    /// Transactions without a known status are skipped
+    pub fn write_transaction_statuses(
+        &self,
+        slot: u64,
+        statuses: &[Option<transaction::Result<()>>],
+    ) -> Result<()> {
+        let mut batch_processor = self.batch_processor.write().unwrap();
+        let mut write_batch = batch_processor.batch()?;
+        for (index, status) in statuses.iter().enumerate() {
+            if let Some(status) = status {
+                write_batch.put::<cf::TransactionStatus>((slot, index as u64), status)?;
+            }
+        }
+        batch_processor.write(write_batch)?;
+        Ok(())
+    }
+
     pub fn get_orphans(&self, max: Option<usize>) -> Vec<u64> {
         let mut results = vec![];
 
@@ -1130,17 +1307,21 @@ fn insert_data_blob_batch<'a, I>(
     index_working_set: &mut HashMap<u64, Index>,
     prev_inserted_blob_datas: &mut HashMap<(u64, u64), &'a [u8]>,
     write_batch: &mut WriteBatch,
+    leader_schedule: Option<&LeaderScheduleCache>,
 ) -> Result<()>
 where
     I: IntoIterator<Item = &'a Blob>,
 {
     for blob in new_blobs.into_iter() {
+        let slot_leader = leader_schedule
+            .and_then(|leader_schedule| leader_schedule.slot_leader_at(blob.slot(), None));
         let inserted = check_insert_data_blob(
             blob,
             db,
             slot_meta_working_set,
             prev_inserted_blob_datas,
             write_batch,
+            slot_leader,
         );
 
         if inserted {
@@ -1215,13 +1396,15 @@ fn insert_data_blob<'a>(
 }
 
 /// Checks to see if the data blob passes integrity checks for insertion. Proceeds with
-/// insertion if it does.
+/// insertion if it does.  A blob that conflicts with one already inserted is recorded as
+/// a proof against `slot_leader`, the scheduled leader of the blob's slot, if known.
 fn check_insert_data_blob<'a>(
     blob: &'a Blob,
     db: &Database,
     slot_meta_working_set: &mut HashMap<u64, (Rc<RefCell<SlotMeta>>, Option<SlotMeta>)>,
     prev_inserted_blob_datas: &mut HashMap<(u64, u64), &'a [u8]>,
     write_batch: &mut WriteBatch,
+    slot_leader: Option<Pubkey>,
 ) -> bool {
     let blob_slot = blob.slot();
     let parent_slot = blob.parent();
@@ -1254,6 +1437,39 @@ fn check_insert_data_blob<'a>(
 
     let slot_meta = &mut entry.0.borrow_mut();
 
+    if let Some(existing_blob) = find_conflicting_blob(blob, db, prev_inserted_blob_datas) {
+        datapoint_error!(
+            "blocktree_error",
+            (
+                "error",
+                format!(
+                    "Leader {} produced a duplicate blob for slot {} index {}",
+                    blob.id(),
+                    blob_slot,
+                    blob.index()
+                ),
+                String
+            )
+        );
+        let has_proof = db
+            .get::<cf::DuplicateSlots>(blob_slot)
+            .map(|proof| proof.is_some())
+            .unwrap_or(false);
+        if let (false, Some(slot_leader)) = (has_proof, slot_leader) {
+            let proof = DuplicateSlotProof {
+                blob1: existing_blob,
+                blob2: blob.data[..BLOB_HEADER_SIZE + blob.size()].to_vec(),
+            };
+            // Only the scheduled leader signing both blobs makes them a proof against it
+            if proof.verify(blob_slot, &slot_leader) {
+                if let Err(e) = write_batch.put::<cf::DuplicateSlots>(blob_slot, &proof) {
+                    error!("Unable to store duplicate slot proof: {:?}", e);
+                }
+            }
+        }
+        return false;
+    }
+
     // This slot is full, skip the bogus blob
     // Check if this blob should be inserted
     if !should_insert_blob(&slot_meta, db, &prev_inserted_blob_datas, blob) {
@@ -1264,6 +1480,25 @@ fn check_insert_data_blob<'a>(
     }
 }
 
+/// Returns the blob already stored at the slot and index of `blob` if its signed contents
+/// differ from `blob`'s, which means the leader signed two versions of the slot
+fn find_conflicting_blob(
+    blob: &Blob,
+    db: &Database,
+    prev_inserted_blob_datas: &HashMap<(u64, u64), &[u8]>,
+) -> Option<Vec<u8>> {
+    let key = (blob.slot(), blob.index());
+    let existing_blob = match prev_inserted_blob_datas.get(&key) {
+        Some(bytes) => bytes.to_vec(),
+        None => db.column::<cf::Data>().get_bytes(key).ok()??,
+    };
+    if Blob::new(&existing_blob).signable_data() != blob.signable_data() {
+        Some(existing_blob)
+    } else {
+        None
+    }
+}
+
 fn should_insert_blob(
     slot: &SlotMeta,
     db: &Database,
@@ -2081,10 +2316,12 @@ pub mod tests {
     use super::*;
     use crate::entry::{create_ticks, make_tiny_test_entries, Entry, EntrySlice};
     use crate::erasure::{CodingGenerator, ErasureConfig};
+    use crate::genesis_utils::create_genesis_block_with_leader;
     use crate::packet;
     use rand::seq::SliceRandom;
     use rand::thread_rng;
     use rand::Rng;
+    use solana_runtime::bank::Bank;
     use solana_sdk::hash::Hash;
     use solana_sdk::pubkey::Pubkey;
     use std::cmp::min;
@@ -2173,6 +2410,40 @@ pub mod tests {
         Blocktree::destroy(&ledger_path).expect("Expected successful database destruction");
     }
 
+    #[test]
+    fn test_write_slot_entries() {
+        let ledger_path = get_tmp_ledger_path!();
+        {
+            let ledger = Blocktree::open(&ledger_path).unwrap();
+            let ticks = create_ticks(4, Hash::default());
+
+            // slot 3 skips over slots 1 and 2 to chain directly to slot 0
+            ledger.write_slot_entries(0, 0, true, &ticks[..2]).unwrap();
+            ledger.write_slot_entries(3, 0, true, &ticks[2..]).unwrap();
+            // slot 4 is left partial
+            ledger.write_slot_entries(4, 3, false, &ticks[..1]).unwrap();
+
+            let meta = ledger.meta(0).unwrap().unwrap();
+            assert!(meta.is_full());
+            assert_eq!(meta.next_slots, vec![3]);
+
+            let meta = ledger.meta(3).unwrap().unwrap();
+            assert!(meta.is_full());
+            assert_eq!(meta.parent_slot, 0);
+            assert_eq!(meta.next_slots, vec![4]);
+            assert_eq!(
+                &ticks[2..],
+                &ledger.get_slot_entries(3, 0, None).unwrap()[..]
+            );
+
+            let meta = ledger.meta(4).unwrap().unwrap();
+            assert!(!meta.is_full());
+            assert_eq!(meta.parent_slot, 3);
+            assert_eq!(meta.consumed, 1);
+        }
+        Blocktree::destroy(&ledger_path).expect("Expected successful database destruction");
+    }
+
     #[test]
     fn test_put_get_simple() {
         let ledger_path = get_tmp_ledger_path("test_put_get_simple");
@@ -2607,6 +2878,93 @@ pub mod tests {
         Blocktree::destroy(&blocktree_path).expect("Expected successful database destruction");
     }
 
+    #[test]
+    pub fn test_duplicate_slot() {
+        let blocktree_path = get_tmp_ledger_path("test_duplicate_slot");
+        {
+            let blocktree = Blocktree::open(&blocktree_path).unwrap();
+            let leader = Keypair::new();
+            let genesis_block_info = create_genesis_block_with_leader(100, &leader.pubkey(), 10);
+            let bank = Bank::new(&genesis_block_info.genesis_block);
+            let leader_schedule = LeaderScheduleCache::new_from_bank(&bank);
+            let make_blob_from = |keypair: &Keypair, hash: Hash| {
+                let mut blob = create_ticks(1, hash)[0].to_blob();
+                blob.set_slot(1);
+                blob.set_parent(0);
+                blob.set_index(0);
+                blob.set_id(&keypair.pubkey());
+                blob.sign(keypair);
+                blob
+            };
+            let make_blob = |hash: Hash| make_blob_from(&leader, hash);
+            let insert_blobs = |blobs: Vec<Blob>| {
+                blocktree
+                    .insert_data_blobs_with_leader_schedule(blobs, Some(&leader_schedule))
+                    .unwrap()
+            };
+            let blob1 = make_blob(Hash::default());
+            let blob2 = make_blob(Hash::new(&[1; 32]));
+
+            insert_blobs(vec![blob1.clone()]);
+            assert!(!blocktree.has_duplicate_slot(1));
+
+            // The same blob again, even forwarded, isn't a duplicate
+            let mut forwarded_blob1 = blob1.clone();
+            forwarded_blob1.set_forwarded(true);
+            insert_blobs(vec![forwarded_blob1]);
+            assert!(!blocktree.has_duplicate_slot(1));
+
+            // Without the leader schedule, a conflicting blob can't be checked against the leader
+            blocktree.insert_data_blobs(vec![blob2.clone()]).unwrap();
+            assert!(!blocktree.has_duplicate_slot(1));
+
+            // A different blob for the same index is
+            insert_blobs(vec![blob2.clone()]);
+            let proof = blocktree.get_duplicate_slot(1).unwrap();
+            assert_eq!(
+                Blob::new(&proof.blob1).signable_data(),
+                blob1.signable_data()
+            );
+            assert_eq!(
+                Blob::new(&proof.blob2).signable_data(),
+                blob2.signable_data()
+            );
+            assert!(proof.verify(1, &leader.pubkey()));
+            assert!(!proof.verify(2, &leader.pubkey()));
+            assert!(!proof.verify(1, &Pubkey::new_rand()));
+
+            // The first version is kept
+            assert_eq!(
+                blocktree.get_slot_entries(1, 0, None).unwrap(),
+                create_ticks(1, Hash::default())
+            );
+
+            blocktree.purge_slots(1, None);
+            assert!(!blocktree.has_duplicate_slot(1));
+
+            // Blobs that aren't both signed by the leader aren't a proof
+            let mut blob3 = make_blob(Hash::default());
+            blob3.set_slot(2);
+            blob3.sign(&leader);
+            let mut blob4 = make_blob(Hash::new(&[1; 32]));
+            blob4.set_slot(2);
+            insert_blobs(vec![blob3, blob4]);
+            assert!(!blocktree.has_duplicate_slot(2));
+
+            // Nor are blobs signed by a node other than the scheduled leader, whatever id they
+            // claim
+            let impostor = Keypair::new();
+            let mut blob5 = make_blob_from(&impostor, Hash::default());
+            blob5.set_slot(3);
+            blob5.sign(&impostor);
+            let mut blob6 = make_blob_from(&impostor, Hash::new(&[1; 32]));
+            blob6.set_slot(3);
+            blob6.sign(&impostor);
+            insert_blobs(vec![blob5, blob6]);
+            assert!(!blocktree.has_duplicate_slot(3));
+        }
+        Blocktree::destroy(&blocktree_path).expect("Expected successful database destruction");
+    }
     #[test]
     pub fn test_new_blobs_signal() {
         // Initialize ledger
@@ -3590,6 +3948,11 @@ pub mod tests {
                 assert!(slot > 5);
             });
 
+        // A single slot can be purged
+        blocktree.purge_slots(6, Some(6));
+        assert!(blocktree.meta(6).unwrap().is_none());
+        assert!(blocktree.meta(7).unwrap().is_some());
+
         blocktree.purge_slots(0, None);
 
         blocktree.slot_meta_iterator(0).unwrap().for_each(|(_, _)| {
@@ -3790,7 +4153,7 @@ pub mod tests {
                 let deleted_data = data_blobs[0].clone();
 
                 blocktree
-                    .write_shared_blobs(data_blobs.iter().skip(1))
+                    .write_shared_blobs(data_blobs.iter().skip(1), None)
                     .unwrap();
 
                 // This should trigger recovery of the missing data blob
@@ -4139,7 +4502,7 @@ pub mod tests {
                             loop {
                                 if rng.gen() {
                                     blocktree
-                                        .write_shared_blobs(&erasure_set.data)
+                                        .write_shared_blobs(&erasure_set.data, None)
                                         .expect("Writing data blobs must succeed");
                                     trace!(
                                         "multislot: wrote data: slot: {}, erasure_set: {}",
@@ -4169,7 +4532,7 @@ pub mod tests {
                                     );
 
                                     blocktree
-                                        .write_shared_blobs(&erasure_set.data)
+                                        .write_shared_blobs(&erasure_set.data, None)
                                         .expect("Writing data blobs must succeed");
                                     trace!(
                                         "multislot: wrote data: slot: {}, erasure_set: {}",
@@ -33,6 +33,10 @@ pub mod columns {
     /// Data Column
     pub struct DeadSlots;
 
+    #[derive(Debug)]
+    /// The duplicate slots column
+    pub struct DuplicateSlots;
+
     #[derive(Debug)]
     /// The erasure meta column
     pub struct ErasureMeta;
@@ -52,6 +56,10 @@ pub mod columns {
     #[derive(Debug)]
     /// The shred erasure code column
     pub struct ShredCode;
+
+    #[derive(Debug)]
+    /// The transaction status column
+    pub struct TransactionStatus;
 }
 
 pub trait Backend: Sized + Send + Sync {
@@ -81,6 +89,15 @@ pub trait Backend: Sized + Send + Sync {
 
     fn raw_iterator_cf(&self, cf: Self::ColumnFamily) -> Result<Self::Cursor>;
 
+    /// Compact the keys from `from` to `to` in `cf`, reclaiming the disk space held by
+    /// deleted keys
+    fn compact_range_cf(
+        &self,
+        cf: Self::ColumnFamily,
+        from: &Self::Key,
+        to: &Self::Key,
+    ) -> Result<()>;
+
     fn write(&self, batch: Self::WriteBatch) -> Result<()>;
 
     fn batch(&self) -> Result<Self::WriteBatch>;
@@ -445,6 +462,14 @@ where
         Ok(end)
     }
 
+    /// Compact the keys of slots `from` through `to`, see `Backend::compact_range_cf()`
+    pub fn compact_range(&self, from: Slot, to: Slot) -> Result<()> {
+        let from = C::key(C::as_index(from));
+        let to = C::key(C::as_index(to.saturating_add(1)));
+        self.backend
+            .compact_range_cf(self.handle(), from.borrow(), to.borrow())
+    }
+
     #[inline]
     pub fn handle(&self) -> B::ColumnFamily {
         self.backend.cf_handle(C::NAME).clone()
@@ -3,72 +3,170 @@ use crate::blocktree::db::{Backend, Column, DbCursor, IWriteBatch, TypedColumn};
 use crate::blocktree::BlocktreeError;
 use crate::result::{Error, Result};
 use byteorder::{BigEndian, ByteOrder};
-use solana_kvstore::{self as kvstore, Key, KvStore};
+use solana_kvstore::{self as kvstore, Key, KvStore, Snapshot, WriteBatchConfig};
+use solana_sdk::timing::Slot;
+use std::fs;
+use std::ops::RangeInclusive;
 use std::path::Path;
 
+/// Column families are emulated by prefixing every key with the column's handle
 type ColumnFamily = u64;
 
+/// Bytes of a `Key` reserved for the column family prefix
+const CF_PREFIX_LEN: usize = 8;
+
 #[derive(Debug)]
 pub struct Kvs(KvStore);
 
-/// Dummy struct for now
-#[derive(Debug, Clone, Copy)]
-pub struct Dummy;
+/// Forward iterator over the rows of a single column family
+pub struct Iter(Box<dyn Iterator<Item = (Key, Vec<u8>)>>);
+
+/// Seekable cursor over a snapshot of a single column family
+pub struct Cursor {
+    cf: ColumnFamily,
+    snapshot: Snapshot,
+    iter: Box<dyn Iterator<Item = (Key, Vec<u8>)>>,
+    current: Option<(Key, Vec<u8>)>,
+}
+
+/// Write batch that prefixes keys with their column family before staging them
+pub struct WriteBatch(kvstore::WriteBatch);
 
 impl Backend for Kvs {
     type Key = Key;
     type OwnedKey = Key;
     type ColumnFamily = ColumnFamily;
-    type Cursor = Dummy;
-    type Iter = Dummy;
-    type WriteBatch = Dummy;
+    type Cursor = Cursor;
+    type Iter = Iter;
+    type WriteBatch = WriteBatch;
     type Error = kvstore::Error;
 
-    fn open(_path: &Path) -> Result<Kvs> {
-        unimplemented!()
+    fn open(path: &Path) -> Result<Kvs> {
+        fs::create_dir_all(&path)?;
+
+        let db = Kvs(KvStore::open_default(path)?);
+
+        Ok(db)
     }
 
     fn columns(&self) -> Vec<&'static str> {
-        unimplemented!()
+        use crate::blocktree::db::columns::{
+            Coding, Data, DeadSlots, DuplicateSlots, ErasureMeta, Index, Orphans, Root, ShredCode,
+            ShredData, SlotMeta, TransactionStatus,
+        };
+
+        vec![
+            Coding::NAME,
+            ErasureMeta::NAME,
+            DeadSlots::NAME,
+            Data::NAME,
+            Index::NAME,
+            Orphans::NAME,
+            Root::NAME,
+            SlotMeta::NAME,
+            ShredData::NAME,
+            ShredCode::NAME,
+            DuplicateSlots::NAME,
+            TransactionStatus::NAME,
+        ]
+    }
+
+    fn destroy(path: &Path) -> Result<()> {
+        KvStore::destroy(path)?;
+
+        Ok(())
     }
 
-    fn destroy(_path: &Path) -> Result<()> {
-        unimplemented!()
+    fn cf_handle(&self, cf: &str) -> ColumnFamily {
+        use crate::blocktree::db::columns::{
+            Coding, Data, DeadSlots, DuplicateSlots, ErasureMeta, Index, Orphans, Root, ShredCode,
+            ShredData, SlotMeta, TransactionStatus,
+        };
+
+        // Handles are persisted as key prefixes, so they must never be reordered.
+        //  0 is left unused so that no column shares a prefix with `Key::default()`
+        match cf {
+            Coding::NAME => 1,
+            ErasureMeta::NAME => 2,
+            DeadSlots::NAME => 3,
+            Data::NAME => 4,
+            Index::NAME => 5,
+            Orphans::NAME => 6,
+            Root::NAME => 7,
+            SlotMeta::NAME => 8,
+            ShredData::NAME => 9,
+            ShredCode::NAME => 10,
+            DuplicateSlots::NAME => 11,
+            TransactionStatus::NAME => 12,
+            _ => panic!("should never get an unknown column"),
+        }
     }
 
-    fn cf_handle(&self, _cf: &str) -> ColumnFamily {
-        unimplemented!()
+    fn get_cf(&self, cf: ColumnFamily, key: &Key) -> Result<Option<Vec<u8>>> {
+        let opt = self.0.get(&cf_key(cf, key))?;
+        Ok(opt)
     }
 
-    fn get_cf(&self, _cf: ColumnFamily, _key: &Key) -> Result<Option<Vec<u8>>> {
-        unimplemented!()
+    fn put_cf(&self, cf: ColumnFamily, key: &Key, value: &[u8]) -> Result<()> {
+        self.0.put(&cf_key(cf, key), value)?;
+        Ok(())
     }
 
-    fn put_cf(&self, _cf: ColumnFamily, _key: &Key, _value: &[u8]) -> Result<()> {
-        unimplemented!()
+    fn delete_cf(&self, cf: ColumnFamily, key: &Key) -> Result<()> {
+        self.0.delete(&cf_key(cf, key))?;
+        Ok(())
     }
 
-    fn delete_cf(&self, _cf: ColumnFamily, _key: &Key) -> Result<()> {
-        unimplemented!()
+    fn iterator_cf(&self, cf: ColumnFamily, start_from: Option<&Key>) -> Result<Iter> {
+        let range = cf_range(cf, start_from);
+        let iter = Iter(Box::new(self.0.range(range)?));
+
+        Ok(iter)
     }
 
-    fn iterator_cf(&self, _cf: ColumnFamily) -> Result<Dummy> {
-        unimplemented!()
+    fn raw_iterator_cf(&self, cf: ColumnFamily) -> Result<Cursor> {
+        let snapshot = self.0.snapshot();
+        let iter = Box::new(snapshot.range(cf_range(cf, None))?);
+
+        Ok(Cursor {
+            cf,
+            snapshot,
+            iter,
+            current: None,
+        })
     }
 
-    fn raw_iterator_cf(&self, _cf: ColumnFamily) -> Result<Dummy> {
-        unimplemented!()
+    fn compact_range_cf(&self, _cf: ColumnFamily, _from: &Key, _to: &Key) -> Result<()> {
+        // The KvStore can only compact all of its tables at once, which is a no-op when
+        //  nothing has been written since the last time
+        self.0.compact()?;
+        Ok(())
     }
 
-    fn batch(&self) -> Result<Dummy> {
-        unimplemented!()
+    fn batch(&self) -> Result<WriteBatch> {
+        Ok(WriteBatch(self.0.batch(WriteBatchConfig::default())))
     }
 
-    fn write(&self, _batch: Dummy) -> Result<()> {
-        unimplemented!()
+    fn write(&self, batch: WriteBatch) -> Result<()> {
+        self.0.commit(batch.0)?;
+        Ok(())
     }
 }
 
+/// Returns `key` with its column family prefix set to `cf`
+fn cf_key(cf: ColumnFamily, key: &Key) -> Key {
+    let mut key = *key;
+    BigEndian::write_u64(&mut key.0[..CF_PREFIX_LEN], cf);
+    key
+}
+
+/// Returns the range of keys in column family `cf`, starting from `start_from` if given
+fn cf_range(cf: ColumnFamily, start_from: Option<&Key>) -> RangeInclusive<Key> {
+    let start = cf_key(cf, start_from.unwrap_or(&Key::MIN));
+    let end = cf_key(cf, &Key::MAX);
+    start..=end
+}
+
 impl Column<Kvs> for cf::Coding {
     const NAME: &'static str = super::ERASURE_CF;
     type Index = (u64, u64);
@@ -80,6 +178,14 @@ impl Column<Kvs> for cf::Coding {
     fn index(key: &Key) -> (u64, u64) {
         cf::Data::index(key)
     }
+
+    fn slot(index: Self::Index) -> Slot {
+        index.0
+    }
+
+    fn as_index(slot: Slot) -> Self::Index {
+        (slot, 0)
+    }
 }
 
 impl Column<Kvs> for cf::Data {
@@ -98,6 +204,61 @@ impl Column<Kvs> for cf::Data {
         let index = BigEndian::read_u64(&key.0[16..]);
         (slot, index)
     }
+
+    fn slot(index: Self::Index) -> Slot {
+        index.0
+    }
+
+    fn as_index(slot: Slot) -> Self::Index {
+        (slot, 0)
+    }
+}
+
+impl Column<Kvs> for cf::ShredCode {
+    const NAME: &'static str = super::CODE_SHRED_CF;
+    type Index = (u64, u64);
+
+    fn key(index: (u64, u64)) -> Key {
+        cf::ShredData::key(index)
+    }
+
+    fn index(key: &Key) -> (u64, u64) {
+        cf::ShredData::index(key)
+    }
+
+    fn slot(index: Self::Index) -> Slot {
+        index.0
+    }
+
+    fn as_index(slot: Slot) -> Self::Index {
+        (slot, 0)
+    }
+}
+
+impl Column<Kvs> for cf::ShredData {
+    const NAME: &'static str = super::DATA_SHRED_CF;
+    type Index = (u64, u64);
+
+    fn key((slot, index): (u64, u64)) -> Key {
+        let mut key = Key::default();
+        BigEndian::write_u64(&mut key.0[8..16], slot);
+        BigEndian::write_u64(&mut key.0[16..], index);
+        key
+    }
+
+    fn index(key: &Key) -> (u64, u64) {
+        let slot = BigEndian::read_u64(&key.0[8..16]);
+        let index = BigEndian::read_u64(&key.0[16..]);
+        (slot, index)
+    }
+
+    fn slot(index: Self::Index) -> Slot {
+        index.0
+    }
+
+    fn as_index(slot: Slot) -> Self::Index {
+        (slot, 0)
+    }
 }
 
 impl Column<Kvs> for cf::Index {
@@ -113,6 +274,14 @@ impl Column<Kvs> for cf::Index {
     fn index(key: &Key) -> u64 {
         BigEndian::read_u64(&key.0[8..16])
     }
+
+    fn slot(index: Self::Index) -> Slot {
+        index
+    }
+
+    fn as_index(slot: Slot) -> Self::Index {
+        slot
+    }
 }
 
 impl TypedColumn<Kvs> for cf::Index {
@@ -120,7 +289,7 @@ impl TypedColumn<Kvs> for cf::Index {
 }
 
 impl Column<Kvs> for cf::DeadSlots {
-    const NAME: &'static str = super::DEAD_SLOTS;
+    const NAME: &'static str = super::DEAD_SLOTS_CF;
     type Index = u64;
 
     fn key(slot: u64) -> Key {
@@ -132,14 +301,22 @@ impl Column<Kvs> for cf::DeadSlots {
     fn index(key: &Key) -> u64 {
         BigEndian::read_u64(&key.0[8..16])
     }
+
+    fn slot(index: Self::Index) -> Slot {
+        index
+    }
+
+    fn as_index(slot: Slot) -> Self::Index {
+        slot
+    }
 }
 
-impl TypedColumn<Kvs> for cf::Root {
+impl TypedColumn<Kvs> for cf::DeadSlots {
     type Type = bool;
 }
 
-impl Column<Kvs> for cf::Orphans {
-    const NAME: &'static str = super::ORPHANS_CF;
+impl Column<Kvs> for cf::DuplicateSlots {
+    const NAME: &'static str = super::DUPLICATE_SLOTS_CF;
     type Index = u64;
 
     fn key(slot: u64) -> Key {
@@ -151,14 +328,47 @@ impl Column<Kvs> for cf::Orphans {
     fn index(key: &Key) -> u64 {
         BigEndian::read_u64(&key.0[8..16])
     }
+
+    fn slot(index: Self::Index) -> Slot {
+        index
+    }
+
+    fn as_index(slot: Slot) -> Self::Index {
+        slot
+    }
 }
 
-impl TypedColumn<Kvs> for cf::Orphans {
-    type Type = bool;
+impl TypedColumn<Kvs> for cf::DuplicateSlots {
+    type Type = crate::blocktree::meta::DuplicateSlotProof;
 }
 
-impl Column<Kvs> for cf::Root {
-    const NAME: &'static str = super::ROOT_CF;
+impl Column<Kvs> for cf::TransactionStatus {
+    const NAME: &'static str = super::TRANSACTION_STATUS_CF;
+    type Index = (u64, u64);
+
+    fn key(index: (u64, u64)) -> Key {
+        cf::Data::key(index)
+    }
+
+    fn index(key: &Key) -> (u64, u64) {
+        cf::Data::index(key)
+    }
+
+    fn slot(index: Self::Index) -> Slot {
+        index.0
+    }
+
+    fn as_index(slot: Slot) -> Self::Index {
+        (slot, 0)
+    }
+}
+
+impl TypedColumn<Kvs> for cf::TransactionStatus {
+    type Type = solana_sdk::transaction::Result<()>;
+}
+
+impl Column<Kvs> for cf::Orphans {
+    const NAME: &'static str = super::ORPHANS_CF;
     type Index = u64;
 
     fn key(slot: u64) -> Key {
@@ -170,14 +380,22 @@ impl Column<Kvs> for cf::Root {
     fn index(key: &Key) -> u64 {
         BigEndian::read_u64(&key.0[8..16])
     }
+
+    fn slot(index: Self::Index) -> Slot {
+        index
+    }
+
+    fn as_index(slot: Slot) -> Self::Index {
+        slot
+    }
 }
 
-impl TypedColumn<Kvs> for cf::Root {
+impl TypedColumn<Kvs> for cf::Orphans {
     type Type = bool;
 }
 
-impl Column<Kvs> for cf::SlotMeta {
-    const NAME: &'static str = super::META_CF;
+impl Column<Kvs> for cf::Root {
+    const NAME: &'static str = super::ROOT_CF;
     type Index = u64;
 
     fn key(slot: u64) -> Key {
@@ -189,6 +407,18 @@ impl Column<Kvs> for cf::SlotMeta {
     fn index(key: &Key) -> u64 {
         BigEndian::read_u64(&key.0[8..16])
     }
+
+    fn slot(index: Self::Index) -> Slot {
+        index
+    }
+
+    fn as_index(slot: Slot) -> Self::Index {
+        slot
+    }
+}
+
+impl TypedColumn<Kvs> for cf::Root {
+    type Type = bool;
 }
 
 impl Column<Kvs> for cf::SlotMeta {
@@ -204,6 +434,14 @@ impl Column<Kvs> for cf::SlotMeta {
     fn index(key: &Key) -> u64 {
         BigEndian::read_u64(&key.0[8..16])
     }
+
+    fn slot(index: Self::Index) -> Slot {
+        index
+    }
+
+    fn as_index(slot: Slot) -> Self::Index {
+        slot
+    }
 }
 
 impl TypedColumn<Kvs> for cf::SlotMeta {
@@ -226,53 +464,81 @@ impl Column<Kvs> for cf::ErasureMeta {
         let set_index = BigEndian::read_u64(&key.0[16..]);
         (slot, set_index)
     }
+
+    fn slot(index: Self::Index) -> Slot {
+        index.0
+    }
+
+    fn as_index(slot: Slot) -> Self::Index {
+        (slot, 0)
+    }
 }
 
 impl TypedColumn<Kvs> for cf::ErasureMeta {
     type Type = super::ErasureMeta;
 }
 
-impl DbCursor<Kvs> for Dummy {
+impl DbCursor<Kvs> for Cursor {
     fn valid(&self) -> bool {
-        unimplemented!()
+        self.current.is_some()
     }
 
-    fn seek(&mut self, _key: &Key) {
-        unimplemented!()
+    fn seek(&mut self, key: &Key) {
+        self.reset(Some(key));
     }
 
     fn seek_to_first(&mut self) {
-        unimplemented!()
+        self.reset(None);
     }
 
     fn next(&mut self) {
-        unimplemented!()
+        self.current = self.iter.next();
     }
 
     fn key(&self) -> Option<Key> {
-        unimplemented!()
+        self.current.as_ref().map(|(key, _)| *key)
     }
 
     fn value(&self) -> Option<Vec<u8>> {
-        unimplemented!()
+        self.current.as_ref().map(|(_, value)| value.clone())
+    }
+}
+
+impl Cursor {
+    fn reset(&mut self, start_from: Option<&Key>) {
+        // A failed read leaves the cursor invalid, as with a rocksdb raw iterator
+        let iter: Box<dyn Iterator<Item = (Key, Vec<u8>)>> =
+            match self.snapshot.range(cf_range(self.cf, start_from)) {
+                Ok(iter) => Box::new(iter),
+                Err(e) => {
+                    error!("Error: {:?} while seeking in column family {}", e, self.cf);
+                    Box::new(std::iter::empty())
+                }
+            };
+        self.iter = iter;
+        self.next();
     }
 }
 
-impl IWriteBatch<Kvs> for Dummy {
-    fn put_cf(&mut self, _cf: ColumnFamily, _key: &Key, _value: &[u8]) -> Result<()> {
-        unimplemented!()
+impl IWriteBatch<Kvs> for WriteBatch {
+    fn put_cf(&mut self, cf: ColumnFamily, key: &Key, value: &[u8]) -> Result<()> {
+        self.0.put(&cf_key(cf, key), value)?;
+        Ok(())
     }
 
-    fn delete_cf(&mut self, _cf: ColumnFamily, _key: &Key) -> Result<()> {
-        unimplemented!()
+    fn delete_cf(&mut self, cf: ColumnFamily, key: &Key) -> Result<()> {
+        self.0.delete(&cf_key(cf, key));
+        Ok(())
     }
 }
 
-impl Iterator for Dummy {
+impl Iterator for Iter {
     type Item = (Box<Key>, Box<[u8]>);
 
     fn next(&mut self) -> Option<Self::Item> {
-        unimplemented!()
+        self.0
+            .next()
+            .map(|(key, value)| (Box::new(key), value.into_boxed_slice()))
     }
 }
 
@@ -1,5 +1,8 @@
 use crate::erasure::ErasureConfig;
+use crate::packet::{Blob, BLOB_SIZE};
 use solana_metrics::datapoint;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signable;
 use std::{collections::BTreeSet, ops::RangeBounds};
 
 #[derive(Clone, Debug, Default, Deserialize, Serialize, Eq, PartialEq)]
@@ -59,6 +62,14 @@ pub struct ErasureMeta {
     config: ErasureConfig,
 }
 
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+/// Proof that the leader of a slot produced two versions of it: two different blobs it signed
+/// for the same index of the slot
+pub struct DuplicateSlotProof {
+    pub blob1: Vec<u8>,
+    pub blob2: Vec<u8>,
+}
+
 #[derive(Debug, PartialEq)]
 pub enum ErasureMetaStatus {
     CanRecover,
@@ -66,6 +77,37 @@ pub enum ErasureMetaStatus {
     StillNeed(usize),
 }
 
+impl DuplicateSlotProof {
+    /// Whether both blobs are for the same index of `slot`, signed by `leader`, and differ in
+    /// their signed contents
+    pub fn verify(&self, slot: u64, leader: &Pubkey) -> bool {
+        let to_blob = |bytes: &[u8]| {
+            if bytes.len() > BLOB_SIZE {
+                return None;
+            }
+            let blob = Blob::new(bytes);
+            if blob.data_size() as usize > BLOB_SIZE {
+                None
+            } else {
+                Some(blob)
+            }
+        };
+        match (to_blob(&self.blob1), to_blob(&self.blob2)) {
+            (Some(blob1), Some(blob2)) => {
+                blob1.slot() == slot
+                    && blob2.slot() == slot
+                    && blob1.index() == blob2.index()
+                    && blob1.id() == *leader
+                    && blob2.id() == *leader
+                    && blob1.signable_data() != blob2.signable_data()
+                    && blob1.verify()
+                    && blob2.verify()
+            }
+            _ => false,
+        }
+    }
+}
+
 impl Index {
     pub(in crate::blocktree) fn new(slot: u64) -> Self {
         Index {
@@ -33,8 +33,8 @@ impl Backend for Rocks {
 
     fn open(path: &Path) -> Result<Rocks> {
         use crate::blocktree::db::columns::{
-            Coding, Data, DeadSlots, ErasureMeta, Index, Orphans, Root, ShredCode, ShredData,
-            SlotMeta,
+            Coding, Data, DeadSlots, DuplicateSlots, ErasureMeta, Index, Orphans, Root, ShredCode,
+            ShredData, SlotMeta, TransactionStatus,
         };
 
         fs::create_dir_all(&path)?;
@@ -63,6 +63,12 @@ impl Backend for Rocks {
             ColumnFamilyDescriptor::new(ShredData::NAME, get_cf_options(ShredData::NAME));
         let shred_code_cf_descriptor =
             ColumnFamilyDescriptor::new(ShredCode::NAME, get_cf_options(ShredCode::NAME));
+        let duplicate_slots_cf_descriptor =
+            ColumnFamilyDescriptor::new(DuplicateSlots::NAME, get_cf_options(DuplicateSlots::NAME));
+        let transaction_status_cf_descriptor = ColumnFamilyDescriptor::new(
+            TransactionStatus::NAME,
+            get_cf_options(TransactionStatus::NAME),
+        );
 
         let cfs = vec![
             meta_cf_descriptor,
@@ -75,6 +81,8 @@ impl Backend for Rocks {
             index_cf_descriptor,
             shred_data_cf_descriptor,
             shred_code_cf_descriptor,
+            duplicate_slots_cf_descriptor,
+            transaction_status_cf_descriptor,
         ];
 
         // Open the database
@@ -85,8 +93,8 @@ impl Backend for Rocks {
 
     fn columns(&self) -> Vec<&'static str> {
         use crate::blocktree::db::columns::{
-            Coding, Data, DeadSlots, ErasureMeta, Index, Orphans, Root, ShredCode, ShredData,
-            SlotMeta,
+            Coding, Data, DeadSlots, DuplicateSlots, ErasureMeta, Index, Orphans, Root, ShredCode,
+            ShredData, SlotMeta, TransactionStatus,
         };
 
         vec![
@@ -100,6 +108,8 @@ impl Backend for Rocks {
             SlotMeta::NAME,
             ShredData::NAME,
             ShredCode::NAME,
+            DuplicateSlots::NAME,
+            TransactionStatus::NAME,
         ]
     }
 
@@ -149,6 +159,11 @@ impl Backend for Rocks {
         Ok(raw_iter)
     }
 
+    fn compact_range_cf(&self, cf: ColumnFamily, from: &[u8], to: &[u8]) -> Result<()> {
+        self.0.compact_range_cf(cf, Some(from), Some(to));
+        Ok(())
+    }
+
     fn batch(&self) -> Result<RWriteBatch> {
         Ok(RWriteBatch::default())
     }
@@ -307,6 +322,58 @@ impl TypedColumn<Rocks> for cf::DeadSlots {
     type Type = bool;
 }
 
+impl Column<Rocks> for cf::DuplicateSlots {
+    const NAME: &'static str = super::DUPLICATE_SLOTS_CF;
+    type Index = u64;
+
+    fn key(slot: u64) -> Vec<u8> {
+        let mut key = vec![0; 8];
+        BigEndian::write_u64(&mut key[..], slot);
+        key
+    }
+
+    fn index(key: &[u8]) -> u64 {
+        BigEndian::read_u64(&key[..8])
+    }
+
+    fn slot(index: Self::Index) -> Slot {
+        index
+    }
+
+    fn as_index(slot: Slot) -> Self::Index {
+        slot
+    }
+}
+
+impl TypedColumn<Rocks> for cf::DuplicateSlots {
+    type Type = crate::blocktree::meta::DuplicateSlotProof;
+}
+
+impl Column<Rocks> for cf::TransactionStatus {
+    const NAME: &'static str = super::TRANSACTION_STATUS_CF;
+    type Index = (u64, u64);
+
+    fn key(index: (u64, u64)) -> Vec<u8> {
+        cf::Data::key(index)
+    }
+
+    fn index(key: &[u8]) -> (u64, u64) {
+        cf::Data::index(key)
+    }
+
+    fn slot(index: Self::Index) -> Slot {
+        index.0
+    }
+
+    fn as_index(slot: Slot) -> Self::Index {
+        (slot, 0)
+    }
+}
+
+impl TypedColumn<Rocks> for cf::TransactionStatus {
+    type Type = solana_sdk::transaction::Result<()>;
+}
+
 impl Column<Rocks> for cf::Orphans {
     const NAME: &'static str = super::ORPHANS_CF;
     type Index = u64;
@@ -143,13 +143,42 @@ pub fn process_blocktree(
     account_paths: Option<String>,
     verify_ledger: bool,
     dev_halt_at_slot: Option<Slot>,
+) -> result::Result<(BankForks, Vec<BankForksInfo>, LeaderScheduleCache), BlocktreeProcessorError> {
+    process_blocktree_with_callback(
+        genesis_block,
+        blocktree,
+        account_paths,
+        verify_ledger,
+        dev_halt_at_slot,
+        None,
+    )
+}
+
+/// Same as `process_blocktree`, also handing every bank to `slot_callback` as soon as it has
+/// been replayed and frozen
+pub fn process_blocktree_with_callback(
+    genesis_block: &GenesisBlock,
+    blocktree: &Blocktree,
+    account_paths: Option<String>,
+    verify_ledger: bool,
+    dev_halt_at_slot: Option<Slot>,
+    slot_callback: Option<&dyn Fn(&Bank)>,
 ) -> result::Result<(BankForks, Vec<BankForksInfo>, LeaderScheduleCache), BlocktreeProcessorError> {
     info!("processing ledger from bank 0...");
 
     // Setup bank for slot 0
     let bank0 = Arc::new(Bank::new_with_paths(&genesis_block, account_paths));
     process_bank_0(&bank0, blocktree, verify_ledger)?;
-    process_blocktree_from_root(blocktree, bank0, verify_ledger, dev_halt_at_slot)
+    if let Some(slot_callback) = slot_callback {
+        slot_callback(&bank0);
+    }
+    do_process_blocktree_from_root(
+        blocktree,
+        bank0,
+        verify_ledger,
+        dev_halt_at_slot,
+        slot_callback,
+    )
 }
 
 // Process blocktree from a known root bank
@@ -158,6 +187,16 @@ pub fn process_blocktree_from_root(
     bank: Arc<Bank>,
     verify_ledger: bool,
     dev_halt_at_slot: Option<Slot>,
+) -> result::Result<(BankForks, Vec<BankForksInfo>, LeaderScheduleCache), BlocktreeProcessorError> {
+    do_process_blocktree_from_root(blocktree, bank, verify_ledger, dev_halt_at_slot, None)
+}
+
+fn do_process_blocktree_from_root(
+    blocktree: &Blocktree,
+    bank: Arc<Bank>,
+    verify_ledger: bool,
+    dev_halt_at_slot: Option<Slot>,
+    slot_callback: Option<&dyn Fn(&Bank)>,
 ) -> result::Result<(BankForks, Vec<BankForksInfo>, LeaderScheduleCache), BlocktreeProcessorError> {
     info!("processing ledger from root: {}...", bank.slot());
     // Starting slot must be a root, and thus has no parents
@@ -186,6 +225,7 @@ pub fn process_blocktree_from_root(
                 &mut rooted_path,
                 verify_ledger,
                 dev_halt_at_slot,
+                slot_callback,
             )?;
             let (banks, bank_forks_info): (Vec<_>, Vec<_>) = fork_info.into_iter().unzip();
             let bank_forks = BankForks::new_from_banks(&banks, rooted_path);
@@ -340,6 +380,7 @@ fn process_pending_slots(
     rooted_path: &mut Vec<u64>,
     verify_ledger: bool,
     dev_halt_at_slot: Slot,
+    slot_callback: Option<&dyn Fn(&Bank)>,
 ) -> result::Result<Vec<(Arc<Bank>, BankForksInfo)>, BlocktreeProcessorError> {
     let mut fork_info = vec![];
     let mut last_status_report = Instant::now();
@@ -370,6 +411,9 @@ fn process_pending_slots(
         verify_and_process_entries(&bank, &entries, verify_ledger, last_entry_hash)?;
 
         bank.freeze(); // all banks handled by this routine are created from complete slots
+        if let Some(slot_callback) = slot_callback {
+            slot_callback(&bank);
+        }
 
         if blocktree.is_root(slot) {
             let parents = bank.parents().into_iter().map(|b| b.slot()).rev().skip(1);
@@ -491,6 +535,42 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn test_process_blocktree_with_callback() {
+        solana_logger::setup();
+
+        let GenesisBlockInfo { genesis_block, .. } = create_genesis_block(10_000);
+        let ticks_per_slot = genesis_block.ticks_per_slot;
+
+        // slot 0 <- slot 1 <- slot 2, all full of ticks
+        let (ledger_path, blockhash) = create_new_tmp_ledger!(&genesis_block);
+        let blocktree =
+            Blocktree::open(&ledger_path).expect("Expected to successfully open database ledger");
+        let blockhash = fill_blocktree_slot_with_ticks(&blocktree, ticks_per_slot, 1, 0, blockhash);
+        fill_blocktree_slot_with_ticks(&blocktree, ticks_per_slot, 2, 1, blockhash);
+
+        let replayed = RefCell::new(vec![]);
+        let (bank_forks, _bank_forks_info, _) = process_blocktree_with_callback(
+            &genesis_block,
+            &blocktree,
+            None,
+            true,
+            None,
+            Some(&|bank: &Bank| {
+                assert!(bank.is_frozen());
+                replayed.borrow_mut().push((bank.slot(), bank.hash()));
+            }),
+        )
+        .unwrap();
+
+        let replayed = replayed.into_inner();
+        assert_eq!(
+            replayed.iter().map(|(slot, _)| *slot).collect::<Vec<_>>(),
+            vec![0, 1, 2]
+        );
+        assert_eq!(replayed[2].1, bank_forks[2].hash());
+    }
+
     #[test]
     fn test_process_blocktree_with_two_forks_and_squash() {
         solana_logger::setup();
@@ -65,7 +65,7 @@ impl BroadcastRun for BroadcastBadBlobSizes {
             w_b.meta.size = real_size;
         }
 
-        blocktree.write_shared_blobs(data_blobs.iter())?;
+        blocktree.write_shared_blobs(data_blobs.iter(), None)?;
         blocktree.put_shared_coding_blobs(coding_blobs.iter())?;
 
         // 3) Start broadcast step
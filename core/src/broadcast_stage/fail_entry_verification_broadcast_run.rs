@@ -52,7 +52,7 @@ impl BroadcastRun for FailEntryVerificationBroadcastRun {
             &mut broadcast.coding_generator,
         );
 
-        blocktree.write_shared_blobs(data_blobs.iter())?;
+        blocktree.write_shared_blobs(data_blobs.iter(), None)?;
         blocktree.put_shared_coding_blobs(coding_blobs.iter())?;
 
         // 3) Start broadcast step
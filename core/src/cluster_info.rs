@@ -13,17 +13,18 @@
 //!
 //! Bank needs to provide an interface for us to query the stake weight
 use crate::bank_forks::BankForks;
-use crate::blocktree::Blocktree;
+use crate::blocktree::{Blocktree, DuplicateSlotProof};
 use crate::contact_info::ContactInfo;
 use crate::crds_gossip::CrdsGossip;
 use crate::crds_gossip_error::CrdsGossipError;
 use crate::crds_gossip_pull::{CrdsFilter, CRDS_GOSSIP_PULL_CRDS_TIMEOUT_MS};
-use crate::crds_value::{CrdsValue, CrdsValueLabel, EpochSlots, Vote};
+use crate::crds_value::{CrdsValue, CrdsValueLabel, DuplicateSlot, EpochSlots, Version, Vote};
 use crate::packet::{to_shared_blob, SharedBlob, BLOB_SIZE};
 use crate::repair_service::RepairType;
 use crate::result::Result;
 use crate::staking_utils;
 use crate::streamer::{BlobReceiver, BlobSender};
+use crate::version;
 use crate::weighted_shuffle::weighted_shuffle;
 use bincode::{deserialize, serialize, serialized_size};
 use core::cmp;
@@ -36,6 +37,7 @@ use solana_metrics::{datapoint_debug, inc_new_counter_debug, inc_new_counter_err
 use solana_netutil::{
     bind_in_range, bind_to, find_available_port_in_range, multi_bind_in_range, PortRange,
 };
+use solana_sdk::hash::hashv;
 use solana_sdk::packet::PACKET_DATA_SIZE;
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::{Keypair, KeypairUtil, Signable, Signature};
@@ -207,9 +209,10 @@ impl ClusterInfo {
         my_data.wallclock = now;
         let mut entry = CrdsValue::ContactInfo(my_data);
         entry.sign(&self.keypair);
+        let version = self.new_version(now);
         self.gossip.refresh_push_active_set(stakes);
         self.gossip
-            .process_push_message(&self.id(), vec![entry], now);
+            .process_push_message(&self.id(), vec![entry, version], now);
     }
 
     // TODO kill insert_info, only used by tests
@@ -263,13 +266,16 @@ impl ClusterInfo {
 
                 format!(
                     "- gossip: {:20} | {:5}ms | {} {}\n  \
-                     tpu:    {:20} |         |\n  \
+                     tpu:    {:20} |         | {}\n  \
                      rpc:    {:20} |         |\n",
                     addr_to_string(&node.gossip),
                     now.saturating_sub(last_updated),
                     node.id,
                     if node.id == my_pubkey { "(me)" } else { "" }.to_string(),
                     addr_to_string(&node.tpu),
+                    self.get_version_for_node(&node.id)
+                        .map(|version| version.to_string())
+                        .unwrap_or_else(|| "unknown version".to_string()),
                     addr_to_string(&node.rpc),
                 )
             })
@@ -303,6 +309,26 @@ impl ClusterInfo {
             .process_push_message(&self.id(), vec![entry], now);
     }
 
+    /// The signed software version and feature set of this node.  `push_self()` re-pushes it
+    /// with the node's contact info, so it doesn't time out of the other nodes' tables.
+    fn new_version(&self, now: u64) -> CrdsValue {
+        let mut entry = CrdsValue::Version(Version::new(
+            self.id(),
+            version::VERSION.to_string(),
+            version::feature_set(),
+            now,
+        ));
+        entry.sign(&self.keypair);
+        entry
+    }
+
+    pub fn get_version_for_node(&self, pubkey: &Pubkey) -> Option<&Version> {
+        self.gossip
+            .crds
+            .lookup(&CrdsValueLabel::Version(*pubkey))
+            .and_then(CrdsValue::version)
+    }
+
     pub fn push_vote(&mut self, vote: Transaction) {
         let now = timestamp();
         let vote = Vote::new(&self.id(), vote, now);
@@ -312,6 +338,64 @@ impl ClusterInfo {
             .process_push_message(&self.id(), vec![entry], now);
     }
 
+    /// Gossip a proof that the leader of `slot` produced more than one version of it
+    pub fn push_duplicate_slot(&mut self, slot: u64, proof: &DuplicateSlotProof) {
+        let now = timestamp();
+        let proof = serialize(proof).expect("unable to serialize DuplicateSlotProof");
+        let entries = DuplicateSlot::new_chunks(self.id(), slot, &proof, now)
+            .into_iter()
+            .map(|duplicate_slot| {
+                let mut entry = CrdsValue::DuplicateSlot(duplicate_slot);
+                entry.sign(&self.keypair);
+                entry
+            })
+            .collect();
+        self.gossip.process_push_message(&self.id(), entries, now);
+    }
+
+    /// Get the duplicate slot proofs in the crds whose chunks have all arrived, as
+    /// `(from, wallclock, slot, proof)`.  The proofs are unverified.
+    pub fn get_duplicate_slot_proofs(&self) -> Vec<(Pubkey, u64, u64, DuplicateSlotProof)> {
+        // Chunks of the same proof share their sender, wallclock and slot
+        let mut chunks: HashMap<(Pubkey, u64, u64), Vec<&DuplicateSlot>> = HashMap::new();
+        for duplicate_slot in self
+            .gossip
+            .crds
+            .table
+            .values()
+            .filter_map(|x| x.value.duplicate_slot())
+        {
+            chunks
+                .entry((
+                    duplicate_slot.from,
+                    duplicate_slot.wallclock,
+                    duplicate_slot.slot,
+                ))
+                .or_default()
+                .push(duplicate_slot);
+        }
+        chunks
+            .into_iter()
+            .filter_map(|((from, wallclock, slot), mut chunks)| {
+                chunks.sort_by_key(|chunk| chunk.chunk_index);
+                let is_complete = chunks.len() == chunks[0].num_chunks as usize
+                    && chunks.iter().enumerate().all(|(i, chunk)| {
+                        chunk.chunk_index as usize == i && chunk.num_chunks == chunks[0].num_chunks
+                    });
+                if !is_complete {
+                    return None;
+                }
+                let proof: Vec<u8> = chunks
+                    .iter()
+                    .flat_map(|chunk| chunk.chunk.iter().cloned())
+                    .collect();
+                deserialize(&proof)
+                    .ok()
+                    .map(|proof| (from, wallclock, slot, proof))
+            })
+            .collect()
+    }
+
     /// Get votes in the crds
     /// * since - The timestamp of when the vote inserted must be greater than
     /// since. This allows the bank to query for new votes only.
@@ -514,6 +598,18 @@ impl ClusterInfo {
         out
     }
 
+    /// Seed of the shuffle that lays out the retransmit tree of the blob at `index` in `slot`,
+    /// broadcast by `leader`.  Every blob is retransmitted over its own tree, which the leader
+    /// can't pick by changing the blob's contents.  The trees are predictable, anyone who
+    /// knows the leader schedule and the stakes can compute them ahead of time.
+    pub fn retransmit_seed(slot: u64, index: u64, leader: &Pubkey) -> [u8; 32] {
+        let mut seed = [0; 32];
+        seed.copy_from_slice(
+            hashv(&[&slot.to_le_bytes(), &index.to_le_bytes(), leader.as_ref()]).as_ref(),
+        );
+        seed
+    }
+
     /// Return sorted Retransmit peers and index of `Self.id()` as if it were in that list
     pub fn shuffle_peers_and_index<S: std::hash::BuildHasher>(
         &self,
@@ -711,7 +807,8 @@ impl ClusterInfo {
         blobs.into_iter().for_each(|b| {
             blobs_len += 1;
             let blob = b.borrow().read().unwrap();
-            let broadcast_table = self.sorted_tvu_peers(stakes, ChaChaRng::from_seed(blob.seed()));
+            let seed = Self::retransmit_seed(blob.slot(), blob.index(), &self.id());
+            let broadcast_table = self.sorted_tvu_peers(stakes, ChaChaRng::from_seed(seed));
             broadcast_table_len = cmp::max(broadcast_table_len, broadcast_table.len());
 
             if !broadcast_table.is_empty() {
@@ -1469,6 +1566,7 @@ impl ClusterInfo {
             daddr,
             daddr,
             daddr,
+            daddr,
             timestamp(),
         );
         (node, gossip_socket)
@@ -1488,6 +1586,7 @@ impl ClusterInfo {
             daddr,
             daddr,
             daddr,
+            daddr,
             timestamp(),
         );
         (node, gossip_socket)
@@ -1536,6 +1635,7 @@ pub struct Sockets {
     pub tvu: Vec<UdpSocket>,
     pub tpu: Vec<UdpSocket>,
     pub tpu_forwards: Vec<UdpSocket>,
+    pub tpu_quic: Option<UdpSocket>,
     pub broadcast: UdpSocket,
     pub repair: UdpSocket,
     pub retransmit: UdpSocket,
@@ -1568,6 +1668,7 @@ impl Node {
             tvu.local_addr().unwrap(),
             empty,
             empty,
+            empty,
             storage.local_addr().unwrap(),
             empty,
             empty,
@@ -1581,6 +1682,7 @@ impl Node {
                 tvu: vec![tvu],
                 tpu: vec![],
                 tpu_forwards: vec![],
+                tpu_quic: None,
                 broadcast,
                 repair,
                 retransmit,
@@ -1593,6 +1695,7 @@ impl Node {
         let gossip = UdpSocket::bind("127.0.0.1:0").unwrap();
         let tvu = UdpSocket::bind("127.0.0.1:0").unwrap();
         let tpu_forwards = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let tpu_quic = UdpSocket::bind("127.0.0.1:0").unwrap();
         let repair = UdpSocket::bind("127.0.0.1:0").unwrap();
         let rpc_port = find_available_port_in_range((1024, 65535)).unwrap();
         let rpc_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), rpc_port);
@@ -1609,6 +1712,7 @@ impl Node {
             tvu.local_addr().unwrap(),
             tpu.local_addr().unwrap(),
             tpu_forwards.local_addr().unwrap(),
+            tpu_quic.local_addr().unwrap(),
             storage.local_addr().unwrap(),
             rpc_addr,
             rpc_pubsub_addr,
@@ -1621,6 +1725,7 @@ impl Node {
                 tvu: vec![tvu],
                 tpu: vec![tpu],
                 tpu_forwards: vec![tpu_forwards],
+                tpu_quic: Some(tpu_quic),
                 broadcast,
                 repair,
                 retransmit,
@@ -1657,6 +1762,8 @@ impl Node {
         let (tpu_forwards_port, tpu_forwards_sockets) =
             multi_bind_in_range(port_range, 8).expect("tpu multi_bind");
 
+        let (tpu_quic_port, tpu_quic) = Self::bind(port_range);
+
         let (_, repair) = Self::bind(port_range);
         let (_, broadcast) = Self::bind(port_range);
         let (_, retransmit) = Self::bind(port_range);
@@ -1667,6 +1774,7 @@ impl Node {
             SocketAddr::new(gossip_addr.ip(), tvu_port),
             SocketAddr::new(gossip_addr.ip(), tpu_port),
             SocketAddr::new(gossip_addr.ip(), tpu_forwards_port),
+            SocketAddr::new(gossip_addr.ip(), tpu_quic_port),
             socketaddr_any!(),
             socketaddr_any!(),
             socketaddr_any!(),
@@ -1681,6 +1789,7 @@ impl Node {
                 tvu: tvu_sockets,
                 tpu: tpu_sockets,
                 tpu_forwards: tpu_forwards_sockets,
+                tpu_quic: Some(tpu_quic),
                 broadcast,
                 repair,
                 retransmit,
@@ -1702,8 +1811,10 @@ impl Node {
         let empty = socketaddr_any!();
         new.info.tpu = empty;
         new.info.tpu_forwards = empty;
+        new.info.tpu_quic = empty;
         new.sockets.tpu = vec![];
         new.sockets.tpu_forwards = vec![];
+        new.sockets.tpu_quic = None;
 
         new
     }
@@ -1815,6 +1926,7 @@ mod tests {
             socketaddr!([127, 0, 0, 1], 1238),
             socketaddr!([127, 0, 0, 1], 1239),
             socketaddr!([127, 0, 0, 1], 1240),
+            socketaddr!([127, 0, 0, 1], 1241),
             0,
         );
         cluster_info.insert_info(nxt.clone());
@@ -1834,6 +1946,7 @@ mod tests {
             socketaddr!([127, 0, 0, 1], 1238),
             socketaddr!([127, 0, 0, 1], 1239),
             socketaddr!([127, 0, 0, 1], 1240),
+            socketaddr!([127, 0, 0, 1], 1241),
             0,
         );
         cluster_info.insert_info(nxt);
@@ -1870,6 +1983,7 @@ mod tests {
                 socketaddr!("127.0.0.1:1238"),
                 socketaddr!("127.0.0.1:1239"),
                 socketaddr!("127.0.0.1:1240"),
+                socketaddr!("127.0.0.1:1241"),
                 0,
             );
             let rv = ClusterInfo::run_window_request(
@@ -1893,7 +2007,7 @@ mod tests {
             }
 
             blocktree
-                .write_shared_blobs(vec![&blob])
+                .write_shared_blobs(vec![&blob], None)
                 .expect("Expect successful ledger write");
 
             let rv = ClusterInfo::run_window_request(
@@ -2025,6 +2139,13 @@ mod tests {
 
         check_sockets(&node.sockets.tvu, ip, range);
         check_sockets(&node.sockets.tpu, ip, range);
+
+        let tpu_quic = node.sockets.tpu_quic.as_ref().unwrap();
+        check_socket(tpu_quic, ip, range);
+        assert_eq!(
+            node.info.tpu_quic.port(),
+            tpu_quic.local_addr().unwrap().port()
+        );
     }
 
     #[test]
@@ -2274,6 +2395,67 @@ mod tests {
         assert_eq!(max_ts, new_max_ts);
     }
 
+    #[test]
+    fn test_push_duplicate_slot() {
+        let keypair = Arc::new(Keypair::new());
+        let mut cluster_info = ClusterInfo::new(
+            ContactInfo::new_localhost(&keypair.pubkey(), timestamp()),
+            keypair,
+        );
+        assert!(cluster_info.get_duplicate_slot_proofs().is_empty());
+
+        let proof = DuplicateSlotProof {
+            blob1: vec![1; BLOB_SIZE],
+            blob2: vec![2; BLOB_SIZE],
+        };
+        cluster_info.push_duplicate_slot(5, &proof);
+        let proofs = cluster_info.get_duplicate_slot_proofs();
+        assert_eq!(proofs.len(), 1);
+        assert_eq!(proofs[0].0, cluster_info.id());
+        assert_eq!((proofs[0].2, &proofs[0].3), (5, &proof));
+
+        // A proof for another slot doesn't replace the first one
+        cluster_info.push_duplicate_slot(6, &proof);
+        let mut slots: Vec<_> = cluster_info
+            .get_duplicate_slot_proofs()
+            .into_iter()
+            .map(|(_, _, slot, _)| slot)
+            .collect();
+        slots.sort();
+        assert_eq!(slots, vec![5, 6]);
+
+        // Every chunk fits in a push message
+        for value in cluster_info.gossip.crds.table.values() {
+            if value.value.duplicate_slot().is_some() {
+                let protocol = Protocol::PushMessage(Pubkey::default(), vec![value.value.clone()]);
+                assert!(serialized_size(&protocol).unwrap() <= PACKET_DATA_SIZE as u64);
+            }
+        }
+    }
+
+    #[test]
+    fn test_push_version() {
+        let keypair = Arc::new(Keypair::new());
+        let id = keypair.pubkey();
+        let mut cluster_info =
+            ClusterInfo::new(ContactInfo::new_localhost(&id, timestamp()), keypair);
+        let version = cluster_info.get_version_for_node(&id).unwrap().clone();
+        assert_eq!(version.version, version::VERSION);
+        assert_eq!(version.feature_set, version::feature_set());
+        assert!(CrdsValue::Version(version.clone()).verify());
+        assert!(cluster_info
+            .contact_info_trace()
+            .contains(&version.to_string()));
+        assert!(cluster_info
+            .get_version_for_node(&Pubkey::new_rand())
+            .is_none());
+
+        // The version is refreshed along with the contact info
+        sleep(Duration::from_millis(10));
+        cluster_info.push_self(&HashMap::new());
+        assert!(cluster_info.get_version_for_node(&id).unwrap().wallclock > version.wallclock);
+    }
+
     #[test]
     fn test_add_entrypoint() {
         let node_keypair = Arc::new(Keypair::new());
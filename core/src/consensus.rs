@@ -1,11 +1,18 @@
 use crate::bank_forks::BankForks;
+use crate::result::{Error, Result};
+use bincode::{deserialize, serialize};
 use solana_metrics::datapoint_info;
 use solana_runtime::bank::Bank;
 use solana_sdk::account::Account;
 use solana_sdk::hash::Hash;
 use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, KeypairUtil, Signature};
+use solana_sdk::sysvar::slot_hashes::{self, SlotHashes};
 use solana_vote_api::vote_state::{Lockout, Vote, VoteState, MAX_LOCKOUT_HISTORY};
 use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 pub const VOTE_THRESHOLD_DEPTH: usize = 8;
@@ -27,23 +34,46 @@ impl StakeLockout {
     }
 }
 
-#[derive(Default)]
+#[derive(Debug)]
+pub enum TowerError {
+    /// The saved tower wasn't signed by the node it was restored for
+    InvalidSignature,
+    /// The saved tower belongs to another node
+    WrongTower(String),
+}
+
+#[derive(Default, Serialize, Deserialize)]
 pub struct Tower {
     node_pubkey: Pubkey,
     threshold_depth: usize,
     threshold_size: f64,
     lockouts: VoteState,
     recent_votes: VecDeque<Vote>,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+/// A serialized `Tower` signed by its node
+#[derive(Serialize, Deserialize)]
+struct SavedTower {
+    signature: Signature,
+    data: Vec<u8>,
 }
 
 impl Tower {
-    pub fn new(node_pubkey: &Pubkey, vote_account_pubkey: &Pubkey, bank_forks: &BankForks) -> Self {
+    pub fn new(
+        node_pubkey: &Pubkey,
+        vote_account_pubkey: &Pubkey,
+        bank_forks: &BankForks,
+        ledger_path: &Path,
+    ) -> Self {
         let mut tower = Self {
             node_pubkey: *node_pubkey,
             threshold_depth: VOTE_THRESHOLD_DEPTH,
             threshold_size: VOTE_THRESHOLD_SIZE,
             lockouts: VoteState::default(),
             recent_votes: VecDeque::default(),
+            path: Self::get_filename(ledger_path, node_pubkey),
         };
 
         tower.initialize_lockouts_from_bank_forks(&bank_forks, vote_account_pubkey);
@@ -204,6 +234,10 @@ impl Tower {
         self.lockouts.root_slot
     }
 
+    pub fn last_vote(&self) -> Option<u64> {
+        self.lockouts.votes.back().map(|vote| vote.slot)
+    }
+
     pub fn calculate_weight(&self, stake_lockouts: &HashMap<u64, StakeLockout>) -> u128 {
         let mut sum = 0u128;
         let root_slot = self.lockouts.root_slot.unwrap_or(0);
@@ -216,6 +250,111 @@ impl Tower {
         sum
     }
 
+    /// The file in `ledger_path` that the tower of `node_pubkey` is saved to
+    pub fn get_filename(ledger_path: &Path, node_pubkey: &Pubkey) -> PathBuf {
+        ledger_path
+            .join(format!("tower-{}", node_pubkey))
+            .with_extension("bin")
+    }
+
+    /// Sign the tower with `node_keypair` and save it, replacing the previously saved tower
+    pub fn save(&self, node_keypair: &Keypair) -> Result<()> {
+        let data = serialize(self)?;
+        let saved_tower = SavedTower {
+            signature: node_keypair.sign_message(&data),
+            data,
+        };
+
+        // Write to a temporary file first so a crash never leaves a partial tower behind
+        let new_path = self.path.with_extension("new");
+        {
+            let mut file = File::create(&new_path)?;
+            file.write_all(&serialize(&saved_tower)?)?;
+            file.sync_all()?;
+        }
+        fs::rename(&new_path, &self.path)?;
+        Ok(())
+    }
+
+    /// Load the tower `node_pubkey` saved in `ledger_path`
+    pub fn restore(ledger_path: &Path, node_pubkey: &Pubkey) -> Result<Self> {
+        let path = Self::get_filename(ledger_path, node_pubkey);
+        let saved_tower: SavedTower = deserialize(&fs::read(&path)?)?;
+        if !saved_tower
+            .signature
+            .verify(node_pubkey.as_ref(), &saved_tower.data)
+        {
+            return Err(Error::TowerError(TowerError::InvalidSignature));
+        }
+        let mut tower: Self = deserialize(&saved_tower.data)?;
+        if tower.node_pubkey != *node_pubkey {
+            return Err(Error::TowerError(TowerError::WrongTower(format!(
+                "tower belongs to {}, expected {}",
+                tower.node_pubkey, node_pubkey
+            ))));
+        }
+        tower.path = path;
+        Ok(tower)
+    }
+
+    /// Treat the votes older than `root_bank` as rooted.  A node that boots from a snapshot
+    /// at `root_bank` can't replay the forks those votes were on, and the cluster has rooted
+    /// `root_bank` past them.  A vote on another fork than `root_bank`'s that still locks out
+    /// slots past it can't be dropped though, the tower is left as is and won't be voted
+    /// with until that vote expires.  Must be called on a restored tower before it is voted
+    /// with.
+    pub fn adjust_lockouts_to_root(&mut self, root_bank: &Bank) {
+        let root = root_bank.slot();
+        if self
+            .lockouts
+            .root_slot
+            .map_or(false, |tower_root| tower_root >= root)
+        {
+            return;
+        }
+        // A vote on `root_bank` or one of the recent slots it descends from doesn't lock out
+        // anything past it
+        let ancestors: HashSet<u64> = root_bank
+            .get_account(&slot_hashes::id())
+            .and_then(|account| SlotHashes::from(&account))
+            .map(|slot_hashes| slot_hashes.iter().map(|(slot, _)| *slot).collect())
+            .unwrap_or_default();
+        if let Some(vote) = self.lockouts.votes.iter().find(|vote| {
+            vote.slot < root && !ancestors.contains(&vote.slot) && vote.expiration_slot() > root
+        }) {
+            warn!(
+                "Vote on slot {} locks out slots past root {} on another fork, not voting until slot {}",
+                vote.slot,
+                root,
+                vote.expiration_slot()
+            );
+            return;
+        }
+        self.lockouts.votes.retain(|vote| vote.slot > root);
+        self.recent_votes.retain(|vote| vote.slot > root);
+        self.lockouts.root_slot = Some(root);
+    }
+
+    /// Whether every vote in the tower, and its root, is on a fork that has been replayed
+    /// into `bank_forks`.  Lockouts can't be checked against `bank_forks` until it is, so a
+    /// restored tower must not be voted with before then.
+    pub fn is_consistent_with(&self, bank_forks: &BankForks) -> bool {
+        let is_replayed = |slot: u64| {
+            bank_forks
+                .get(slot)
+                .map(|bank| bank.is_frozen())
+                .unwrap_or(false)
+        };
+        // A vote on an abandoned fork is never replayed, but stops locking anything out once
+        // it expires
+        let latest_slot = bank_forks.working_bank().slot();
+        self.lockouts
+            .votes
+            .iter()
+            .all(|vote| is_replayed(vote.slot) || vote.is_expired(latest_slot))
+            && self.lockouts.root_slot.map(is_replayed).unwrap_or(true)
+    }
+
     pub fn has_voted(&self, slot: u64) -> bool {
         for vote in &self.lockouts.votes {
             if vote.slot == slot {
@@ -228,16 +367,26 @@ impl Tower {
     pub fn is_locked_out(&self, slot: u64, descendants: &HashMap<u64, HashSet<u64>>) -> bool {
         let mut lockouts = self.lockouts.clone();
         lockouts.process_slot_vote_unchecked(slot);
+        // A vote on a slot missing from `descendants` hasn't been replayed, so `slot` can't be
+        // shown to descend from it.  A restored tower has its votes older than the bank forks
+        // root rooted first, see `adjust_lockouts_to_root()`
         for vote in &lockouts.votes {
             if vote.slot == slot {
                 continue;
             }
-            if !descendants[&vote.slot].contains(&slot) {
+            if !descendants
+                .get(&vote.slot)
+                .map(|descendants| descendants.contains(&slot))
+                .unwrap_or(false)
+            {
                 return true;
             }
         }
         if let Some(root) = lockouts.root_slot {
-            !descendants[&root].contains(&slot)
+            !descendants
+                .get(&root)
+                .map(|descendants| descendants.contains(&slot))
+                .unwrap_or(false)
         } else {
             false
         }
@@ -365,6 +514,7 @@ impl Tower {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::genesis_utils::{create_genesis_block, GenesisBlockInfo};
 
     fn gen_stakes(stake_votes: &[(u64, &[u64])]) -> Vec<(Pubkey, (u64, Account))> {
         let mut stakes = vec![];
@@ -842,4 +992,153 @@ mod test {
     fn test_recent_votes_exact() {
         vote_and_check_recent(MAX_RECENT_VOTES)
     }
+
+    #[test]
+    fn test_save_and_restore_tower() {
+        let ledger_dir = tempfile::TempDir::new().unwrap();
+        let node_keypair = Keypair::new();
+        let mut tower = Tower {
+            node_pubkey: node_keypair.pubkey(),
+            path: Tower::get_filename(ledger_dir.path(), &node_keypair.pubkey()),
+            ..Tower::new_for_tests(VOTE_THRESHOLD_DEPTH, VOTE_THRESHOLD_SIZE)
+        };
+        for slot in 0..=(MAX_LOCKOUT_HISTORY as u64) {
+            tower.record_vote(slot, Hash::default());
+        }
+        tower.save(&node_keypair).unwrap();
+
+        let restored = Tower::restore(ledger_dir.path(), &node_keypair.pubkey()).unwrap();
+        assert_eq!(restored.lockouts, tower.lockouts);
+        assert_eq!(restored.recent_votes(), tower.recent_votes());
+        assert_eq!(restored.root(), Some(0));
+
+        // Another node has no saved tower
+        let other_pubkey = Pubkey::new_rand();
+        match Tower::restore(ledger_dir.path(), &other_pubkey) {
+            Err(Error::IO(ref err)) if err.kind() == std::io::ErrorKind::NotFound => (),
+            _ => panic!("expected no saved tower"),
+        }
+
+        // A tower signed by someone else is rejected
+        fs::copy(
+            &tower.path,
+            Tower::get_filename(ledger_dir.path(), &other_pubkey),
+        )
+        .unwrap();
+        match Tower::restore(ledger_dir.path(), &other_pubkey) {
+            Err(Error::TowerError(TowerError::InvalidSignature)) => (),
+            _ => panic!("expected an invalid signature"),
+        }
+    }
+
+    #[test]
+    fn test_is_consistent_with() {
+        let GenesisBlockInfo { genesis_block, .. } = create_genesis_block(10_000);
+        let bank0 = Bank::new(&genesis_block);
+        bank0.freeze();
+        let mut bank_forks = BankForks::new(0, bank0);
+        let bank1 = Bank::new_from_parent(&bank_forks[0], &Pubkey::default(), 1);
+        bank1.freeze();
+        bank_forks.insert(bank1);
+
+        let mut tower = Tower::new_for_tests(VOTE_THRESHOLD_DEPTH, VOTE_THRESHOLD_SIZE);
+        assert!(tower.is_consistent_with(&bank_forks));
+
+        tower.record_vote(0, Hash::default());
+        tower.record_vote(1, Hash::default());
+        assert!(tower.is_consistent_with(&bank_forks));
+
+        // Slot 2 hasn't been replayed yet
+        tower.record_vote(2, Hash::default());
+        assert!(!tower.is_consistent_with(&bank_forks));
+        let bank2 = Bank::new_from_parent(&bank_forks[1], &Pubkey::default(), 2);
+        bank_forks.insert(bank2);
+        assert!(!tower.is_consistent_with(&bank_forks));
+        bank_forks[2].freeze();
+        assert!(tower.is_consistent_with(&bank_forks));
+
+        // Votes older than the root are dropped from bank_forks until they're rooted
+        bank_forks.set_root(2, &None);
+        assert!(!tower.is_consistent_with(&bank_forks));
+        tower.adjust_lockouts_to_root(&bank_forks[bank_forks.root()]);
+        assert!(tower.is_consistent_with(&bank_forks));
+    }
+
+    #[test]
+    fn test_is_consistent_with_abandoned_fork() {
+        let GenesisBlockInfo { genesis_block, .. } = create_genesis_block(10_000);
+        let bank0 = Bank::new(&genesis_block);
+        bank0.freeze();
+        let mut bank_forks = BankForks::new(0, bank0);
+
+        // Slot 1 was on a fork that will never be replayed
+        let mut tower = Tower::new_for_tests(VOTE_THRESHOLD_DEPTH, VOTE_THRESHOLD_SIZE);
+        tower.record_vote(1, Hash::default());
+        assert!(!tower.is_consistent_with(&bank_forks));
+
+        // The vote stops blocking once it has expired
+        let bank3 = Bank::new_from_parent(&bank_forks[0], &Pubkey::default(), 3);
+        bank3.freeze();
+        bank_forks.insert(bank3);
+        assert!(!tower.is_consistent_with(&bank_forks));
+        let bank4 = Bank::new_from_parent(&bank_forks[0], &Pubkey::default(), 4);
+        bank4.freeze();
+        bank_forks.insert(bank4);
+        assert!(tower.is_consistent_with(&bank_forks));
+    }
+
+    #[test]
+    fn test_adjust_lockouts_to_root() {
+        let GenesisBlockInfo { genesis_block, .. } = create_genesis_block(10_000);
+        let bank0 = Arc::new(Bank::new(&genesis_block));
+        let bank1 = Bank::new_from_parent(&bank0, &Pubkey::default(), 1);
+        let mut tower = Tower::new_for_tests(VOTE_THRESHOLD_DEPTH, VOTE_THRESHOLD_SIZE);
+        for slot in 0..4 {
+            tower.record_vote(slot, Hash::default());
+        }
+        tower.adjust_lockouts_to_root(&bank1);
+        assert_eq!(tower.root(), Some(1));
+        assert_eq!(
+            tower
+                .lockouts
+                .votes
+                .iter()
+                .map(|vote| vote.slot)
+                .collect::<Vec<_>>(),
+            vec![2, 3]
+        );
+        assert!(tower.recent_votes.iter().all(|vote| vote.slot > 1));
+
+        // An older root doesn't undo a newer one
+        tower.adjust_lockouts_to_root(&bank0);
+        assert_eq!(tower.root(), Some(1));
+        assert_eq!(tower.lockouts.votes.len(), 2);
+
+        // Slot 1 isn't an ancestor of slot 2, and the vote on it locks out slot 3
+        let bank2 = Bank::new_from_parent(&bank0, &Pubkey::default(), 2);
+        let mut tower = Tower::new_for_tests(VOTE_THRESHOLD_DEPTH, VOTE_THRESHOLD_SIZE);
+        tower.record_vote(0, Hash::default());
+        tower.record_vote(1, Hash::default());
+        tower.adjust_lockouts_to_root(&bank2);
+        assert_eq!(tower.root(), None);
+        assert_eq!(tower.lockouts.votes.len(), 2);
+
+        // Once that vote has expired by the root, the tower can be adjusted
+        let bank3 = Bank::new_from_parent(&Arc::new(bank2), &Pubkey::default(), 3);
+        tower.adjust_lockouts_to_root(&bank3);
+        assert_eq!(tower.root(), Some(3));
+        assert!(tower.lockouts.votes.is_empty());
+    }
+
+    #[test]
+    fn test_is_locked_out_unreplayed_vote() {
+        let mut tower = Tower::new_for_tests(0, 0.67);
+        let descendants = vec![(0, vec![1].into_iter().collect())]
+            .into_iter()
+            .collect();
+        tower.record_vote(0, Hash::default());
+        assert!(!tower.is_locked_out(1, &descendants));
+        // Slot 1 isn't known to descend from the vote on slot 0
+        assert!(tower.is_locked_out(1, &HashMap::new()));
+    }
 }
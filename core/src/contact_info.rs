@@ -24,6 +24,8 @@ pub struct ContactInfo {
     pub tpu: SocketAddr,
     /// address to forward unprocessed transactions to
     pub tpu_forwards: SocketAddr,
+    /// transactions address over QUIC
+    pub tpu_quic: SocketAddr,
     /// storage data address
     pub storage_addr: SocketAddr,
     /// address to which to send JSON-RPC requests
@@ -79,6 +81,7 @@ impl Default for ContactInfo {
             tvu: socketaddr_any!(),
             tpu: socketaddr_any!(),
             tpu_forwards: socketaddr_any!(),
+            tpu_quic: socketaddr_any!(),
             storage_addr: socketaddr_any!(),
             rpc: socketaddr_any!(),
             rpc_pubsub: socketaddr_any!(),
@@ -95,6 +98,7 @@ impl ContactInfo {
         tvu: SocketAddr,
         tpu: SocketAddr,
         tpu_forwards: SocketAddr,
+        tpu_quic: SocketAddr,
         storage_addr: SocketAddr,
         rpc: SocketAddr,
         rpc_pubsub: SocketAddr,
@@ -107,6 +111,7 @@ impl ContactInfo {
             tvu,
             tpu,
             tpu_forwards,
+            tpu_quic,
             storage_addr,
             rpc,
             rpc_pubsub,
@@ -124,6 +129,7 @@ impl ContactInfo {
             socketaddr!("127.0.0.1:1238"),
             socketaddr!("127.0.0.1:1239"),
             socketaddr!("127.0.0.1:1240"),
+            socketaddr!("127.0.0.1:1241"),
             now,
         )
     }
@@ -142,6 +148,7 @@ impl ContactInfo {
             addr,
             addr,
             addr,
+            addr,
             0,
         )
     }
@@ -158,6 +165,7 @@ impl ContactInfo {
         let gossip_addr = next_port(&bind_addr, 1);
         let tvu_addr = next_port(&bind_addr, 2);
         let tpu_forwards_addr = next_port(&bind_addr, 3);
+        let tpu_quic_addr = next_port(&bind_addr, 4);
         let rpc_addr = SocketAddr::new(bind_addr.ip(), rpc_port::DEFAULT_RPC_PORT);
         let rpc_pubsub_addr = SocketAddr::new(bind_addr.ip(), rpc_port::DEFAULT_RPC_PUBSUB_PORT);
         Self::new(
@@ -166,6 +174,7 @@ impl ContactInfo {
             tvu_addr,
             tpu_addr,
             tpu_forwards_addr,
+            tpu_quic_addr,
             "0.0.0.0:0".parse().unwrap(),
             rpc_addr,
             rpc_pubsub_addr,
@@ -191,6 +200,7 @@ impl ContactInfo {
             daddr,
             daddr,
             daddr,
+            daddr,
             timestamp(),
         )
     }
@@ -234,6 +244,7 @@ impl Signable for ContactInfo {
             tvu: SocketAddr,
             tpu: SocketAddr,
             tpu_forwards: SocketAddr,
+            tpu_quic: SocketAddr,
             storage_addr: SocketAddr,
             rpc: SocketAddr,
             rpc_pubsub: SocketAddr,
@@ -248,6 +259,7 @@ impl Signable for ContactInfo {
             tpu: me.tpu,
             storage_addr: me.storage_addr,
             tpu_forwards: me.tpu_forwards,
+            tpu_quic: me.tpu_quic,
             rpc: me.rpc,
             rpc_pubsub: me.rpc_pubsub,
             wallclock: me.wallclock,
@@ -288,6 +300,7 @@ mod tests {
         assert!(ci.gossip.ip().is_unspecified());
         assert!(ci.tvu.ip().is_unspecified());
         assert!(ci.tpu_forwards.ip().is_unspecified());
+        assert!(ci.tpu_quic.ip().is_unspecified());
         assert!(ci.rpc.ip().is_unspecified());
         assert!(ci.rpc_pubsub.ip().is_unspecified());
         assert!(ci.tpu.ip().is_unspecified());
@@ -299,6 +312,7 @@ mod tests {
         assert!(ci.gossip.ip().is_multicast());
         assert!(ci.tvu.ip().is_multicast());
         assert!(ci.tpu_forwards.ip().is_multicast());
+        assert!(ci.tpu_quic.ip().is_multicast());
         assert!(ci.rpc.ip().is_multicast());
         assert!(ci.rpc_pubsub.ip().is_multicast());
         assert!(ci.tpu.ip().is_multicast());
@@ -311,6 +325,7 @@ mod tests {
         assert_eq!(ci.gossip, addr);
         assert!(ci.tvu.ip().is_unspecified());
         assert!(ci.tpu_forwards.ip().is_unspecified());
+        assert!(ci.tpu_quic.ip().is_unspecified());
         assert!(ci.rpc.ip().is_unspecified());
         assert!(ci.rpc_pubsub.ip().is_unspecified());
         assert!(ci.tpu.ip().is_unspecified());
@@ -324,6 +339,7 @@ mod tests {
         assert_eq!(ci.gossip.port(), 11);
         assert_eq!(ci.tvu.port(), 12);
         assert_eq!(ci.tpu_forwards.port(), 13);
+        assert_eq!(ci.tpu_quic.port(), 14);
         assert_eq!(ci.rpc.port(), 8899);
         assert_eq!(ci.rpc_pubsub.port(), 8900);
         assert!(ci.storage_addr.ip().is_unspecified());
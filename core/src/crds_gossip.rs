@@ -79,8 +79,7 @@ impl CrdsGossip {
         let mut prune_map: HashMap<Pubkey, HashSet<_>> = HashMap::new();
         for val in versioned {
             let origin = val.value.pubkey();
-            let hash = val.value_hash;
-            let peers = push.prune_received_cache(id, &origin, hash, stakes);
+            let peers = push.prune_received_cache(id, &origin, stakes);
             for from in peers {
                 prune_map.entry(from).or_default().insert(origin);
             }
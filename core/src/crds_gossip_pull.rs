@@ -50,6 +50,8 @@ impl CrdsFilter {
         }
     }
     // generates a vec of filters that together hold a complete set of Hashes
+    // each filter only holds the hashes whose first `mask_bits` bits match its mask, so the
+    // number of filters grows with `num_items` and each stays within `max_bytes`
     pub fn new_complete_set(num_items: usize, max_bytes: usize) -> Vec<Self> {
         let max_bits = (max_bytes * 8) as f64;
         let max_items = Self::max_items(max_bits, FALSE_RATE, KEYS);
@@ -589,6 +591,29 @@ mod test {
         assert!(filter.contains(&h));
     }
     #[test]
+    fn test_crds_filter_complete_set_false_rate() {
+        // As the table grows it's split across more filters, each still within its false rate
+        for num_items in &[1_000, 10_000, 50_000] {
+            let mut filters = CrdsFilter::new_complete_set(*num_items, PACKET_DATA_SIZE);
+            for i in 0..*num_items {
+                let h = hash(format!("item {}", i).as_bytes());
+                filters.iter_mut().for_each(|filter| filter.add(&h));
+            }
+            let num_probes = 10_000;
+            let false_positives = (0..num_probes)
+                .filter(|i| {
+                    let h = hash(format!("probe {}", i).as_bytes());
+                    filters
+                        .iter()
+                        .filter(|filter| filter.test_mask(&h))
+                        .any(|filter| filter.contains(&h))
+                })
+                .count();
+            assert!(*num_items < 10_000 || filters.len() > 1);
+            assert!((false_positives as f64 / num_probes as f64) < FALSE_RATE * 1.5);
+        }
+    }
+    #[test]
     fn test_mask() {
         for i in 0..16 {
             run_test_mask(i);
@@ -34,6 +34,21 @@ pub const CRDS_GOSSIP_PUSH_FANOUT: usize = 6;
 pub const CRDS_GOSSIP_PUSH_MSG_TIMEOUT_MS: u64 = 5000;
 pub const CRDS_GOSSIP_PRUNE_MSG_TIMEOUT_MS: u64 = 500;
 pub const CRDS_GOSSIP_PRUNE_STAKE_THRESHOLD_PCT: f64 = 0.15;
+/// New values received from an origin before the peers pushing them are considered for pruning
+pub const CRDS_GOSSIP_PRUNE_MIN_VALUES: usize = 3;
+/// Peers kept pushing an origin's values, whatever their stake
+pub const CRDS_GOSSIP_PRUNE_MIN_INGRESS_NODES: usize = 2;
+
+/// How useful the peers pushing an origin's values have been
+#[derive(Clone, Default)]
+struct ReceivedStats {
+    /// time the first of these values was received
+    start: u64,
+    /// new values received from the origin
+    num_values: usize,
+    /// peer => (values it pushed first, duplicates it pushed)
+    peers: HashMap<Pubkey, (usize, usize)>,
+}
 
 #[derive(Clone)]
 pub struct CrdsGossipPush {
@@ -43,8 +58,10 @@ pub struct CrdsGossipPush {
     active_set: IndexMap<Pubkey, Bloom<Pubkey>>,
     /// push message queue
     push_messages: HashMap<CrdsValueLabel, Hash>,
-    /// cache that tracks which validators a message was received from
-    received_cache: HashMap<Hash, (u64, HashSet<Pubkey>)>,
+    /// hash and time received of recent push messages
+    received_cache: HashMap<Hash, u64>,
+    /// origin => how useful the peers pushing its values have been
+    received_stats: HashMap<Pubkey, ReceivedStats>,
     pub num_active: usize,
     pub push_fanout: usize,
     pub msg_timeout: u64,
@@ -58,6 +75,7 @@ impl Default for CrdsGossipPush {
             active_set: IndexMap::new(),
             push_messages: HashMap::new(),
             received_cache: HashMap::new(),
+            received_stats: HashMap::new(),
             num_active: CRDS_GOSSIP_NUM_ACTIVE,
             push_fanout: CRDS_GOSSIP_PUSH_FANOUT,
             msg_timeout: CRDS_GOSSIP_PUSH_MSG_TIMEOUT_MS,
@@ -75,58 +93,101 @@ impl CrdsGossipPush {
         ((CRDS_GOSSIP_PRUNE_STAKE_THRESHOLD_PCT * min_path_stake as f64).round() as u64).max(1)
     }
 
+    /// Once enough of `origin`'s values have been received, choose which of the peers pushing
+    /// them to keep and return the others that pushed duplicates, to be pruned.  Peers are kept
+    /// in a shuffle weighted by stake and by how often they pushed a value first rather than a
+    /// duplicate, until the kept stake reaches the prune threshold.
     pub fn prune_received_cache(
         &mut self,
         self_pubkey: &Pubkey,
         origin: &Pubkey,
-        hash: Hash,
         stakes: &HashMap<Pubkey, u64>,
     ) -> Vec<Pubkey> {
-        let origin_stake = stakes.get(origin).unwrap_or(&0);
-        let self_stake = stakes.get(self_pubkey).unwrap_or(&0);
-        let cache = self.received_cache.get(&hash);
-        if cache.is_none() {
+        let enough_values = self
+            .received_stats
+            .get(origin)
+            .map(|stats| stats.num_values >= CRDS_GOSSIP_PRUNE_MIN_VALUES)
+            .unwrap_or(false);
+        if !enough_values {
             return Vec::new();
         }
+        // count from scratch, pruned peers won't push the origin's values anymore
+        let stats = self.received_stats.remove(origin).unwrap();
 
-        let peers = &cache.unwrap().1;
-        let peer_stake_total: u64 = peers.iter().map(|p| stakes.get(p).unwrap_or(&0)).sum();
+        let origin_stake = stakes.get(origin).unwrap_or(&0);
+        let self_stake = stakes.get(self_pubkey).unwrap_or(&0);
+        let peers: Vec<(Pubkey, (usize, usize))> = stats
+            .peers
+            .into_iter()
+            .filter(|(p, _)| p != self_pubkey && p != origin)
+            .collect();
+        let peer_stake_total: u64 = peers.iter().map(|(p, _)| stakes.get(p).unwrap_or(&0)).sum();
         let prune_stake_threshold = Self::prune_stake_threshold(*self_stake, *origin_stake);
         if peer_stake_total < prune_stake_threshold {
             return Vec::new();
         }
 
-        let staked_peers: Vec<(Pubkey, u64)> = peers
+        let max_weight = u64::from(u32::max_value());
+        let staked_peers: Vec<(Pubkey, u64, u64)> = peers
             .iter()
-            .filter_map(|p| stakes.get(p).map(|s| (*p, *s)))
-            .filter(|(_, s)| *s > 0)
+            .filter_map(|(p, (num_first, num_duplicates))| {
+                let stake = *stakes.get(p)?;
+                if stake == 0 {
+                    return None;
+                }
+                let weight = stake.min(max_weight).saturating_mul(*num_first as u64 + 1)
+                    / (*num_duplicates as u64 + 1);
+                Some((*p, stake, weight.min(max_weight).max(1)))
+            })
             .collect();
 
         let mut seed = [0; 32];
         seed[0..8].copy_from_slice(&thread_rng().next_u64().to_le_bytes());
         let shuffle = weighted_shuffle(
-            staked_peers.iter().map(|(_, stake)| *stake).collect_vec(),
+            staked_peers
+                .iter()
+                .map(|(_, _, weight)| *weight)
+                .collect_vec(),
             ChaChaRng::from_seed(seed),
         );
 
         let mut keep = HashSet::new();
         let mut peer_stake_sum = 0;
         for next in shuffle {
-            let (next_peer, next_stake) = staked_peers[next];
+            let (next_peer, next_stake, _) = staked_peers[next];
             keep.insert(next_peer);
             peer_stake_sum += next_stake;
-            if peer_stake_sum >= prune_stake_threshold {
+            if peer_stake_sum >= prune_stake_threshold
+                && keep.len() >= CRDS_GOSSIP_PRUNE_MIN_INGRESS_NODES
+            {
                 break;
             }
         }
 
         peers
-            .iter()
-            .filter(|p| !keep.contains(p))
-            .cloned()
+            .into_iter()
+            .filter(|(p, (_, num_duplicates))| *num_duplicates > 0 && !keep.contains(p))
+            .map(|(p, _)| p)
             .collect()
     }
 
+    fn record_received(&mut self, origin: &Pubkey, from: &Pubkey, first: bool, now: u64) {
+        let stats = self
+            .received_stats
+            .entry(*origin)
+            .or_insert_with(|| ReceivedStats {
+                start: now,
+                ..ReceivedStats::default()
+            });
+        let (num_first, num_duplicates) = stats.peers.entry(*from).or_default();
+        if first {
+            stats.num_values += 1;
+            *num_first += 1;
+        } else {
+            *num_duplicates += 1;
+        }
+    }
+
     /// process a push message to the network
     pub fn process_push_message(
         &mut self,
@@ -142,20 +203,21 @@ impl CrdsGossipPush {
             return Err(CrdsGossipError::PushMessageTimeout);
         }
         let label = value.label();
+        let origin = label.pubkey();
         let new_value = crds.new_versioned(now, value);
         let value_hash = new_value.value_hash;
-        if let Some((_, ref mut received_set)) = self.received_cache.get_mut(&value_hash) {
-            received_set.insert(from.clone());
+        if self.received_cache.contains_key(&value_hash) {
+            self.record_received(&origin, from, false, now);
             return Err(CrdsGossipError::PushMessageAlreadyReceived);
         }
         let old = crds.insert_versioned(new_value);
         if old.is_err() {
+            self.record_received(&origin, from, false, now);
             return Err(CrdsGossipError::PushMessageOldVersion);
         }
-        let mut received_set = HashSet::new();
-        received_set.insert(from.clone());
+        self.record_received(&origin, from, true, now);
         self.push_messages.insert(label, value_hash);
-        self.received_cache.insert(value_hash, (now, received_set));
+        self.received_cache.insert(value_hash, now);
         Ok(old.ok().and_then(|opt| opt))
     }
 
@@ -322,17 +384,20 @@ impl CrdsGossipPush {
         }
     }
 
-    /// purge received push message cache
+    /// purge received push message cache, and the peer stats of origins not heard from enough
+    /// since `min_time` to be considered for pruning
     pub fn purge_old_received_cache(&mut self, min_time: u64) {
         let old_msgs: Vec<Hash> = self
             .received_cache
             .iter()
-            .filter_map(|(k, (rcvd_time, _))| if *rcvd_time < min_time { Some(k) } else { None })
+            .filter_map(|(k, rcvd_time)| if *rcvd_time < min_time { Some(k) } else { None })
             .cloned()
             .collect();
         for k in old_msgs {
             self.received_cache.remove(&k);
         }
+        self.received_stats
+            .retain(|_, stats| stats.start >= min_time);
     }
 }
 
@@ -342,6 +407,22 @@ mod test {
     use crate::contact_info::ContactInfo;
     use solana_sdk::signature::Signable;
 
+    // Each of `peers`, in order, pushes `origin`'s contact info at each of `wallclocks`
+    fn push_values(
+        push: &mut CrdsGossipPush,
+        crds: &mut Crds,
+        origin: &Pubkey,
+        peers: &[Pubkey],
+        wallclocks: std::ops::Range<u64>,
+    ) {
+        for wallclock in wallclocks {
+            let value = CrdsValue::ContactInfo(ContactInfo::new_localhost(origin, wallclock));
+            for peer in peers {
+                let _ = push.process_push_message(crds, peer, value.clone(), wallclock);
+            }
+        }
+    }
+
     #[test]
     fn test_prune() {
         let mut crds = Crds::default();
@@ -353,42 +434,97 @@ mod test {
         stakes.insert(self_id, 100);
         stakes.insert(origin, 100);
 
-        let value = CrdsValue::ContactInfo(ContactInfo::new_localhost(&origin, 0));
-        let label = value.label();
-        let low_staked_peers = (0..10).map(|_| Pubkey::new_rand());
-        let mut low_staked_set = HashSet::new();
-        low_staked_peers.for_each(|p| {
-            let _ = push.process_push_message(&mut crds, &p, value.clone(), 0);
-            low_staked_set.insert(p);
-            stakes.insert(p, 1);
+        let low_staked_peers: Vec<_> = (0..10).map(|_| Pubkey::new_rand()).collect();
+        low_staked_peers.iter().for_each(|p| {
+            stakes.insert(*p, 1);
         });
-
-        let versioned = crds
-            .lookup_versioned(&label)
-            .expect("versioned value should exist");
-        let hash = versioned.value_hash;
-        let pruned = push.prune_received_cache(&self_id, &origin, hash, &stakes);
+        let min_values = CRDS_GOSSIP_PRUNE_MIN_VALUES as u64;
+        push_values(
+            &mut push,
+            &mut crds,
+            &origin,
+            &low_staked_peers,
+            0..min_values,
+        );
+        let pruned = push.prune_received_cache(&self_id, &origin, &stakes);
         assert!(
             pruned.is_empty(),
             "should not prune if min threshold has not been reached"
         );
 
+        // the high staked peer always pushes first
         let high_staked_peer = Pubkey::new_rand();
         let high_stake = CrdsGossipPush::prune_stake_threshold(100, 100) + 10;
         stakes.insert(high_staked_peer, high_stake);
-        let _ = push.process_push_message(&mut crds, &high_staked_peer, value.clone(), 0);
+        let peers = [vec![high_staked_peer], low_staked_peers.clone()].concat();
+        push_values(
+            &mut push,
+            &mut crds,
+            &origin,
+            &peers,
+            min_values..min_values * 2 - 1,
+        );
+        let pruned = push.prune_received_cache(&self_id, &origin, &stakes);
+        assert!(
+            pruned.is_empty(),
+            "should not prune before enough values have been received"
+        );
 
-        let pruned = push.prune_received_cache(&self_id, &origin, hash, &stakes);
+        push_values(
+            &mut push,
+            &mut crds,
+            &origin,
+            &peers,
+            min_values * 2 - 1..min_values * 2,
+        );
+        let pruned = push.prune_received_cache(&self_id, &origin, &stakes);
+        assert!(!pruned.is_empty());
         assert!(
-            pruned.len() < low_staked_set.len() + 1,
+            pruned.len() <= low_staked_peers.len() + 1 - CRDS_GOSSIP_PRUNE_MIN_INGRESS_NODES,
             "should not prune all peers"
         );
         pruned.iter().for_each(|p| {
             assert!(
-                low_staked_set.contains(p),
+                low_staked_peers.contains(p),
                 "only low staked peers should be pruned"
             );
         });
+
+        // counting starts over after pruning
+        assert!(push
+            .prune_received_cache(&self_id, &origin, &stakes)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_prune_only_duplicates() {
+        let mut crds = Crds::default();
+        let mut push = CrdsGossipPush::default();
+        let mut stakes = HashMap::new();
+
+        let self_id = Pubkey::new_rand();
+        let origin = Pubkey::new_rand();
+        stakes.insert(self_id, 100);
+        stakes.insert(origin, 100);
+        let peers: Vec<_> = (0..10).map(|_| Pubkey::new_rand()).collect();
+        peers.iter().for_each(|p| {
+            stakes.insert(*p, 100);
+        });
+
+        // the origin and the first peer only ever push new values, so they aren't pruned
+        let min_values = CRDS_GOSSIP_PRUNE_MIN_VALUES as u64;
+        push_values(&mut push, &mut crds, &origin, &[origin], 0..min_values);
+        push_values(
+            &mut push,
+            &mut crds,
+            &origin,
+            &peers,
+            min_values..min_values * 2,
+        );
+        let pruned = push.prune_received_cache(&self_id, &origin, &stakes);
+        assert!(!pruned.is_empty());
+        assert!(!pruned.contains(&origin));
+        assert!(!pruned.contains(&peers[0]));
     }
 
     #[test]
@@ -639,6 +775,7 @@ mod test {
 
         // purge the old pushed
         push.purge_old_received_cache(1);
+        assert!(push.received_stats.is_empty());
 
         // push it again
         assert_eq!(
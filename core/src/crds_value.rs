@@ -16,8 +16,21 @@ pub enum CrdsValue {
     Vote(Vote),
     /// * Merge Strategy - Latest wallclock is picked
     EpochSlots(EpochSlots),
+    /// * Merge Strategy - Latest wallclock is picked
+    DuplicateSlot(DuplicateSlot),
+    /// * Merge Strategy - Latest wallclock is picked
+    Version(Version),
 }
 
+/// Number of chunks a duplicate slot proof is split into, a whole proof doesn't fit in a
+/// gossip packet
+pub const MAX_DUPLICATE_SLOT_CHUNKS: u8 = 4;
+/// Number of duplicate slot proofs a node can have in the crds at once, proofs are keyed by
+/// `slot % MAX_DUPLICATE_SLOTS`
+pub const MAX_DUPLICATE_SLOTS: u8 = 8;
+/// Size of each chunk of a duplicate slot proof
+pub const DUPLICATE_SLOT_CHUNK_SIZE: usize = 1024;
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct EpochSlots {
     pub from: Pubkey,
@@ -68,6 +81,135 @@ impl Signable for EpochSlots {
     }
 }
 
+/// One chunk of a serialized `DuplicateSlotProof` for `slot`.  The chunks of a proof share
+/// the same wallclock.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct DuplicateSlot {
+    pub from: Pubkey,
+    pub slot: u64,
+    pub chunk_index: u8,
+    pub num_chunks: u8,
+    pub chunk: Vec<u8>,
+    pub signature: Signature,
+    pub wallclock: u64,
+}
+
+impl DuplicateSlot {
+    /// Split a serialized proof for `slot` into chunks
+    pub fn new_chunks(from: Pubkey, slot: u64, proof: &[u8], wallclock: u64) -> Vec<Self> {
+        let num_chunks = (proof.len() + DUPLICATE_SLOT_CHUNK_SIZE - 1) / DUPLICATE_SLOT_CHUNK_SIZE;
+        assert!(num_chunks <= MAX_DUPLICATE_SLOT_CHUNKS as usize);
+        proof
+            .chunks(DUPLICATE_SLOT_CHUNK_SIZE)
+            .enumerate()
+            .map(|(chunk_index, chunk)| Self {
+                from,
+                slot,
+                chunk_index: chunk_index as u8,
+                num_chunks: num_chunks as u8,
+                chunk: chunk.to_vec(),
+                signature: Signature::default(),
+                wallclock,
+            })
+            .collect()
+    }
+
+    /// Index of this proof in the sender's ring of duplicate slot proofs
+    pub fn ring_index(&self) -> u8 {
+        (self.slot % u64::from(MAX_DUPLICATE_SLOTS)) as u8
+    }
+}
+
+impl Signable for DuplicateSlot {
+    fn pubkey(&self) -> Pubkey {
+        self.from
+    }
+
+    fn signable_data(&self) -> Cow<[u8]> {
+        #[derive(Serialize)]
+        struct SignData<'a> {
+            slot: u64,
+            chunk_index: u8,
+            num_chunks: u8,
+            chunk: &'a [u8],
+            wallclock: u64,
+        }
+        let data = SignData {
+            slot: self.slot,
+            chunk_index: self.chunk_index,
+            num_chunks: self.num_chunks,
+            chunk: &self.chunk,
+            wallclock: self.wallclock,
+        };
+        Cow::Owned(serialize(&data).expect("unable to serialize DuplicateSlot"))
+    }
+
+    fn get_signature(&self) -> Signature {
+        self.signature
+    }
+
+    fn set_signature(&mut self, signature: Signature) {
+        self.signature = signature
+    }
+}
+
+/// The software version and feature set a node runs
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Version {
+    pub from: Pubkey,
+    pub version: String,
+    pub feature_set: u32,
+    pub signature: Signature,
+    pub wallclock: u64,
+}
+
+impl Version {
+    pub fn new(from: Pubkey, version: String, feature_set: u32, wallclock: u64) -> Self {
+        Self {
+            from,
+            version,
+            feature_set,
+            signature: Signature::default(),
+            wallclock,
+        }
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (feature set {})", self.version, self.feature_set)
+    }
+}
+
+impl Signable for Version {
+    fn pubkey(&self) -> Pubkey {
+        self.from
+    }
+
+    fn signable_data(&self) -> Cow<[u8]> {
+        #[derive(Serialize)]
+        struct SignData<'a> {
+            version: &'a str,
+            feature_set: u32,
+            wallclock: u64,
+        }
+        let data = SignData {
+            version: &self.version,
+            feature_set: self.feature_set,
+            wallclock: self.wallclock,
+        };
+        Cow::Owned(serialize(&data).expect("unable to serialize Version"))
+    }
+
+    fn get_signature(&self) -> Signature {
+        self.signature
+    }
+
+    fn set_signature(&mut self, signature: Signature) {
+        self.signature = signature
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct Vote {
     pub from: Pubkey,
@@ -121,6 +263,9 @@ pub enum CrdsValueLabel {
     ContactInfo(Pubkey),
     Vote(Pubkey),
     EpochSlots(Pubkey),
+    /// Sender, ring index and chunk index
+    DuplicateSlot(Pubkey, u8, u8),
+    Version(Pubkey),
 }
 
 impl fmt::Display for CrdsValueLabel {
@@ -129,6 +274,14 @@ impl fmt::Display for CrdsValueLabel {
             CrdsValueLabel::ContactInfo(_) => write!(f, "ContactInfo({})", self.pubkey()),
             CrdsValueLabel::Vote(_) => write!(f, "Vote({})", self.pubkey()),
             CrdsValueLabel::EpochSlots(_) => write!(f, "EpochSlots({})", self.pubkey()),
+            CrdsValueLabel::DuplicateSlot(_, ring_index, chunk_index) => write!(
+                f,
+                "DuplicateSlot({}, {}, {})",
+                self.pubkey(),
+                ring_index,
+                chunk_index
+            ),
+            CrdsValueLabel::Version(_) => write!(f, "Version({})", self.pubkey()),
         }
     }
 }
@@ -139,6 +292,8 @@ impl CrdsValueLabel {
             CrdsValueLabel::ContactInfo(p) => *p,
             CrdsValueLabel::Vote(p) => *p,
             CrdsValueLabel::EpochSlots(p) => *p,
+            CrdsValueLabel::DuplicateSlot(p, _, _) => *p,
+            CrdsValueLabel::Version(p) => *p,
         }
     }
 }
@@ -152,6 +307,8 @@ impl CrdsValue {
             CrdsValue::ContactInfo(contact_info) => contact_info.wallclock,
             CrdsValue::Vote(vote) => vote.wallclock,
             CrdsValue::EpochSlots(vote) => vote.wallclock,
+            CrdsValue::DuplicateSlot(duplicate_slot) => duplicate_slot.wallclock,
+            CrdsValue::Version(version) => version.wallclock,
         }
     }
     pub fn label(&self) -> CrdsValueLabel {
@@ -161,6 +318,12 @@ impl CrdsValue {
             }
             CrdsValue::Vote(vote) => CrdsValueLabel::Vote(vote.pubkey()),
             CrdsValue::EpochSlots(slots) => CrdsValueLabel::EpochSlots(slots.pubkey()),
+            CrdsValue::DuplicateSlot(duplicate_slot) => CrdsValueLabel::DuplicateSlot(
+                duplicate_slot.pubkey(),
+                duplicate_slot.ring_index(),
+                duplicate_slot.chunk_index,
+            ),
+            CrdsValue::Version(version) => CrdsValueLabel::Version(version.pubkey()),
         }
     }
     pub fn contact_info(&self) -> Option<&ContactInfo> {
@@ -181,13 +344,32 @@ impl CrdsValue {
             _ => None,
         }
     }
+    pub fn duplicate_slot(&self) -> Option<&DuplicateSlot> {
+        match self {
+            CrdsValue::DuplicateSlot(duplicate_slot) => Some(duplicate_slot),
+            _ => None,
+        }
+    }
+    pub fn version(&self) -> Option<&Version> {
+        match self {
+            CrdsValue::Version(version) => Some(version),
+            _ => None,
+        }
+    }
     /// Return all the possible labels for a record identified by Pubkey.
-    pub fn record_labels(key: &Pubkey) -> [CrdsValueLabel; 3] {
-        [
+    pub fn record_labels(key: &Pubkey) -> Vec<CrdsValueLabel> {
+        let mut labels = vec![
             CrdsValueLabel::ContactInfo(*key),
             CrdsValueLabel::Vote(*key),
             CrdsValueLabel::EpochSlots(*key),
-        ]
+            CrdsValueLabel::Version(*key),
+        ];
+        labels.extend((0..MAX_DUPLICATE_SLOTS).flat_map(|ring_index| {
+            (0..MAX_DUPLICATE_SLOT_CHUNKS).map(move |chunk_index| {
+                CrdsValueLabel::DuplicateSlot(*key, ring_index, chunk_index)
+            })
+        }));
+        labels
     }
 
     /// Returns the size (in bytes) of a CrdsValue
@@ -202,6 +384,8 @@ impl Signable for CrdsValue {
             CrdsValue::ContactInfo(contact_info) => contact_info.sign(keypair),
             CrdsValue::Vote(vote) => vote.sign(keypair),
             CrdsValue::EpochSlots(epoch_slots) => epoch_slots.sign(keypair),
+            CrdsValue::DuplicateSlot(duplicate_slot) => duplicate_slot.sign(keypair),
+            CrdsValue::Version(version) => version.sign(keypair),
         };
     }
 
@@ -210,6 +394,8 @@ impl Signable for CrdsValue {
             CrdsValue::ContactInfo(contact_info) => contact_info.verify(),
             CrdsValue::Vote(vote) => vote.verify(),
             CrdsValue::EpochSlots(epoch_slots) => epoch_slots.verify(),
+            CrdsValue::DuplicateSlot(duplicate_slot) => duplicate_slot.verify(),
+            CrdsValue::Version(version) => version.verify(),
         }
     }
 
@@ -218,6 +404,8 @@ impl Signable for CrdsValue {
             CrdsValue::ContactInfo(contact_info) => contact_info.pubkey(),
             CrdsValue::Vote(vote) => vote.pubkey(),
             CrdsValue::EpochSlots(epoch_slots) => epoch_slots.pubkey(),
+            CrdsValue::DuplicateSlot(duplicate_slot) => duplicate_slot.pubkey(),
+            CrdsValue::Version(version) => version.pubkey(),
         }
     }
 
@@ -230,6 +418,8 @@ impl Signable for CrdsValue {
             CrdsValue::ContactInfo(contact_info) => contact_info.get_signature(),
             CrdsValue::Vote(vote) => vote.get_signature(),
             CrdsValue::EpochSlots(epoch_slots) => epoch_slots.get_signature(),
+            CrdsValue::DuplicateSlot(duplicate_slot) => duplicate_slot.get_signature(),
+            CrdsValue::Version(version) => version.get_signature(),
         }
     }
 
@@ -249,13 +439,15 @@ mod test {
 
     #[test]
     fn test_labels() {
-        let mut hits = [false; 3];
+        let mut hits = [false; 5];
         // this method should cover all the possible labels
         for v in &CrdsValue::record_labels(&Pubkey::default()) {
             match v {
                 CrdsValueLabel::ContactInfo(_) => hits[0] = true,
                 CrdsValueLabel::Vote(_) => hits[1] = true,
                 CrdsValueLabel::EpochSlots(_) => hits[2] = true,
+                CrdsValueLabel::DuplicateSlot(_, _, _) => hits[3] = true,
+                CrdsValueLabel::Version(_) => hits[4] = true,
             }
         }
         assert!(hits.iter().all(|x| *x));
@@ -276,6 +468,26 @@ mod test {
         assert_eq!(v.wallclock(), 0);
         let key = v.clone().epoch_slots().unwrap().from;
         assert_eq!(v.label(), CrdsValueLabel::EpochSlots(key));
+
+        let chunks = DuplicateSlot::new_chunks(Pubkey::default(), 1, &[0; 1500], 0);
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[1].chunk.len(), 1500 - DUPLICATE_SLOT_CHUNK_SIZE);
+        let v = CrdsValue::DuplicateSlot(chunks[1].clone());
+        assert_eq!(v.wallclock(), 0);
+        let key = v.clone().duplicate_slot().unwrap().from;
+        assert_eq!(v.label(), CrdsValueLabel::DuplicateSlot(key, 1, 1));
+
+        // Proofs for different slots from the same node don't replace each other
+        let other = DuplicateSlot::new_chunks(Pubkey::default(), 2, &[0; 1500], 0);
+        assert_ne!(
+            CrdsValue::DuplicateSlot(other[1].clone()).label(),
+            v.label()
+        );
+
+        let v = CrdsValue::Version(Version::new(Pubkey::default(), "0.18.0".to_string(), 1, 0));
+        assert_eq!(v.wallclock(), 0);
+        let key = v.clone().version().unwrap().from;
+        assert_eq!(v.label(), CrdsValueLabel::Version(key));
     }
     #[test]
     fn test_signature() {
@@ -289,6 +501,17 @@ mod test {
         let btreeset: BTreeSet<u64> = vec![1, 2, 3, 6, 8].into_iter().collect();
         v = CrdsValue::EpochSlots(EpochSlots::new(keypair.pubkey(), 0, btreeset, timestamp()));
         verify_signatures(&mut v, &keypair, &wrong_keypair);
+        v = CrdsValue::DuplicateSlot(
+            DuplicateSlot::new_chunks(keypair.pubkey(), 1, &[1, 2, 3], timestamp()).remove(0),
+        );
+        verify_signatures(&mut v, &keypair, &wrong_keypair);
+        v = CrdsValue::Version(Version::new(
+            keypair.pubkey(),
+            "0.18.0".to_string(),
+            1,
+            timestamp(),
+        ));
+        verify_signatures(&mut v, &keypair, &wrong_keypair);
     }
 
     fn test_serialize_deserialize_value(value: &mut CrdsValue, keypair: &Keypair) {
@@ -757,7 +757,9 @@ pub mod test {
             let slot = slot_model.slot;
 
             for erasure_set in slot_model.chunks {
-                blocktree.write_shared_blobs(erasure_set.data).unwrap();
+                blocktree
+                    .write_shared_blobs(erasure_set.data, None)
+                    .unwrap();
 
                 for shared_coding_blob in erasure_set.coding.into_iter() {
                     let blob = shared_coding_blob.read().unwrap();
@@ -0,0 +1,259 @@
+//! The `fork_tree` module describes the forks ReplayStage is choosing between, so the
+//! reasons a validator picked a fork can be inspected over RPC or in a Graphviz/JSON dump.
+
+use crate::bank_forks::BankForks;
+use crate::consensus::Tower;
+use crate::result::Result;
+use solana_sdk::timing::Slot;
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+pub const FORK_TREE_JSON_FILENAME: &str = "fork_tree.json";
+pub const FORK_TREE_DOT_FILENAME: &str = "fork_tree.dot";
+
+/// A bank in the fork tree, with the confidence ReplayStage last computed for it
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForkTreeSlot {
+    pub slot: Slot,
+    pub parent: Option<Slot>,
+    pub is_frozen: bool,
+    pub is_dead: bool,
+    /// Only known once the bank is frozen
+    pub hash: Option<String>,
+    pub fork_stakes: u64,
+    pub epoch_stakes: u64,
+    pub lockouts: u64,
+    pub stake_weighted_lockouts: u128,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForkTree {
+    pub root: Slot,
+    pub tower_root: Option<Slot>,
+    pub last_vote: Option<Slot>,
+    /// Ordered by slot
+    pub slots: Vec<ForkTreeSlot>,
+}
+
+impl ForkTree {
+    pub fn new<F>(bank_forks: &BankForks, tower: &Tower, is_dead: F) -> Self
+    where
+        F: Fn(Slot) -> bool,
+    {
+        let mut slots: Vec<_> = bank_forks
+            .banks()
+            .values()
+            .map(|bank| {
+                let slot = bank.slot();
+                let mut fork_tree_slot = ForkTreeSlot {
+                    slot,
+                    parent: bank.parent().map(|parent| parent.slot()),
+                    is_frozen: bank.is_frozen(),
+                    is_dead: is_dead(slot),
+                    hash: if bank.is_frozen() {
+                        Some(bank.hash().to_string())
+                    } else {
+                        None
+                    },
+                    ..ForkTreeSlot::default()
+                };
+                if let Some(confidence) = bank_forks.get_fork_confidence(slot) {
+                    fork_tree_slot.fork_stakes = confidence.fork_stakes();
+                    fork_tree_slot.epoch_stakes = confidence.epoch_stakes();
+                    fork_tree_slot.lockouts = confidence.lockouts();
+                    fork_tree_slot.stake_weighted_lockouts = confidence.stake_weighted_lockouts();
+                }
+                fork_tree_slot
+            })
+            .collect();
+        slots.sort_by_key(|fork_tree_slot| fork_tree_slot.slot);
+
+        Self {
+            root: bank_forks.root(),
+            tower_root: tower.root(),
+            last_vote: tower.last_vote(),
+            slots,
+        }
+    }
+
+    /// Render the fork tree as a Graphviz digraph, with an edge from each parent to its
+    /// children.  The root is boxed, the last vote is blue and dead slots are red.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph fork_tree {\n");
+        for fork_tree_slot in &self.slots {
+            let slot = fork_tree_slot.slot;
+            let mut attributes = vec![format!(
+                "label=\"{}\\nstake {}/{}\\nlockouts {}\\nweight {}\"",
+                slot,
+                fork_tree_slot.fork_stakes,
+                fork_tree_slot.epoch_stakes,
+                fork_tree_slot.lockouts,
+                fork_tree_slot.stake_weighted_lockouts
+            )];
+            if slot == self.root {
+                attributes.push("shape=box".to_string());
+            }
+            if fork_tree_slot.is_dead {
+                attributes.push("color=red".to_string());
+            } else if Some(slot) == self.last_vote {
+                attributes.push("color=blue".to_string());
+            }
+            if !fork_tree_slot.is_frozen {
+                attributes.push("style=dashed".to_string());
+            }
+            writeln!(dot, "  \"{}\" [{}];", slot, attributes.join(",")).unwrap();
+            if let Some(parent) = fork_tree_slot.parent {
+                writeln!(dot, "  \"{}\" -> \"{}\";", parent, slot).unwrap();
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Write the fork tree to `FORK_TREE_JSON_FILENAME` and `FORK_TREE_DOT_FILENAME` in `dir`
+    pub fn dump(&self, dir: &Path) -> Result<()> {
+        fs::write(
+            dir.join(FORK_TREE_JSON_FILENAME),
+            serde_json::to_vec_pretty(self)?,
+        )?;
+        fs::write(dir.join(FORK_TREE_DOT_FILENAME), self.to_dot())?;
+        Ok(())
+    }
+}
+
+/// The fork tree most recently exported by ReplayStage, shared with the RPC service
+#[derive(Clone, Default)]
+pub struct ForkTreeExport {
+    fork_tree: Arc<RwLock<ForkTree>>,
+    dump_path: PathBuf,
+    enabled: bool,
+}
+
+impl ForkTreeExport {
+    /// `dump_path` is the directory the fork tree is dumped to.  ReplayStage only builds the
+    /// fork tree if `enabled`
+    pub fn new(dump_path: &Path, enabled: bool) -> Self {
+        Self {
+            fork_tree: Arc::default(),
+            dump_path: dump_path.to_path_buf(),
+            enabled,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn update(&self, fork_tree: ForkTree) {
+        *self.fork_tree.write().unwrap() = fork_tree;
+    }
+
+    pub fn fork_tree(&self) -> ForkTree {
+        self.fork_tree.read().unwrap().clone()
+    }
+
+    /// Dump the exported fork tree to `dump_path`, nothing is written unless `enabled`
+    pub fn dump(&self) -> Result<()> {
+        if !self.enabled {
+            return Ok(());
+        }
+        self.fork_tree().dump(&self.dump_path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::genesis_utils::{create_genesis_block, GenesisBlockInfo};
+    use solana_runtime::bank::Bank;
+    use solana_sdk::pubkey::Pubkey;
+
+    fn new_fork_tree() -> ForkTree {
+        let GenesisBlockInfo { genesis_block, .. } = create_genesis_block(10_000);
+        let bank0 = Bank::new(&genesis_block);
+        let mut bank_forks = BankForks::new(0, bank0);
+        let bank1 = Bank::new_from_parent(&bank_forks[0], &Pubkey::default(), 1);
+        let bank2 = Bank::new_from_parent(&bank_forks[0], &Pubkey::default(), 2);
+        bank_forks.insert(bank1);
+        bank_forks.insert(bank2);
+        bank_forks[0].freeze();
+        bank_forks[1].freeze();
+        bank_forks.cache_fork_confidence(1, 10, 30, 2);
+
+        let mut tower = Tower::new_for_tests(0, 0.67);
+        tower.record_vote(1, bank_forks[1].hash());
+        ForkTree::new(&bank_forks, &tower, |slot| slot == 2)
+    }
+
+    #[test]
+    fn test_fork_tree() {
+        let fork_tree = new_fork_tree();
+        assert_eq!(fork_tree.root, 0);
+        assert_eq!(fork_tree.last_vote, Some(1));
+        assert_eq!(fork_tree.tower_root, None);
+        assert_eq!(
+            fork_tree
+                .slots
+                .iter()
+                .map(|fork_tree_slot| (
+                    fork_tree_slot.slot,
+                    fork_tree_slot.parent,
+                    fork_tree_slot.is_frozen,
+                    fork_tree_slot.is_dead
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (0, None, true, false),
+                (1, Some(0), true, false),
+                (2, Some(0), false, true)
+            ]
+        );
+        assert_eq!(fork_tree.slots[1].fork_stakes, 10);
+        assert_eq!(fork_tree.slots[1].epoch_stakes, 30);
+        assert_eq!(fork_tree.slots[1].lockouts, 2);
+        assert!(fork_tree.slots[2].hash.is_none());
+
+        let dot = fork_tree.to_dot();
+        assert!(dot.starts_with("digraph fork_tree {\n"));
+        assert!(dot.contains("\"0\" -> \"1\";"));
+        assert!(dot.contains("\"0\" -> \"2\";"));
+        assert!(dot.contains("color=blue"));
+        assert!(dot.contains("color=red"));
+    }
+
+    #[test]
+    fn test_fork_tree_export_dump() {
+        let dump_dir = tempfile::TempDir::new().unwrap();
+        let fork_tree_export = ForkTreeExport::new(dump_dir.path(), true);
+        let fork_tree = new_fork_tree();
+        fork_tree_export.update(fork_tree.clone());
+        assert_eq!(fork_tree_export.fork_tree(), fork_tree);
+
+        fork_tree_export.dump().unwrap();
+        let json = fs::read(dump_dir.path().join(FORK_TREE_JSON_FILENAME)).unwrap();
+        assert_eq!(
+            serde_json::from_slice::<ForkTree>(&json).unwrap(),
+            fork_tree
+        );
+        assert_eq!(
+            fs::read_to_string(dump_dir.path().join(FORK_TREE_DOT_FILENAME)).unwrap(),
+            fork_tree.to_dot()
+        );
+    }
+
+    #[test]
+    fn test_fork_tree_export_dump_disabled() {
+        let dump_dir = tempfile::TempDir::new().unwrap();
+        let fork_tree_export = ForkTreeExport::new(dump_dir.path(), false);
+        fork_tree_export.update(new_fork_tree());
+
+        fork_tree_export.dump().unwrap();
+        assert!(!dump_dir.path().join(FORK_TREE_JSON_FILENAME).exists());
+        assert!(!dump_dir.path().join(FORK_TREE_DOT_FILENAME).exists());
+        assert_eq!(fs::read_dir(dump_dir.path()).unwrap().count(), 0);
+    }
+}
@@ -5,6 +5,7 @@ use crate::blocktree::Blocktree;
 use crate::cluster_info::ClusterInfo;
 use crate::cluster_info::FULLNODE_PORT_RANGE;
 use crate::contact_info::ContactInfo;
+use crate::crds_value::Version;
 use crate::service::Service;
 use crate::streamer;
 use rand::{thread_rng, Rng};
@@ -71,6 +72,23 @@ pub fn discover(
     find_node: Option<Pubkey>,
     gossip_addr: Option<&SocketAddr>,
 ) -> std::io::Result<(Vec<ContactInfo>, Vec<ContactInfo>)> {
+    let (nodes, replicators) =
+        discover_with_versions(entry_point, num_nodes, timeout, find_node, gossip_addr)?;
+    Ok((
+        nodes.into_iter().map(|(node, _version)| node).collect(),
+        replicators,
+    ))
+}
+
+/// Like `discover`, but also returns the software version each node gossiped, if it was
+/// received
+pub fn discover_with_versions(
+    entry_point: &SocketAddr,
+    num_nodes: Option<usize>,
+    timeout: Option<u64>,
+    find_node: Option<Pubkey>,
+    gossip_addr: Option<&SocketAddr>,
+) -> std::io::Result<(Vec<(ContactInfo, Option<Version>)>, Vec<ContactInfo>)> {
     let exit = Arc::new(AtomicBool::new(false));
     let (gossip_service, spy_ref) = make_gossip_node(entry_point, &exit, gossip_addr);
 
@@ -84,6 +102,17 @@ pub fn discover(
     exit.store(true, Ordering::Relaxed);
     gossip_service.join().unwrap();
 
+    let tvu_peers: Vec<_> = {
+        let spy = spy_ref.read().unwrap();
+        tvu_peers
+            .into_iter()
+            .map(|node| {
+                let version = spy.get_version_for_node(&node.id).cloned();
+                (node, version)
+            })
+            .collect()
+    };
+
     if met_criteria {
         info!(
             "discover success in {}s...\n{}",
@@ -0,0 +1,244 @@
+//! The `ledger_archive` module reads and writes a portable, versioned archive of a range of
+//! slots in a Blocktree, used to store and share ledgers outside of the database.
+//!
+//! An archive is a header followed by one record per slot.  Each record is a little-endian
+//! u64 length followed by that many bytes of bincode-encoded `ArchivedSlot`.
+
+use crate::blocktree::Blocktree;
+use crate::entry::Entry;
+use crate::result::{Error, Result};
+use bincode::{deserialize, serialize};
+use solana_sdk::timing::Slot;
+use std::io::{self, ErrorKind, Read, Write};
+
+/// Version of the archive format written by `export_ledger`
+pub const LEDGER_ARCHIVE_VERSION: u32 = 1;
+
+const LEDGER_ARCHIVE_MAGIC: [u8; 8] = *b"SOLLEDGR";
+
+// Largest record `read_record` accepts, so a corrupt length can't exhaust memory
+const MAX_RECORD_SIZE: u64 = 1 << 30;
+
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+struct ArchiveHeader {
+    magic: [u8; 8],
+    version: u32,
+}
+
+/// One slot of the archived ledger
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+pub struct ArchivedSlot {
+    pub slot: Slot,
+    pub parent_slot: Slot,
+    pub is_full: bool,
+    pub is_root: bool,
+    pub entries: Vec<Entry>,
+}
+
+fn write_record<W: Write, T: serde::Serialize>(writer: &mut W, value: &T) -> Result<()> {
+    let bytes = serialize(value)?;
+    writer.write_all(&(bytes.len() as u64).to_le_bytes())?;
+    writer.write_all(&bytes)?;
+    Ok(())
+}
+
+// Returns None at the end of the archive, a record cut short is an error
+fn read_record<R: Read, T: serde::de::DeserializeOwned>(reader: &mut R) -> Result<Option<T>> {
+    let mut len = [0u8; 8];
+    let mut read = 0;
+    while read < len.len() {
+        match reader.read(&mut len[read..]) {
+            Ok(0) if read == 0 => return Ok(None),
+            Ok(0) => return Err(get_io_error("Truncated ledger archive record length")),
+            Ok(n) => read += n,
+            Err(ref err) if err.kind() == ErrorKind::Interrupted => (),
+            Err(err) => return Err(Error::IO(err)),
+        }
+    }
+    let len = u64::from_le_bytes(len);
+    if len > MAX_RECORD_SIZE {
+        return Err(get_io_error(&format!(
+            "Ledger archive record of {} bytes is larger than the {} byte limit",
+            len, MAX_RECORD_SIZE
+        )));
+    }
+    let mut bytes = vec![0u8; len as usize];
+    reader.read_exact(&mut bytes)?;
+    Ok(Some(deserialize(&bytes)?))
+}
+
+fn get_io_error(error: &str) -> Error {
+    Error::IO(io::Error::new(ErrorKind::Other, error))
+}
+
+/// Write every slot in `start_slot..=end_slot` that has received entries to `writer`,
+/// returning the number of slots written
+pub fn export_ledger<W: Write>(
+    blocktree: &Blocktree,
+    start_slot: Slot,
+    end_slot: Slot,
+    mut writer: W,
+) -> Result<usize> {
+    write_record(
+        &mut writer,
+        &ArchiveHeader {
+            magic: LEDGER_ARCHIVE_MAGIC,
+            version: LEDGER_ARCHIVE_VERSION,
+        },
+    )?;
+
+    let mut num_slots = 0;
+    for (slot, meta) in blocktree
+        .slot_meta_iterator(start_slot)?
+        .take_while(|(slot, _)| *slot <= end_slot)
+    {
+        let entries = blocktree.get_slot_entries(slot, 0, None)?;
+        // Placeholder metas of slots only known as parents carry no entries
+        if entries.is_empty() {
+            continue;
+        }
+        write_record(
+            &mut writer,
+            &ArchivedSlot {
+                slot,
+                parent_slot: meta.parent_slot,
+                is_full: meta.is_full(),
+                is_root: blocktree.is_root(slot),
+                entries,
+            },
+        )?;
+        num_slots += 1;
+    }
+    writer.flush()?;
+
+    Ok(num_slots)
+}
+
+/// Write every slot of the archive in `reader` into `blocktree`, restoring roots, and
+/// return the number of slots imported
+pub fn import_ledger<R: Read>(blocktree: &Blocktree, mut reader: R) -> Result<usize> {
+    let header: ArchiveHeader =
+        read_record(&mut reader)?.ok_or_else(|| get_io_error("Empty ledger archive"))?;
+    if header.magic != LEDGER_ARCHIVE_MAGIC {
+        return Err(get_io_error("Not a ledger archive"));
+    }
+    if header.version != LEDGER_ARCHIVE_VERSION {
+        return Err(get_io_error(&format!(
+            "Unsupported ledger archive version {}, expected {}",
+            header.version, LEDGER_ARCHIVE_VERSION
+        )));
+    }
+
+    let mut num_slots = 0;
+    let mut roots = vec![];
+    while let Some(archived_slot) = read_record::<_, ArchivedSlot>(&mut reader)? {
+        blocktree.write_slot_entries(
+            archived_slot.slot,
+            archived_slot.parent_slot,
+            archived_slot.is_full,
+            &archived_slot.entries,
+        )?;
+        if archived_slot.is_root {
+            roots.push(archived_slot.slot);
+        }
+        num_slots += 1;
+    }
+    blocktree.set_roots(&roots)?;
+
+    Ok(num_slots)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blocktree::get_tmp_ledger_path;
+    use crate::entry::create_ticks;
+    use solana_sdk::hash::Hash;
+
+    #[test]
+    fn test_export_import_ledger() {
+        let ledger_path = get_tmp_ledger_path!();
+        let import_path = get_tmp_ledger_path!();
+        {
+            let blocktree = Blocktree::open(&ledger_path).unwrap();
+            let ticks = create_ticks(8, Hash::default());
+
+            // slot 0 <- slot 1 <- slot 3 (partial)
+            //       \
+            //        slot 2
+            blocktree
+                .write_slot_entries(0, 0, true, &ticks[0..2])
+                .unwrap();
+            blocktree
+                .write_slot_entries(1, 0, true, &ticks[2..4])
+                .unwrap();
+            blocktree
+                .write_slot_entries(2, 0, true, &ticks[4..6])
+                .unwrap();
+            blocktree
+                .write_slot_entries(3, 1, false, &ticks[6..7])
+                .unwrap();
+            blocktree.set_roots(&[0, 1]).unwrap();
+
+            let mut archive = vec![];
+            assert_eq!(export_ledger(&blocktree, 0, 3, &mut archive).unwrap(), 4);
+
+            let imported = Blocktree::open(&import_path).unwrap();
+            assert_eq!(import_ledger(&imported, &archive[..]).unwrap(), 4);
+            for slot in 0..4 {
+                let meta = blocktree.meta(slot).unwrap().unwrap();
+                let imported_meta = imported.meta(slot).unwrap().unwrap();
+                assert_eq!(imported_meta, meta);
+                assert_eq!(
+                    imported.get_slot_entries(slot, 0, None).unwrap(),
+                    blocktree.get_slot_entries(slot, 0, None).unwrap()
+                );
+                assert_eq!(imported.is_root(slot), blocktree.is_root(slot));
+            }
+
+            // A range only exports the slots in it
+            let mut archive = vec![];
+            assert_eq!(export_ledger(&blocktree, 1, 2, &mut archive).unwrap(), 2);
+        }
+        Blocktree::destroy(&ledger_path).expect("Expected successful database destruction");
+        Blocktree::destroy(&import_path).expect("Expected successful database destruction");
+    }
+
+    #[test]
+    fn test_import_ledger_bad_header() {
+        let ledger_path = get_tmp_ledger_path!();
+        {
+            let blocktree = Blocktree::open(&ledger_path).unwrap();
+            assert!(import_ledger(&blocktree, &[][..]).is_err());
+
+            let mut archive = vec![];
+            write_record(
+                &mut archive,
+                &ArchiveHeader {
+                    magic: LEDGER_ARCHIVE_MAGIC,
+                    version: LEDGER_ARCHIVE_VERSION + 1,
+                },
+            )
+            .unwrap();
+            assert!(import_ledger(&blocktree, &archive[..]).is_err());
+        }
+        Blocktree::destroy(&ledger_path).expect("Expected successful database destruction");
+    }
+
+    #[test]
+    fn test_read_record_bad_length() {
+        let mut archive = vec![];
+        write_record(&mut archive, &42u64).unwrap();
+        assert_eq!(read_record::<_, u64>(&mut &archive[..]).unwrap(), Some(42));
+        assert_eq!(read_record::<_, u64>(&mut &[][..]).unwrap(), None);
+
+        // a length cut short is not the end of the archive
+        assert!(read_record::<_, u64>(&mut &archive[..4]).is_err());
+
+        // nor is a record cut short
+        assert!(read_record::<_, u64>(&mut &archive[..12]).is_err());
+
+        let archive = (MAX_RECORD_SIZE + 1).to_le_bytes();
+        assert!(read_record::<_, u64>(&mut &archive[..]).is_err());
+    }
+}
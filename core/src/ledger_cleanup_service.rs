@@ -1,19 +1,48 @@
 //! The `ledger_cleanup_service` drops older ledger data to limit disk space usage
 
+use crate::bank_forks::BankForks;
 use crate::blocktree::Blocktree;
+use crate::long_term_storage::LongTermStorage;
 use crate::result::{Error, Result};
 use crate::service::Service;
+use crate::snapshot_utils;
+use solana_metrics::datapoint_info;
+use solana_runtime::status_cache::MAX_CACHE_ENTRIES;
 use solana_sdk::pubkey::Pubkey;
-use solana_sdk::timing::DEFAULT_SLOTS_PER_EPOCH;
+use solana_sdk::timing::{Slot, DEFAULT_SLOTS_PER_EPOCH};
+use std::cmp;
+use std::path::Path;
 use std::string::ToString;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::{Receiver, RecvTimeoutError};
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 use std::thread;
 use std::thread::{Builder, JoinHandle};
 use std::time::Duration;
 
 pub const DEFAULT_MAX_LEDGER_SLOTS: u64 = 3 * DEFAULT_SLOTS_PER_EPOCH;
+pub const DEFAULT_MIN_ROOT_SLOTS: u64 = MAX_CACHE_ENTRIES as u64;
+
+/// The policies that decide which slots the `LedgerCleanupService` purges
+#[derive(Clone, Debug)]
+pub struct LedgerCleanupConfig {
+    /// Keep at most this many slots below the latest completed slot
+    pub max_ledger_slots: Option<u64>,
+    /// Purge the oldest slots while the ledger occupies more than this many bytes on disk
+    pub max_ledger_bytes: Option<u64>,
+    /// Always keep this many slots below the latest root, as well as every slot above it
+    pub min_root_slots: u64,
+}
+
+impl Default for LedgerCleanupConfig {
+    fn default() -> Self {
+        Self {
+            max_ledger_slots: Some(DEFAULT_MAX_LEDGER_SLOTS),
+            max_ledger_bytes: None,
+            min_root_slots: DEFAULT_MIN_ROOT_SLOTS,
+        }
+    }
+}
 
 pub struct LedgerCleanupService {
     t_cleanup: JoinHandle<()>,
@@ -23,23 +52,27 @@ impl LedgerCleanupService {
     pub fn new(
         slot_full_receiver: Receiver<(u64, Pubkey)>,
         blocktree: Arc<Blocktree>,
-        max_ledger_slots: u64,
+        bank_forks: &Arc<RwLock<BankForks>>,
+        config: LedgerCleanupConfig,
+        long_term_storage: Option<Arc<dyn LongTermStorage>>,
         exit: &Arc<AtomicBool>,
     ) -> Self {
-        info!(
-            "LedgerCleanupService active. Max Ledger Slots {}",
-            max_ledger_slots
-        );
+        info!("LedgerCleanupService active. Config {:?}", config);
         let exit = exit.clone();
+        let bank_forks = bank_forks.clone();
         let t_cleanup = Builder::new()
             .name("solana-ledger-cleanup".to_string())
             .spawn(move || loop {
                 if exit.load(Ordering::Relaxed) {
                     break;
                 }
-                if let Err(e) =
-                    Self::cleanup_ledger(&slot_full_receiver, &blocktree, max_ledger_slots)
-                {
+                if let Err(e) = Self::cleanup_ledger(
+                    &slot_full_receiver,
+                    &blocktree,
+                    &bank_forks,
+                    &config,
+                    long_term_storage.as_ref().map(|storage| storage.as_ref()),
+                ) {
                     match e {
                         Error::RecvTimeoutError(RecvTimeoutError::Disconnected) => break,
                         Error::RecvTimeoutError(RecvTimeoutError::Timeout) => (),
@@ -54,15 +87,121 @@ impl LedgerCleanupService {
     fn cleanup_ledger(
         slot_full_receiver: &Receiver<(u64, Pubkey)>,
         blocktree: &Arc<Blocktree>,
-        max_ledger_slots: u64,
+        bank_forks: &RwLock<BankForks>,
+        config: &LedgerCleanupConfig,
+        long_term_storage: Option<&dyn LongTermStorage>,
     ) -> Result<()> {
-        let (slot, _) = slot_full_receiver.recv_timeout(Duration::from_secs(1))?;
-        if slot > max_ledger_slots {
-            //cleanup
-            blocktree.purge_slots(0, Some(slot - max_ledger_slots));
+        let (mut latest_slot, _) = slot_full_receiver.recv_timeout(Duration::from_secs(1))?;
+        while let Ok((slot, _)) = slot_full_receiver.try_recv() {
+            latest_slot = cmp::max(latest_slot, slot);
+        }
+
+        let lowest_slot = match blocktree.slot_meta_iterator(0)?.next() {
+            Some((slot, _)) => slot,
+            None => return Ok(()),
+        };
+        let storage_size = match config.max_ledger_bytes {
+            Some(_) => Some(blocktree.storage_size()?),
+            None => None,
+        };
+        let (root, snapshot_slot) = {
+            let bank_forks = bank_forks.read().unwrap();
+            let snapshot_slot = bank_forks
+                .snapshot_config()
+                .as_ref()
+                .and_then(|config| Self::oldest_snapshot_slot(config.snapshot_path()));
+            (bank_forks.root(), snapshot_slot)
+        };
+        // Blocks that haven't reached long-term storage yet must stay in the ledger
+        let first_unuploaded_slot = match long_term_storage {
+            Some(long_term_storage) => Some(
+                long_term_storage
+                    .last_uploaded_slot()?
+                    .map_or(0, |slot| slot + 1),
+            ),
+            None => None,
+        };
+
+        if let Some((purge_from, purge_to)) = Self::slots_to_purge(
+            config,
+            lowest_slot,
+            latest_slot,
+            storage_size,
+            root,
+            snapshot_slot,
+            first_unuploaded_slot,
+        ) {
+            blocktree.purge_slots(purge_from, Some(purge_to));
+            // Until the purged range is compacted its space still counts towards
+            //  `storage_size`, and the next pass would purge more slots than needed
+            blocktree.compact_storage(purge_from, purge_to)?;
+            datapoint_info!(
+                "ledger_cleanup_service",
+                ("purged_from_slot", purge_from as i64, i64),
+                ("purged_to_slot", purge_to as i64, i64),
+                ("latest_slot", latest_slot as i64, i64),
+                ("root", root as i64, i64),
+                ("storage_size", storage_size.unwrap_or(0) as i64, i64)
+            );
         }
         Ok(())
     }
+
+    // The oldest slot whose bank is in the latest snapshot package, see
+    // `BankForks::generate_snapshot()`
+    fn oldest_snapshot_slot(snapshot_path: &Path) -> Option<Slot> {
+        let slot_snapshot_paths = snapshot_utils::get_snapshot_paths(snapshot_path);
+        let start = slot_snapshot_paths.len().saturating_sub(MAX_CACHE_ENTRIES);
+        slot_snapshot_paths.get(start).map(|paths| paths.slot)
+    }
+
+    /// Returns the inclusive range of slots to purge from a ledger holding
+    /// `lowest_slot..=latest_slot`, if any.  Slots from `snapshot_slot` and
+    /// `first_unuploaded_slot` on are never purged
+    fn slots_to_purge(
+        config: &LedgerCleanupConfig,
+        lowest_slot: Slot,
+        latest_slot: Slot,
+        storage_size: Option<u64>,
+        root: Slot,
+        snapshot_slot: Option<Slot>,
+        first_unuploaded_slot: Option<Slot>,
+    ) -> Option<(Slot, Slot)> {
+        let mut purge_to = None;
+        if let Some(max_ledger_slots) = config.max_ledger_slots {
+            if latest_slot > max_ledger_slots {
+                purge_to = Some(latest_slot - max_ledger_slots);
+            }
+        }
+        if let (Some(max_ledger_bytes), Some(storage_size)) =
+            (config.max_ledger_bytes, storage_size)
+        {
+            if storage_size > max_ledger_bytes {
+                // Assume slots are about the same size and purge enough of the oldest ones
+                // to get back under budget
+                let num_slots = u128::from(latest_slot.saturating_sub(lowest_slot) + 1);
+                let excess_bytes = u128::from(storage_size - max_ledger_bytes);
+                let excess_slots = (num_slots * excess_bytes + u128::from(storage_size) - 1)
+                    / u128::from(storage_size);
+                let bytes_purge_to = lowest_slot + excess_slots as u64 - 1;
+                purge_to = Some(purge_to.map_or(bytes_purge_to, |purge_to| {
+                    cmp::max(purge_to, bytes_purge_to)
+                }));
+            }
+        }
+
+        let first_protected_slot = root
+            .saturating_sub(config.min_root_slots)
+            .min(snapshot_slot.unwrap_or(std::u64::MAX))
+            .min(first_unuploaded_slot.unwrap_or(std::u64::MAX));
+        if first_protected_slot == 0 {
+            return None;
+        }
+        purge_to
+            .map(|purge_to| cmp::min(purge_to, first_protected_slot - 1))
+            .filter(|purge_to| *purge_to >= lowest_slot)
+            .map(|purge_to| (lowest_slot, purge_to))
+    }
 }
 
 impl Service for LedgerCleanupService {
@@ -77,6 +216,8 @@ mod tests {
     use super::*;
     use crate::blocktree::get_tmp_ledger_path;
     use crate::blocktree::tests::make_many_slot_entries;
+    use crate::genesis_utils::{create_genesis_block, GenesisBlockInfo};
+    use solana_runtime::bank::Bank;
     use std::sync::mpsc::channel;
 
     #[test]
@@ -88,9 +229,21 @@ mod tests {
         let blocktree = Arc::new(blocktree);
         let (sender, receiver) = channel();
 
+        // Root bank forks at slot 50 so only the slot count limits the cleanup
+        let GenesisBlockInfo { genesis_block, .. } = create_genesis_block(10_000);
+        let bank = Bank::new(&genesis_block);
+        let bank = Bank::new_from_parent(&Arc::new(bank), &Pubkey::default(), 50);
+        let bank_forks = RwLock::new(BankForks::new(50, bank));
+        let config = LedgerCleanupConfig {
+            max_ledger_slots: Some(10),
+            max_ledger_bytes: None,
+            min_root_slots: 0,
+        };
+
         //send a signal to kill slots 0-40
         sender.send((50, Pubkey::default())).unwrap();
-        LedgerCleanupService::cleanup_ledger(&receiver, &blocktree, 10).unwrap();
+        LedgerCleanupService::cleanup_ledger(&receiver, &blocktree, &bank_forks, &config, None)
+            .unwrap();
 
         //check that 0-40 don't exist
         blocktree
@@ -101,4 +254,91 @@ mod tests {
         drop(blocktree);
         Blocktree::destroy(&blocktree_path).expect("Expected successful database destruction");
     }
+
+    #[test]
+    fn test_slots_to_purge_max_ledger_slots() {
+        let config = LedgerCleanupConfig {
+            max_ledger_slots: Some(10),
+            max_ledger_bytes: None,
+            min_root_slots: 5,
+        };
+        let slots_to_purge = |lowest_slot, latest_slot, root, snapshot_slot| {
+            LedgerCleanupService::slots_to_purge(
+                &config,
+                lowest_slot,
+                latest_slot,
+                None,
+                root,
+                snapshot_slot,
+                None,
+            )
+        };
+
+        assert_eq!(slots_to_purge(0, 10, 10, None), None);
+        assert_eq!(slots_to_purge(0, 50, 50, None), Some((0, 40)));
+        assert_eq!(slots_to_purge(41, 50, 50, None), None);
+
+        // Slots above the root and `min_root_slots` below it are kept
+        assert_eq!(slots_to_purge(0, 50, 30, None), Some((0, 24)));
+        assert_eq!(slots_to_purge(0, 50, 5, None), None);
+
+        // As are the slots in the latest snapshot
+        assert_eq!(slots_to_purge(0, 50, 50, Some(20)), Some((0, 19)));
+        assert_eq!(slots_to_purge(20, 50, 50, Some(20)), None);
+
+        // And the slots that haven't been uploaded to long-term storage
+        let purge_uploaded = |first_unuploaded_slot| {
+            LedgerCleanupService::slots_to_purge(
+                &config,
+                0,
+                50,
+                None,
+                50,
+                None,
+                first_unuploaded_slot,
+            )
+        };
+        assert_eq!(purge_uploaded(Some(31)), Some((0, 30)));
+        assert_eq!(purge_uploaded(Some(0)), None);
+    }
+
+    #[test]
+    fn test_slots_to_purge_max_ledger_bytes() {
+        let config = LedgerCleanupConfig {
+            max_ledger_slots: None,
+            max_ledger_bytes: Some(1000),
+            min_root_slots: 0,
+        };
+        let slots_to_purge = |storage_size, root| {
+            LedgerCleanupService::slots_to_purge(
+                &config,
+                0,
+                99,
+                Some(storage_size),
+                root,
+                None,
+                None,
+            )
+        };
+
+        // Under budget
+        assert_eq!(slots_to_purge(1000, 99), None);
+
+        // 25% over budget purges the oldest 25% of the slots, rounded up
+        assert_eq!(slots_to_purge(1250, 99), Some((0, 19)));
+        assert_eq!(slots_to_purge(1001, 99), Some((0, 0)));
+
+        // but never the protected slots
+        assert_eq!(slots_to_purge(1250, 10), Some((0, 9)));
+
+        // The larger of the slot and byte limits wins
+        let config = LedgerCleanupConfig {
+            max_ledger_slots: Some(90),
+            ..config
+        };
+        assert_eq!(
+            LedgerCleanupService::slots_to_purge(&config, 0, 99, Some(1001), 99, None, None),
+            Some((0, 9))
+        );
+    }
 }
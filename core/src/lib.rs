@@ -35,18 +35,25 @@ pub mod cuda_runtime;
 pub mod entry;
 pub mod erasure;
 pub mod fetch_stage;
+pub mod fork_tree;
 pub mod gen_keys;
 pub mod genesis_utils;
 pub mod gossip_service;
 pub mod leader_schedule;
 pub mod leader_schedule_cache;
 pub mod leader_schedule_utils;
+pub mod ledger_archive;
 pub mod ledger_cleanup_service;
 pub mod local_vote_signer_service;
+pub mod long_term_storage;
+pub mod long_term_storage_service;
+pub mod optimistic_confirmation_service;
 pub mod packet;
+pub mod packet_qos;
 pub mod poh;
 pub mod poh_recorder;
 pub mod poh_service;
+pub mod quic_streamer;
 pub mod recvmmsg;
 pub mod repair_service;
 pub mod replay_stage;
@@ -0,0 +1,317 @@
+//! The `long_term_storage` module defines the store that rooted blocks and transaction
+//! statuses are uploaded to, so they can still be served after they have been purged
+//! from the Blocktree.
+
+use crate::blocktree::Blocktree;
+use crate::result::Result;
+use bincode::{deserialize, serialize};
+use solana_runtime::bank::Bank;
+use solana_sdk::hash::Hash;
+use solana_sdk::signature::Signature;
+use solana_sdk::timing::Slot;
+use solana_sdk::transaction::{self, Transaction};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+/// A rooted block and the status of each of its transactions, if one was known when the
+/// block was read
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfirmedBlock {
+    pub slot: Slot,
+    pub parent_slot: Slot,
+    pub blockhash: Hash,
+    pub previous_blockhash: Hash,
+    pub transactions: Vec<(Transaction, Option<transaction::Result<()>>)>,
+}
+
+/// The outcome of a transaction in a rooted block
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransactionStatus {
+    pub slot: Slot,
+    pub status: transaction::Result<()>,
+}
+
+/// A store for rooted blocks that outlives the Blocktree
+pub trait LongTermStorage: Send + Sync {
+    /// Store `block`, along with the status of each of its transactions
+    fn upload_block(&self, block: &ConfirmedBlock) -> Result<()>;
+
+    fn get_block(&self, slot: Slot) -> Result<Option<ConfirmedBlock>>;
+
+    fn get_transaction_status(&self, signature: &Signature) -> Result<Option<TransactionStatus>>;
+
+    /// The slot of the most recently uploaded block, uploads happen in slot order
+    fn last_uploaded_slot(&self) -> Result<Option<Slot>>;
+}
+
+/// Record the status of each transaction in `bank`'s slot in `blocktree` as the slot is
+/// rooted, so it is still known after the status cache has moved past the slot
+pub fn record_transaction_statuses(blocktree: &Blocktree, bank: &Bank) -> Result<()> {
+    let slot = bank.slot();
+    let statuses: Vec<_> = blocktree
+        .get_slot_entries(slot, 0, None)?
+        .into_iter()
+        .flat_map(|entry| entry.transactions)
+        .map(|transaction| {
+            transaction
+                .signatures
+                .get(0)
+                .and_then(|signature| bank.get_signature_status(signature))
+        })
+        .collect();
+    blocktree.write_transaction_statuses(slot, &statuses)
+}
+
+/// Read the rooted block at `slot` from `blocktree`.  Transaction statuses come from those
+/// recorded in `blocktree`, falling back to `bank`.  Returns None if the slot isn't a root or
+/// its entries are no longer available.
+pub fn get_confirmed_block(
+    blocktree: &Blocktree,
+    bank: &Bank,
+    slot: Slot,
+) -> Result<Option<ConfirmedBlock>> {
+    if !blocktree.is_root(slot) {
+        return Ok(None);
+    }
+    let meta = match blocktree.meta(slot)? {
+        Some(meta) => meta,
+        None => return Ok(None),
+    };
+    let entries = blocktree.get_slot_entries(slot, 0, None)?;
+    let blockhash = match entries.last() {
+        Some(entry) => entry.hash,
+        None => return Ok(None),
+    };
+    // The parent may already have been purged
+    let previous_blockhash = blocktree
+        .get_slot_entries(meta.parent_slot, 0, None)?
+        .last()
+        .map(|entry| entry.hash)
+        .unwrap_or_default();
+
+    let transactions = entries
+        .into_iter()
+        .flat_map(|entry| entry.transactions)
+        .enumerate()
+        .map(|(index, transaction)| {
+            let status = match blocktree.get_transaction_status(slot, index as u64)? {
+                Some(status) => Some(status),
+                None => transaction
+                    .signatures
+                    .get(0)
+                    .and_then(|signature| bank.get_signature_status(signature)),
+            };
+            Ok((transaction, status))
+        })
+        .collect::<Result<_>>()?;
+
+    Ok(Some(ConfirmedBlock {
+        slot,
+        parent_slot: meta.parent_slot,
+        blockhash,
+        previous_blockhash,
+        transactions,
+    }))
+}
+
+/// A `LongTermStorage` that keeps blocks and transaction statuses in files under a directory
+pub struct FileLongTermStorage {
+    path: PathBuf,
+}
+
+impl FileLongTermStorage {
+    pub fn new(path: &Path) -> Result<Self> {
+        fs::create_dir_all(path.join("blocks"))?;
+        fs::create_dir_all(path.join("transactions"))?;
+        Ok(Self {
+            path: path.to_path_buf(),
+        })
+    }
+
+    fn block_path(&self, slot: Slot) -> PathBuf {
+        self.path.join("blocks").join(slot.to_string())
+    }
+
+    fn transaction_path(&self, signature: &Signature) -> PathBuf {
+        self.path.join("transactions").join(signature.to_string())
+    }
+
+    fn last_uploaded_slot_path(&self) -> PathBuf {
+        self.path.join("last_uploaded_slot")
+    }
+
+    // Returns None if there is no file at `path`
+    fn read<T: serde::de::DeserializeOwned>(path: &Path) -> Result<Option<T>> {
+        match fs::read(path) {
+            Ok(bytes) => Ok(Some(deserialize(&bytes)?)),
+            Err(ref err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    // Writes through a temporary file, so `path` never holds a partial write
+    fn write(path: &Path, bytes: &[u8]) -> Result<()> {
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, bytes)?;
+        fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+}
+
+impl LongTermStorage for FileLongTermStorage {
+    fn upload_block(&self, block: &ConfirmedBlock) -> Result<()> {
+        for (transaction, status) in &block.transactions {
+            if let (Some(signature), Some(status)) = (transaction.signatures.get(0), status) {
+                Self::write(
+                    &self.transaction_path(signature),
+                    &serialize(&TransactionStatus {
+                        slot: block.slot,
+                        status: status.clone(),
+                    })?,
+                )?;
+            }
+        }
+        Self::write(&self.block_path(block.slot), &serialize(block)?)?;
+        // Only once everything else in the block is in place
+        Self::write(&self.last_uploaded_slot_path(), &serialize(&block.slot)?)?;
+        Ok(())
+    }
+
+    fn get_block(&self, slot: Slot) -> Result<Option<ConfirmedBlock>> {
+        Self::read(&self.block_path(slot))
+    }
+
+    fn get_transaction_status(&self, signature: &Signature) -> Result<Option<TransactionStatus>> {
+        Self::read(&self.transaction_path(signature))
+    }
+
+    fn last_uploaded_slot(&self) -> Result<Option<Slot>> {
+        Self::read(&self.last_uploaded_slot_path())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blocktree::get_tmp_ledger_path;
+    use crate::entry::{next_entry_mut, Entry};
+    use crate::genesis_utils::{create_genesis_block, GenesisBlockInfo};
+    use solana_sdk::pubkey::Pubkey;
+    use solana_sdk::signature::{Keypair, KeypairUtil};
+    use solana_sdk::system_transaction;
+    use solana_sdk::transaction::TransactionError;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_file_long_term_storage() {
+        let storage_dir = tempfile::TempDir::new().unwrap();
+        let storage = FileLongTermStorage::new(storage_dir.path()).unwrap();
+        assert_eq!(storage.last_uploaded_slot().unwrap(), None);
+        assert_eq!(storage.get_block(1).unwrap(), None);
+
+        let keypair = Keypair::new();
+        let ok_tx = system_transaction::transfer(&keypair, &Pubkey::new_rand(), 1, Hash::default());
+        let failed_tx =
+            system_transaction::transfer(&keypair, &Pubkey::new_rand(), 2, Hash::default());
+        let unknown_tx =
+            system_transaction::transfer(&keypair, &Pubkey::new_rand(), 3, Hash::default());
+        let block = ConfirmedBlock {
+            slot: 1,
+            parent_slot: 0,
+            blockhash: Hash::new(&[1; 32]),
+            previous_blockhash: Hash::default(),
+            transactions: vec![
+                (ok_tx.clone(), Some(Ok(()))),
+                (failed_tx.clone(), Some(Err(TransactionError::AccountInUse))),
+                (unknown_tx.clone(), None),
+            ],
+        };
+        storage.upload_block(&block).unwrap();
+
+        assert_eq!(storage.last_uploaded_slot().unwrap(), Some(1));
+        assert!(!storage_dir.path().join("last_uploaded_slot.tmp").exists());
+        assert_eq!(storage.get_block(1).unwrap(), Some(block));
+        assert_eq!(
+            storage
+                .get_transaction_status(&ok_tx.signatures[0])
+                .unwrap(),
+            Some(TransactionStatus {
+                slot: 1,
+                status: Ok(())
+            })
+        );
+        assert_eq!(
+            storage
+                .get_transaction_status(&failed_tx.signatures[0])
+                .unwrap(),
+            Some(TransactionStatus {
+                slot: 1,
+                status: Err(TransactionError::AccountInUse)
+            })
+        );
+        assert_eq!(
+            storage
+                .get_transaction_status(&unknown_tx.signatures[0])
+                .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_get_confirmed_block() {
+        let GenesisBlockInfo {
+            genesis_block,
+            mint_keypair,
+            ..
+        } = create_genesis_block(10_000);
+        let bank0 = Arc::new(Bank::new(&genesis_block));
+        let bank = Bank::new_from_parent(&bank0, &Pubkey::default(), 1);
+        let ledger_path = get_tmp_ledger_path!();
+        {
+            let blocktree = Blocktree::open(&ledger_path).unwrap();
+            let mut hash = bank.last_blockhash();
+            let tx = system_transaction::transfer(&mint_keypair, &Pubkey::new_rand(), 42, hash);
+            bank.process_transaction(&tx).unwrap();
+
+            let parent_entries = vec![next_entry_mut(&mut hash, 1, vec![])];
+            let entries: Vec<Entry> = vec![
+                next_entry_mut(&mut hash, 1, vec![tx.clone()]),
+                next_entry_mut(&mut hash, 1, vec![]),
+            ];
+            blocktree
+                .write_slot_entries(0, 0, true, &parent_entries)
+                .unwrap();
+            blocktree.write_slot_entries(1, 0, true, &entries).unwrap();
+
+            // Only rooted slots are confirmed
+            assert_eq!(get_confirmed_block(&blocktree, &bank, 1).unwrap(), None);
+
+            blocktree.set_roots(&[0, 1]).unwrap();
+            assert_eq!(
+                get_confirmed_block(&blocktree, &bank, 1).unwrap(),
+                Some(ConfirmedBlock {
+                    slot: 1,
+                    parent_slot: 0,
+                    blockhash: entries[1].hash,
+                    previous_blockhash: parent_entries[0].hash,
+                    transactions: vec![(tx.clone(), Some(Ok(())))],
+                })
+            );
+
+            // Once recorded, statuses outlive the bank that knew them
+            record_transaction_statuses(&blocktree, &bank).unwrap();
+            let new_bank = Bank::new(&genesis_block);
+            assert_eq!(new_bank.get_signature_status(&tx.signatures[0]), None);
+            assert_eq!(
+                get_confirmed_block(&blocktree, &new_bank, 1)
+                    .unwrap()
+                    .unwrap()
+                    .transactions,
+                vec![(tx, Some(Ok(())))]
+            );
+        }
+        Blocktree::destroy(&ledger_path).expect("Expected successful database destruction");
+    }
+}
@@ -0,0 +1,173 @@
+//! The `long_term_storage_service` uploads each new rooted block from the Blocktree to a
+//! `LongTermStorage`, along with the transaction statuses ReplayStage recorded as it was rooted
+
+use crate::bank_forks::BankForks;
+use crate::blocktree::Blocktree;
+use crate::long_term_storage::{get_confirmed_block, LongTermStorage};
+use crate::result::Result;
+use crate::service::Service;
+use solana_sdk::timing::Slot;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::thread::{self, sleep, Builder, JoinHandle};
+use std::time::Duration;
+
+// Upper bound on the blocks uploaded per pass, so a lagging uploader still checks for exit
+const MAX_UPLOADS_PER_PASS: usize = 64;
+
+pub struct LongTermStorageService {
+    t_upload: JoinHandle<()>,
+}
+
+impl LongTermStorageService {
+    pub fn new(
+        blocktree: Arc<Blocktree>,
+        bank_forks: Arc<RwLock<BankForks>>,
+        long_term_storage: Arc<dyn LongTermStorage>,
+        exit: &Arc<AtomicBool>,
+    ) -> Self {
+        let exit = exit.clone();
+        let t_upload = Builder::new()
+            .name("solana-long-term-storage".to_string())
+            .spawn(move || {
+                let mut next_slot = match long_term_storage.last_uploaded_slot() {
+                    Ok(last_uploaded_slot) => last_uploaded_slot.map_or(0, |slot| slot + 1),
+                    Err(e) => {
+                        warn!("Unable to read the last uploaded slot: {:?}", e);
+                        return;
+                    }
+                };
+                info!("LongTermStorageService active. Next slot {}", next_slot);
+                loop {
+                    if exit.load(Ordering::Relaxed) {
+                        break;
+                    }
+                    match Self::upload_rooted_blocks(
+                        &blocktree,
+                        &bank_forks,
+                        long_term_storage.as_ref(),
+                        next_slot,
+                    ) {
+                        Ok(slot) => next_slot = slot,
+                        Err(e) => info!("Error from upload_rooted_blocks: {:?}", e),
+                    }
+                    sleep(Duration::from_millis(500));
+                }
+            })
+            .unwrap();
+        Self { t_upload }
+    }
+
+    /// Upload the rooted blocks from `next_slot` up to the current root, returning the next
+    /// slot to upload from
+    pub fn upload_rooted_blocks(
+        blocktree: &Blocktree,
+        bank_forks: &RwLock<BankForks>,
+        long_term_storage: &dyn LongTermStorage,
+        mut next_slot: Slot,
+    ) -> Result<Slot> {
+        let root_bank = {
+            let bank_forks = bank_forks.read().unwrap();
+            bank_forks[bank_forks.root()].clone()
+        };
+        let root = root_bank.slot();
+
+        let rooted_slots: Vec<_> = blocktree
+            .slot_meta_iterator(next_slot)?
+            .map(|(slot, _)| slot)
+            .take_while(|slot| *slot <= root)
+            .filter(|slot| blocktree.is_root(*slot))
+            .take(MAX_UPLOADS_PER_PASS)
+            .collect();
+        if rooted_slots.len() < MAX_UPLOADS_PER_PASS {
+            // Everything up to the root is about to be uploaded
+            next_slot = next_slot.max(root + 1);
+        }
+        for slot in rooted_slots {
+            if let Some(block) = get_confirmed_block(blocktree, &root_bank, slot)? {
+                long_term_storage.upload_block(&block)?;
+                debug!("uploaded block {} to long-term storage", slot);
+            }
+            next_slot = next_slot.max(slot + 1);
+        }
+        Ok(next_slot)
+    }
+}
+
+impl Service for LongTermStorageService {
+    type JoinReturnType = ();
+
+    fn join(self) -> thread::Result<()> {
+        self.t_upload.join()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blocktree::get_tmp_ledger_path;
+    use crate::entry::create_ticks;
+    use crate::genesis_utils::{create_genesis_block, GenesisBlockInfo};
+    use crate::long_term_storage::FileLongTermStorage;
+    use solana_runtime::bank::Bank;
+    use solana_sdk::pubkey::Pubkey;
+
+    #[test]
+    fn test_upload_rooted_blocks() {
+        let GenesisBlockInfo { genesis_block, .. } = create_genesis_block(10_000);
+        let bank0 = Bank::new(&genesis_block);
+        let bank_forks = RwLock::new(BankForks::new(0, bank0));
+        let storage_dir = tempfile::TempDir::new().unwrap();
+        let long_term_storage = FileLongTermStorage::new(storage_dir.path()).unwrap();
+        let ledger_path = get_tmp_ledger_path!();
+        {
+            let blocktree = Blocktree::open(&ledger_path).unwrap();
+            let ticks = create_ticks(3, genesis_block.hash());
+            blocktree
+                .write_slot_entries(0, 0, true, &ticks[0..1])
+                .unwrap();
+            blocktree
+                .write_slot_entries(1, 0, true, &ticks[1..2])
+                .unwrap();
+            blocktree
+                .write_slot_entries(2, 1, true, &ticks[2..3])
+                .unwrap();
+            blocktree.set_roots(&[0, 1, 2]).unwrap();
+
+            // Only blocks up to the bank forks root are uploaded
+            assert_eq!(
+                LongTermStorageService::upload_rooted_blocks(
+                    &blocktree,
+                    &bank_forks,
+                    &long_term_storage,
+                    0
+                )
+                .unwrap(),
+                1
+            );
+            assert_eq!(long_term_storage.last_uploaded_slot().unwrap(), Some(0));
+            assert!(long_term_storage.get_block(1).unwrap().is_none());
+
+            let bank0 = bank_forks.read().unwrap()[0].clone();
+            let bank2 = Bank::new_from_parent(&bank0, &Pubkey::default(), 2);
+            bank_forks.write().unwrap().insert(bank2);
+            bank_forks.write().unwrap().set_root(2, &None);
+            assert_eq!(
+                LongTermStorageService::upload_rooted_blocks(
+                    &blocktree,
+                    &bank_forks,
+                    &long_term_storage,
+                    1
+                )
+                .unwrap(),
+                3
+            );
+            assert_eq!(long_term_storage.last_uploaded_slot().unwrap(), Some(2));
+            let block = long_term_storage.get_block(2).unwrap().unwrap();
+            assert_eq!(block.parent_slot, 1);
+            assert_eq!(block.blockhash, ticks[2].hash);
+            assert_eq!(block.previous_blockhash, ticks[1].hash);
+        }
+        Blocktree::destroy(&ledger_path).expect("Expected successful database destruction");
+    }
+}
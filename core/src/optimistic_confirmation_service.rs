@@ -0,0 +1,255 @@
+//! The `optimistic_confirmation_service` tallies the votes observed in gossip against the stake
+//! of the vote accounts that cast them, and marks a slot optimistically confirmed once more
+//! than 2/3 of the epoch's stake has voted for the same version of it, whether or not that
+//! slot is on the local fork.
+
+use crate::bank_forks::BankForks;
+use crate::cluster_info::{ClusterInfo, GOSSIP_SLEEP_MILLIS};
+use crate::rpc_subscriptions::RpcSubscriptions;
+use crate::service::Service;
+use crate::staking_utils;
+use solana_metrics::datapoint_info;
+use solana_runtime::bank::Bank;
+use solana_sdk::hash::Hash;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::timing::{Epoch, Slot};
+use solana_sdk::transaction::Transaction;
+use solana_vote_api::vote_instruction::parse_vote_transaction;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::thread::{self, sleep, Builder, JoinHandle};
+use std::time::Duration;
+
+struct EpochStakes {
+    total_stake: u64,
+    // vote account => (stake, authorized voter)
+    vote_accounts: HashMap<Pubkey, (u64, Pubkey)>,
+}
+
+impl EpochStakes {
+    fn new(bank: &Bank, epoch: Epoch) -> Option<Self> {
+        let vote_states = staking_utils::vote_account_states_at_epoch(bank, epoch)?;
+        let total_stake = vote_states.values().map(|(stake, _)| stake).sum();
+        let vote_accounts = vote_states
+            .into_iter()
+            .filter_map(|(vote_pubkey, (stake, vote_state))| {
+                vote_state
+                    .authorized_voter(epoch)
+                    .map(|authorized_voter| (vote_pubkey, (stake, *authorized_voter)))
+            })
+            .collect();
+        Some(Self {
+            total_stake,
+            vote_accounts,
+        })
+    }
+}
+
+/// Tallies the stake that has voted for each version of the slots past the root
+#[derive(Default)]
+pub struct VoteTracker {
+    // (slot, hash) => the vote accounts that voted for it, and their total stake
+    slot_votes: HashMap<(Slot, Hash), (HashSet<Pubkey>, u64)>,
+    confirmed_slots: BTreeMap<Slot, Hash>,
+    epoch_stakes: HashMap<Epoch, EpochStakes>,
+}
+
+impl VoteTracker {
+    /// Count the vote of `vote_pubkey`, holding `stake` of `total_stake`, for the version
+    /// `hash` of `slot`.  Returns true if this vote confirmed the slot.
+    pub fn add_vote(
+        &mut self,
+        slot: Slot,
+        hash: Hash,
+        vote_pubkey: Pubkey,
+        stake: u64,
+        total_stake: u64,
+    ) -> bool {
+        if self.confirmed_slots.contains_key(&slot) {
+            return false;
+        }
+        let (voters, voted_stake) = self.slot_votes.entry((slot, hash)).or_default();
+        if !voters.insert(vote_pubkey) {
+            return false;
+        }
+        *voted_stake += stake;
+        if u128::from(*voted_stake) * 3 > u128::from(total_stake) * 2 {
+            self.confirmed_slots.insert(slot, hash);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Count the votes in the gossiped vote `transactions` against the epoch stakes known to
+    /// `root_bank`, returning the slots they confirmed along with the confirmed hash
+    pub fn process_votes(
+        &mut self,
+        root_bank: &Bank,
+        transactions: &[Transaction],
+    ) -> Vec<(Slot, Hash)> {
+        let root = root_bank.slot();
+        let root_epoch = root_bank.epoch();
+        self.slot_votes.retain(|(slot, _), _| *slot > root);
+        self.confirmed_slots = self.confirmed_slots.split_off(&(root + 1));
+        self.epoch_stakes.retain(|epoch, _| *epoch >= root_epoch);
+
+        let mut newly_confirmed = vec![];
+        for transaction in transactions {
+            let (vote_pubkey, voter_pubkey, votes) = match parse_vote_transaction(transaction) {
+                Some(vote) => vote,
+                None => continue,
+            };
+            for vote in votes.into_iter().filter(|vote| vote.slot > root) {
+                let (epoch, _) = root_bank.get_epoch_and_slot_index(vote.slot);
+                if !self.epoch_stakes.contains_key(&epoch) {
+                    match EpochStakes::new(root_bank, epoch) {
+                        Some(epoch_stakes) => {
+                            self.epoch_stakes.insert(epoch, epoch_stakes);
+                        }
+                        None => continue,
+                    }
+                }
+                let epoch_stakes = &self.epoch_stakes[&epoch];
+                let total_stake = epoch_stakes.total_stake;
+                let stake = match epoch_stakes.vote_accounts.get(&vote_pubkey) {
+                    Some((stake, authorized_voter)) if *authorized_voter == voter_pubkey => *stake,
+                    _ => continue,
+                };
+                if self.add_vote(vote.slot, vote.hash, vote_pubkey, stake, total_stake) {
+                    newly_confirmed.push((vote.slot, vote.hash));
+                }
+            }
+        }
+        newly_confirmed
+    }
+}
+
+pub struct OptimisticConfirmationService {
+    t_confirm: JoinHandle<()>,
+}
+
+impl OptimisticConfirmationService {
+    pub fn new(
+        cluster_info: &Arc<RwLock<ClusterInfo>>,
+        bank_forks: &Arc<RwLock<BankForks>>,
+        subscriptions: &Arc<RpcSubscriptions>,
+        exit: &Arc<AtomicBool>,
+    ) -> Self {
+        let cluster_info = cluster_info.clone();
+        let bank_forks = bank_forks.clone();
+        let subscriptions = subscriptions.clone();
+        let exit = exit.clone();
+        let t_confirm = Builder::new()
+            .name("solana-optimistic-confirmation".to_string())
+            .spawn(move || {
+                let mut vote_tracker = VoteTracker::default();
+                let mut last_ts = 0;
+                loop {
+                    if exit.load(Ordering::Relaxed) {
+                        break;
+                    }
+                    let (votes, new_ts) = cluster_info.read().unwrap().get_votes(last_ts);
+                    last_ts = new_ts;
+                    let root_bank = {
+                        let bank_forks = bank_forks.read().unwrap();
+                        bank_forks[bank_forks.root()].clone()
+                    };
+                    for (slot, hash) in vote_tracker.process_votes(&root_bank, &votes) {
+                        datapoint_info!(
+                            "optimistic_confirmation",
+                            ("slot", slot as i64, i64),
+                            ("hash", hash.to_string(), String)
+                        );
+                        bank_forks
+                            .write()
+                            .unwrap()
+                            .set_optimistically_confirmed(slot, hash);
+                        subscriptions.notify_optimistically_confirmed(slot);
+                    }
+                    sleep(Duration::from_millis(GOSSIP_SLEEP_MILLIS));
+                }
+            })
+            .unwrap();
+        Self { t_confirm }
+    }
+}
+
+impl Service for OptimisticConfirmationService {
+    type JoinReturnType = ();
+
+    fn join(self) -> thread::Result<()> {
+        self.t_confirm.join()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::genesis_utils::{create_genesis_block, GenesisBlockInfo};
+    use solana_sdk::signature::{Keypair, KeypairUtil};
+    use solana_vote_api::vote_instruction;
+    use solana_vote_api::vote_state::Vote;
+
+    #[test]
+    fn test_add_vote() {
+        let mut vote_tracker = VoteTracker::default();
+        let voters: Vec<_> = (0..3).map(|_| Pubkey::new_rand()).collect();
+        let hash = Hash::new(&[1; 32]);
+
+        assert!(!vote_tracker.add_vote(1, hash, voters[0], 40, 100));
+        // Votes for another version of the slot don't count
+        assert!(!vote_tracker.add_vote(1, Hash::default(), voters[1], 30, 100));
+        // Nor does a second vote from the same account
+        assert!(!vote_tracker.add_vote(1, hash, voters[0], 40, 100));
+        assert!(!vote_tracker.add_vote(1, hash, voters[2], 26, 100));
+        assert!(vote_tracker.add_vote(1, hash, voters[1], 1, 100));
+        // The slot is only confirmed once
+        assert!(!vote_tracker.add_vote(1, hash, Pubkey::new_rand(), 100, 100));
+        assert_eq!(vote_tracker.confirmed_slots.get(&1), Some(&hash));
+    }
+
+    #[test]
+    fn test_process_votes() {
+        let GenesisBlockInfo {
+            genesis_block,
+            voting_keypair,
+            ..
+        } = create_genesis_block(10_000);
+        let bank = Bank::new(&genesis_block);
+        let vote_pubkey = voting_keypair.pubkey();
+        let hash = Hash::new(&[1; 32]);
+        let new_vote_transaction = |signer: &Keypair, slot| {
+            Transaction::new_signed_instructions(
+                &[signer],
+                vec![vote_instruction::vote(
+                    &vote_pubkey,
+                    &signer.pubkey(),
+                    vec![Vote::new(slot, hash)],
+                )],
+                Hash::default(),
+            )
+        };
+        let mut vote_tracker = VoteTracker::default();
+
+        // Votes not signed by the authorized voter are ignored
+        let forged = new_vote_transaction(&Keypair::new(), 1);
+        assert!(vote_tracker.process_votes(&bank, &[forged]).is_empty());
+        let mut tampered = new_vote_transaction(&voting_keypair, 1);
+        tampered.message.recent_blockhash = hash;
+        assert!(vote_tracker.process_votes(&bank, &[tampered]).is_empty());
+
+        // The bootstrap leader holds all of the stake
+        let vote = new_vote_transaction(&voting_keypair, 1);
+        assert_eq!(
+            vote_tracker.process_votes(&bank, &[vote.clone()]),
+            vec![(1, hash)]
+        );
+        assert!(vote_tracker.process_votes(&bank, &[vote]).is_empty());
+
+        // Votes at or below the root are ignored
+        let vote = new_vote_transaction(&voting_keypair, 0);
+        assert!(vote_tracker.process_votes(&bank, &[vote]).is_empty());
+    }
+}
@@ -0,0 +1,325 @@
+//! The `packet_qos` module decides which inbound TPU packets are worth verifying when the
+//! validator can't keep up.  Packets sent from the addresses of staked nodes, taken from
+//! their gossiped contact infos, are kept ahead of packets from unstaked addresses, and the
+//! packet rate of each source IP can be capped.
+
+use crate::bank_forks::BankForks;
+use crate::cluster_info::ClusterInfo;
+use crate::contact_info::ContactInfo;
+use crate::packet::Packets;
+use crate::service::Service;
+use crate::staking_utils;
+use solana_metrics::{datapoint_info, inc_new_counter_info};
+use solana_runtime::bank::Bank;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::thread::{self, sleep, Builder, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// How often the addresses of staked nodes are refreshed from gossip
+pub const STAKED_ADDRESSES_REFRESH_MS: u64 = 5_000;
+
+const RATE_WINDOW: Duration = Duration::from_secs(1);
+
+#[derive(Clone, Debug, Default)]
+pub struct PacketQosConfig {
+    /// Packets per second accepted from an unstaked IP address, 0 for no cap
+    pub max_unstaked_packets_per_second: u64,
+    /// Packets per second accepted from the IP address of a staked node, 0 for no cap
+    pub max_staked_packets_per_second: u64,
+}
+
+// Counts the packets from each source IP in the current one second window
+struct IpRateMeter {
+    window_start: Instant,
+    packet_counts: HashMap<IpAddr, u64>,
+}
+
+impl IpRateMeter {
+    fn new() -> Self {
+        Self {
+            window_start: Instant::now(),
+            packet_counts: HashMap::new(),
+        }
+    }
+
+    // Count a packet from `ip`, returning the number of packets seen from it in this window
+    fn count(&mut self, ip: IpAddr, now: Instant) -> u64 {
+        if now.duration_since(self.window_start) >= RATE_WINDOW {
+            if let Some(max_packets) = self.packet_counts.values().max() {
+                datapoint_info!(
+                    "packet_qos-ip_rates",
+                    ("num_ips", self.packet_counts.len(), i64),
+                    ("max_packets_per_ip", *max_packets, i64)
+                );
+            }
+            self.packet_counts.clear();
+            self.window_start = now;
+        }
+        let packet_count = self.packet_counts.entry(ip).or_insert(0);
+        *packet_count += 1;
+        *packet_count
+    }
+}
+
+pub struct PacketQos {
+    config: PacketQosConfig,
+    // ip => stake of the nodes gossiping that address
+    staked_addresses: RwLock<HashMap<IpAddr, u64>>,
+    ip_rate_meter: Mutex<IpRateMeter>,
+}
+
+impl Default for PacketQos {
+    fn default() -> Self {
+        Self::new(PacketQosConfig::default())
+    }
+}
+
+impl PacketQos {
+    pub fn new(config: PacketQosConfig) -> Self {
+        Self {
+            config,
+            staked_addresses: RwLock::default(),
+            ip_rate_meter: Mutex::new(IpRateMeter::new()),
+        }
+    }
+
+    /// Map the addresses staked nodes gossip to the stake `bank` gives them in its epoch
+    pub fn refresh_staked_addresses(&self, cluster_info: &ClusterInfo, bank: &Bank) {
+        let staked_nodes =
+            staking_utils::staked_nodes_at_epoch(bank, bank.epoch()).unwrap_or_default();
+        let mut staked_addresses = HashMap::new();
+        for (contact_info, _) in cluster_info.all_peers() {
+            let stake = match staked_nodes.get(&contact_info.id) {
+                Some(stake) if *stake > 0 => *stake,
+                _ => continue,
+            };
+            for addr in &[
+                contact_info.gossip,
+                contact_info.tpu,
+                contact_info.tpu_forwards,
+            ] {
+                if ContactInfo::is_valid_address(addr) {
+                    let address_stake = staked_addresses.entry(addr.ip()).or_insert(0);
+                    *address_stake = (*address_stake).max(stake);
+                }
+            }
+        }
+        *self.staked_addresses.write().unwrap() = staked_addresses;
+    }
+
+    /// Discard the packets in `batch` whose source IP is over its rate cap, then, if more
+    /// than `max_packets` remain, discard the packets from the least staked addresses until
+    /// `max_packets` are left.  Packets from addresses with the same stake are kept in the
+    /// order they arrived.  Discarded packets are zero sized so they fail verification, and
+    /// batches left with no packets are removed.  Returns the number of packets discarded.
+    pub fn apply(&self, batch: &mut Vec<Packets>, max_packets: usize) -> usize {
+        let staked_addresses = self.staked_addresses.read().unwrap();
+        let now = Instant::now();
+        let mut num_rate_limited = 0;
+        // (stake, batch index, packet index) of each packet kept
+        let mut kept = vec![];
+        {
+            let mut ip_rate_meter = self.ip_rate_meter.lock().unwrap();
+            for (i, packets) in batch.iter_mut().enumerate() {
+                for (j, packet) in packets.packets.iter_mut().enumerate() {
+                    if packet.meta.size == 0 {
+                        continue;
+                    }
+                    let ip = packet.meta.addr().ip();
+                    let stake = staked_addresses.get(&ip).cloned().unwrap_or(0);
+                    let max_packets_per_second = if stake > 0 {
+                        self.config.max_staked_packets_per_second
+                    } else {
+                        self.config.max_unstaked_packets_per_second
+                    };
+                    let packets_this_second = ip_rate_meter.count(ip, now);
+                    if max_packets_per_second > 0 && packets_this_second > max_packets_per_second {
+                        packet.meta.size = 0;
+                        num_rate_limited += 1;
+                    } else {
+                        kept.push((stake, i, j));
+                    }
+                }
+            }
+        }
+
+        let mut num_shed = 0;
+        if kept.len() > max_packets {
+            // The sort is stable, so arrival order breaks ties
+            kept.sort_by(|a, b| b.0.cmp(&a.0));
+            for (stake, i, j) in kept.drain(max_packets..) {
+                if stake > 0 {
+                    inc_new_counter_info!("packet_qos-shed_staked", 1);
+                }
+                batch[i].packets[j].meta.size = 0;
+                num_shed += 1;
+            }
+        }
+        batch.retain(|packets| packets.packets.iter().any(|packet| packet.meta.size > 0));
+
+        inc_new_counter_info!("packet_qos-rate_limited", num_rate_limited);
+        inc_new_counter_info!("packet_qos-shed", num_shed);
+        num_rate_limited + num_shed
+    }
+}
+
+/// Periodically refreshes the staked addresses of a `PacketQos`
+pub struct PacketQosService {
+    thread_hdl: JoinHandle<()>,
+}
+
+impl PacketQosService {
+    pub fn new(
+        packet_qos: &Arc<PacketQos>,
+        cluster_info: &Arc<RwLock<ClusterInfo>>,
+        bank_forks: &Arc<RwLock<BankForks>>,
+        exit: &Arc<AtomicBool>,
+    ) -> Self {
+        let packet_qos = packet_qos.clone();
+        let cluster_info = cluster_info.clone();
+        let bank_forks = bank_forks.clone();
+        let exit = exit.clone();
+        let thread_hdl = Builder::new()
+            .name("solana-packet-qos".to_string())
+            .spawn(move || {
+                let mut last_refresh: Option<Instant> = None;
+                loop {
+                    if exit.load(Ordering::Relaxed) {
+                        break;
+                    }
+                    if last_refresh.map_or(true, |last_refresh| {
+                        last_refresh.elapsed() >= Duration::from_millis(STAKED_ADDRESSES_REFRESH_MS)
+                    }) {
+                        let bank = bank_forks.read().unwrap().working_bank();
+                        packet_qos.refresh_staked_addresses(&cluster_info.read().unwrap(), &bank);
+                        last_refresh = Some(Instant::now());
+                    }
+                    sleep(Duration::from_millis(100));
+                }
+            })
+            .unwrap();
+        Self { thread_hdl }
+    }
+}
+
+impl Service for PacketQosService {
+    type JoinReturnType = ();
+
+    fn join(self) -> thread::Result<()> {
+        self.thread_hdl.join()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packet::Packet;
+    use std::net::SocketAddr;
+
+    fn new_packets(addrs: &[SocketAddr]) -> Packets {
+        Packets::new(
+            addrs
+                .iter()
+                .map(|addr| {
+                    let mut packet = Packet::default();
+                    packet.meta.size = 1;
+                    packet.meta.set_addr(addr);
+                    packet
+                })
+                .collect(),
+        )
+    }
+
+    fn packet_addrs(batch: &[Packets]) -> Vec<SocketAddr> {
+        batch
+            .iter()
+            .flat_map(|packets| packets.packets.iter())
+            .filter(|packet| packet.meta.size > 0)
+            .map(|packet| packet.meta.addr())
+            .collect()
+    }
+
+    #[test]
+    fn test_apply_prefers_staked_addresses() {
+        let packet_qos = PacketQos::default();
+        let unstaked: SocketAddr = "1.1.1.1:8000".parse().unwrap();
+        let small_stake: SocketAddr = "2.2.2.2:8000".parse().unwrap();
+        let large_stake: SocketAddr = "3.3.3.3:8000".parse().unwrap();
+        {
+            let mut staked_addresses = packet_qos.staked_addresses.write().unwrap();
+            staked_addresses.insert(small_stake.ip(), 1);
+            staked_addresses.insert(large_stake.ip(), 10);
+        }
+
+        // Nothing is dropped under the limit
+        let mut batch = vec![new_packets(&[unstaked, small_stake, large_stake])];
+        assert_eq!(packet_qos.apply(&mut batch, 3), 0);
+        assert_eq!(packet_addrs(&batch).len(), 3);
+
+        let mut batch = vec![
+            new_packets(&[unstaked, unstaked]),
+            new_packets(&[small_stake, large_stake, unstaked]),
+        ];
+        assert_eq!(packet_qos.apply(&mut batch, 3), 2);
+        assert_eq!(
+            packet_addrs(&batch),
+            vec![small_stake, large_stake, unstaked]
+        );
+
+        // Batches left empty are removed
+        let mut batch = vec![new_packets(&[unstaked]), new_packets(&[large_stake])];
+        assert_eq!(packet_qos.apply(&mut batch, 1), 1);
+        assert_eq!(batch.len(), 1);
+        assert_eq!(packet_addrs(&batch), vec![large_stake]);
+    }
+
+    #[test]
+    fn test_apply_rate_caps() {
+        let packet_qos = PacketQos::new(PacketQosConfig {
+            max_unstaked_packets_per_second: 2,
+            max_staked_packets_per_second: 3,
+        });
+        let unstaked: SocketAddr = "1.1.1.1:8000".parse().unwrap();
+        let other_unstaked: SocketAddr = "4.4.4.4:8000".parse().unwrap();
+        let staked: SocketAddr = "2.2.2.2:8000".parse().unwrap();
+        packet_qos
+            .staked_addresses
+            .write()
+            .unwrap()
+            .insert(staked.ip(), 1);
+
+        let mut batch = vec![new_packets(&[
+            unstaked,
+            unstaked,
+            unstaked,
+            other_unstaked,
+            staked,
+            staked,
+            staked,
+            staked,
+        ])];
+        assert_eq!(packet_qos.apply(&mut batch, 100), 2);
+        assert_eq!(
+            packet_addrs(&batch),
+            vec![unstaked, unstaked, other_unstaked, staked, staked, staked]
+        );
+
+        // The cap applies across batches within the same second
+        let mut batch = vec![new_packets(&[unstaked, other_unstaked])];
+        assert_eq!(packet_qos.apply(&mut batch, 100), 1);
+        assert_eq!(packet_addrs(&batch), vec![other_unstaked]);
+    }
+
+    #[test]
+    fn test_ip_rate_meter_window() {
+        let mut ip_rate_meter = IpRateMeter::new();
+        let ip: IpAddr = "1.1.1.1".parse().unwrap();
+        let now = ip_rate_meter.window_start;
+        assert_eq!(ip_rate_meter.count(ip, now), 1);
+        assert_eq!(ip_rate_meter.count(ip, now), 2);
+        assert_eq!(ip_rate_meter.count(ip, now + RATE_WINDOW), 1);
+    }
+}
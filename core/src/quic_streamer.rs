@@ -0,0 +1,385 @@
+//! The `quic_streamer` module accepts transactions on the QUIC port of the TPU and sends
+//! them to the same channel as the packets `streamer::receiver` pulls from UDP sockets.
+//! Each transaction arrives on its own unidirectional stream.  Unlike a UDP sender, a QUIC
+//! sender's address is validated by the handshake, and it can't have more streams in flight
+//! than the connection allows.  Each IP address may only have a few connections open, and
+//! transactions are dropped rather than queued without bound if the TPU falls behind.
+
+use crate::packet::{Packet, Packets, PACKET_DATA_SIZE};
+use crate::result::{Error, Result};
+use crate::streamer::PacketSender;
+use futures::{future, StreamExt, TryFutureExt};
+use quinn::{
+    Connecting, Endpoint, NewConnection, ServerConfig, ServerConfigBuilder, TransportConfig,
+};
+use solana_client::quic_client::QUIC_SERVER_NAME;
+use solana_metrics::inc_new_counter_info;
+use std::collections::HashMap;
+use std::io;
+use std::net::{IpAddr, SocketAddr, UdpSocket};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver, RecvTimeoutError, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread::{Builder, JoinHandle};
+use std::time::Duration;
+use tokio_02::runtime;
+
+#[derive(Clone, Debug)]
+pub struct QuicConfig {
+    /// Streams, so transactions, each connection may have in flight at once
+    pub max_concurrent_uni_streams: u64,
+    /// Connections a single IP address may have open at once
+    pub max_connections_per_ip: usize,
+    /// Transactions received but not yet sent on to the TPU, more are dropped
+    pub max_queued_packets: usize,
+}
+
+impl Default for QuicConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrent_uni_streams: 128,
+            max_connections_per_ip: 8,
+            max_queued_packets: 10_000,
+        }
+    }
+}
+
+/// Most transactions forwarded to the TPU in one `Packets`
+const MAX_FORWARD_BATCH: usize = 1024;
+
+/// The number of open connections from each IP address
+#[derive(Clone, Default)]
+struct ConnectionTable(Arc<Mutex<HashMap<IpAddr, usize>>>);
+
+impl ConnectionTable {
+    /// Count a new connection from `ip`, None if `ip` already has `max_connections` open
+    fn try_add(&self, ip: IpAddr, max_connections: usize) -> Option<ConnectionEntry> {
+        let mut table = self.0.lock().unwrap();
+        let count = table.entry(ip).or_insert(0);
+        if *count >= max_connections {
+            return None;
+        }
+        *count += 1;
+        Some(ConnectionEntry {
+            table: self.clone(),
+            ip,
+        })
+    }
+}
+
+/// Removes its connection from the `ConnectionTable` when dropped
+struct ConnectionEntry {
+    table: ConnectionTable,
+    ip: IpAddr,
+}
+
+impl Drop for ConnectionEntry {
+    fn drop(&mut self) {
+        let mut table = self.table.0.lock().unwrap();
+        if let Some(count) = table.get_mut(&self.ip) {
+            *count -= 1;
+            if *count == 0 {
+                table.remove(&self.ip);
+            }
+        }
+    }
+}
+
+fn to_io_error<E: std::fmt::Display>(e: E) -> Error {
+    Error::IO(io::Error::new(io::ErrorKind::Other, e.to_string()))
+}
+
+fn new_server_config(config: &QuicConfig) -> Result<ServerConfig> {
+    let mut transport_config = TransportConfig::default();
+    transport_config.stream_window_uni = config.max_concurrent_uni_streams;
+    transport_config.stream_window_bidi = 0;
+    let mut server_config = ServerConfig::default();
+    server_config.transport = Arc::new(transport_config);
+
+    // Senders don't verify the certificate, it only needs to complete the handshake
+    let cert = rcgen::generate_simple_self_signed(vec![QUIC_SERVER_NAME.to_string()])
+        .map_err(to_io_error)?;
+    let cert_der = cert.serialize_der().map_err(to_io_error)?;
+    let key_der = cert.serialize_private_key_der();
+    let mut builder = ServerConfigBuilder::new(server_config);
+    builder
+        .certificate(
+            quinn::CertificateChain::from_certs(vec![
+                quinn::Certificate::from_der(&cert_der).map_err(to_io_error)?
+            ]),
+            quinn::PrivateKey::from_der(&key_der).map_err(to_io_error)?,
+        )
+        .map_err(to_io_error)?;
+    Ok(builder.build())
+}
+
+fn new_packet(data: &[u8], from: &SocketAddr) -> Packet {
+    let mut packet = Packet::default();
+    packet.data[..data.len()].copy_from_slice(data);
+    packet.meta.size = data.len();
+    packet.meta.set_addr(from);
+    packet
+}
+
+async fn handle_connection(
+    connecting: Connecting,
+    packet_sender: SyncSender<Packet>,
+    connection_table: ConnectionTable,
+    max_connections_per_ip: usize,
+) {
+    let NewConnection {
+        driver,
+        connection,
+        mut uni_streams,
+        ..
+    } = match connecting.await {
+        Ok(new_connection) => new_connection,
+        Err(e) => {
+            debug!("QUIC handshake failed: {}", e);
+            return;
+        }
+    };
+    let from = connection.remote_address();
+    let _connection_entry = match connection_table.try_add(from.ip(), max_connections_per_ip) {
+        Some(connection_entry) => connection_entry,
+        None => {
+            inc_new_counter_info!("quic_streamer-too_many_connections", 1);
+            connection.close(0u32.into(), b"too many connections");
+            tokio_02::spawn(driver.unwrap_or_else(|_| ()));
+            return;
+        }
+    };
+    inc_new_counter_info!("quic_streamer-connections", 1);
+    tokio_02::spawn(
+        driver.unwrap_or_else(move |e| debug!("QUIC connection from {} closed: {}", from, e)),
+    );
+    // Bidirectional streams aren't allowed, see `new_server_config()`
+    while let Some(Ok(recv)) = uni_streams.next().await {
+        let packet_sender = packet_sender.clone();
+        tokio_02::spawn(async move {
+            match recv.read_to_end(PACKET_DATA_SIZE).await {
+                Ok(data) => match packet_sender.try_send(new_packet(&data, &from)) {
+                    Err(TrySendError::Full(_)) => {
+                        inc_new_counter_info!("quic_streamer-dropped_packets", 1)
+                    }
+                    Ok(_) | Err(TrySendError::Disconnected(_)) => (),
+                },
+                Err(_) => inc_new_counter_info!("quic_streamer-bad_streams", 1),
+            }
+        });
+    }
+}
+
+// Send the queued transactions on to the TPU in batches until the exit signal
+fn forward_loop(exit: &AtomicBool, queue: &Receiver<Packet>, packet_sender: &PacketSender) {
+    while !exit.load(Ordering::Relaxed) {
+        let packet = match queue.recv_timeout(Duration::from_millis(100)) {
+            Ok(packet) => packet,
+            Err(RecvTimeoutError::Timeout) => continue,
+            Err(RecvTimeoutError::Disconnected) => break,
+        };
+        let mut packets = vec![packet];
+        while packets.len() < MAX_FORWARD_BATCH {
+            match queue.try_recv() {
+                Ok(packet) => packets.push(packet),
+                Err(_) => break,
+            }
+        }
+        if packet_sender.send(Packets::new(packets)).is_err() {
+            break;
+        }
+    }
+}
+
+fn recv_loop(
+    socket: UdpSocket,
+    server_config: ServerConfig,
+    config: QuicConfig,
+    exit: Arc<AtomicBool>,
+    packet_sender: PacketSender,
+) -> Result<()> {
+    let (queue_sender, queue_receiver) = sync_channel(config.max_queued_packets);
+    let forward_exit = exit.clone();
+    let t_forward = Builder::new()
+        .name("solana-quic-forward".to_string())
+        .spawn(move || forward_loop(&forward_exit, &queue_receiver, &packet_sender))
+        .unwrap();
+
+    let mut runtime = runtime::Builder::new()
+        .basic_scheduler()
+        .enable_all()
+        .build()?;
+    let mut builder = Endpoint::builder();
+    builder.listen(server_config);
+    let (driver, _endpoint, incoming) = runtime
+        .enter(|| builder.with_socket(socket))
+        .map_err(to_io_error)?;
+    runtime.spawn(driver.unwrap_or_else(|e| warn!("QUIC endpoint failed: {}", e)));
+    let connection_table = ConnectionTable::default();
+    runtime.spawn(incoming.for_each(move |connecting| {
+        tokio_02::spawn(handle_connection(
+            connecting,
+            queue_sender.clone(),
+            connection_table.clone(),
+            config.max_connections_per_ip,
+        ));
+        future::ready(())
+    }));
+
+    // Serve connections until the exit signal
+    runtime.block_on(async move {
+        let mut interval = tokio_02::time::interval(Duration::from_millis(100));
+        while !exit.load(Ordering::Relaxed) {
+            interval.tick().await;
+        }
+    });
+    t_forward.join().unwrap();
+    Ok(())
+}
+
+/// Accept transactions over QUIC on `socket` and send them to `packet_sender`
+pub fn receiver(
+    socket: UdpSocket,
+    exit: &Arc<AtomicBool>,
+    packet_sender: PacketSender,
+    config: &QuicConfig,
+) -> Result<JoinHandle<()>> {
+    let server_config = new_server_config(config)?;
+    let config = config.clone();
+    let exit = exit.clone();
+    Ok(Builder::new()
+        .name("solana-quic-receiver".to_string())
+        .spawn(move || {
+            if let Err(e) = recv_loop(socket, server_config, config, exit, packet_sender) {
+                error!("QUIC receiver failed: {:?}", e);
+            }
+        })
+        .unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bincode::deserialize;
+    use solana_client::quic_client::QuicClient;
+    use solana_client::thin_client::ThinClient;
+    use solana_sdk::client::AsyncClient;
+    use solana_sdk::hash::Hash;
+    use solana_sdk::pubkey::Pubkey;
+    use solana_sdk::signature::{Keypair, KeypairUtil};
+    use solana_sdk::transaction::Transaction;
+    use std::sync::mpsc::{channel, Receiver};
+
+    // Wait for `num_packets` packets to be forwarded, however they are batched
+    fn recv_packets(packet_receiver: &Receiver<Packets>, num_packets: usize) -> Vec<Packet> {
+        let mut packets = vec![];
+        while packets.len() < num_packets {
+            let mut batch = packet_receiver
+                .recv_timeout(Duration::from_secs(5))
+                .unwrap();
+            packets.append(&mut batch.packets);
+        }
+        assert_eq!(packets.len(), num_packets);
+        packets
+    }
+
+    #[test]
+    fn test_quic_receiver() {
+        let exit = Arc::new(AtomicBool::new(false));
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let addr = socket.local_addr().unwrap();
+        let (packet_sender, packet_receiver) = channel();
+        let t_receiver = receiver(socket, &exit, packet_sender, &QuicConfig::default()).unwrap();
+
+        let client_socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let client_addr = client_socket.local_addr().unwrap();
+        let quic_client = QuicClient::new(client_socket).unwrap();
+        for i in 0..3 {
+            quic_client.send(vec![i; 10], &addr).unwrap();
+        }
+        let mut received: Vec<_> = recv_packets(&packet_receiver, 3)
+            .into_iter()
+            .map(|packet| {
+                assert_eq!(packet.meta.size, 10);
+                assert_eq!(packet.meta.addr(), client_addr);
+                packet.data[0]
+            })
+            .collect();
+        received.sort();
+        assert_eq!(received, vec![0, 1, 2]);
+
+        // Streams longer than a packet are dropped
+        let _ = quic_client.send(vec![0; PACKET_DATA_SIZE + 1], &addr);
+        assert!(packet_receiver
+            .recv_timeout(Duration::from_millis(500))
+            .is_err());
+
+        exit.store(true, Ordering::Relaxed);
+        t_receiver.join().unwrap();
+    }
+
+    #[test]
+    fn test_quic_receiver_max_connections_per_ip() {
+        let exit = Arc::new(AtomicBool::new(false));
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let addr = socket.local_addr().unwrap();
+        let (packet_sender, packet_receiver) = channel();
+        let config = QuicConfig {
+            max_connections_per_ip: 1,
+            ..QuicConfig::default()
+        };
+        let t_receiver = receiver(socket, &exit, packet_sender, &config).unwrap();
+
+        let quic_client = QuicClient::new(UdpSocket::bind("127.0.0.1:0").unwrap()).unwrap();
+        quic_client.send(vec![1; 10], &addr).unwrap();
+        assert_eq!(recv_packets(&packet_receiver, 1)[0].data[0], 1);
+
+        // A second connection from the same IP address is closed
+        let other_client = QuicClient::new(UdpSocket::bind("127.0.0.1:0").unwrap()).unwrap();
+        let _ = other_client.send(vec![2; 10], &addr);
+        assert!(packet_receiver
+            .recv_timeout(Duration::from_millis(500))
+            .is_err());
+
+        // The first connection is still served
+        quic_client.send(vec![3; 10], &addr).unwrap();
+        assert_eq!(recv_packets(&packet_receiver, 1)[0].data[0], 3);
+
+        exit.store(true, Ordering::Relaxed);
+        t_receiver.join().unwrap();
+    }
+
+    #[test]
+    fn test_quic_receiver_thin_client() {
+        let exit = Arc::new(AtomicBool::new(false));
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let quic_addr = socket.local_addr().unwrap();
+        let (packet_sender, packet_receiver) = channel();
+        let t_receiver = receiver(socket, &exit, packet_sender, &QuicConfig::default()).unwrap();
+
+        // Nothing listens on the RPC and UDP TPU addresses, the transaction must arrive over QUIC
+        let unused_addr = UdpSocket::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let thin_client = ThinClient::new_with_quic(
+            unused_addr,
+            unused_addr,
+            quic_addr,
+            UdpSocket::bind("127.0.0.1:0").unwrap(),
+        )
+        .unwrap();
+        let keypair = Keypair::new();
+        let signature = thin_client
+            .async_transfer(1, &keypair, &Pubkey::new_rand(), Hash::default())
+            .unwrap();
+
+        let packet = &recv_packets(&packet_receiver, 1)[0];
+        let transaction: Transaction = deserialize(&packet.data[..packet.meta.size]).unwrap();
+        assert_eq!(transaction.signatures[0], signature);
+        assert!(signature.verify(keypair.pubkey().as_ref(), &transaction.message_data()));
+
+        exit.store(true, Ordering::Relaxed);
+        t_receiver.join().unwrap();
+    }
+}
@@ -1,12 +1,14 @@
 //! The `replay_stage` replays transactions broadcast by the leader.
 
 use crate::bank_forks::BankForks;
-use crate::blocktree::{Blocktree, BlocktreeError};
+use crate::blocktree::{Blocktree, BlocktreeError, DuplicateSlotProof};
 use crate::blocktree_processor;
 use crate::cluster_info::ClusterInfo;
 use crate::consensus::{StakeLockout, Tower};
 use crate::entry::{Entry, EntrySlice};
+use crate::fork_tree::{ForkTree, ForkTreeExport};
 use crate::leader_schedule_cache::LeaderScheduleCache;
+use crate::long_term_storage::record_transaction_statuses;
 use crate::packet::BlobError;
 use crate::poh_recorder::PohRecorder;
 use crate::result::{Error, Result};
@@ -21,7 +23,8 @@ use solana_sdk::signature::KeypairUtil;
 use solana_sdk::timing::{self, duration_as_ms};
 use solana_sdk::transaction::Transaction;
 use solana_vote_api::vote_instruction;
-use std::collections::HashMap;
+use solana_vote_api::vote_state::VoteState;
+use std::collections::{HashMap, HashSet};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
 use std::sync::{Arc, Mutex, RwLock};
@@ -82,9 +85,11 @@ impl ReplayStage {
     pub fn new<T>(
         my_pubkey: &Pubkey,
         vote_account: &Pubkey,
-        voting_keypair: Option<&Arc<T>>,
+        authorized_voter_keypairs: &[Arc<T>],
         blocktree: Arc<Blocktree>,
         bank_forks: &Arc<RwLock<BankForks>>,
+        mut tower: Tower,
+        fork_tree_export: &ForkTreeExport,
         cluster_info: Arc<RwLock<ClusterInfo>>,
         exit: &Arc<AtomicBool>,
         ledger_signal_receiver: Receiver<bool>,
@@ -104,11 +109,11 @@ impl ReplayStage {
         let bank_forks = bank_forks.clone();
         let poh_recorder = poh_recorder.clone();
         let my_pubkey = *my_pubkey;
-        let mut tower = Tower::new(&my_pubkey, &vote_account, &bank_forks.read().unwrap());
         // Start the replay stage loop
         let leader_schedule_cache = leader_schedule_cache.clone();
         let vote_account = *vote_account;
-        let voting_keypair = voting_keypair.cloned();
+        let authorized_voter_keypairs = authorized_voter_keypairs.to_vec();
+        let fork_tree_export = fork_tree_export.clone();
 
         let (lockouts_sender, t_lockouts) = aggregate_stake_lockouts(exit);
 
@@ -118,6 +123,9 @@ impl ReplayStage {
                 let _exit = Finalizer::new(exit_.clone());
                 let mut progress = HashMap::new();
                 let mut current_leader = None;
+                let mut is_tower_consistent = false;
+                let mut duplicate_slots = HashSet::new();
+                let mut rejected_duplicate_slot_proofs = HashSet::new();
 
                 loop {
                     let now = Instant::now();
@@ -142,7 +150,34 @@ impl ReplayStage {
                         &slot_full_senders,
                     );
 
-                    let votable = Self::generate_votable_banks(&bank_forks, &tower, &mut progress);
+                    Self::update_duplicate_slots(
+                        &blocktree,
+                        &bank_forks,
+                        &cluster_info,
+                        &leader_schedule_cache,
+                        &mut duplicate_slots,
+                        &mut rejected_duplicate_slot_proofs,
+                    );
+
+                    if !is_tower_consistent {
+                        is_tower_consistent = tower.is_consistent_with(&bank_forks.read().unwrap());
+                        if is_tower_consistent {
+                            info!("{} tower is consistent with the replayed forks", my_pubkey);
+                        }
+                    }
+
+                    // Refuse to vote until the tower's lockouts can be checked against the
+                    // replayed forks
+                    let votable = if is_tower_consistent {
+                        Self::generate_votable_banks(
+                            &bank_forks,
+                            &tower,
+                            &mut progress,
+                            &duplicate_slots,
+                        )
+                    } else {
+                        vec![]
+                    };
 
                     if let Some((_, bank, lockouts, total_staked)) = votable.into_iter().last() {
                         subscriptions.notify_subscribers(bank.slot(), &bank_forks);
@@ -175,7 +210,7 @@ impl ReplayStage {
                             &mut tower,
                             &mut progress,
                             &vote_account,
-                            &voting_keypair,
+                            &authorized_voter_keypairs,
                             &cluster_info,
                             &blocktree,
                             &leader_schedule_cache,
@@ -214,6 +249,14 @@ impl ReplayStage {
                         }
                     }
 
+                    if fork_tree_export.is_enabled() {
+                        fork_tree_export.update(ForkTree::new(
+                            &bank_forks.read().unwrap(),
+                            &tower,
+                            |slot| progress.get(&slot).map(|p| p.is_dead).unwrap_or(false),
+                        ));
+                    }
+
                     inc_new_counter_info!(
                         "replicate_stage-duration",
                         duration_as_ms(&now.elapsed()) as usize
@@ -394,7 +437,7 @@ impl ReplayStage {
         tower: &mut Tower,
         progress: &mut HashMap<u64, ForkProgress>,
         vote_account: &Pubkey,
-        voting_keypair: &Option<Arc<T>>,
+        authorized_voter_keypairs: &[Arc<T>],
         cluster_info: &Arc<RwLock<ClusterInfo>>,
         blocktree: &Arc<Blocktree>,
         leader_schedule_cache: &Arc<LeaderScheduleCache>,
@@ -408,7 +451,23 @@ impl ReplayStage {
         T: 'static + KeypairUtil + Send + Sync,
     {
         trace!("handle votable bank {}", bank.slot());
-        if let Some(new_root) = tower.record_vote(bank.slot(), bank.hash()) {
+        let new_root = tower.record_vote(bank.slot(), bank.hash());
+        let node_keypair = cluster_info.read().unwrap().keypair.clone();
+        // The tower must be on disk before the vote leaves this node, otherwise a restart
+        // could vote against its lockouts
+        let is_tower_saved = match tower.save(&node_keypair) {
+            Ok(()) => true,
+            Err(e) => {
+                error!(
+                    "Unable to save tower, not voting on {}: {:?}",
+                    bank.slot(),
+                    e
+                );
+                datapoint_error!("replay_stage-tower_save_error", ("slot", bank.slot(), i64));
+                false
+            }
+        };
+        if let Some(new_root) = new_root {
             // get the root bank before squash
             let root_bank = bank_forks
                 .read()
@@ -422,6 +481,17 @@ impl ReplayStage {
             blocktree
                 .set_roots(&rooted_slots)
                 .expect("Ledger set roots failed");
+            // Each newly rooted slot's own bank still knows the statuses of its transactions
+            let old_root = bank_forks.read().unwrap().root();
+            for rooted_bank in rooted_banks.iter().filter(|bank| bank.slot() > old_root) {
+                if let Err(e) = record_transaction_statuses(blocktree, rooted_bank) {
+                    error!(
+                        "Unable to record transaction statuses of slot {}: {:?}",
+                        rooted_bank.slot(),
+                        e
+                    );
+                }
+            }
             // Set root first in leader schedule_cache before bank_forks because bank_forks.root
             // is consumed by repair_service to update gossip, so we don't want to get blobs for
             // repair on gossip before we update leader schedule, otherwise they may get dropped.
@@ -439,13 +509,16 @@ impl ReplayStage {
         }
         Self::update_confidence_cache(bank_forks, tower, lockouts, total_staked, lockouts_sender);
 
-        if let Some(ref voting_keypair) = voting_keypair {
-            let node_keypair = cluster_info.read().unwrap().keypair.clone();
-
+        if !is_tower_saved {
+            return Ok(());
+        }
+        if let Some(authorized_voter_keypair) =
+            Self::authorized_voter_keypair(bank, vote_account, authorized_voter_keypairs)
+        {
             // Send our last few votes along with the new one
             let vote_ix = vote_instruction::vote(
                 &vote_account,
-                &voting_keypair.pubkey(),
+                &authorized_voter_keypair.pubkey(),
                 tower.recent_votes(),
             );
 
@@ -454,12 +527,56 @@ impl ReplayStage {
 
             let blockhash = bank.last_blockhash();
             vote_tx.partial_sign(&[node_keypair.as_ref()], blockhash);
-            vote_tx.partial_sign(&[voting_keypair.as_ref()], blockhash);
+            vote_tx.partial_sign(&[authorized_voter_keypair.as_ref()], blockhash);
             cluster_info.write().unwrap().push_vote(vote_tx);
         }
         Ok(())
     }
 
+    // Find the keypair for the voter the vote account authorizes in the bank's epoch
+    fn authorized_voter_keypair<'a, T>(
+        bank: &Bank,
+        vote_account: &Pubkey,
+        authorized_voter_keypairs: &'a [Arc<T>],
+    ) -> Option<&'a Arc<T>>
+    where
+        T: KeypairUtil,
+    {
+        if authorized_voter_keypairs.is_empty() {
+            return None;
+        }
+
+        let vote_state = bank
+            .get_account(vote_account)
+            .and_then(|account| VoteState::from(&account));
+        let authorized_voter_pubkey = match vote_state
+            .as_ref()
+            .and_then(|vote_state| vote_state.authorized_voter(bank.epoch()))
+        {
+            Some(authorized_voter_pubkey) => authorized_voter_pubkey,
+            None => {
+                warn!(
+                    "Vote account {} is not initialized in slot {}",
+                    vote_account,
+                    bank.slot()
+                );
+                return None;
+            }
+        };
+
+        let authorized_voter_keypair = authorized_voter_keypairs
+            .iter()
+            .find(|keypair| keypair.pubkey() == *authorized_voter_pubkey);
+        if authorized_voter_keypair.is_none() {
+            warn!(
+                "No keypair for {}, the authorized voter for epoch {}",
+                authorized_voter_pubkey,
+                bank.epoch()
+            );
+        }
+        authorized_voter_keypair
+    }
+
     fn update_confidence_cache(
         bank_forks: &Arc<RwLock<BankForks>>,
         tower: &Tower,
@@ -564,11 +681,94 @@ impl ReplayStage {
         did_complete_bank
     }
 
+    // Track the slots whose leader produced more than one version of them.  Proofs found in
+    // the blocktree are gossiped to the cluster, and proofs gossiped by others are verified
+    // against the leader schedule and stored in the blocktree.  Gossiped proofs that fail
+    // verification are remembered by their (from, wallclock) so they aren't verified again
+    // on every pass.
+    fn update_duplicate_slots(
+        blocktree: &Blocktree,
+        bank_forks: &RwLock<BankForks>,
+        cluster_info: &RwLock<ClusterInfo>,
+        leader_schedule_cache: &LeaderScheduleCache,
+        duplicate_slots: &mut HashSet<u64>,
+        rejected_proofs: &mut HashSet<(Pubkey, u64)>,
+    ) {
+        let (root, working_bank, mut frozen_slots) = {
+            let bank_forks = bank_forks.read().unwrap();
+            (
+                bank_forks.root(),
+                bank_forks.working_bank(),
+                bank_forks
+                    .frozen_banks()
+                    .keys()
+                    .cloned()
+                    .collect::<Vec<_>>(),
+            )
+        };
+        // Rooted slots can no longer be voted on
+        duplicate_slots.retain(|slot| *slot > root);
+
+        // None if the slot's leader isn't known yet
+        let verify = |slot: u64, proof: &DuplicateSlotProof| {
+            leader_schedule_cache
+                .slot_leader_at(slot, Some(&working_bank))
+                .map(|leader| proof.verify(slot, &leader))
+        };
+
+        frozen_slots.retain(|slot| *slot > root && !duplicate_slots.contains(slot));
+        for slot in frozen_slots {
+            if let Some(proof) = blocktree.get_duplicate_slot(slot) {
+                if verify(slot, &proof) != Some(true) {
+                    continue;
+                }
+                warn!("leader produced more than one version of slot {}", slot);
+                cluster_info
+                    .write()
+                    .unwrap()
+                    .push_duplicate_slot(slot, &proof);
+                duplicate_slots.insert(slot);
+            }
+        }
+
+        let proofs = cluster_info.read().unwrap().get_duplicate_slot_proofs();
+        // Forget rejected proofs that have left the crds
+        rejected_proofs.retain(|key| {
+            proofs
+                .iter()
+                .any(|(from, wallclock, _, _)| *key == (*from, *wallclock))
+        });
+        for (from, wallclock, slot, proof) in proofs {
+            if slot <= root
+                || duplicate_slots.contains(&slot)
+                || rejected_proofs.contains(&(from, wallclock))
+            {
+                continue;
+            }
+            match verify(slot, &proof) {
+                Some(true) => {
+                    warn!("cluster reported more than one version of slot {}", slot);
+                    if let Err(e) = blocktree.store_duplicate_slot(slot, &proof) {
+                        error!("Unable to store duplicate slot proof: {:?}", e);
+                    }
+                    duplicate_slots.insert(slot);
+                }
+                Some(false) => {
+                    inc_new_counter_info!("replay_stage-invalid_duplicate_slot_proof", 1);
+                    rejected_proofs.insert((from, wallclock));
+                }
+                // Retry once the leader schedule for the slot is known
+                None => (),
+            }
+        }
+    }
+
     #[allow(clippy::type_complexity)]
     fn generate_votable_banks(
         bank_forks: &Arc<RwLock<BankForks>>,
         tower: &Tower,
         progress: &mut HashMap<u64, ForkProgress>,
+        duplicate_slots: &HashSet<u64>,
     ) -> Vec<(u128, Arc<Bank>, HashMap<u64, StakeLockout>, u64)> {
         let tower_start = Instant::now();
         // Tower voting
@@ -604,6 +804,20 @@ impl ReplayStage {
                     ),
                 )
             })
+            .filter(|(b, (stake_lockouts, total_staked))| {
+                // Don't vote on a fork through a slot its leader produced more than one
+                // version of, until a supermajority has voted on the version in this fork
+                let has_unconfirmed_duplicate = duplicate_slots.iter().any(|slot| {
+                    (*slot == b.slot() || ancestors[&b.slot()].contains(slot))
+                        && !tower.is_slot_confirmed(*slot, stake_lockouts, *total_staked)
+                });
+                trace!(
+                    "bank has unconfirmed duplicate: {} {}",
+                    b.slot(),
+                    has_unconfirmed_duplicate
+                );
+                !has_unconfirmed_duplicate
+            })
             .filter(|(b, (stake_lockouts, total_staked))| {
                 let vote_threshold =
                     tower.check_vote_stake_threshold(b.slot(), &stake_lockouts, *total_staked);
@@ -841,6 +1055,7 @@ mod test {
     use super::*;
     use crate::bank_forks::Confidence;
     use crate::blocktree::get_tmp_ledger_path;
+    use crate::consensus::{VOTE_THRESHOLD_DEPTH, VOTE_THRESHOLD_SIZE};
     use crate::entry;
     use crate::erasure::ErasureConfig;
     use crate::genesis_utils::{create_genesis_block, create_genesis_block_with_leader};
@@ -913,6 +1128,70 @@ mod test {
         assert!(progress.is_empty());
     }
 
+    #[test]
+    fn test_authorized_voter_keypair() {
+        let GenesisBlockInfo {
+            genesis_block,
+            mint_keypair,
+            voting_keypair,
+        } = create_genesis_block_with_leader(10_000, &Pubkey::new_rand(), 100);
+        let bank0 = Arc::new(Bank::new(&genesis_block));
+        let vote_pubkey = voting_keypair.pubkey();
+        let voting_keypair = Arc::new(voting_keypair);
+        let new_voting_keypair = Arc::new(Keypair::new());
+
+        // no keypairs, voting is disabled
+        let keypairs: Vec<Arc<Keypair>> = vec![];
+        assert!(ReplayStage::authorized_voter_keypair(&bank0, &vote_pubkey, &keypairs).is_none());
+
+        // unknown vote account
+        let keypairs = vec![voting_keypair.clone()];
+        assert!(
+            ReplayStage::authorized_voter_keypair(&bank0, &Pubkey::new_rand(), &keypairs).is_none()
+        );
+
+        // the vote account's voter is picked out of the set
+        let keypairs = vec![new_voting_keypair.clone(), voting_keypair.clone()];
+        assert_eq!(
+            ReplayStage::authorized_voter_keypair(&bank0, &vote_pubkey, &keypairs)
+                .unwrap()
+                .pubkey(),
+            vote_pubkey
+        );
+
+        // schedule a new voter, which takes over at the next epoch
+        let tx = Transaction::new_signed_with_payer(
+            vec![vote_instruction::authorize_voter(
+                &vote_pubkey,
+                &vote_pubkey,
+                &new_voting_keypair.pubkey(),
+            )],
+            Some(&mint_keypair.pubkey()),
+            &[&mint_keypair, voting_keypair.as_ref()],
+            bank0.last_blockhash(),
+        );
+        bank0.process_transaction(&tx).unwrap();
+        assert_eq!(
+            ReplayStage::authorized_voter_keypair(&bank0, &vote_pubkey, &keypairs)
+                .unwrap()
+                .pubkey(),
+            vote_pubkey
+        );
+
+        let next_epoch_slot = bank0.epoch_schedule().get_first_slot_in_epoch(1);
+        let bank1 = Bank::new_from_parent(&bank0, &Pubkey::default(), next_epoch_slot);
+        assert_eq!(
+            ReplayStage::authorized_voter_keypair(&bank1, &vote_pubkey, &keypairs)
+                .unwrap()
+                .pubkey(),
+            new_voting_keypair.pubkey()
+        );
+
+        // the validator doesn't hold the new voter's keypair
+        let keypairs = vec![voting_keypair.clone()];
+        assert!(ReplayStage::authorized_voter_keypair(&bank1, &vote_pubkey, &keypairs).is_none());
+    }
+
     #[test]
     fn test_dead_fork_transaction_error() {
         let keypair1 = Keypair::new();
@@ -1065,11 +1344,22 @@ mod test {
             vec![0],
         )));
         let pubkey = Pubkey::new_rand();
-        let mut tower = Tower::new(&pubkey, &Pubkey::new_rand(), &bank_forks.read().unwrap());
+        let ledger_dir = tempfile::TempDir::new().unwrap();
+        let mut tower = Tower::new(
+            &pubkey,
+            &Pubkey::new_rand(),
+            &bank_forks.read().unwrap(),
+            ledger_dir.path(),
+        );
         let mut progress = HashMap::new();
 
         leader_vote(&arc_bank0, &leader_voting_pubkey);
-        let votable = ReplayStage::generate_votable_banks(&bank_forks, &tower, &mut progress);
+        let votable = ReplayStage::generate_votable_banks(
+            &bank_forks,
+            &tower,
+            &mut progress,
+            &HashSet::new(),
+        );
         if let Some((_, _, lockouts, total_staked)) = votable.into_iter().last() {
             ReplayStage::update_confidence_cache(
                 &bank_forks,
@@ -1097,7 +1387,12 @@ mod test {
         bank_forks.write().unwrap().insert(bank1);
         let arc_bank1 = bank_forks.read().unwrap().get(1).unwrap().clone();
         leader_vote(&arc_bank1, &leader_voting_pubkey);
-        let votable = ReplayStage::generate_votable_banks(&bank_forks, &tower, &mut progress);
+        let votable = ReplayStage::generate_votable_banks(
+            &bank_forks,
+            &tower,
+            &mut progress,
+            &HashSet::new(),
+        );
         if let Some((_, _, lockouts, total_staked)) = votable.into_iter().last() {
             ReplayStage::update_confidence_cache(
                 &bank_forks,
@@ -1119,7 +1414,12 @@ mod test {
         bank_forks.write().unwrap().insert(bank2);
         let arc_bank2 = bank_forks.read().unwrap().get(2).unwrap().clone();
         leader_vote(&arc_bank2, &leader_voting_pubkey);
-        let votable = ReplayStage::generate_votable_banks(&bank_forks, &tower, &mut progress);
+        let votable = ReplayStage::generate_votable_banks(
+            &bank_forks,
+            &tower,
+            &mut progress,
+            &HashSet::new(),
+        );
         if let Some((_, _, lockouts, total_staked)) = votable.into_iter().last() {
             ReplayStage::update_confidence_cache(
                 &bank_forks,
@@ -1144,4 +1444,58 @@ mod test {
             &Confidence::new_with_stake_weighted(0, 3, 2, 0)
         );
     }
+
+    #[test]
+    fn test_generate_votable_banks_duplicate_slot() {
+        fn vote(bank: &Bank, vote_pubkey: &Pubkey, slot: u64) {
+            let mut vote_account = bank.get_account(&vote_pubkey).unwrap();
+            let mut vote_state = VoteState::from(&vote_account).unwrap();
+            vote_state.process_slot_vote_unchecked(slot);
+            vote_state.to(&mut vote_account).unwrap();
+            bank.store_account(&vote_pubkey, &vote_account);
+        }
+
+        let leader_pubkey = Pubkey::new_rand();
+        let genesis_block_info = create_genesis_block_with_leader(50, &leader_pubkey, 3);
+        let leader_voting_pubkey = genesis_block_info.voting_keypair.pubkey();
+        let genesis_block = genesis_block_info.genesis_block;
+        let bank0 = Bank::new(&genesis_block);
+        bank0.freeze();
+        let bank_forks = Arc::new(RwLock::new(BankForks::new(0, bank0)));
+        // Insert a votable child of `parent_slot` in which the leader has voted on `vote_slot`
+        let insert_bank = |slot: u64, parent_slot: u64, vote_slot: u64| {
+            let parent = bank_forks.read().unwrap()[parent_slot].clone();
+            let bank = Bank::new_from_parent(&parent, &Pubkey::default(), slot);
+            let _res = bank.transfer(10, &genesis_block_info.mint_keypair, &Pubkey::new_rand());
+            vote(&bank, &leader_voting_pubkey, vote_slot);
+            for _ in 0..genesis_block.ticks_per_slot {
+                bank.register_tick(&Hash::default());
+            }
+            bank.freeze();
+            bank_forks.write().unwrap().insert(bank);
+        };
+        insert_bank(1, 0, 0);
+        let tower = Tower::new_for_tests(VOTE_THRESHOLD_DEPTH, VOTE_THRESHOLD_SIZE);
+
+        let votable_slots = |duplicate_slots: &HashSet<u64>| -> Vec<u64> {
+            ReplayStage::generate_votable_banks(
+                &bank_forks,
+                &tower,
+                &mut HashMap::new(),
+                duplicate_slots,
+            )
+            .iter()
+            .map(|(_, bank, _, _)| bank.slot())
+            .collect()
+        };
+        let duplicate_slots: HashSet<_> = vec![1].into_iter().collect();
+        assert!(votable_slots(&HashSet::new()).contains(&1));
+        assert!(!votable_slots(&duplicate_slots).contains(&1));
+
+        // Once the supermajority votes on slot 1, its descendants can be voted on
+        insert_bank(2, 1, 1);
+        let votable = votable_slots(&duplicate_slots);
+        assert!(!votable.contains(&1));
+        assert!(votable.contains(&2));
+    }
 }
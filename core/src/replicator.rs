@@ -463,6 +463,7 @@ impl Replicator {
             &exit,
             RepairStrategy::RepairRange(repair_slot_range),
             |_, _, _| true,
+            None,
         );
         info!("waiting for ledger download");
         Self::wait_for_segment_download(
@@ -850,7 +851,7 @@ impl Replicator {
                 while let Ok(mut more) = r_reader.try_recv() {
                     blobs.append(&mut more);
                 }
-                window_service::process_blobs(&blobs, blocktree)?;
+                window_service::process_blobs(&blobs, blocktree, None)?;
             }
             // check if all the slots in the segment are complete
             if Self::segment_complete(start_slot, slots_per_segment, blocktree) {
@@ -2,6 +2,7 @@
 
 use crate::blocktree;
 use crate::cluster_info;
+use crate::consensus;
 use crate::packet;
 use crate::poh_recorder;
 use bincode;
@@ -30,6 +31,7 @@ pub enum Error {
     SendError,
     PohRecorderError(poh_recorder::PohRecorderError),
     BlocktreeError(blocktree::BlocktreeError),
+    TowerError(consensus::TowerError),
     FsExtra(fs_extra::error::Error),
     ToBlobError,
 }
@@ -134,6 +136,11 @@ impl std::convert::From<blocktree::BlocktreeError> for Error {
         Error::BlocktreeError(e)
     }
 }
+impl std::convert::From<consensus::TowerError> for Error {
+    fn from(e: consensus::TowerError) -> Error {
+        Error::TowerError(e)
+    }
+}
 
 #[cfg(test)]
 mod tests {
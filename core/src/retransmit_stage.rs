@@ -12,9 +12,10 @@ use crate::streamer::BlobReceiver;
 use crate::window_service::{should_retransmit_and_persist, WindowService};
 use rand::SeedableRng;
 use rand_chacha::ChaChaRng;
-use solana_metrics::{datapoint_info, inc_new_counter_error};
+use solana_metrics::{datapoint_info, inc_new_counter_error, inc_new_counter_info};
 use solana_runtime::epoch_schedule::EpochSchedule;
 use std::cmp;
+use std::collections::HashMap;
 use std::net::UdpSocket;
 use std::sync::atomic::AtomicBool;
 use std::sync::mpsc::channel;
@@ -40,19 +41,39 @@ fn retransmit(
 
     let r_bank = bank_forks.read().unwrap().working_bank();
     let bank_epoch = r_bank.get_stakers_epoch(r_bank.slot());
+    // Each blob's tree is weighted by the stakes the leader of its slot broadcast it with
+    let mut epoch_stakes = HashMap::new();
     let mut peers_len = 0;
     for blob in &blobs {
-        let (my_index, mut peers) = cluster_info.read().unwrap().shuffle_peers_and_index(
-            staking_utils::staked_nodes_at_epoch(&r_bank, bank_epoch).as_ref(),
-            ChaChaRng::from_seed(blob.read().unwrap().seed()),
-        );
+        let (slot, index, blob_id) = {
+            let blob = blob.read().unwrap();
+            (blob.slot(), blob.index(), blob.id())
+        };
+        let stakers_epoch = r_bank.get_stakers_epoch(slot);
+        let stakes = epoch_stakes.entry(stakers_epoch).or_insert_with(|| {
+            staking_utils::staked_nodes_at_epoch(&r_bank, stakers_epoch).or_else(|| {
+                // The working bank doesn't know the stakes of the blob's epoch, so this node's
+                // tree may not match the leader's
+                inc_new_counter_info!("retransmit_stage-epoch_stakes_fallback", 1);
+                warn!(
+                    "no stakes for epoch {} of slot {}, retransmitting with epoch {} stakes",
+                    stakers_epoch, slot, bank_epoch
+                );
+                staking_utils::staked_nodes_at_epoch(&r_bank, bank_epoch)
+            })
+        });
+        let leader = leader_schedule_cache.slot_leader_at(slot, Some(r_bank.as_ref()));
+        // Blobs from anyone but the slot's leader are filtered out before they get here
+        let seed = ClusterInfo::retransmit_seed(slot, index, &leader.unwrap_or(blob_id));
+        let (my_index, mut peers) = cluster_info
+            .read()
+            .unwrap()
+            .shuffle_peers_and_index(stakes.as_ref(), ChaChaRng::from_seed(seed));
         peers_len = cmp::max(peers_len, peers.len());
         peers.remove(my_index);
 
         let (neighbors, children) = compute_retransmit_peers(DATA_PLANE_FANOUT, my_index, peers);
 
-        let leader = leader_schedule_cache
-            .slot_leader_at(blob.read().unwrap().slot(), Some(r_bank.as_ref()));
         if blob.read().unwrap().meta.forward {
             ClusterInfo::retransmit_to(&cluster_info, &neighbors, blob, leader, sock, true)?;
             ClusterInfo::retransmit_to(&cluster_info, &children, blob, leader, sock, false)?;
@@ -143,6 +164,7 @@ impl RetransmitStage {
             epoch_schedule,
         };
         let leader_schedule_cache = leader_schedule_cache.clone();
+        let window_leader_schedule_cache = Some(leader_schedule_cache.clone());
         let window_service = WindowService::new(
             blocktree,
             cluster_info.clone(),
@@ -154,6 +176,7 @@ impl RetransmitStage {
             move |id, blob, working_bank| {
                 should_retransmit_and_persist(blob, working_bank, &leader_schedule_cache, id)
             },
+            window_leader_schedule_cache,
         );
 
         let thread_hdls = vec![t_retransmit];
@@ -1,11 +1,14 @@
 //! The `rpc` module implements the Solana RPC interface.
 
 use crate::bank_forks::BankForks;
+use crate::blocktree::Blocktree;
 use crate::cluster_info::ClusterInfo;
 use crate::contact_info::ContactInfo;
+use crate::fork_tree::{ForkTree, ForkTreeExport};
+use crate::long_term_storage::{self, ConfirmedBlock, LongTermStorage};
 use crate::packet::PACKET_DATA_SIZE;
 use crate::storage_stage::StorageState;
-use crate::version::VERSION;
+use crate::version::{feature_set, VERSION};
 use bincode::{deserialize, serialize};
 use jsonrpc_core::{Error, Metadata, Result};
 use jsonrpc_derive::rpc;
@@ -15,6 +18,7 @@ use solana_sdk::account::Account;
 use solana_sdk::fee_calculator::FeeCalculator;
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::Signature;
+use solana_sdk::timing::Slot;
 use solana_sdk::transaction::{self, Transaction};
 use solana_vote_api::vote_state::{VoteState, MAX_LOCKOUT_HISTORY};
 use std::net::{SocketAddr, UdpSocket};
@@ -25,7 +29,8 @@ use std::time::{Duration, Instant};
 
 #[derive(Debug, Clone)]
 pub struct JsonRpcConfig {
-    pub enable_fullnode_exit: bool, // Enable the 'fullnodeExit' command
+    pub enable_fullnode_exit: bool,    // Enable the 'fullnodeExit' command
+    pub enable_fork_tree_export: bool, // Enable the 'getForkTree' and 'dumpForkTree' commands
     pub drone_addr: Option<SocketAddr>,
 }
 
@@ -33,6 +38,7 @@ impl Default for JsonRpcConfig {
     fn default() -> Self {
         Self {
             enable_fullnode_exit: false,
+            enable_fork_tree_export: false,
             drone_addr: None,
         }
     }
@@ -41,7 +47,10 @@ impl Default for JsonRpcConfig {
 #[derive(Clone)]
 pub struct JsonRpcRequestProcessor {
     bank_forks: Arc<RwLock<BankForks>>,
+    blocktree: Arc<Blocktree>,
+    long_term_storage: Option<Arc<dyn LongTermStorage>>,
     storage_state: StorageState,
+    fork_tree_export: ForkTreeExport,
     config: JsonRpcConfig,
     fullnode_exit: Arc<AtomicBool>,
 }
@@ -53,13 +62,19 @@ impl JsonRpcRequestProcessor {
 
     pub fn new(
         storage_state: StorageState,
+        fork_tree_export: ForkTreeExport,
         config: JsonRpcConfig,
         bank_forks: Arc<RwLock<BankForks>>,
+        blocktree: Arc<Blocktree>,
+        long_term_storage: Option<Arc<dyn LongTermStorage>>,
         fullnode_exit: &Arc<AtomicBool>,
     ) -> Self {
         JsonRpcRequestProcessor {
             bank_forks,
+            blocktree,
+            long_term_storage,
             storage_state,
+            fork_tree_export,
             config,
             fullnode_exit: fullnode_exit.clone(),
         }
@@ -89,9 +104,24 @@ impl JsonRpcRequestProcessor {
         (blockhash.to_string(), fee_calculator)
     }
 
-    pub fn get_signature_status(&self, signature: Signature) -> Option<transaction::Result<()>> {
-        self.get_signature_confirmation_status(signature)
-            .map(|x| x.1)
+    /// Falls back to long-term storage for signatures that are no longer in the status cache
+    pub fn get_signature_status(
+        &self,
+        signature: Signature,
+    ) -> Result<Option<transaction::Result<()>>> {
+        if let Some((_, status)) = self.get_signature_confirmation_status(signature) {
+            return Ok(Some(status));
+        }
+        match &self.long_term_storage {
+            Some(long_term_storage) => long_term_storage
+                .get_transaction_status(&signature)
+                .map(|status| status.map(|status| status.status))
+                .map_err(|err| {
+                    info!("get_signature_status: long-term storage error: {:?}", err);
+                    Error::internal_error()
+                }),
+            None => Ok(None),
+        }
     }
 
     pub fn get_signature_confirmations(&self, signature: Signature) -> Option<usize> {
@@ -106,6 +136,30 @@ impl JsonRpcRequestProcessor {
         self.bank().get_signature_confirmation_status(&signature)
     }
 
+    /// Serves rooted blocks from the Blocktree, falling back to long-term storage for blocks
+    /// that have been purged from it
+    pub fn get_confirmed_block(&self, slot: Slot) -> Result<Option<ConfirmedBlock>> {
+        let root_bank = {
+            let bank_forks = self.bank_forks.read().unwrap();
+            bank_forks[bank_forks.root()].clone()
+        };
+        let block = match long_term_storage::get_confirmed_block(&self.blocktree, &root_bank, slot)
+        {
+            Ok(None) => match &self.long_term_storage {
+                Some(long_term_storage) => long_term_storage.get_block(slot),
+                None => Ok(None),
+            },
+            block => block,
+        };
+        block.map_err(|err| {
+            info!(
+                "get_confirmed_block: error reading slot {}: {:?}",
+                slot, err
+            );
+            Error::internal_error()
+        })
+    }
+
     fn get_slot(&self) -> Result<u64> {
         Ok(self.bank().slot())
     }
@@ -192,6 +246,26 @@ impl JsonRpcRequestProcessor {
             Ok(false)
         }
     }
+
+    pub fn get_fork_tree(&self) -> Result<ForkTree> {
+        if self.config.enable_fork_tree_export {
+            Ok(self.fork_tree_export.fork_tree())
+        } else {
+            Err(Error::invalid_request())
+        }
+    }
+
+    pub fn dump_fork_tree(&self) -> Result<bool> {
+        if !self.config.enable_fork_tree_export {
+            debug!("dump_fork_tree ignored");
+            return Ok(false);
+        }
+        self.fork_tree_export.dump().map_err(|err| {
+            info!("dump_fork_tree: error writing the fork tree: {:?}", err);
+            Error::internal_error()
+        })?;
+        Ok(true)
+    }
 }
 
 fn get_tpu_addr(cluster_info: &Arc<RwLock<ClusterInfo>>) -> Result<SocketAddr> {
@@ -215,6 +289,7 @@ pub struct Meta {
 impl Metadata for Meta {}
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
 pub struct RpcContactInfo {
     /// Pubkey of the node as a base-58 string
     pub pubkey: String,
@@ -224,6 +299,10 @@ pub struct RpcContactInfo {
     pub tpu: Option<SocketAddr>,
     /// JSON RPC port
     pub rpc: Option<SocketAddr>,
+    /// Software version the node gossips, if any
+    pub version: Option<String>,
+    /// Feature set identifier the node gossips, if any
+    pub feature_set: Option<u32>,
 }
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
@@ -272,6 +351,8 @@ pub struct RpcEpochInfo {
 pub struct RpcVersionInfo {
     /// The current version of solana-core
     pub solana_core: String,
+    /// Identifies the cluster features this version supports
+    pub feature_set: u32,
 }
 
 #[rpc(server)]
@@ -309,6 +390,9 @@ pub trait RpcSol {
         _: String,
     ) -> Result<Option<transaction::Result<()>>>;
 
+    #[rpc(meta, name = "getConfirmedBlock")]
+    fn get_confirmed_block(&self, _: Self::Metadata, _: Slot) -> Result<Option<ConfirmedBlock>>;
+
     #[rpc(meta, name = "getSlot")]
     fn get_slot(&self, _: Self::Metadata) -> Result<u64>;
 
@@ -345,6 +429,12 @@ pub trait RpcSol {
     #[rpc(meta, name = "fullnodeExit")]
     fn fullnode_exit(&self, _: Self::Metadata) -> Result<bool>;
 
+    #[rpc(meta, name = "getForkTree")]
+    fn get_fork_tree(&self, _: Self::Metadata) -> Result<ForkTree>;
+
+    #[rpc(meta, name = "dumpForkTree")]
+    fn dump_fork_tree(&self, _: Self::Metadata) -> Result<bool>;
+
     #[rpc(meta, name = "getNumBlocksSinceSignatureConfirmation")]
     fn get_num_blocks_since_signature_confirmation(
         &self,
@@ -422,11 +512,14 @@ impl RpcSol for RpcSolImpl {
             .iter()
             .filter_map(|(contact_info, _)| {
                 if ContactInfo::is_valid_address(&contact_info.gossip) {
+                    let version = cluster_info.get_version_for_node(&contact_info.id);
                     Some(RpcContactInfo {
                         pubkey: contact_info.id.to_string(),
                         gossip: Some(contact_info.gossip),
                         tpu: valid_address_or_none(&contact_info.tpu),
                         rpc: valid_address_or_none(&contact_info.rpc),
+                        version: version.map(|version| version.version.clone()),
+                        feature_set: version.map(|version| version.feature_set),
                     })
                 } else {
                     None // Exclude spy nodes
@@ -475,8 +568,24 @@ impl RpcSol for RpcSolImpl {
         meta: Self::Metadata,
         id: String,
     ) -> Result<Option<transaction::Result<()>>> {
-        self.get_signature_confirmation(meta, id)
-            .map(|res| res.map(|x| x.1))
+        debug!("get_signature_status rpc request received: {:?}", id);
+        let signature = verify_signature(&id)?;
+        meta.request_processor
+            .read()
+            .unwrap()
+            .get_signature_status(signature)
+    }
+
+    fn get_confirmed_block(
+        &self,
+        meta: Self::Metadata,
+        slot: Slot,
+    ) -> Result<Option<ConfirmedBlock>> {
+        debug!("get_confirmed_block rpc request received: {:?}", slot);
+        meta.request_processor
+            .read()
+            .unwrap()
+            .get_confirmed_block(slot)
     }
 
     fn get_slot(&self, meta: Self::Metadata) -> Result<u64> {
@@ -542,7 +651,7 @@ impl RpcSol for RpcSolImpl {
             .map_err(|err| {
                 info!("request_airdrop_transaction failed: {:?}", err);
                 Error::internal_error()
-            })?;;
+            })?;
 
         let data = serialize(&transaction).map_err(|err| {
             info!("request_airdrop: serialize error: {:?}", err);
@@ -566,7 +675,7 @@ impl RpcSol for RpcSolImpl {
                 .request_processor
                 .read()
                 .unwrap()
-                .get_signature_status(signature);
+                .get_signature_status(signature)?;
 
             if signature_status == Some(Ok(())) {
                 info!("airdrop signature ok");
@@ -647,9 +756,18 @@ impl RpcSol for RpcSolImpl {
         meta.request_processor.read().unwrap().fullnode_exit()
     }
 
+    fn get_fork_tree(&self, meta: Self::Metadata) -> Result<ForkTree> {
+        meta.request_processor.read().unwrap().get_fork_tree()
+    }
+
+    fn dump_fork_tree(&self, meta: Self::Metadata) -> Result<bool> {
+        meta.request_processor.read().unwrap().dump_fork_tree()
+    }
+
     fn get_version(&self, _: Self::Metadata) -> Result<RpcVersionInfo> {
         Ok(RpcVersionInfo {
             solana_core: VERSION.to_string(),
+            feature_set: feature_set(),
         })
     }
 
@@ -663,7 +781,9 @@ impl RpcSol for RpcSolImpl {
 mod tests {
     use super::*;
     use crate::contact_info::ContactInfo;
+    use crate::entry::next_entry;
     use crate::genesis_utils::{create_genesis_block, GenesisBlockInfo};
+    use crate::long_term_storage::FileLongTermStorage;
     use jsonrpc_core::{MetaIoHandler, Output, Response, Value};
     use solana_sdk::fee_calculator::DEFAULT_BURN_PERCENT;
     use solana_sdk::hash::{hash, Hash};
@@ -677,8 +797,17 @@ mod tests {
 
     fn start_rpc_handler_with_tx(
         pubkey: &Pubkey,
-    ) -> (MetaIoHandler<Meta>, Meta, Arc<Bank>, Hash, Keypair, Pubkey) {
+    ) -> (
+        MetaIoHandler<Meta>,
+        Meta,
+        Arc<Bank>,
+        Hash,
+        Keypair,
+        Pubkey,
+        tempfile::TempDir,
+    ) {
         let (bank_forks, alice) = new_bank_forks();
+        let (blocktree, ledger_dir) = new_blocktree();
         let bank = bank_forks.read().unwrap().working_bank();
         let leader_pubkey = *bank.collector_id();
         let exit = Arc::new(AtomicBool::new(false));
@@ -692,8 +821,11 @@ mod tests {
 
         let request_processor = Arc::new(RwLock::new(JsonRpcRequestProcessor::new(
             StorageState::default(),
+            ForkTreeExport::default(),
             JsonRpcConfig::default(),
             bank_forks,
+            blocktree,
+            None,
             &exit,
         )));
         let cluster_info = Arc::new(RwLock::new(ClusterInfo::new_with_invalid_keypair(
@@ -715,19 +847,23 @@ mod tests {
             request_processor,
             cluster_info,
         };
-        (io, meta, bank, blockhash, alice, leader_pubkey)
+        (io, meta, bank, blockhash, alice, leader_pubkey, ledger_dir)
     }
 
     #[test]
     fn test_rpc_request_processor_new() {
         let bob_pubkey = Pubkey::new_rand();
         let exit = Arc::new(AtomicBool::new(false));
+        let (blocktree, _ledger_dir) = new_blocktree();
         let (bank_forks, alice) = new_bank_forks();
         let bank = bank_forks.read().unwrap().working_bank();
         let request_processor = JsonRpcRequestProcessor::new(
             StorageState::default(),
+            ForkTreeExport::default(),
             JsonRpcConfig::default(),
             bank_forks,
+            blocktree,
+            None,
             &exit,
         );
         thread::spawn(move || {
@@ -743,7 +879,7 @@ mod tests {
     #[test]
     fn test_rpc_get_balance() {
         let bob_pubkey = Pubkey::new_rand();
-        let (io, meta, _bank, _blockhash, _alice, _leader_pubkey) =
+        let (io, meta, _bank, _blockhash, _alice, _leader_pubkey, _ledger_dir) =
             start_rpc_handler_with_tx(&bob_pubkey);
 
         let req = format!(
@@ -762,7 +898,7 @@ mod tests {
     #[test]
     fn test_rpc_get_cluster_nodes() {
         let bob_pubkey = Pubkey::new_rand();
-        let (io, meta, _bank, _blockhash, _alice, leader_pubkey) =
+        let (io, meta, _bank, _blockhash, _alice, leader_pubkey, _ledger_dir) =
             start_rpc_handler_with_tx(&bob_pubkey);
 
         let req = format!(r#"{{"jsonrpc":"2.0","id":1,"method":"getClusterNodes"}}"#);
@@ -771,7 +907,7 @@ mod tests {
             .expect("actual response deserialization");
 
         let expected = format!(
-            r#"{{"jsonrpc":"2.0","result":[{{"pubkey": "{}", "gossip": "127.0.0.1:1235", "tpu": "127.0.0.1:1234", "rpc": "127.0.0.1:8899"}}],"id":1}}"#,
+            r#"{{"jsonrpc":"2.0","result":[{{"pubkey": "{}", "gossip": "127.0.0.1:1235", "tpu": "127.0.0.1:1234", "rpc": "127.0.0.1:8899", "version": null, "featureSet": null}}],"id":1}}"#,
             leader_pubkey,
         );
 
@@ -783,7 +919,7 @@ mod tests {
     #[test]
     fn test_rpc_get_slot_leader() {
         let bob_pubkey = Pubkey::new_rand();
-        let (io, meta, _bank, _blockhash, _alice, leader_pubkey) =
+        let (io, meta, _bank, _blockhash, _alice, leader_pubkey, _ledger_dir) =
             start_rpc_handler_with_tx(&bob_pubkey);
 
         let req = format!(r#"{{"jsonrpc":"2.0","id":1,"method":"getSlotLeader"}}"#);
@@ -799,7 +935,7 @@ mod tests {
     #[test]
     fn test_rpc_get_tx_count() {
         let bob_pubkey = Pubkey::new_rand();
-        let (io, meta, _bank, _blockhash, _alice, _leader_pubkey) =
+        let (io, meta, _bank, _blockhash, _alice, _leader_pubkey, _ledger_dir) =
             start_rpc_handler_with_tx(&bob_pubkey);
 
         let req = format!(r#"{{"jsonrpc":"2.0","id":1,"method":"getTransactionCount"}}"#);
@@ -815,7 +951,7 @@ mod tests {
     #[test]
     fn test_rpc_get_total_supply() {
         let bob_pubkey = Pubkey::new_rand();
-        let (io, meta, _bank, _blockhash, _alice, _leader_pubkey) =
+        let (io, meta, _bank, _blockhash, _alice, _leader_pubkey, _ledger_dir) =
             start_rpc_handler_with_tx(&bob_pubkey);
 
         let req = format!(r#"{{"jsonrpc":"2.0","id":1,"method":"getTotalSupply"}}"#);
@@ -841,7 +977,7 @@ mod tests {
     #[test]
     fn test_rpc_get_account_info() {
         let bob_pubkey = Pubkey::new_rand();
-        let (io, meta, _bank, _blockhash, _alice, _leader_pubkey) =
+        let (io, meta, _bank, _blockhash, _alice, _leader_pubkey, _ledger_dir) =
             start_rpc_handler_with_tx(&bob_pubkey);
 
         let req = format!(
@@ -869,7 +1005,7 @@ mod tests {
     #[test]
     fn test_rpc_get_program_accounts() {
         let bob = Keypair::new();
-        let (io, meta, bank, blockhash, _alice, _leader_pubkey) =
+        let (io, meta, bank, blockhash, _alice, _leader_pubkey, _ledger_dir) =
             start_rpc_handler_with_tx(&bob.pubkey());
 
         let new_program_id = Pubkey::new_rand();
@@ -904,7 +1040,7 @@ mod tests {
     #[test]
     fn test_rpc_confirm_tx() {
         let bob_pubkey = Pubkey::new_rand();
-        let (io, meta, _bank, blockhash, alice, _leader_pubkey) =
+        let (io, meta, _bank, blockhash, alice, _leader_pubkey, _ledger_dir) =
             start_rpc_handler_with_tx(&bob_pubkey);
         let tx = system_transaction::transfer(&alice, &bob_pubkey, 20, blockhash);
 
@@ -924,7 +1060,7 @@ mod tests {
     #[test]
     fn test_rpc_get_signature_status() {
         let bob_pubkey = Pubkey::new_rand();
-        let (io, meta, _bank, blockhash, alice, _leader_pubkey) =
+        let (io, meta, _bank, blockhash, alice, _leader_pubkey, _ledger_dir) =
             start_rpc_handler_with_tx(&bob_pubkey);
         let tx = system_transaction::transfer(&alice, &bob_pubkey, 20, blockhash);
 
@@ -989,7 +1125,7 @@ mod tests {
     #[test]
     fn test_rpc_get_recent_blockhash() {
         let bob_pubkey = Pubkey::new_rand();
-        let (io, meta, _bank, blockhash, _alice, _leader_pubkey) =
+        let (io, meta, _bank, blockhash, _alice, _leader_pubkey, _ledger_dir) =
             start_rpc_handler_with_tx(&bob_pubkey);
 
         let req = format!(r#"{{"jsonrpc":"2.0","id":1,"method":"getRecentBlockhash"}}"#);
@@ -1016,7 +1152,7 @@ mod tests {
     #[test]
     fn test_rpc_fail_request_airdrop() {
         let bob_pubkey = Pubkey::new_rand();
-        let (io, meta, _bank, _blockhash, _alice, _leader_pubkey) =
+        let (io, meta, _bank, _blockhash, _alice, _leader_pubkey, _ledger_dir) =
             start_rpc_handler_with_tx(&bob_pubkey);
 
         // Expect internal error because no drone is available
@@ -1037,6 +1173,7 @@ mod tests {
     #[test]
     fn test_rpc_send_bad_tx() {
         let exit = Arc::new(AtomicBool::new(false));
+        let (blocktree, _ledger_dir) = new_blocktree();
 
         let mut io = MetaIoHandler::default();
         let rpc = RpcSolImpl;
@@ -1045,8 +1182,11 @@ mod tests {
             request_processor: {
                 let request_processor = JsonRpcRequestProcessor::new(
                     StorageState::default(),
+                    ForkTreeExport::default(),
                     JsonRpcConfig::default(),
                     new_bank_forks().0,
+                    blocktree,
+                    None,
                     &exit,
                 );
                 Arc::new(RwLock::new(request_processor))
@@ -1104,6 +1244,13 @@ mod tests {
         );
     }
 
+    // The ledger is removed when the returned `TempDir` is dropped
+    fn new_blocktree() -> (Arc<Blocktree>, tempfile::TempDir) {
+        let ledger_dir = tempfile::TempDir::new().unwrap();
+        let blocktree = Arc::new(Blocktree::open(ledger_dir.path()).unwrap());
+        (blocktree, ledger_dir)
+    }
+
     fn new_bank_forks() -> (Arc<RwLock<BankForks>>, Keypair) {
         let GenesisBlockInfo {
             genesis_block,
@@ -1120,10 +1267,14 @@ mod tests {
     #[test]
     fn test_rpc_request_processor_config_default_trait_fullnode_exit_fails() {
         let exit = Arc::new(AtomicBool::new(false));
+        let (blocktree, _ledger_dir) = new_blocktree();
         let request_processor = JsonRpcRequestProcessor::new(
             StorageState::default(),
+            ForkTreeExport::default(),
             JsonRpcConfig::default(),
             new_bank_forks().0,
+            blocktree,
+            None,
             &exit,
         );
         assert_eq!(request_processor.fullnode_exit(), Ok(false));
@@ -1133,29 +1284,153 @@ mod tests {
     #[test]
     fn test_rpc_request_processor_allow_fullnode_exit_config() {
         let exit = Arc::new(AtomicBool::new(false));
+        let (blocktree, _ledger_dir) = new_blocktree();
         let mut config = JsonRpcConfig::default();
         config.enable_fullnode_exit = true;
         let request_processor = JsonRpcRequestProcessor::new(
             StorageState::default(),
+            ForkTreeExport::default(),
             config,
             new_bank_forks().0,
+            blocktree,
+            None,
             &exit,
         );
         assert_eq!(request_processor.fullnode_exit(), Ok(true));
         assert_eq!(exit.load(Ordering::Relaxed), true);
     }
 
+    #[test]
+    fn test_rpc_fork_tree() {
+        let exit = Arc::new(AtomicBool::new(false));
+        let (blocktree, _ledger_dir) = new_blocktree();
+        let dump_dir = tempfile::TempDir::new().unwrap();
+        let fork_tree_export = ForkTreeExport::new(dump_dir.path(), true);
+        let fork_tree = ForkTree {
+            root: 3,
+            ..ForkTree::default()
+        };
+        fork_tree_export.update(fork_tree.clone());
+
+        let request_processor = JsonRpcRequestProcessor::new(
+            StorageState::default(),
+            fork_tree_export.clone(),
+            JsonRpcConfig::default(),
+            new_bank_forks().0,
+            blocktree.clone(),
+            None,
+            &exit,
+        );
+        assert!(request_processor.get_fork_tree().is_err());
+        assert_eq!(request_processor.dump_fork_tree(), Ok(false));
+
+        let mut config = JsonRpcConfig::default();
+        config.enable_fork_tree_export = true;
+        let request_processor = JsonRpcRequestProcessor::new(
+            StorageState::default(),
+            fork_tree_export,
+            config,
+            new_bank_forks().0,
+            blocktree,
+            None,
+            &exit,
+        );
+        assert_eq!(request_processor.get_fork_tree(), Ok(fork_tree));
+        assert_eq!(request_processor.dump_fork_tree(), Ok(true));
+        assert!(dump_dir
+            .path()
+            .join(crate::fork_tree::FORK_TREE_DOT_FILENAME)
+            .exists());
+    }
+
+    #[test]
+    fn test_rpc_get_confirmed_block() {
+        let exit = Arc::new(AtomicBool::new(false));
+        let (bank_forks, alice) = new_bank_forks();
+        let blockhash = bank_forks.read().unwrap().working_bank().last_blockhash();
+        let (blocktree, _ledger_dir) = new_blocktree();
+        let storage_dir = tempfile::TempDir::new().unwrap();
+        let long_term_storage = Arc::new(FileLongTermStorage::new(storage_dir.path()).unwrap());
+
+        // Slot 0 is still in the Blocktree, slot 1 was purged after it was uploaded
+        let entry = next_entry(&blockhash, 1, vec![]);
+        blocktree
+            .write_slot_entries(0, 0, true, &[entry.clone()])
+            .unwrap();
+        blocktree.set_roots(&[0]).unwrap();
+        let tx = system_transaction::transfer(&alice, &Pubkey::new_rand(), 20, blockhash);
+        let uploaded_block = ConfirmedBlock {
+            slot: 1,
+            parent_slot: 0,
+            blockhash: hash(&[1]),
+            previous_blockhash: entry.hash,
+            transactions: vec![(tx.clone(), Some(Ok(())))],
+        };
+        long_term_storage.upload_block(&uploaded_block).unwrap();
+
+        let mut io = MetaIoHandler::default();
+        let rpc = RpcSolImpl;
+        io.extend_with(rpc.to_delegate());
+        let meta = Meta {
+            request_processor: Arc::new(RwLock::new(JsonRpcRequestProcessor::new(
+                StorageState::default(),
+                ForkTreeExport::default(),
+                JsonRpcConfig::default(),
+                bank_forks,
+                blocktree,
+                Some(long_term_storage),
+                &exit,
+            ))),
+            cluster_info: Arc::new(RwLock::new(ClusterInfo::new_with_invalid_keypair(
+                ContactInfo::default(),
+            ))),
+        };
+
+        let get_confirmed_block = |slot: Slot| {
+            let req = format!(
+                r#"{{"jsonrpc":"2.0","id":1,"method":"getConfirmedBlock","params":[{}]}}"#,
+                slot
+            );
+            let res = io.handle_request_sync(&req, meta.clone());
+            let result: Value = serde_json::from_str(&res.expect("actual response"))
+                .expect("actual response deserialization");
+            serde_json::from_value::<Option<ConfirmedBlock>>(result["result"].clone()).unwrap()
+        };
+        assert_eq!(
+            get_confirmed_block(0),
+            Some(ConfirmedBlock {
+                slot: 0,
+                parent_slot: 0,
+                blockhash: entry.hash,
+                previous_blockhash: entry.hash,
+                transactions: vec![],
+            })
+        );
+        assert_eq!(get_confirmed_block(1), Some(uploaded_block));
+        assert_eq!(get_confirmed_block(2), None);
+
+        // Signature statuses fall back to long-term storage as well
+        assert_eq!(
+            meta.request_processor
+                .read()
+                .unwrap()
+                .get_signature_status(tx.signatures[0]),
+            Ok(Some(Ok(())))
+        );
+    }
+
     #[test]
     fn test_rpc_get_version() {
         let bob_pubkey = Pubkey::new_rand();
-        let (io, meta, ..) = start_rpc_handler_with_tx(&bob_pubkey);
+        let (io, meta, .., _ledger_dir) = start_rpc_handler_with_tx(&bob_pubkey);
 
         let req = format!(r#"{{"jsonrpc":"2.0","id":1,"method":"getVersion"}}"#);
         let res = io.handle_request_sync(&req, meta);
         let expected = json!({
             "jsonrpc": "2.0",
             "result": {
-                "solana-core": VERSION
+                "solana-core": VERSION,
+                "feature-set": feature_set()
             },
             "id": 1
         });
@@ -83,6 +83,26 @@ pub trait RpcSolPubSub {
         name = "signatureUnsubscribe"
     )]
     fn signature_unsubscribe(&self, _: Option<Self::Metadata>, _: SubscriptionId) -> Result<bool>;
+
+    // Get notification every time a slot is voted on by a supermajority of stake in gossip
+    #[pubsub(
+        subscription = "optimisticConfirmationNotification",
+        subscribe,
+        name = "optimisticConfirmationSubscribe"
+    )]
+    fn optimistic_confirmation_subscribe(&self, _: Self::Metadata, _: Subscriber<u64>);
+
+    // Unsubscribe from optimistic confirmation notification subscription.
+    #[pubsub(
+        subscription = "optimisticConfirmationNotification",
+        unsubscribe,
+        name = "optimisticConfirmationUnsubscribe"
+    )]
+    fn optimistic_confirmation_unsubscribe(
+        &self,
+        _: Option<Self::Metadata>,
+        _: SubscriptionId,
+    ) -> Result<bool>;
 }
 
 #[derive(Default)]
@@ -232,6 +252,40 @@ impl RpcSolPubSub for RpcSolPubSubImpl {
             })
         }
     }
+
+    fn optimistic_confirmation_subscribe(
+        &self,
+        _meta: Self::Metadata,
+        subscriber: Subscriber<u64>,
+    ) {
+        let id = self.uid.fetch_add(1, atomic::Ordering::Relaxed);
+        let sub_id = SubscriptionId::Number(id as u64);
+        info!("optimistic_confirmation_subscribe: id={:?}", sub_id);
+        let sink = subscriber.assign_id(sub_id.clone()).unwrap();
+
+        self.subscriptions
+            .add_optimistic_confirmation_subscription(&sub_id, &sink);
+    }
+
+    fn optimistic_confirmation_unsubscribe(
+        &self,
+        _meta: Option<Self::Metadata>,
+        id: SubscriptionId,
+    ) -> Result<bool> {
+        info!("optimistic_confirmation_unsubscribe: id={:?}", id);
+        if self
+            .subscriptions
+            .remove_optimistic_confirmation_subscription(&id)
+        {
+            Ok(true)
+        } else {
+            Err(Error {
+                code: ErrorCode::InvalidParams,
+                message: "Invalid Request: Subscription id does not exist".into(),
+                data: None,
+            })
+        }
+    }
 }
 
 #[cfg(test)]
@@ -306,7 +360,10 @@ mod tests {
             let expected_res: Option<transaction::Result<()>> = Some(Ok(()));
             let expected_res_str =
                 serde_json::to_string(&serde_json::to_value(expected_res).unwrap()).unwrap();
-            let expected = format!(r#"{{"jsonrpc":"2.0","method":"signatureNotification","params":{{"result":{},"subscription":0}}}}"#, expected_res_str);
+            let expected = format!(
+                r#"{{"jsonrpc":"2.0","method":"signatureNotification","params":{{"result":{},"subscription":0}}}}"#,
+                expected_res_str
+            );
             assert_eq!(expected, response);
         }
     }
@@ -350,7 +407,9 @@ mod tests {
         let req =
             format!(r#"{{"jsonrpc":"2.0","id":1,"method":"signatureUnsubscribe","params":[1]}}"#);
         let res = io.handle_request_sync(&req, session.clone());
-        let expected = format!(r#"{{"jsonrpc":"2.0","error":{{"code":-32602,"message":"Invalid Request: Subscription id does not exist"}},"id":1}}"#);
+        let expected = format!(
+            r#"{{"jsonrpc":"2.0","error":{{"code":-32602,"message":"Invalid Request: Subscription id does not exist"}},"id":1}}"#
+        );
         let expected: Response = serde_json::from_str(&expected).unwrap();
 
         let result: Response = serde_json::from_str(&res.unwrap()).unwrap();
@@ -491,7 +550,9 @@ mod tests {
         let req =
             format!(r#"{{"jsonrpc":"2.0","id":1,"method":"accountUnsubscribe","params":[1]}}"#);
         let res = io.handle_request_sync(&req, session.clone());
-        let expected = format!(r#"{{"jsonrpc":"2.0","error":{{"code":-32602,"message":"Invalid Request: Subscription id does not exist"}},"id":1}}"#);
+        let expected = format!(
+            r#"{{"jsonrpc":"2.0","error":{{"code":-32602,"message":"Invalid Request: Subscription id does not exist"}},"id":1}}"#
+        );
         let expected: Response = serde_json::from_str(&expected).unwrap();
 
         let result: Response = serde_json::from_str(&res.unwrap()).unwrap();
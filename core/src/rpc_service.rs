@@ -1,7 +1,10 @@
 //! The `rpc_service` module implements the Solana JSON RPC service.
 
 use crate::bank_forks::BankForks;
+use crate::blocktree::Blocktree;
 use crate::cluster_info::ClusterInfo;
+use crate::fork_tree::ForkTreeExport;
+use crate::long_term_storage::LongTermStorage;
 use crate::rpc::*;
 use crate::service::Service;
 use crate::storage_stage::StorageState;
@@ -71,6 +74,7 @@ impl RequestMiddleware for RpcRequestMiddleware {
         trace!("request uri: {}", request.uri());
         match request.uri().path() {
             "/snapshot.tar.bz2" => self.get("snapshot.tar.bz2"),
+            "/incremental-snapshot.tar.bz2" => self.get("incremental-snapshot.tar.bz2"),
             "/genesis.tar.bz2" => self.get("genesis.tar.bz2"),
             _ => RequestMiddlewareAction::Proceed {
                 should_continue_on_invalid_cors: false,
@@ -85,8 +89,11 @@ impl JsonRpcService {
         cluster_info: &Arc<RwLock<ClusterInfo>>,
         rpc_addr: SocketAddr,
         storage_state: StorageState,
+        fork_tree_export: ForkTreeExport,
         config: JsonRpcConfig,
         bank_forks: Arc<RwLock<BankForks>>,
+        blocktree: Arc<Blocktree>,
+        long_term_storage: Option<Arc<dyn LongTermStorage>>,
         ledger_path: &Path,
         exit: &Arc<AtomicBool>,
     ) -> Self {
@@ -94,8 +101,11 @@ impl JsonRpcService {
         info!("rpc configuration: {:?}", config);
         let request_processor = Arc::new(RwLock::new(JsonRpcRequestProcessor::new(
             storage_state,
+            fork_tree_export,
             config,
             bank_forks,
+            blocktree,
+            long_term_storage,
             exit,
         )));
         let request_processor_ = request_processor.clone();
@@ -151,6 +161,7 @@ impl Service for JsonRpcService {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::blocktree::get_tmp_ledger_path;
     use crate::contact_info::ContactInfo;
     use crate::genesis_utils::{create_genesis_block, GenesisBlockInfo};
     use solana_runtime::bank::Bank;
@@ -174,12 +185,16 @@ mod tests {
             solana_netutil::find_available_port_in_range((10000, 65535)).unwrap(),
         );
         let bank_forks = Arc::new(RwLock::new(BankForks::new(bank.slot(), bank)));
+        let ledger_path = get_tmp_ledger_path!();
         let rpc_service = JsonRpcService::new(
             &cluster_info,
             rpc_addr,
             StorageState::default(),
+            ForkTreeExport::default(),
             JsonRpcConfig::default(),
             bank_forks,
+            Arc::new(Blocktree::open(&ledger_path).unwrap()),
+            None,
             &PathBuf::from("farf"),
             &exit,
         );
@@ -196,5 +211,6 @@ mod tests {
         );
         exit.store(true, Ordering::Relaxed);
         rpc_service.join().unwrap();
+        Blocktree::destroy(&ledger_path).expect("Expected successful database destruction");
     }
 }
@@ -7,19 +7,22 @@
 
 use crate::cuda_runtime::PinnedVec;
 use crate::packet::Packets;
+use crate::packet_qos::PacketQos;
 use crate::recycler::Recycler;
 use crate::result::{Error, Result};
 use crate::service::Service;
 use crate::sigverify;
 use crate::sigverify::TxOffset;
-use crate::streamer::{self, PacketReceiver};
+use crate::streamer::PacketReceiver;
 use crossbeam_channel::Sender as CrossbeamSender;
 use solana_measure::measure::Measure;
 use solana_metrics::{datapoint_info, inc_new_counter_info};
-use solana_sdk::timing;
+use solana_sdk::timing::{self, duration_as_ms};
+use std::collections::VecDeque;
 use std::sync::mpsc::{Receiver, RecvTimeoutError};
 use std::sync::{Arc, Mutex};
 use std::thread::{self, Builder, JoinHandle};
+use std::time::{Duration, Instant};
 
 #[cfg(feature = "cuda")]
 const RECV_BATCH_MAX: usize = 5_000;
@@ -27,6 +30,10 @@ const RECV_BATCH_MAX: usize = 5_000;
 #[cfg(not(feature = "cuda"))]
 const RECV_BATCH_MAX: usize = 1000;
 
+// Packets a verifier can fall behind by before `PacketQos` sheds all but `RECV_BATCH_MAX` of
+// them
+const MAX_BACKLOG: usize = RECV_BATCH_MAX * 4;
+
 pub type VerifiedPackets = Vec<(Packets, Vec<u8>)>;
 
 pub struct SigVerifyStage {
@@ -39,10 +46,15 @@ impl SigVerifyStage {
         packet_receiver: Receiver<Packets>,
         sigverify_disabled: bool,
         verified_sender: CrossbeamSender<VerifiedPackets>,
+        packet_qos: &Arc<PacketQos>,
     ) -> Self {
         sigverify::init();
-        let thread_hdls =
-            Self::verifier_services(packet_receiver, verified_sender, sigverify_disabled);
+        let thread_hdls = Self::verifier_services(
+            packet_receiver,
+            verified_sender,
+            sigverify_disabled,
+            packet_qos,
+        );
         Self { thread_hdls }
     }
 
@@ -60,19 +72,76 @@ impl SigVerifyStage {
         batch.into_iter().zip(r).collect()
     }
 
+    // Moves packets from `recvr` into `pending` until more than `MAX_BACKLOG` are pending or
+    // `recvr` is empty, waiting for packets only if none are pending.  Returns the number of
+    // packets received and pending.
+    fn recv_pending(
+        recvr: &PacketReceiver,
+        pending: &mut VecDeque<Packets>,
+    ) -> Result<(usize, usize, u64)> {
+        let mut num_received = 0;
+        if pending.is_empty() {
+            let packets = recvr.recv_timeout(Duration::new(1, 0))?;
+            num_received += packets.packets.len();
+            pending.push_back(packets);
+        }
+        let recv_start = Instant::now();
+        let mut num_pending: usize = pending.iter().map(|packets| packets.packets.len()).sum();
+        while num_pending <= MAX_BACKLOG {
+            match recvr.try_recv() {
+                Ok(packets) => {
+                    num_received += packets.packets.len();
+                    num_pending += packets.packets.len();
+                    pending.push_back(packets);
+                }
+                Err(_) => break,
+            }
+        }
+        Ok((
+            num_received,
+            num_pending,
+            duration_as_ms(&recv_start.elapsed()),
+        ))
+    }
+
+    // Takes the next packets to verify from `pending`.  All of them if the verifier has fallen
+    // too far behind, so `PacketQos` can shed the least staked, otherwise about
+    // `RECV_BATCH_MAX` and the rest stay pending for the next pass.
+    fn next_batch(pending: &mut VecDeque<Packets>, num_pending: usize) -> (Vec<Packets>, usize) {
+        if num_pending > MAX_BACKLOG {
+            inc_new_counter_info!("sigverify_stage-backlog_exceeded", 1);
+            return (pending.drain(..).collect(), num_pending);
+        }
+        let mut batch = vec![];
+        let mut len = 0;
+        while let Some(packets) = pending.pop_front() {
+            if !batch.is_empty() && len + packets.packets.len() > RECV_BATCH_MAX {
+                pending.push_front(packets);
+                break;
+            }
+            len += packets.packets.len();
+            batch.push(packets);
+        }
+        (batch, len)
+    }
+
+    #[allow(clippy::too_many_arguments)]
     fn verifier(
         recvr: &Arc<Mutex<PacketReceiver>>,
+        pending: &mut VecDeque<Packets>,
         sendr: &CrossbeamSender<VerifiedPackets>,
         sigverify_disabled: bool,
         id: usize,
         recycler: &Recycler<TxOffset>,
         recycler_out: &Recycler<PinnedVec<u8>>,
+        packet_qos: &PacketQos,
     ) -> Result<()> {
-        let (batch, len, recv_time) = streamer::recv_batch(
-            &recvr.lock().expect("'recvr' lock in fn verifier"),
-            RECV_BATCH_MAX,
-        )?;
-        inc_new_counter_info!("sigverify_stage-packets_received", len);
+        let (num_received, num_pending, recv_time) =
+            Self::recv_pending(&recvr.lock().expect("'recvr' lock in fn verifier"), pending)?;
+        inc_new_counter_info!("sigverify_stage-packets_received", num_received);
+
+        let (mut batch, len) = Self::next_batch(pending, num_pending);
+        let len = len - packet_qos.apply(&mut batch, RECV_BATCH_MAX);
 
         let mut verify_batch_time = Measure::start("sigverify_batch_time");
         let batch_len = batch.len();
@@ -123,20 +192,25 @@ impl SigVerifyStage {
         verified_sender: CrossbeamSender<VerifiedPackets>,
         sigverify_disabled: bool,
         id: usize,
+        packet_qos: Arc<PacketQos>,
     ) -> JoinHandle<()> {
         Builder::new()
             .name(format!("solana-verifier-{}", id))
             .spawn(move || {
                 let recycler = Recycler::default();
                 let recycler_out = Recycler::default();
+                // Packets received by this verifier that it hasn't verified yet
+                let mut pending = VecDeque::new();
                 loop {
                     if let Err(e) = Self::verifier(
                         &packet_receiver,
+                        &mut pending,
                         &verified_sender,
                         sigverify_disabled,
                         id,
                         &recycler,
                         &recycler_out,
+                        &packet_qos,
                     ) {
                         match e {
                             Error::RecvTimeoutError(RecvTimeoutError::Disconnected) => break,
@@ -156,6 +230,7 @@ impl SigVerifyStage {
         packet_receiver: PacketReceiver,
         verified_sender: CrossbeamSender<VerifiedPackets>,
         sigverify_disabled: bool,
+        packet_qos: &Arc<PacketQos>,
     ) -> Vec<JoinHandle<()>> {
         let receiver = Arc::new(Mutex::new(packet_receiver));
         (0..4)
@@ -165,6 +240,7 @@ impl SigVerifyStage {
                     verified_sender.clone(),
                     sigverify_disabled,
                     id,
+                    packet_qos.clone(),
                 )
             })
             .collect()
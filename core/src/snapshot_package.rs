@@ -1,5 +1,6 @@
 use crate::result::{Error, Result};
 use crate::service::Service;
+use crate::snapshot_utils;
 use solana_runtime::accounts_db::AccountStorageEntry;
 use std::fs;
 use std::io::{Error as IOError, ErrorKind};
@@ -17,25 +18,46 @@ pub type SnapshotPackageReceiver = Receiver<SnapshotPackage>;
 
 pub const TAR_SNAPSHOTS_DIR: &str = "snapshots";
 pub const TAR_ACCOUNTS_DIR: &str = "accounts";
+pub const TAR_SNAPSHOT_SLOT_FILE: &str = "snapshot_slot";
+pub const TAR_FULL_SNAPSHOT_SLOT_FILE: &str = "full_snapshot_slot";
 
 pub struct SnapshotPackage {
+    slot: u64,
     snapshot_links: TempDir,
     storage_entries: Vec<Arc<AccountStorageEntry>>,
     tar_output_file: PathBuf,
+    // The full snapshot an incremental package is layered on, None for a full package
+    full_snapshot_slot: Option<u64>,
 }
 
 impl SnapshotPackage {
     pub fn new(
+        slot: u64,
         snapshot_links: TempDir,
         storage_entries: Vec<Arc<AccountStorageEntry>>,
         tar_output_file: PathBuf,
+        full_snapshot_slot: Option<u64>,
     ) -> Self {
         Self {
+            slot,
             snapshot_links,
             storage_entries,
             tar_output_file,
+            full_snapshot_slot,
         }
     }
+
+    pub fn slot(&self) -> u64 {
+        self.slot
+    }
+
+    pub fn full_snapshot_slot(&self) -> Option<u64> {
+        self.full_snapshot_slot
+    }
+
+    pub fn is_incremental(&self) -> bool {
+        self.full_snapshot_slot.is_some()
+    }
 }
 
 pub struct SnapshotPackagerService {
@@ -99,6 +121,20 @@ impl SnapshotPackagerService {
             symlink::symlink_dir(storage_path, output_path)?;
         }
 
+        // Record the slot of the snapshot, and which full snapshot an incremental snapshot
+        // has to be layered on.  These go first in the archive so they can be read without
+        // decompressing the accounts, see `snapshot_utils::read_archive_slot()`
+        fs::write(
+            staging_dir.path().join(TAR_SNAPSHOT_SLOT_FILE),
+            snapshot_package.slot.to_string(),
+        )?;
+        if let Some(full_snapshot_slot) = snapshot_package.full_snapshot_slot {
+            fs::write(
+                staging_dir.path().join(TAR_FULL_SNAPSHOT_SLOT_FILE),
+                full_snapshot_slot.to_string(),
+            )?;
+        }
+
         // Tar the staging directory into the archive `temp_tar_gz`
         let temp_tar_gz = tempfile::Builder::new()
             .prefix("new_state")
@@ -109,6 +145,10 @@ impl SnapshotPackagerService {
         args.push(temp_tar_path.to_str().unwrap());
         args.push("-C");
         args.push(staging_dir.path().to_str().unwrap());
+        args.push(TAR_SNAPSHOT_SLOT_FILE);
+        if snapshot_package.is_incremental() {
+            args.push(TAR_FULL_SNAPSHOT_SLOT_FILE);
+        }
         args.push(TAR_ACCOUNTS_DIR);
         args.push(TAR_SNAPSHOTS_DIR);
 
@@ -125,16 +165,31 @@ impl SnapshotPackagerService {
         // can fetch this newly packaged snapshot
         let _ = fs::remove_file(&snapshot_package.tar_output_file);
         fs::hard_link(&temp_tar_path, &snapshot_package.tar_output_file)?;
+
+        // An incremental snapshot layered on the previous full snapshot can't be applied to
+        // this one, so drop it
+        if !snapshot_package.is_incremental() {
+            let _ = fs::remove_file(snapshot_utils::get_incremental_snapshot_tar_path(tar_dir));
+        }
         Ok(())
     }
 
     fn run(snapshot_receiver: &SnapshotPackageReceiver) -> Result<()> {
-        let mut snapshot_package = snapshot_receiver.recv_timeout(Duration::from_secs(1))?;
-        // Only package the latest
-        while let Ok(new_snapshot_package) = snapshot_receiver.try_recv() {
-            snapshot_package = new_snapshot_package;
+        let mut snapshot_packages = vec![snapshot_receiver.recv_timeout(Duration::from_secs(1))?];
+        snapshot_packages.extend(snapshot_receiver.try_iter());
+
+        // Only package the latest full snapshot and the latest incremental snapshot, skipping
+        // the full snapshot would leave the incremental one with nothing to be layered on
+        let latest = snapshot_packages.len() - 1;
+        let latest_full = snapshot_packages
+            .iter()
+            .rposition(|snapshot_package| !snapshot_package.is_incremental());
+        if let Some(latest_full) = latest_full {
+            Self::package_snapshots(&snapshot_packages[latest_full])?;
+        }
+        if latest_full != Some(latest) {
+            Self::package_snapshots(&snapshot_packages[latest])?;
         }
-        Self::package_snapshots(&snapshot_package)?;
         Ok(())
     }
 
@@ -202,9 +257,11 @@ mod tests {
         // Create a packageable snapshot
         let output_tar_path = snapshot_utils::get_snapshot_tar_path(&snapshot_package_output_path);
         let snapshot_package = SnapshotPackage::new(
+            0,
             link_snapshots_dir,
             storage_entries.clone(),
             output_tar_path.clone(),
+            None,
         );
 
         // Make tarball from packageable snapshot
@@ -1,7 +1,9 @@
 use crate::bank_forks::SnapshotConfig;
 use crate::result::{Error, Result};
 use crate::snapshot_package::SnapshotPackage;
-use crate::snapshot_package::{TAR_ACCOUNTS_DIR, TAR_SNAPSHOTS_DIR};
+use crate::snapshot_package::{
+    TAR_ACCOUNTS_DIR, TAR_FULL_SNAPSHOT_SLOT_FILE, TAR_SNAPSHOTS_DIR, TAR_SNAPSHOT_SLOT_FILE,
+};
 use bincode::{deserialize_from, serialize_into};
 use bzip2::bufread::BzDecoder;
 use fs_extra::dir::CopyOptions;
@@ -11,7 +13,7 @@ use solana_sdk::transaction;
 use std::cmp::Ordering;
 use std::fs;
 use std::fs::File;
-use std::io::{BufReader, BufWriter, Error as IOError, ErrorKind};
+use std::io::{BufReader, BufWriter, Error as IOError, ErrorKind, Read};
 use std::path::{Path, PathBuf};
 use tar::Archive;
 
@@ -56,6 +58,40 @@ pub fn package_snapshot<P: AsRef<Path>, Q: AsRef<Path>>(
     snapshot_files: &[SlotSnapshotPaths],
     snapshot_package_output_file: P,
     snapshot_path: Q,
+) -> Result<SnapshotPackage> {
+    package_snapshot_since(
+        bank,
+        None,
+        snapshot_files,
+        snapshot_package_output_file,
+        snapshot_path,
+    )
+}
+
+/// Packages only the AppendVecs written since `full_snapshot_slot`, the resulting archive
+/// can only be loaded on top of the full snapshot archive for that slot
+pub fn package_incremental_snapshot<P: AsRef<Path>, Q: AsRef<Path>>(
+    bank: &Bank,
+    full_snapshot_slot: u64,
+    snapshot_files: &[SlotSnapshotPaths],
+    snapshot_package_output_file: P,
+    snapshot_path: Q,
+) -> Result<SnapshotPackage> {
+    package_snapshot_since(
+        bank,
+        Some(full_snapshot_slot),
+        snapshot_files,
+        snapshot_package_output_file,
+        snapshot_path,
+    )
+}
+
+fn package_snapshot_since<P: AsRef<Path>, Q: AsRef<Path>>(
+    bank: &Bank,
+    full_snapshot_slot: Option<u64>,
+    snapshot_files: &[SlotSnapshotPaths],
+    snapshot_package_output_file: P,
+    snapshot_path: Q,
 ) -> Result<SnapshotPackage> {
     let slot = bank.slot();
 
@@ -68,13 +104,17 @@ pub fn package_snapshot<P: AsRef<Path>, Q: AsRef<Path>>(
         .get_storage_entries()
         .into_iter()
         .filter(|x| x.fork_id() <= bank.slot())
+        // AppendVecs of forks up to the full snapshot are never written again, so an
+        // incremental snapshot can reuse the ones in the full snapshot archive
+        .filter(|x| full_snapshot_slot.map_or(true, |full_slot| x.fork_id() > full_slot))
         .collect();
 
     // Create a snapshot package
     info!(
-        "Snapshot for bank: {} has {} account storage entries",
+        "Snapshot for bank: {} has {} account storage entries, full snapshot slot: {:?}",
         slot,
-        account_storage_entries.len()
+        account_storage_entries.len(),
+        full_snapshot_slot
     );
 
     // Any errors from this point on will cause the above SnapshotPackage to drop, clearing
@@ -84,9 +124,11 @@ pub fn package_snapshot<P: AsRef<Path>, Q: AsRef<Path>>(
     }
 
     let package = SnapshotPackage::new(
+        slot,
         snapshot_hard_links_dir,
         account_storage_entries,
         snapshot_package_output_file.as_ref().to_path_buf(),
+        full_snapshot_slot,
     );
 
     Ok(package)
@@ -144,7 +186,8 @@ pub fn add_snapshot<P: AsRef<Path>>(
     // status cache writer
     let mut status_cache_stream = BufWriter::new(status_cache);
 
-    // Create the snapshot
+    // Create the snapshot, carrying the accounts hash it can be verified against on load
+    bank.update_accounts_hash();
     serialize_into(&mut snapshot_stream, &*bank).map_err(|e| get_io_error(&e.to_string()))?;
     serialize_into(&mut snapshot_stream, &bank.rc).map_err(|e| get_io_error(&e.to_string()))?;
     // write the status cache
@@ -171,19 +214,62 @@ pub fn remove_snapshot<P: AsRef<Path>>(slot: u64, snapshot_path: P) -> Result<()
     Ok(())
 }
 
+/// Rebuilds the bank from a full snapshot archive and, if given, an incremental snapshot
+/// archive layered on top of it
 pub fn bank_from_archive<P: AsRef<Path>>(
     account_paths: String,
     snapshot_config: &SnapshotConfig,
     snapshot_tar: P,
+    incremental_snapshot_tar: Option<&Path>,
 ) -> Result<Bank> {
     // Untar the snapshot into a temp directory under `snapshot_config.snapshot_path()`
     let unpack_dir = tempfile::tempdir_in(snapshot_config.snapshot_path())?;
     untar_snapshot_in(&snapshot_tar, &unpack_dir)?;
 
     let unpacked_accounts_dir = unpack_dir.as_ref().join(TAR_ACCOUNTS_DIR);
-    let unpacked_snapshots_dir = unpack_dir.as_ref().join(TAR_SNAPSHOTS_DIR);
+    let mut unpacked_snapshots_dir = unpack_dir.as_ref().join(TAR_SNAPSHOTS_DIR);
+
+    let incremental_unpack_dir = tempfile::tempdir_in(snapshot_config.snapshot_path())?;
+    if let Some(incremental_snapshot_tar) = incremental_snapshot_tar {
+        untar_snapshot_in(incremental_snapshot_tar, &incremental_unpack_dir)?;
+        let incremental_snapshots_dir = incremental_unpack_dir.as_ref().join(TAR_SNAPSHOTS_DIR);
+
+        let full_slot = get_snapshot_paths(&unpacked_snapshots_dir)
+            .last()
+            .map(|paths| paths.slot);
+        let base_slot = read_full_snapshot_slot(&incremental_unpack_dir)?;
+        if full_slot != Some(base_slot) {
+            return Err(get_io_error(&format!(
+                "Incremental snapshot is layered on full snapshot slot {}, not {:?}",
+                base_slot, full_slot
+            )));
+        }
+        let incremental_slot = get_snapshot_paths(&incremental_snapshots_dir)
+            .last()
+            .map(|paths| paths.slot);
+        if incremental_slot <= full_slot {
+            return Err(get_io_error(&format!(
+                "Incremental snapshot slot {:?} is not newer than full snapshot slot {:?}",
+                incremental_slot, full_slot
+            )));
+        }
+
+        // The incremental AppendVecs join the full snapshot's, while the bank and status
+        // caches come entirely from the incremental snapshot
+        let dir_files = fs::read_dir(incremental_unpack_dir.as_ref().join(TAR_ACCOUNTS_DIR))?;
+        let paths: Vec<PathBuf> = dir_files
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .collect();
+        let mut copy_options = CopyOptions::new();
+        copy_options.overwrite = true;
+        fs_extra::move_items(&paths, &unpacked_accounts_dir, &copy_options)?;
+
+        unpacked_snapshots_dir = incremental_snapshots_dir;
+    }
+
     let snapshot_paths = get_snapshot_paths(&unpacked_snapshots_dir);
     let bank = rebuild_bank_from_snapshots(account_paths, &snapshot_paths, unpacked_accounts_dir)?;
+    verify_snapshot_bank(&bank, snapshot_config)?;
 
     // Move the unpacked snapshots into `snapshot_config.snapshot_path()`
     let dir_files = fs::read_dir(unpacked_snapshots_dir).expect("Invalid snapshot path");
@@ -197,10 +283,143 @@ pub fn bank_from_archive<P: AsRef<Path>>(
     Ok(bank)
 }
 
+/// Rebuilds the bank from the newest bank snapshot in `snapshot_config.snapshot_path()`,
+/// reusing the AppendVecs that are still in `account_paths` from the run that took it
+pub fn bank_from_snapshots(
+    account_paths: String,
+    snapshot_config: &SnapshotConfig,
+) -> Result<Bank> {
+    let snapshot_paths = get_snapshot_paths(snapshot_config.snapshot_path());
+
+    // The bank only takes the AppendVecs it needs, so link all of them into one directory
+    // and let the rest go with it.  Account paths on another filesystem than the snapshot
+    // path can't be linked from there, their AppendVecs are copied instead
+    let append_vecs_dir = tempfile::tempdir_in(snapshot_config.snapshot_path())?;
+    for account_path in account_paths.split(',') {
+        for entry in fs::read_dir(account_path)? {
+            let path = entry?.path();
+            if let (true, Some(file_name)) = (path.is_file(), path.file_name()) {
+                let append_vec_path = append_vecs_dir.path().join(file_name);
+                match fs::hard_link(&path, &append_vec_path) {
+                    Err(ref err) if err.raw_os_error() == Some(libc::EXDEV) => {
+                        fs::copy(&path, &append_vec_path)?;
+                    }
+                    result => result?,
+                }
+            }
+        }
+    }
+
+    let bank = rebuild_bank_from_snapshots(account_paths, &snapshot_paths, &append_vecs_dir)?;
+    verify_snapshot_bank(&bank, snapshot_config)?;
+    Ok(bank)
+}
+
+// Checks `bank` against the cluster's hash for its slot.  The hash the bank was frozen with
+// comes from the snapshot itself, so a snapshot is refused without a trusted one to check it
+// against
+fn verify_snapshot_bank(bank: &Bank, snapshot_config: &SnapshotConfig) -> Result<()> {
+    let expected_bank_hash = snapshot_config.expected_bank_hash().ok_or_else(|| {
+        get_io_error(&format!(
+            "No expected bank hash to verify the snapshot of slot {} against",
+            bank.slot()
+        ))
+    })?;
+    if !bank.verify_snapshot_bank(&expected_bank_hash) {
+        return Err(get_io_error(&format!(
+            "Snapshot of slot {} does not match bank hash {}",
+            bank.slot(),
+            expected_bank_hash
+        )));
+    }
+    Ok(())
+}
+
 pub fn get_snapshot_tar_path<P: AsRef<Path>>(snapshot_output_dir: P) -> PathBuf {
     snapshot_output_dir.as_ref().join("snapshot.tar.bz2")
 }
 
+pub fn get_incremental_snapshot_tar_path<P: AsRef<Path>>(snapshot_output_dir: P) -> PathBuf {
+    snapshot_output_dir
+        .as_ref()
+        .join("incremental-snapshot.tar.bz2")
+}
+
+/// Returns the full snapshot archive in `snapshot_output_dir`, if there is one, along with
+/// the incremental snapshot archive packaged on top of it.  An incremental archive that
+/// records a different full snapshot slot than the full archive's is ignored.
+pub fn get_snapshot_archives<P: AsRef<Path>>(
+    snapshot_output_dir: P,
+) -> Option<(PathBuf, Option<PathBuf>)> {
+    let tar = get_snapshot_tar_path(&snapshot_output_dir);
+    if !tar.exists() {
+        return None;
+    }
+
+    let incremental_tar = get_incremental_snapshot_tar_path(&snapshot_output_dir);
+    if !incremental_tar.exists() {
+        return Some((tar, None));
+    }
+    let full_slot = read_archive_slot(&tar, TAR_SNAPSHOT_SLOT_FILE).ok();
+    let base_slot = read_archive_slot(&incremental_tar, TAR_FULL_SNAPSHOT_SLOT_FILE).ok();
+    if full_slot.is_some() && full_slot == base_slot {
+        Some((tar, Some(incremental_tar)))
+    } else {
+        warn!(
+            "Ignoring {:?}, it is layered on full snapshot slot {:?}, not {:?}",
+            incremental_tar, base_slot, full_slot
+        );
+        Some((tar, None))
+    }
+}
+
+/// Reads the slot recorded in `file_name` at the start of a snapshot archive, without
+/// unpacking the rest of it
+fn read_archive_slot<P: AsRef<Path>>(snapshot_tar: P, file_name: &str) -> Result<u64> {
+    let tar_bz2 = File::open(&snapshot_tar)?;
+    let tar = BzDecoder::new(BufReader::new(tar_bz2));
+    let mut archive = Archive::new(tar);
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?.into_owned();
+        if path == Path::new(file_name) {
+            let mut slot = String::new();
+            entry.read_to_string(&mut slot)?;
+            return slot.trim().parse().map_err(|_| {
+                get_io_error(&format!(
+                    "Invalid {} in {:?}: {:?}",
+                    file_name,
+                    snapshot_tar.as_ref(),
+                    slot
+                ))
+            });
+        }
+        if path != Path::new(TAR_SNAPSHOT_SLOT_FILE)
+            && path != Path::new(TAR_FULL_SNAPSHOT_SLOT_FILE)
+        {
+            // Past the slot files
+            break;
+        }
+    }
+    Err(get_io_error(&format!(
+        "No {} in {:?}",
+        file_name,
+        snapshot_tar.as_ref()
+    )))
+}
+
+/// Reads the full snapshot slot recorded in an unpacked incremental snapshot archive
+fn read_full_snapshot_slot<P: AsRef<Path>>(unpack_dir: P) -> Result<u64> {
+    let full_snapshot_slot =
+        fs::read_to_string(unpack_dir.as_ref().join(TAR_FULL_SNAPSHOT_SLOT_FILE))?;
+    full_snapshot_slot.trim().parse().map_err(|_| {
+        get_io_error(&format!(
+            "Invalid full snapshot slot in incremental snapshot: {:?}",
+            full_snapshot_slot
+        ))
+    })
+}
+
 pub fn untar_snapshot_in<P: AsRef<Path>, Q: AsRef<Path>>(
     snapshot_tar: P,
     unpack_dir: Q,
@@ -263,7 +482,11 @@ fn get_io_error(error: &str) -> Error {
 #[cfg(test)]
 pub mod tests {
     use super::*;
+    use crate::genesis_utils::create_genesis_block;
     use crate::snapshot_package::{TAR_ACCOUNTS_DIR, TAR_SNAPSHOTS_DIR};
+    use bzip2::write::BzEncoder;
+    use bzip2::Compression;
+    use solana_sdk::hash::Hash;
     use tempfile::TempDir;
 
     pub fn verify_snapshot_tar<P, Q, R>(
@@ -287,4 +510,74 @@ pub mod tests {
         let unpacked_accounts = unpack_dir.join(&TAR_ACCOUNTS_DIR);
         assert!(!dir_diff::is_different(&storages_to_verify, unpacked_accounts).unwrap());
     }
+
+    // Writes an archive holding just the given slot files
+    fn write_slot_archive(path: &Path, slot_files: &[(&str, u64)]) {
+        let tar_bz2 = BzEncoder::new(File::create(path).unwrap(), Compression::Best);
+        let mut builder = tar::Builder::new(tar_bz2);
+        for (file_name, slot) in slot_files {
+            let slot = slot.to_string();
+            let mut header = tar::Header::new_gnu();
+            header.set_size(slot.len() as u64);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, file_name, slot.as_bytes())
+                .unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+    }
+
+    #[test]
+    fn test_get_snapshot_archives() {
+        let output_dir = TempDir::new().unwrap();
+        let tar = get_snapshot_tar_path(&output_dir);
+        let incremental_tar = get_incremental_snapshot_tar_path(&output_dir);
+        assert_eq!(get_snapshot_archives(&output_dir), None);
+
+        // An incremental archive can't be loaded without a full one
+        write_slot_archive(
+            &incremental_tar,
+            &[
+                (TAR_SNAPSHOT_SLOT_FILE, 20),
+                (TAR_FULL_SNAPSHOT_SLOT_FILE, 10),
+            ],
+        );
+        assert_eq!(get_snapshot_archives(&output_dir), None);
+
+        // Nor on top of a different full snapshot than the one it was packaged against
+        write_slot_archive(&tar, &[(TAR_SNAPSHOT_SLOT_FILE, 15)]);
+        assert_eq!(
+            get_snapshot_archives(&output_dir),
+            Some((tar.clone(), None))
+        );
+
+        write_slot_archive(&tar, &[(TAR_SNAPSHOT_SLOT_FILE, 10)]);
+        assert_eq!(read_archive_slot(&tar, TAR_SNAPSHOT_SLOT_FILE).unwrap(), 10);
+        assert_eq!(
+            read_archive_slot(&incremental_tar, TAR_FULL_SNAPSHOT_SLOT_FILE).unwrap(),
+            10
+        );
+        assert_eq!(
+            get_snapshot_archives(&output_dir),
+            Some((tar, Some(incremental_tar)))
+        );
+    }
+
+    #[test]
+    fn test_verify_snapshot_bank() {
+        let genesis_block_info = create_genesis_block(500);
+        let bank = Bank::new(&genesis_block_info.genesis_block);
+        bank.freeze();
+        bank.update_accounts_hash();
+
+        // a snapshot is refused without a trusted hash to check it against
+        let mut snapshot_config = SnapshotConfig::new(PathBuf::new(), PathBuf::new(), 0, 0);
+        assert!(verify_snapshot_bank(&bank, &snapshot_config).is_err());
+
+        snapshot_config.set_expected_bank_hash(Some(Hash::default()));
+        assert!(verify_snapshot_bank(&bank, &snapshot_config).is_err());
+
+        snapshot_config.set_expected_bank_hash(Some(bank.hash()));
+        assert!(verify_snapshot_bank(&bank, &snapshot_config).is_ok());
+    }
 }
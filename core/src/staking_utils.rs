@@ -24,6 +24,23 @@ pub fn vote_account_stakes(bank: &Bank) -> HashMap<Pubkey, u64> {
         .collect()
 }
 
+/// At the specified epoch, collect the stake and vote state of each vote account
+pub fn vote_account_states_at_epoch(
+    bank: &Bank,
+    epoch_height: u64,
+) -> Option<HashMap<Pubkey, (u64, VoteState)>> {
+    bank.epoch_vote_accounts(epoch_height).map(|vote_accounts| {
+        vote_accounts
+            .iter()
+            .filter_map(|(vote_pubkey, (stake, account))| {
+                VoteState::deserialize(&account.data)
+                    .ok()
+                    .map(|vote_state| (*vote_pubkey, (*stake, vote_state)))
+            })
+            .collect()
+    })
+}
+
 /// Collect the staked nodes, as named by staked vote accounts from the given bank
 pub fn staked_nodes(bank: &Bank) -> HashMap<Pubkey, u64> {
     to_staked_nodes(to_vote_states(bank.vote_accounts().into_iter()))
@@ -237,6 +254,26 @@ pub(crate) mod tests {
         assert_eq!(result, expected);
     }
 
+    #[test]
+    fn test_vote_account_states_at_epoch() {
+        let GenesisBlockInfo {
+            genesis_block,
+            voting_keypair,
+            ..
+        } = create_genesis_block(10_000);
+        let bank = Bank::new(&genesis_block);
+
+        let vote_states = vote_account_states_at_epoch(&bank, 0).unwrap();
+        assert_eq!(vote_states.len(), 1);
+        let (stake, vote_state) = &vote_states[&voting_keypair.pubkey()];
+        assert!(*stake > 0);
+        assert_eq!(
+            vote_state.authorized_voter(0),
+            Some(&voting_keypair.pubkey())
+        );
+        assert!(vote_account_states_at_epoch(&bank, std::u64::MAX).is_none());
+    }
+
     #[test]
     fn test_find_supermajority_slot() {
         let supermajority = 10;
@@ -1,6 +1,7 @@
 //! The `tpu` module implements the Transaction Processing Unit, a
 //! multi-stage transaction processing pipeline in software.
 
+use crate::bank_forks::BankForks;
 use crate::banking_stage::BankingStage;
 use crate::blocktree::Blocktree;
 use crate::broadcast_stage::{BroadcastStage, BroadcastStageType};
@@ -8,7 +9,9 @@ use crate::cluster_info::ClusterInfo;
 use crate::cluster_info_vote_listener::ClusterInfoVoteListener;
 use crate::erasure::ErasureConfig;
 use crate::fetch_stage::FetchStage;
+use crate::packet_qos::{PacketQos, PacketQosConfig, PacketQosService};
 use crate::poh_recorder::{PohRecorder, WorkingBankEntries};
+use crate::quic_streamer::{self, QuicConfig};
 use crate::service::Service;
 use crate::sigverify_stage::SigVerifyStage;
 use crossbeam_channel::unbounded;
@@ -16,29 +19,34 @@ use std::net::UdpSocket;
 use std::sync::atomic::AtomicBool;
 use std::sync::mpsc::{channel, Receiver};
 use std::sync::{Arc, Mutex, RwLock};
-use std::thread;
+use std::thread::{self, JoinHandle};
 
 pub struct Tpu {
     fetch_stage: FetchStage,
+    quic_receiver: Option<JoinHandle<()>>,
     sigverify_stage: SigVerifyStage,
     banking_stage: BankingStage,
     cluster_info_vote_listener: ClusterInfoVoteListener,
     broadcast_stage: BroadcastStage,
+    packet_qos_service: PacketQosService,
 }
 
 impl Tpu {
     #[allow(clippy::too_many_arguments)]
     pub fn new(
         cluster_info: &Arc<RwLock<ClusterInfo>>,
+        bank_forks: &Arc<RwLock<BankForks>>,
         poh_recorder: &Arc<Mutex<PohRecorder>>,
         entry_receiver: Receiver<WorkingBankEntries>,
         transactions_sockets: Vec<UdpSocket>,
         tpu_forwards_sockets: Vec<UdpSocket>,
+        tpu_quic: Option<(UdpSocket, QuicConfig)>,
         broadcast_socket: UdpSocket,
         sigverify_disabled: bool,
         blocktree: &Arc<Blocktree>,
         broadcast_type: &BroadcastStageType,
         erasure_config: &ErasureConfig,
+        packet_qos_config: &PacketQosConfig,
         exit: &Arc<AtomicBool>,
     ) -> Self {
         let (packet_sender, packet_receiver) = channel();
@@ -49,10 +57,21 @@ impl Tpu {
             &packet_sender,
             &poh_recorder,
         );
+        let quic_receiver = tpu_quic.map(|(socket, quic_config)| {
+            quic_streamer::receiver(socket, &exit, packet_sender.clone(), &quic_config)
+                .expect("quic_streamer::receiver")
+        });
         let (verified_sender, verified_receiver) = unbounded();
 
-        let sigverify_stage =
-            SigVerifyStage::new(packet_receiver, sigverify_disabled, verified_sender.clone());
+        let packet_qos = Arc::new(PacketQos::new(packet_qos_config.clone()));
+        let packet_qos_service =
+            PacketQosService::new(&packet_qos, cluster_info, bank_forks, &exit);
+        let sigverify_stage = SigVerifyStage::new(
+            packet_receiver,
+            sigverify_disabled,
+            verified_sender.clone(),
+            &packet_qos,
+        );
 
         let (verified_vote_sender, verified_vote_receiver) = unbounded();
         let cluster_info_vote_listener = ClusterInfoVoteListener::new(
@@ -81,10 +100,12 @@ impl Tpu {
 
         Self {
             fetch_stage,
+            quic_receiver,
             sigverify_stage,
             banking_stage,
             cluster_info_vote_listener,
             broadcast_stage,
+            packet_qos_service,
         }
     }
 }
@@ -95,9 +116,13 @@ impl Service for Tpu {
     fn join(self) -> thread::Result<()> {
         let mut results = vec![];
         results.push(self.fetch_stage.join());
+        if let Some(quic_receiver) = self.quic_receiver {
+            results.push(quic_receiver.join());
+        }
         results.push(self.sigverify_stage.join());
         results.push(self.cluster_info_vote_listener.join());
         results.push(self.banking_stage.join());
+        results.push(self.packet_qos_service.join());
         let broadcast_result = self.broadcast_stage.join();
         for result in results {
             result?;
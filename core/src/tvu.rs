@@ -17,8 +17,11 @@ use crate::blob_fetch_stage::BlobFetchStage;
 use crate::blockstream_service::BlockstreamService;
 use crate::blocktree::{Blocktree, CompletedSlotsReceiver};
 use crate::cluster_info::ClusterInfo;
+use crate::consensus::Tower;
+use crate::fork_tree::ForkTreeExport;
 use crate::leader_schedule_cache::LeaderScheduleCache;
-use crate::ledger_cleanup_service::LedgerCleanupService;
+use crate::ledger_cleanup_service::{LedgerCleanupConfig, LedgerCleanupService};
+use crate::long_term_storage::LongTermStorage;
 use crate::poh_recorder::PohRecorder;
 use crate::replay_stage::ReplayStage;
 use crate::retransmit_stage::RetransmitStage;
@@ -61,15 +64,18 @@ impl Tvu {
     #[allow(clippy::new_ret_no_self, clippy::too_many_arguments)]
     pub fn new<T>(
         vote_account: &Pubkey,
-        voting_keypair: Option<&Arc<T>>,
+        authorized_voter_keypairs: &[Arc<T>],
         storage_keypair: &Arc<Keypair>,
         bank_forks: &Arc<RwLock<BankForks>>,
+        tower: Tower,
+        fork_tree_export: &ForkTreeExport,
         cluster_info: &Arc<RwLock<ClusterInfo>>,
         sockets: Sockets,
         blocktree: Arc<Blocktree>,
         storage_state: &StorageState,
         blockstream_unix_socket: Option<&PathBuf>,
-        max_ledger_slots: Option<u64>,
+        ledger_cleanup_config: Option<LedgerCleanupConfig>,
+        long_term_storage: Option<Arc<dyn LongTermStorage>>,
         ledger_signal_receiver: Receiver<bool>,
         subscriptions: &Arc<RpcSubscriptions>,
         poh_recorder: &Arc<Mutex<PohRecorder>>,
@@ -133,9 +139,11 @@ impl Tvu {
         let (replay_stage, root_bank_receiver) = ReplayStage::new(
             &keypair.pubkey(),
             vote_account,
-            voting_keypair,
+            authorized_voter_keypairs,
             blocktree.clone(),
             &bank_forks,
+            tower,
+            fork_tree_export,
             cluster_info.clone(),
             &exit,
             ledger_signal_receiver,
@@ -158,11 +166,13 @@ impl Tvu {
             None
         };
 
-        let ledger_cleanup_service = max_ledger_slots.map(|max_ledger_slots| {
+        let ledger_cleanup_service = ledger_cleanup_config.map(|ledger_cleanup_config| {
             LedgerCleanupService::new(
                 ledger_cleanup_slot_receiver,
                 blocktree.clone(),
-                max_ledger_slots,
+                bank_forks,
+                ledger_cleanup_config,
+                long_term_storage,
                 &exit,
             )
         });
@@ -249,11 +259,19 @@ pub mod tests {
         let voting_keypair = Keypair::new();
         let storage_keypair = Arc::new(Keypair::new());
         let leader_schedule_cache = Arc::new(LeaderScheduleCache::new_from_bank(&bank));
+        let tower = Tower::new(
+            &cref1.read().unwrap().keypair.pubkey(),
+            &voting_keypair.pubkey(),
+            &bank_forks,
+            &blocktree_path,
+        );
         let tvu = Tvu::new(
             &voting_keypair.pubkey(),
-            Some(&Arc::new(voting_keypair)),
+            &[Arc::new(voting_keypair)],
             &storage_keypair,
             &Arc::new(RwLock::new(bank_forks)),
+            tower,
+            &ForkTreeExport::default(),
             &cref1,
             {
                 Sockets {
@@ -266,6 +284,7 @@ pub mod tests {
             &StorageState::default(),
             None,
             None,
+            None,
             l_receiver,
             &Arc::new(RpcSubscriptions::default()),
             &poh_recorder,
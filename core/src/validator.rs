@@ -5,12 +5,21 @@ use crate::blocktree::{Blocktree, CompletedSlotsReceiver};
 use crate::blocktree_processor::{self, BankForksInfo};
 use crate::broadcast_stage::BroadcastStageType;
 use crate::cluster_info::{ClusterInfo, Node};
+use crate::consensus::Tower;
 use crate::contact_info::ContactInfo;
 use crate::erasure::ErasureConfig;
+use crate::fork_tree::ForkTreeExport;
 use crate::gossip_service::{discover_cluster, GossipService};
 use crate::leader_schedule_cache::LeaderScheduleCache;
+use crate::ledger_cleanup_service::LedgerCleanupConfig;
+use crate::long_term_storage::{FileLongTermStorage, LongTermStorage};
+use crate::long_term_storage_service::LongTermStorageService;
+use crate::optimistic_confirmation_service::OptimisticConfirmationService;
+use crate::packet_qos::PacketQosConfig;
 use crate::poh_recorder::PohRecorder;
 use crate::poh_service::PohService;
+use crate::quic_streamer::QuicConfig;
+use crate::result;
 use crate::rpc::JsonRpcConfig;
 use crate::rpc_pubsub_service::PubSubService;
 use crate::rpc_service::JsonRpcService;
@@ -27,6 +36,7 @@ use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::{Keypair, KeypairUtil};
 use solana_sdk::timing::{timestamp, Slot, DEFAULT_SLOTS_PER_TURN};
 use std::fs;
+use std::io;
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -38,15 +48,20 @@ use std::thread::Result;
 pub struct ValidatorConfig {
     pub dev_sigverify_disabled: bool,
     pub dev_halt_at_slot: Option<Slot>,
+    pub full_replay: bool,
     pub voting_disabled: bool,
     pub blockstream_unix_socket: Option<PathBuf>,
     pub storage_slots_per_turn: u64,
     pub account_paths: Option<String>,
     pub rpc_config: JsonRpcConfig,
     pub snapshot_config: Option<SnapshotConfig>,
-    pub max_ledger_slots: Option<u64>,
+    pub ledger_cleanup_config: Option<LedgerCleanupConfig>,
     pub broadcast_stage_type: BroadcastStageType,
     pub erasure_config: ErasureConfig,
+    pub long_term_storage_path: Option<PathBuf>,
+    pub packet_qos_config: PacketQosConfig,
+    /// Also accept transactions over QUIC, on the node's `tpu_quic` port
+    pub tpu_quic_config: Option<QuicConfig>,
 }
 
 impl Default for ValidatorConfig {
@@ -54,15 +69,19 @@ impl Default for ValidatorConfig {
         Self {
             dev_sigverify_disabled: false,
             dev_halt_at_slot: None,
+            full_replay: false,
             voting_disabled: false,
             blockstream_unix_socket: None,
             storage_slots_per_turn: DEFAULT_SLOTS_PER_TURN,
-            max_ledger_slots: None,
+            ledger_cleanup_config: None,
             account_paths: None,
             rpc_config: JsonRpcConfig::default(),
             snapshot_config: None,
             broadcast_stage_type: BroadcastStageType::Standard,
             erasure_config: ErasureConfig::default(),
+            long_term_storage_path: None,
+            packet_qos_config: PacketQosConfig::default(),
+            tpu_quic_config: None,
         }
     }
 }
@@ -72,6 +91,8 @@ pub struct Validator {
     exit: Arc<AtomicBool>,
     rpc_service: Option<JsonRpcService>,
     rpc_pubsub_service: Option<PubSubService>,
+    long_term_storage_service: Option<LongTermStorageService>,
+    optimistic_confirmation_service: OptimisticConfirmationService,
     gossip_service: GossipService,
     poh_recorder: Arc<Mutex<PohRecorder>>,
     poh_service: PohService,
@@ -86,7 +107,7 @@ impl Validator {
         keypair: &Arc<Keypair>,
         ledger_path: &Path,
         vote_account: &Pubkey,
-        voting_keypair: &Arc<Keypair>,
+        authorized_voter_keypairs: &[Arc<Keypair>],
         storage_keypair: &Arc<Keypair>,
         entrypoint_info_option: Option<&ContactInfo>,
         verify_ledger: bool,
@@ -132,14 +153,21 @@ impl Validator {
             config.snapshot_config.clone(),
             verify_ledger,
             config.dev_halt_at_slot,
+            config.full_replay,
         );
 
         let leader_schedule_cache = Arc::new(leader_schedule_cache);
         let exit = Arc::new(AtomicBool::new(false));
         let bank_info = &bank_forks_info[0];
         let bank = bank_forks[bank_info.bank_slot].clone();
+        let tower = restore_tower(ledger_path, &id, vote_account, &bank_forks);
         let bank_forks = Arc::new(RwLock::new(bank_forks));
 
+        if config.tpu_quic_config.is_none() {
+            // Don't advertise a QUIC port nothing is listening on
+            node.info.tpu_quic = socketaddr_any!();
+            node.sockets.tpu_quic = None;
+        }
         node.info.wallclock = timestamp();
         let cluster_info = Arc::new(RwLock::new(ClusterInfo::new(
             node.info.clone(),
@@ -152,6 +180,24 @@ impl Validator {
             bank.slots_per_segment(),
         );
 
+        let blocktree = Arc::new(blocktree);
+        let fork_tree_export =
+            ForkTreeExport::new(ledger_path, config.rpc_config.enable_fork_tree_export);
+
+        let long_term_storage: Option<Arc<dyn LongTermStorage>> =
+            config.long_term_storage_path.as_ref().map(|path| {
+                Arc::new(FileLongTermStorage::new(path).expect("Failed to open long-term storage"))
+                    as Arc<dyn LongTermStorage>
+            });
+        let long_term_storage_service = long_term_storage.as_ref().map(|long_term_storage| {
+            LongTermStorageService::new(
+                blocktree.clone(),
+                bank_forks.clone(),
+                long_term_storage.clone(),
+                &exit,
+            )
+        });
+
         let rpc_service = if node.info.rpc.port() == 0 {
             None
         } else {
@@ -159,8 +205,11 @@ impl Validator {
                 &cluster_info,
                 SocketAddr::new(IpAddr::V4(Ipv4Addr::new(0, 0, 0, 0)), node.info.rpc.port()),
                 storage_state.clone(),
+                fork_tree_export.clone(),
                 config.rpc_config.clone(),
                 bank_forks.clone(),
+                blocktree.clone(),
+                long_term_storage.clone(),
                 ledger_path,
                 &exit,
             ))
@@ -180,6 +229,9 @@ impl Validator {
             ))
         };
 
+        let optimistic_confirmation_service =
+            OptimisticConfirmationService::new(&cluster_info, &bank_forks, &subscriptions, &exit);
+
         info!(
             "Starting PoH: epoch={} slot={} tick_height={} blockhash={} leader={:?}",
             bank.epoch(),
@@ -195,8 +247,6 @@ impl Validator {
             std::thread::park();
         }
 
-        let blocktree = Arc::new(blocktree);
-
         let poh_config = Arc::new(poh_config);
         let (mut poh_recorder, entry_receiver) = PohRecorder::new_with_clear_signal(
             bank.tick_height(),
@@ -261,23 +311,26 @@ impl Validator {
                 .collect(),
         };
 
-        let voting_keypair = if config.voting_disabled {
-            None
+        let authorized_voter_keypairs: &[Arc<Keypair>] = if config.voting_disabled {
+            &[]
         } else {
-            Some(voting_keypair)
+            authorized_voter_keypairs
         };
 
         let tvu = Tvu::new(
             vote_account,
-            voting_keypair,
+            authorized_voter_keypairs,
             storage_keypair,
             &bank_forks,
+            tower,
+            &fork_tree_export,
             &cluster_info,
             sockets,
             blocktree.clone(),
             &storage_state,
             config.blockstream_unix_socket.as_ref(),
-            config.max_ledger_slots,
+            config.ledger_cleanup_config.clone(),
+            long_term_storage,
             ledger_signal_receiver,
             &subscriptions,
             &poh_recorder,
@@ -290,17 +343,28 @@ impl Validator {
             warn!("signature verification disabled");
         }
 
+        let tpu_quic = match (node.sockets.tpu_quic, &config.tpu_quic_config) {
+            (Some(socket), Some(quic_config)) => {
+                info!("local TPU QUIC address: {}", socket.local_addr().unwrap());
+                Some((socket, quic_config.clone()))
+            }
+            _ => None,
+        };
+
         let tpu = Tpu::new(
             &cluster_info,
+            &bank_forks,
             &poh_recorder,
             entry_receiver,
             node.sockets.tpu,
             node.sockets.tpu_forwards,
+            tpu_quic,
             node.sockets.broadcast,
             config.dev_sigverify_disabled,
             &blocktree,
             &config.broadcast_stage_type,
             &config.erasure_config,
+            &config.packet_qos_config,
             &exit,
         );
 
@@ -310,6 +374,8 @@ impl Validator {
             gossip_service,
             rpc_service,
             rpc_pubsub_service,
+            long_term_storage_service,
+            optimistic_confirmation_service,
             tpu,
             tvu,
             exit,
@@ -337,33 +403,66 @@ fn get_bank_forks(
     snapshot_config: Option<SnapshotConfig>,
     verify_ledger: bool,
     dev_halt_at_slot: Option<Slot>,
+    full_replay: bool,
 ) -> (BankForks, Vec<BankForksInfo>, LeaderScheduleCache) {
     let (mut bank_forks, bank_forks_info, leader_schedule_cache) = {
         let mut result = None;
         if snapshot_config.is_some() {
             let snapshot_config = snapshot_config.as_ref().unwrap();
-
-            // Blow away any remnants in the snapshots directory
-            let _ = fs::remove_dir_all(snapshot_config.snapshot_path());
+            let account_paths = || {
+                account_paths
+                    .clone()
+                    .expect("Account paths not present when booting from snapshot")
+            };
             fs::create_dir_all(&snapshot_config.snapshot_path())
                 .expect("Couldn't create snapshot directory");
 
-            // Get the path to the tar
-            let tar = snapshot_utils::get_snapshot_tar_path(
-                &snapshot_config.snapshot_package_output_path(),
-            );
+            // The newest bank snapshot this node took is at least as new as the archives it
+            // packaged from them
+            let mut deserialized_bank = None;
+            if full_replay {
+                info!("Full replay requested, ignoring snapshots");
+            } else if snapshot_config.expected_bank_hash().is_none() {
+                warn!("No expected bank hash given, snapshots can't be verified and are ignored");
+            } else if !snapshot_utils::get_snapshot_paths(snapshot_config.snapshot_path())
+                .is_empty()
+            {
+                match snapshot_utils::bank_from_snapshots(account_paths(), snapshot_config) {
+                    Ok(bank) => deserialized_bank = Some(bank),
+                    Err(err) => warn!("Unable to load the newest bank snapshot: {:?}", err),
+                }
+            }
 
-            // Check that the snapshot tar exists, try to load the snapshot if it does
-            if tar.exists() {
-                // Fail hard here if snapshot fails to load, don't silently continue
-                let deserialized_bank = snapshot_utils::bank_from_archive(
-                    account_paths
-                        .clone()
-                        .expect("Account paths not present when booting from snapshot"),
-                    snapshot_config,
-                    &tar,
-                )
-                .expect("Load from snapshot failed");
+            if deserialized_bank.is_none() {
+                // Blow away the bank snapshots that weren't loaded, the snapshots taken from
+                // here on would be packaged along with them otherwise
+                let _ = fs::remove_dir_all(snapshot_config.snapshot_path());
+                fs::create_dir_all(&snapshot_config.snapshot_path())
+                    .expect("Couldn't create snapshot directory");
+
+                let snapshot_archives = snapshot_utils::get_snapshot_archives(
+                    &snapshot_config.snapshot_package_output_path(),
+                );
+                let load_archives = !full_replay && snapshot_config.expected_bank_hash().is_some();
+                if let (true, Some((tar, incremental_tar))) = (load_archives, snapshot_archives) {
+                    // Fail hard here if snapshot fails to load, don't silently continue
+                    deserialized_bank = Some(
+                        snapshot_utils::bank_from_archive(
+                            account_paths(),
+                            snapshot_config,
+                            &tar,
+                            incremental_tar.as_ref().map(PathBuf::as_path),
+                        )
+                        .expect("Load from snapshot failed"),
+                    );
+                }
+            }
+
+            if let Some(deserialized_bank) = deserialized_bank {
+                info!(
+                    "Loaded snapshot of slot {}, replaying the ledger from there",
+                    deserialized_bank.slot()
+                );
 
                 result = Some(
                     blocktree_processor::process_blocktree_from_root(
@@ -401,12 +500,41 @@ fn get_bank_forks(
     (bank_forks, bank_forks_info, leader_schedule_cache)
 }
 
+/// Restore the tower this node saved in `ledger_path`, or initialize one from its vote account
+/// in `bank_forks` if it hasn't saved one yet
+fn restore_tower(
+    ledger_path: &Path,
+    node_pubkey: &Pubkey,
+    vote_account: &Pubkey,
+    bank_forks: &BankForks,
+) -> Tower {
+    let mut tower = match Tower::restore(ledger_path, node_pubkey) {
+        Ok(tower) => {
+            info!("Restored tower with root {:?}", tower.root());
+            tower
+        }
+        Err(result::Error::IO(ref err)) if err.kind() == io::ErrorKind::NotFound => {
+            info!("No saved tower, initializing it from the vote account");
+            Tower::new(node_pubkey, vote_account, bank_forks, ledger_path)
+        }
+        Err(err) => panic!(
+            "Unable to restore tower from {:?}: {:?}",
+            Tower::get_filename(ledger_path, node_pubkey),
+            err
+        ),
+    };
+    // The forks of any votes older than the bank forks root won't be replayed
+    tower.adjust_lockouts_to_root(&bank_forks[bank_forks.root()]);
+    tower
+}
+
 pub fn new_banks_from_blocktree(
     blocktree_path: &Path,
     account_paths: Option<String>,
     snapshot_config: Option<SnapshotConfig>,
     verify_ledger: bool,
     dev_halt_at_slot: Option<Slot>,
+    full_replay: bool,
 ) -> (
     BankForks,
     Vec<BankForksInfo>,
@@ -430,6 +558,7 @@ pub fn new_banks_from_blocktree(
         snapshot_config,
         verify_ledger,
         dev_halt_at_slot,
+        full_replay,
     );
 
     (
@@ -455,6 +584,10 @@ impl Service for Validator {
         if let Some(rpc_pubsub_service) = self.rpc_pubsub_service {
             rpc_pubsub_service.join()?;
         }
+        if let Some(long_term_storage_service) = self.long_term_storage_service {
+            long_term_storage_service.join()?;
+        }
+        self.optimistic_confirmation_service.join()?;
 
         self.gossip_service.join()?;
         self.tpu.join()?;
@@ -491,7 +624,7 @@ pub fn new_validator_for_tests() -> (Validator, ContactInfo, Keypair, PathBuf) {
         &node_keypair,
         &ledger_path,
         &voting_keypair.pubkey(),
-        &voting_keypair,
+        &[voting_keypair.clone()],
         &storage_keypair,
         None,
         true,
@@ -527,7 +660,7 @@ mod tests {
             &Arc::new(validator_keypair),
             &validator_ledger_path,
             &voting_keypair.pubkey(),
-            &voting_keypair,
+            &[voting_keypair.clone()],
             &storage_keypair,
             Some(&leader_node.info),
             true,
@@ -559,7 +692,7 @@ mod tests {
                     &Arc::new(validator_keypair),
                     &validator_ledger_path,
                     &voting_keypair.pubkey(),
-                    &voting_keypair,
+                    &[voting_keypair.clone()],
                     &storage_keypair,
                     Some(&leader_node.info),
                     true,
@@ -580,4 +713,113 @@ mod tests {
             remove_dir_all(path).unwrap();
         }
     }
+
+    #[test]
+    fn test_get_bank_forks_from_snapshot() {
+        use crate::blocktree_processor::tests::fill_blocktree_slot_with_ticks;
+        use crate::genesis_utils::{create_genesis_block, GenesisBlockInfo};
+        use crate::snapshot_package::SnapshotPackagerService;
+        use tempfile::TempDir;
+
+        let GenesisBlockInfo { genesis_block, .. } = create_genesis_block(10_000);
+        let ticks_per_slot = genesis_block.ticks_per_slot;
+        let (ledger_path, blockhash) = create_new_tmp_ledger!(&genesis_block);
+        {
+            // slot 0 <- slot 1 <- slot 2
+            let blocktree = Blocktree::open(&ledger_path).unwrap();
+            let last_hash =
+                fill_blocktree_slot_with_ticks(&blocktree, ticks_per_slot, 1, 0, blockhash);
+            fill_blocktree_slot_with_ticks(&blocktree, ticks_per_slot, 2, 1, last_hash);
+
+            let snapshot_dir = TempDir::new().unwrap();
+            let snapshot_config = SnapshotConfig::new(
+                snapshot_dir.path().join("snapshot"),
+                snapshot_dir.path().to_path_buf(),
+                100,
+                100,
+            );
+            let account_paths = |dir: &TempDir| Some(dir.path().to_str().unwrap().to_string());
+
+            // Without a snapshot archive the ledger is replayed from genesis
+            let accounts_dir = TempDir::new().unwrap();
+            let (bank_forks, _, _) = get_bank_forks(
+                &genesis_block,
+                &blocktree,
+                account_paths(&accounts_dir),
+                Some(snapshot_config.clone()),
+                false,
+                None,
+                false,
+            );
+            assert!(bank_forks.get(0).is_some());
+
+            // Package a snapshot of slot 1
+            let bank1 = bank_forks[1].clone();
+            bank1.squash();
+            snapshot_utils::add_snapshot(snapshot_config.snapshot_path(), &bank1, &[]).unwrap();
+            let snapshot_package = snapshot_utils::package_snapshot(
+                &bank1,
+                &snapshot_utils::get_snapshot_paths(snapshot_config.snapshot_path()),
+                snapshot_utils::get_snapshot_tar_path(
+                    snapshot_config.snapshot_package_output_path(),
+                ),
+                snapshot_config.snapshot_path(),
+            )
+            .unwrap();
+            SnapshotPackagerService::package_snapshots(&snapshot_package).unwrap();
+            drop(snapshot_package);
+            drop(bank1);
+            drop(bank_forks);
+
+            // A full replay ignores the snapshot archive
+            let accounts_dir = TempDir::new().unwrap();
+            let (bank_forks, _, _) = get_bank_forks(
+                &genesis_block,
+                &blocktree,
+                account_paths(&accounts_dir),
+                Some(snapshot_config.clone()),
+                false,
+                None,
+                true,
+            );
+            assert!(bank_forks.get(0).is_some());
+            drop(bank_forks);
+
+            // Otherwise only the slots descending from the snapshot are replayed
+            let accounts_dir = TempDir::new().unwrap();
+            let (bank_forks, _, _) = get_bank_forks(
+                &genesis_block,
+                &blocktree,
+                account_paths(&accounts_dir),
+                Some(snapshot_config.clone()),
+                false,
+                None,
+                false,
+            );
+            assert_eq!(bank_forks.root(), 1);
+            assert!(bank_forks.get(0).is_none());
+            assert_eq!(bank_forks[2].parent().unwrap().slot(), 1);
+            drop(bank_forks);
+
+            // The bank snapshot left in the snapshot path is loaded without the archive, from
+            // the AppendVecs in the account paths
+            fs::remove_file(snapshot_utils::get_snapshot_tar_path(
+                snapshot_config.snapshot_package_output_path(),
+            ))
+            .unwrap();
+            let (bank_forks, _, _) = get_bank_forks(
+                &genesis_block,
+                &blocktree,
+                account_paths(&accounts_dir),
+                Some(snapshot_config),
+                false,
+                None,
+                false,
+            );
+            assert_eq!(bank_forks.root(), 1);
+            assert!(bank_forks.get(0).is_none());
+            assert_eq!(bank_forks[2].parent().unwrap().slot(), 1);
+        }
+        remove_dir_all(ledger_path).unwrap();
+    }
 }
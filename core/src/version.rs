@@ -1 +1,15 @@
+use solana_sdk::hash::hash;
+
 pub(crate) const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Cluster features this release supports.  Adding one changes the feature set identifier.
+const FEATURES: &[&str] = &[];
+
+/// Identifies the cluster features this release supports, nodes gossiping the same
+/// identifier support the same features
+pub(crate) fn feature_set() -> u32 {
+    let hash = hash(FEATURES.join(",").as_bytes());
+    let mut bytes = [0u8; 4];
+    bytes.copy_from_slice(&hash.as_ref()[..4]);
+    u32::from_le_bytes(bytes)
+}
@@ -45,8 +45,13 @@ fn retransmit_blobs(blobs: &[SharedBlob], retransmit: &BlobSender, id: &Pubkey)
     Ok(())
 }
 
-/// Process a blob: Add blob to the ledger window.
-pub fn process_blobs(blobs: &[SharedBlob], blocktree: &Arc<Blocktree>) -> Result<()> {
+/// Process a blob: Add blob to the ledger window. Duplicate slot proofs are
+/// only recorded for slots whose leader is known to `leader_schedule_cache`.
+pub fn process_blobs(
+    blobs: &[SharedBlob],
+    blocktree: &Arc<Blocktree>,
+    leader_schedule_cache: Option<&LeaderScheduleCache>,
+) -> Result<()> {
     // make an iterator for insert_data_blobs()
     //let blobs: Vec<_> = blobs.iter().map(move |blob| blob.read().unwrap()).collect();
 
@@ -54,6 +59,7 @@ pub fn process_blobs(blobs: &[SharedBlob], blocktree: &Arc<Blocktree>) -> Result
         blobs
             .iter()
             .filter(|blob| !blob.read().unwrap().is_coding()),
+        leader_schedule_cache,
     )?;
 
     blocktree
@@ -94,6 +100,7 @@ pub fn should_retransmit_and_persist(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn recv_window<F>(
     blocktree: &Arc<Blocktree>,
     my_pubkey: &Pubkey,
@@ -101,6 +108,7 @@ fn recv_window<F>(
     retransmit: &BlobSender,
     blob_filter: F,
     thread_pool: &ThreadPool,
+    leader_schedule_cache: Option<&LeaderScheduleCache>,
 ) -> Result<()>
 where
     F: Fn(&Blob) -> bool,
@@ -130,7 +138,7 @@ where
 
     trace!("{} num blobs received: {}", my_pubkey, blobs.len());
 
-    process_blobs(&blobs, blocktree)?;
+    process_blobs(&blobs, blocktree, leader_schedule_cache)?;
 
     trace!(
         "Elapsed processing time in recv_window(): {}",
@@ -174,6 +182,7 @@ impl WindowService {
         exit: &Arc<AtomicBool>,
         repair_strategy: RepairStrategy,
         blob_filter: F,
+        leader_schedule_cache: Option<Arc<LeaderScheduleCache>>,
     ) -> WindowService
     where
         F: 'static
@@ -230,6 +239,7 @@ impl WindowService {
                             )
                         },
                         &thread_pool,
+                        leader_schedule_cache.as_ref().map(|cache| cache.as_ref()),
                     ) {
                         match e {
                             Error::RecvTimeoutError(RecvTimeoutError::Disconnected) => break,
@@ -299,7 +309,7 @@ mod test {
         index_blobs(&shared_blobs, &Pubkey::new_rand(), 0, 0, 0);
 
         for blob in shared_blobs.into_iter().rev() {
-            process_blobs(&[blob], &blocktree).expect("Expect successful processing of blob");
+            process_blobs(&[blob], &blocktree, None).expect("Expect successful processing of blob");
         }
 
         assert_eq!(
@@ -395,6 +405,7 @@ mod test {
             &exit,
             repair_strategy,
             |_, _, _| true,
+            None,
         );
         let t_responder = {
             let (s_responder, r_responder) = channel();
@@ -477,6 +488,7 @@ mod test {
             &exit,
             repair_strategy,
             |_, _, _| true,
+            None,
         );
         let t_responder = {
             let (s_responder, r_responder) = channel();
@@ -1,3 +1,4 @@
+use log::*;
 use rand::SeedableRng;
 use rand_chacha::ChaChaRng;
 use rayon::iter::ParallelIterator;
@@ -6,7 +7,7 @@ use serial_test_derive::serial;
 use solana::cluster_info::{compute_retransmit_peers, ClusterInfo};
 use solana::contact_info::ContactInfo;
 use solana_sdk::pubkey::Pubkey;
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::mpsc::channel;
 use std::sync::mpsc::TryRecvError;
 use std::sync::mpsc::{Receiver, Sender};
@@ -185,9 +186,162 @@ fn run_simulation(stakes: &[u64], fanout: usize) {
     });
 }
 
-// Recommended to not run these tests in parallel (they are resource heavy and want all the compute)
+// Deliver `num_blobs` blobs over the retransmit tree `seed` picks for each blob index, with
+// the `failed` nodes neither receiving nor retransmitting.  Each node retransmits the first
+// copy of a blob it receives.  Returns the number of blobs each node received.
+fn run_delivery_simulation<F>(
+    cluster_info: &ClusterInfo,
+    staked_nodes: &HashMap<Pubkey, u64>,
+    fanout: usize,
+    num_blobs: u64,
+    failed: &HashSet<Pubkey>,
+    seed: F,
+) -> HashMap<Pubkey, u64>
+where
+    F: Fn(u64) -> [u8; 32],
+{
+    let mut num_received = HashMap::new();
+    for index in 0..num_blobs {
+        let (_, mut peers) = cluster_info
+            .shuffle_peers_and_index(Some(staked_nodes), ChaChaRng::from_seed(seed(index)));
+        peers.retain(|c| c.id != cluster_info.id());
+        let positions: HashMap<_, _> = peers.iter().enumerate().map(|(i, c)| (c.id, i)).collect();
+
+        // the leader broadcasts to the root of the tree
+        let mut queue = VecDeque::new();
+        queue.push_back((peers[0].id, true));
+        let mut received = HashSet::new();
+        while let Some((id, forward)) = queue.pop_front() {
+            if failed.contains(&id) || !received.insert(id) {
+                continue;
+            }
+            let my_index = positions[&id];
+            let mut others = peers.clone();
+            others.remove(my_index);
+            let (neighbors, children) = compute_retransmit_peers(fanout, my_index, others);
+            if forward {
+                queue.extend(neighbors.iter().map(|c| (c.id, false)));
+                queue.extend(children.iter().map(|c| (c.id, true)));
+            } else {
+                queue.extend(children.iter().map(|c| (c.id, false)));
+            }
+        }
+        for id in received {
+            *num_received.entry(id).or_insert(0) += 1;
+        }
+    }
+    num_received
+}
+
+// Lowest and average number of blobs the nodes that didn't fail received
+fn delivery_stats(
+    staked_nodes: &HashMap<Pubkey, u64>,
+    failed: &HashSet<Pubkey>,
+    num_received: &HashMap<Pubkey, u64>,
+) -> (u64, f64) {
+    let live: Vec<u64> = staked_nodes
+        .keys()
+        .filter(|id| !failed.contains(id))
+        .map(|id| *num_received.get(id).unwrap_or(&0))
+        .collect();
+    let min = *live.iter().min().unwrap();
+    let avg = live.iter().sum::<u64>() as f64 / live.len() as f64;
+    (min, avg)
+}
 
-//todo add tests with network failures
+#[test]
+fn test_retransmit_delivery_with_failures() {
+    solana_logger::setup();
+    let num_nodes = 200;
+    let fanout = 8;
+    let num_blobs = 64;
+    let slot = 7;
+    let leader_info = ContactInfo::new_localhost(&Pubkey::new_rand(), 0);
+    let mut cluster_info = ClusterInfo::new_with_invalid_keypair(leader_info);
+    let mut staked_nodes = HashMap::new();
+    for stake in 1..=num_nodes {
+        let node = ContactInfo::new_localhost(&Pubkey::new_rand(), 0);
+        staked_nodes.insert(node.id, stake);
+        cluster_info.insert_info(node);
+    }
+    let leader = cluster_info.id();
+    let per_blob_tree = |index: u64| ClusterInfo::retransmit_seed(slot, index, &leader);
+    let fixed_tree = |_: u64| ClusterInfo::retransmit_seed(slot, 0, &leader);
+
+    // every node receives every blob
+    let num_received = run_delivery_simulation(
+        &cluster_info,
+        &staked_nodes,
+        fanout,
+        num_blobs,
+        &HashSet::new(),
+        per_blob_tree,
+    );
+    assert_eq!(
+        delivery_stats(&staked_nodes, &HashSet::new(), &num_received).0,
+        num_blobs
+    );
+
+    // A failed node in layer 0 of a tree used for every blob starves its subtree of all of
+    // them, while over a tree per blob it only costs each node a few
+    let (_, peers) = cluster_info
+        .shuffle_peers_and_index(Some(&staked_nodes), ChaChaRng::from_seed(fixed_tree(0)));
+    let peers: Vec<_> = peers
+        .into_iter()
+        .filter(|c| c.id != cluster_info.id())
+        .collect();
+    let failed: HashSet<_> = vec![peers[1].id].into_iter().collect();
+    let num_received = run_delivery_simulation(
+        &cluster_info,
+        &staked_nodes,
+        fanout,
+        num_blobs,
+        &failed,
+        fixed_tree,
+    );
+    let (fixed_min, fixed_avg) = delivery_stats(&staked_nodes, &failed, &num_received);
+    let num_received = run_delivery_simulation(
+        &cluster_info,
+        &staked_nodes,
+        fanout,
+        num_blobs,
+        &failed,
+        per_blob_tree,
+    );
+    let (min, avg) = delivery_stats(&staked_nodes, &failed, &num_received);
+    info!(
+        "one failed node, fixed tree: min {} avg {}, tree per blob: min {} avg {}",
+        fixed_min, fixed_avg, min, avg
+    );
+    assert_eq!(fixed_min, 0);
+    assert!(min >= num_blobs / 2);
+
+    // a tenth of the nodes failed
+    let failed: HashSet<_> = staked_nodes
+        .iter()
+        .filter(|(_, stake)| *stake % 10 == 0)
+        .map(|(id, _)| *id)
+        .collect();
+    let num_received = run_delivery_simulation(
+        &cluster_info,
+        &staked_nodes,
+        fanout,
+        num_blobs,
+        &failed,
+        per_blob_tree,
+    );
+    let (min, avg) = delivery_stats(&staked_nodes, &failed, &num_received);
+    info!(
+        "{} failed nodes, tree per blob: min {} avg {}",
+        failed.len(),
+        min,
+        avg
+    );
+    assert!(min > 0);
+    assert!(avg >= num_blobs as f64 / 2.0);
+}
+
+// Recommended to not run these tests in parallel (they are resource heavy and want all the compute)
 
 // Run with a single layer
 #[test]
@@ -1,5 +1,6 @@
 use bincode::serialized_size;
 use log::*;
+use rand::{thread_rng, Rng};
 use rayon::prelude::*;
 use solana::cluster_info::ClusterInfo;
 use solana::contact_info::ContactInfo;
@@ -13,6 +14,7 @@ use solana_sdk::pubkey::Pubkey;
 use solana_sdk::timing::timestamp;
 use std::collections::{HashMap, HashSet};
 use std::ops::Deref;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 
 #[derive(Clone)]
@@ -39,10 +41,40 @@ impl Deref for Node {
     }
 }
 
+/// Conditions of the link a node sends to another node over, the default is a perfect link
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Link {
+    /// Probability that a message sent over the link is lost
+    loss_rate: f64,
+    /// Ticks a message spends on the link before it arrives
+    latency: usize,
+}
+
+impl Link {
+    fn down() -> Self {
+        Link {
+            loss_rate: 1.0,
+            latency: 0,
+        }
+    }
+}
+
+// Messages on a link, with the tick they arrive at
+type InFlight<T> = Mutex<Vec<(usize, T)>>;
+
 struct Network {
     nodes: HashMap<Pubkey, Node>,
     stake_pruned: u64,
     connections_pruned: HashSet<(Pubkey, Pubkey)>,
+    /// (from, to) => conditions of the links that aren't perfect
+    links: HashMap<(Pubkey, Pubkey), Link>,
+    /// Partition of each node, nodes can only reach nodes in their own partition
+    partitions: HashMap<Pubkey, usize>,
+    /// (from, to, push messages)
+    in_flight_pushes: InFlight<(Pubkey, Pubkey, Vec<CrdsValue>)>,
+    /// (requester, pull response)
+    in_flight_pull_responses: InFlight<(Pubkey, Vec<CrdsValue>)>,
+    num_lost: AtomicUsize,
 }
 
 impl Network {
@@ -51,8 +83,72 @@ impl Network {
             nodes,
             connections_pruned: HashSet::new(),
             stake_pruned: 0,
+            links: HashMap::new(),
+            partitions: HashMap::new(),
+            in_flight_pushes: Mutex::default(),
+            in_flight_pull_responses: Mutex::default(),
+            num_lost: AtomicUsize::new(0),
         }
     }
+
+    /// Set the conditions of the links between `a` and `b`, in both directions
+    fn set_link(&mut self, a: &Pubkey, b: &Pubkey, link: Link) {
+        self.links.insert((*a, *b), link);
+        self.links.insert((*b, *a), link);
+    }
+
+    fn drop_link(&mut self, a: &Pubkey, b: &Pubkey) {
+        self.set_link(a, b, Link::down());
+    }
+
+    /// Set the conditions of every link in the network
+    fn set_all_links(&mut self, link: Link) {
+        let keys: Vec<Pubkey> = self.nodes.keys().cloned().collect();
+        for a in &keys {
+            for b in &keys {
+                if a != b {
+                    self.links.insert((*a, *b), link);
+                }
+            }
+        }
+    }
+
+    /// Split the network so nodes can only reach nodes in their own partition.  Nodes
+    /// missing from `partitions` form one more partition.
+    fn partition(&mut self, partitions: &[Vec<Pubkey>]) {
+        self.partitions = partitions
+            .iter()
+            .enumerate()
+            .flat_map(|(i, partition)| partition.iter().map(move |id| (*id, i)))
+            .collect();
+    }
+
+    fn heal(&mut self) {
+        self.partitions.clear();
+    }
+
+    /// Returns the tick a message sent from `from` to `to` at tick `t` arrives at, or None
+    /// if the message is lost
+    fn send(&self, from: &Pubkey, to: &Pubkey, t: usize) -> Option<usize> {
+        let link = self.links.get(&(*from, *to)).cloned().unwrap_or_default();
+        if self.partitions.get(from) != self.partitions.get(to)
+            || (link.loss_rate > 0.0 && thread_rng().gen::<f64>() < link.loss_rate)
+        {
+            self.num_lost.fetch_add(1, Ordering::Relaxed);
+            None
+        } else {
+            Some(t + link.latency)
+        }
+    }
+}
+
+// Remove the messages that have arrived by tick `t`
+fn take_arrived<T>(in_flight: &InFlight<T>, t: usize) -> Vec<T> {
+    let mut in_flight = in_flight.lock().unwrap();
+    let (arrived, in_flight_still): (Vec<_>, Vec<_>) =
+        in_flight.drain(..).partition(|(arrival, _)| *arrival <= t);
+    *in_flight = in_flight_still;
+    arrived.into_iter().map(|(_, message)| message).collect()
 }
 
 impl Deref for Network {
@@ -193,6 +289,79 @@ fn network_simulator_pull_only(network: &mut Network) {
     assert!(converged >= 0.9);
 }
 
+// Every node pushes a new contact info of its own with wallclock `now`
+fn network_update_contact_infos(network: &Network, now: u64) {
+    let network_values: Vec<Node> = network.values().cloned().collect();
+    network_values.par_iter().for_each(|locked_node| {
+        let node = &mut locked_node.lock().unwrap();
+        let mut m = node
+            .crds
+            .lookup(&CrdsValueLabel::ContactInfo(node.id))
+            .and_then(|v| v.contact_info().cloned())
+            .unwrap();
+        m.wallclock = now;
+        node.process_push_message(&Pubkey::default(), vec![CrdsValue::ContactInfo(m)], now);
+    });
+}
+
+// Fraction of the `holders` that have the contact infos of `origins` as of `wallclock`
+fn network_freshness(
+    network: &Network,
+    holders: &[Pubkey],
+    origins: &[Pubkey],
+    wallclock: u64,
+) -> f64 {
+    let fresh: usize = holders
+        .par_iter()
+        .map(|holder| {
+            let node = network[holder].lock().unwrap();
+            origins
+                .iter()
+                .filter(|origin| {
+                    node.crds
+                        .lookup(&CrdsValueLabel::ContactInfo(**origin))
+                        .map(|v| v.wallclock() >= wallclock)
+                        .unwrap_or(false)
+                })
+                .count()
+        })
+        .sum();
+    fresh as f64 / (holders.len() * origins.len()) as f64
+}
+
+/// Push and pull from tick `start` until at least `min_freshness` of the network has every
+/// node's contact info as of `wallclock`, for at most `max_ticks`.  Returns the ticks it took,
+/// if the network got there, and the bytes sent.
+fn network_run_until_fresh(
+    network: &mut Network,
+    start: usize,
+    max_ticks: usize,
+    wallclock: u64,
+    min_freshness: f64,
+) -> (Option<usize>, usize) {
+    let keys: Vec<Pubkey> = network.keys().cloned().collect();
+    let mut total_bytes = 0;
+    for t in start..start + max_ticks {
+        let (_, bytes_tx) = network_run_push(network, t, t + 1);
+        total_bytes += bytes_tx;
+        let (_, bytes_tx) = network_run_pull(network, t, t + 1, 1.0);
+        total_bytes += bytes_tx;
+        let freshness = network_freshness(network, &keys, &keys, wallclock);
+        trace!(
+            "network_run_until_fresh_{}: tick: {} freshness: {} total_bytes: {} lost: {}",
+            keys.len(),
+            t,
+            freshness,
+            total_bytes,
+            network.num_lost.load(Ordering::Relaxed)
+        );
+        if freshness >= min_freshness {
+            return (Some(t + 1 - start), total_bytes);
+        }
+    }
+    (None, total_bytes)
+}
+
 fn network_simulator(network: &mut Network, max_convergance: f64) {
     let num = network.len();
     // run for a small amount of time
@@ -211,16 +380,7 @@ fn network_simulator(network: &mut Network, max_convergance: f64) {
         let end = (second + 1) * 10;
         let now = (start * 100) as u64;
         // push a message to the network
-        network_values.par_iter().for_each(|locked_node| {
-            let node = &mut locked_node.lock().unwrap();
-            let mut m = node
-                .crds
-                .lookup(&CrdsValueLabel::ContactInfo(node.id))
-                .and_then(|v| v.contact_info().cloned())
-                .unwrap();
-            m.wallclock = now;
-            node.process_push_message(&Pubkey::default(), vec![CrdsValue::ContactInfo(m)], now);
-        });
+        network_update_contact_infos(network, now);
         // push for a bit
         let (queue_size, bytes_tx) = network_run_push(network, start, end);
         total_bytes += bytes_tx;
@@ -246,6 +406,63 @@ fn network_simulator(network: &mut Network, max_convergance: f64) {
     }
 }
 
+// Deliver push `msgs` from `from` to `to` at tick `t`, and send back the prunes they cause.
+// Returns the bytes and number of prune messages sent, and the (from, origin) connections
+// pruned.
+fn network_deliver_push(
+    network: &Network,
+    from: &Pubkey,
+    to: &Pubkey,
+    msgs: Vec<CrdsValue>,
+    t: usize,
+    stakes: &HashMap<Pubkey, u64>,
+) -> (usize, usize, HashSet<(Pubkey, Pubkey)>) {
+    let now = t as u64 * 100;
+    let mut bytes: usize = 0;
+    let mut delivered: usize = 0;
+    let mut pruned: HashSet<(Pubkey, Pubkey)> = HashSet::new();
+    let updated = network
+        .get(to)
+        .map(|node| node.lock().unwrap().process_push_message(from, msgs, now))
+        .unwrap();
+
+    let updated_labels: Vec<_> = updated.into_iter().map(|u| u.value.label()).collect();
+    let prunes_map = network
+        .get(to)
+        .map(|node| {
+            node.lock()
+                .unwrap()
+                .prune_received_cache(updated_labels, stakes)
+        })
+        .unwrap();
+
+    for (from, prune_set) in prunes_map {
+        let prune_keys: Vec<_> = prune_set.into_iter().collect();
+        for prune_key in &prune_keys {
+            pruned.insert((from, *prune_key));
+        }
+
+        bytes += serialized_size(&prune_keys).unwrap() as usize;
+        delivered += 1;
+
+        // prunes can be lost, but aren't delayed
+        if network.send(to, &from, t).is_none() {
+            continue;
+        }
+        network
+            .get(&from)
+            .map(|node| {
+                let mut node = node.lock().unwrap();
+                let destination = node.id;
+                let now = timestamp();
+                node.process_prune_msg(to, &destination, &prune_keys, now, now)
+                    .unwrap()
+            })
+            .unwrap();
+    }
+    (bytes, delivered, pruned)
+}
+
 fn network_run_push(network: &mut Network, start: usize, end: usize) -> (usize, usize) {
     let mut bytes: usize = 0;
     let mut num_msgs: usize = 0;
@@ -265,65 +482,42 @@ fn network_run_push(network: &mut Network, start: usize, end: usize) -> (usize,
                 node.lock().unwrap().new_push_messages(now)
             })
             .collect();
-        let transfered: Vec<_> = requests
+        let mut arrived = take_arrived(&network.in_flight_pushes, t);
+        let sent: Vec<_> = requests
             .into_par_iter()
             .map(|(from, push_messages)| {
                 let mut bytes: usize = 0;
-                let mut delivered: usize = 0;
                 let mut num_msgs: usize = 0;
-                let mut pruned: HashSet<(Pubkey, Pubkey)> = HashSet::new();
+                let mut arrived = vec![];
                 for (to, msgs) in push_messages {
                     bytes += serialized_size(&msgs).unwrap() as usize;
                     num_msgs += 1;
-                    let updated = network
-                        .get(&to)
-                        .map(|node| {
-                            node.lock()
-                                .unwrap()
-                                .process_push_message(&from, msgs.clone(), now)
-                        })
-                        .unwrap();
-
-                    let updated_labels: Vec<_> =
-                        updated.into_iter().map(|u| u.value.label()).collect();
-                    let prunes_map = network
-                        .get(&to)
-                        .map(|node| {
-                            node.lock()
-                                .unwrap()
-                                .prune_received_cache(updated_labels, &stakes)
-                        })
-                        .unwrap();
-
-                    for (from, prune_set) in prunes_map {
-                        let prune_keys: Vec<_> = prune_set.into_iter().collect();
-                        for prune_key in &prune_keys {
-                            pruned.insert((from, *prune_key));
-                        }
-
-                        bytes += serialized_size(&prune_keys).unwrap() as usize;
-                        delivered += 1;
-
-                        network
-                            .get(&from)
-                            .map(|node| {
-                                let mut node = node.lock().unwrap();
-                                let destination = node.id;
-                                let now = timestamp();
-                                node.process_prune_msg(&to, &destination, &prune_keys, now, now)
-                                    .unwrap()
-                            })
-                            .unwrap();
+                    match network.send(&from, &to, t) {
+                        Some(arrival) if arrival > t => network
+                            .in_flight_pushes
+                            .lock()
+                            .unwrap()
+                            .push((arrival, (from, to, msgs))),
+                        Some(_) => arrived.push((from, to, msgs)),
+                        None => (),
                     }
                 }
-                (bytes, delivered, num_msgs, pruned)
+                (bytes, num_msgs, arrived)
             })
             .collect();
+        for (b, m, a) in sent {
+            bytes += b;
+            num_msgs += m;
+            arrived.extend(a);
+        }
+        let transfered: Vec<_> = arrived
+            .into_par_iter()
+            .map(|(from, to, msgs)| network_deliver_push(network, &from, &to, msgs, t, &stakes))
+            .collect();
 
-        for (b, d, m, p) in transfered {
+        for (b, d, p) in transfered {
             bytes += b;
             delivered += d;
-            num_msgs += m;
 
             for (from, to) in p {
                 let from_stake = stakes.get(&from).unwrap();
@@ -386,12 +580,11 @@ fn network_run_pull(
                 })
                 .collect()
         };
-        let transfered: Vec<_> = requests
+        let mut arrived = take_arrived(&network.in_flight_pull_responses, t);
+        let sent: Vec<_> = requests
             .into_par_iter()
             .map(|(to, filters, caller_info)| {
                 let mut bytes: usize = 0;
-                let mut msgs: usize = 0;
-                let mut overhead: usize = 0;
                 let from = caller_info.label().pubkey();
                 bytes += filters.iter().map(|f| f.filter.keys.len()).sum::<usize>();
                 bytes += filters
@@ -399,6 +592,12 @@ fn network_run_pull(
                     .map(|f| f.filter.bits.len() as usize / 8)
                     .sum::<usize>();
                 bytes += serialized_size(&caller_info).unwrap() as usize;
+                // The request is answered as soon as it's sent, its latency is added to the
+                // response's
+                let request_arrival = match network.send(&from, &to, t) {
+                    Some(request_arrival) => request_arrival,
+                    None => return (bytes, 0, None),
+                };
                 let filters = filters
                     .into_iter()
                     .map(|f| (caller_info.clone(), f))
@@ -420,21 +619,36 @@ fn network_run_pull(
                     })
                     .unwrap();
                 bytes += serialized_size(&rsp).unwrap() as usize;
-                msgs += rsp.len();
-                network.get(&from).map(|node| {
-                    node.lock()
-                        .unwrap()
-                        .mark_pull_request_creation_time(&from, now);
-                    overhead += node.lock().unwrap().process_pull_response(&from, rsp, now);
-                });
-                (bytes, msgs, overhead)
+                let msgs = rsp.len();
+                match network.send(&to, &from, request_arrival) {
+                    Some(arrival) if arrival > t => {
+                        network
+                            .in_flight_pull_responses
+                            .lock()
+                            .unwrap()
+                            .push((arrival, (from, rsp)));
+                        (bytes, msgs, None)
+                    }
+                    Some(_) => (bytes, msgs, Some((from, rsp))),
+                    None => (bytes, msgs, None),
+                }
             })
             .collect();
-        for (b, m, o) in transfered {
+        for (b, m, a) in sent {
             bytes += b;
             msgs += m;
-            overhead += o;
+            arrived.extend(a);
         }
+        overhead += arrived
+            .into_par_iter()
+            .map(|(from, rsp)| {
+                let node = &network[&from];
+                node.lock()
+                    .unwrap()
+                    .mark_pull_request_creation_time(&from, now);
+                node.lock().unwrap().process_pull_response(&from, rsp, now)
+            })
+            .sum::<usize>();
         let total: usize = network_values
             .par_iter()
             .map(|v| v.lock().unwrap().crds.table.len())
@@ -511,6 +725,82 @@ fn test_connected_staked_network() {
     )
 }
 #[test]
+fn test_network_links() {
+    let mut network = star_network_create(3);
+    let keys: Vec<Pubkey> = network.keys().cloned().collect();
+    let (a, b, c) = (keys[0], keys[1], keys[2]);
+    assert_eq!(network.send(&a, &b, 0), Some(0));
+
+    network.set_link(
+        &a,
+        &b,
+        Link {
+            loss_rate: 0.0,
+            latency: 3,
+        },
+    );
+    assert_eq!(network.send(&b, &a, 1), Some(4));
+    network.drop_link(&a, &c);
+    assert_eq!(network.send(&c, &a, 0), None);
+
+    network.partition(&[vec![a]]);
+    assert_eq!(network.send(&a, &b, 0), None);
+    assert_eq!(network.send(&b, &c, 0), Some(0));
+    network.heal();
+    assert_eq!(network.send(&a, &b, 0), Some(3));
+    assert_eq!(network.num_lost.load(Ordering::Relaxed), 2);
+}
+#[test]
+fn test_star_network_pull_lossy_50() {
+    let mut network = star_network_create(50);
+    network.set_all_links(Link {
+        loss_rate: 0.2,
+        latency: 2,
+    });
+    let (converged, bytes_tx) = network_run_pull(&mut network, 0, 200, 0.9);
+    trace!(
+        "lossy star network: converged: {} total_bytes: {} lost: {}",
+        converged,
+        bytes_tx,
+        network.num_lost.load(Ordering::Relaxed)
+    );
+    assert!(converged >= 0.9);
+    assert!(network.num_lost.load(Ordering::Relaxed) > 0);
+}
+#[test]
+fn test_network_partition_heal() {
+    solana_logger::setup();
+    let mut network = connected_staked_network_create(&[1; 40]);
+    let keys: Vec<Pubkey> = network.keys().cloned().collect();
+    let (left, right) = keys.split_at(keys.len() / 2);
+    network.partition(&[left.to_vec(), right.to_vec()]);
+    let network_values: Vec<Node> = network.values().cloned().collect();
+    network_values.par_iter().for_each(|node| {
+        node.lock()
+            .unwrap()
+            .refresh_push_active_set(&HashMap::new());
+    });
+
+    // updates spread within each partition, but not across
+    let wallclock = 1000;
+    network_update_contact_infos(&network, wallclock);
+    let (ticks, _) = network_run_until_fresh(&mut network, 10, 30, wallclock, 1.0);
+    assert_eq!(ticks, None);
+    assert!(network_freshness(&network, left, left, wallclock) > 0.9);
+    assert!(network_freshness(&network, right, right, wallclock) > 0.9);
+    assert_eq!(network_freshness(&network, left, right, wallclock), 0.0);
+    assert_eq!(network_freshness(&network, right, left, wallclock), 0.0);
+
+    network.heal();
+    let (ticks, bytes_tx) = network_run_until_fresh(&mut network, 40, 100, wallclock, 0.95);
+    trace!(
+        "partitioned network healed: ticks: {:?} total_bytes: {}",
+        ticks,
+        bytes_tx
+    );
+    assert!(ticks.is_some(), "network should converge once healed");
+}
+#[test]
 #[ignore]
 fn test_star_network_large_pull() {
     solana_logger::setup();
@@ -5,8 +5,10 @@ use log::*;
 use solana::banking_stage::create_test_recorder;
 use solana::blocktree::{create_new_tmp_ledger, Blocktree};
 use solana::cluster_info::{ClusterInfo, Node};
+use solana::consensus::Tower;
 use solana::entry::next_entry_mut;
 use solana::entry::EntrySlice;
+use solana::fork_tree::ForkTreeExport;
 use solana::genesis_utils::{create_genesis_block_with_leader, GenesisBlockInfo};
 use solana::gossip_service::GossipService;
 use solana::packet::index_blobs;
@@ -97,7 +99,7 @@ fn test_replay() {
         completed_slots_receiver,
         leader_schedule_cache,
         _,
-    ) = validator::new_banks_from_blocktree(&blocktree_path, None, None, true, None);
+    ) = validator::new_banks_from_blocktree(&blocktree_path, None, None, true, None, false);
     let working_bank = bank_forks.working_bank();
     assert_eq!(
         working_bank.get_balance(&mint_keypair.pubkey()),
@@ -118,11 +120,19 @@ fn test_replay() {
     {
         let (poh_service_exit, poh_recorder, poh_service, _entry_receiver) =
             create_test_recorder(&working_bank, &blocktree);
+        let tower = Tower::new(
+            &cref1.read().unwrap().keypair.pubkey(),
+            &voting_keypair.pubkey(),
+            &bank_forks.read().unwrap(),
+            &blocktree_path,
+        );
         let tvu = Tvu::new(
             &voting_keypair.pubkey(),
-            Some(&Arc::new(voting_keypair)),
+            &[Arc::new(voting_keypair)],
             &storage_keypair,
             &bank_forks,
+            tower,
+            &ForkTreeExport::default(),
             &cref1,
             {
                 Sockets {
@@ -135,6 +145,7 @@ fn test_replay() {
             &StorageState::default(),
             None,
             None,
+            None,
             ledger_signal_receiver,
             &Arc::new(RpcSubscriptions::default()),
             &poh_recorder,
@@ -12,6 +12,8 @@ extern crate solana_exchange_program;
 #[macro_use]
 extern crate solana_move_loader_program;
 #[macro_use]
+extern crate solana_stake_pool_program;
+#[macro_use]
 extern crate solana_stake_program;
 #[macro_use]
 extern crate solana_storage_program;
@@ -29,6 +31,7 @@ pub fn get() -> Vec<(String, Pubkey)> {
         solana_exchange_program!(),
         solana_move_loader_program!(),
         solana_stake_program!(),
+        solana_stake_pool_program!(),
         solana_storage_program!(),
         solana_token_program!(),
         solana_vote_program!(),
@@ -51,6 +54,7 @@ mod tests {
             solana_sdk::native_loader::id(),
             solana_sdk::system_program::id(),
             solana_stake_api::id(),
+            solana_stake_pool_api::id(),
             solana_storage_api::id(),
             solana_token_api::id(),
             solana_vote_api::id(),
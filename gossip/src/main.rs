@@ -8,7 +8,7 @@ use clap::{
     SubCommand,
 };
 use solana::contact_info::ContactInfo;
-use solana::gossip_service::discover;
+use solana::gossip_service::{discover, discover_with_versions};
 use solana_client::rpc_client::RpcClient;
 use solana_sdk::pubkey::Pubkey;
 use std::error;
@@ -141,7 +141,7 @@ fn main() -> Result<(), Box<dyn error::Error>> {
                 .value_of("node_pubkey")
                 .map(|pubkey_str| pubkey_str.parse::<Pubkey>().unwrap());
 
-            let (nodes, _replicators) = discover(
+            let (nodes, _replicators) = discover_with_versions(
                 &entrypoint_addr,
                 num_nodes,
                 timeout,
@@ -149,6 +149,18 @@ fn main() -> Result<(), Box<dyn error::Error>> {
                 gossip_addr.as_ref(),
             )?;
 
+            for (node, version) in &nodes {
+                println!(
+                    "{} gossip {}: {}",
+                    node.id,
+                    node.gossip,
+                    version
+                        .as_ref()
+                        .map(|version| version.to_string())
+                        .unwrap_or_else(|| "unknown version".to_string())
+                );
+            }
+
             if timeout.is_some() {
                 if let Some(num) = num_nodes {
                     if nodes.len() < num {
@@ -164,7 +176,7 @@ fn main() -> Result<(), Box<dyn error::Error>> {
                     }
                 }
                 if let Some(node) = pubkey {
-                    if nodes.iter().find(|x| x.id == node).is_none() {
+                    if nodes.iter().find(|(x, _)| x.id == node).is_none() {
                         eprintln!("Error: Could not find node {:?}", node);
                     }
                 }
@@ -59,6 +59,7 @@ pub struct KvStore {
     sender: Mutex<Sender<compactor::Req>>,
     receiver: Mutex<Receiver<compactor::Resp>>,
     compactor_handle: JoinHandle<()>,
+    pending_compactions: AtomicUsize,
 }
 
 impl KvStore {
@@ -214,6 +215,45 @@ impl KvStore {
         storage::range(&memtable.values, &*tables, range)
     }
 
+    /// Flushes the memtable and compacts every level, dropping deleted rows from disk.
+    /// Blocks until the compactor is done
+    pub fn compact(&self) -> Result<()> {
+        let mut memtable = self.mem.write().unwrap();
+        let mut log = self.log.write().unwrap();
+        let mut tables = self.tables.write().unwrap();
+
+        if !memtable.values.is_empty() {
+            storage::flush_table(&memtable.values, &*self.mapper, &mut *tables)?;
+            memtable.values.clear();
+            memtable.mem_size = 0;
+            log.reset().expect("Write-log rotation failed");
+        }
+        // level 0 holds everything written since the last compaction
+        if tables.first().map_or(true, BTreeMap::is_empty) {
+            return Ok(());
+        }
+
+        let mut sender = self.sender.lock().unwrap();
+        let receiver = self.receiver.lock().unwrap();
+        sender.send(compactor::Req::Start(PathBuf::new()))?;
+        self.pending_compactions.fetch_add(1, Ordering::SeqCst);
+
+        // compactions are run in order, so once none are pending this one is done
+        while self.pending_compactions.load(Ordering::SeqCst) > 0 {
+            let resp = receiver.recv()?;
+            apply_compaction(
+                &self.root,
+                &*self.mapper,
+                &mut *tables,
+                &mut *sender,
+                &self.pending_compactions,
+                resp,
+            )?;
+        }
+
+        Ok(())
+    }
+
     pub fn destroy<P>(path: P) -> Result<()>
     where
         P: AsRef<Path>,
@@ -239,6 +279,7 @@ impl KvStore {
                 &mut *tables,
                 &mut *receiver,
                 &mut *sender,
+                &self.pending_compactions,
             )?;
         }
 
@@ -261,6 +302,7 @@ impl KvStore {
             let sender = self.sender.lock().unwrap();
 
             sender.send(compactor::Req::Start(PathBuf::new()))?;
+            self.pending_compactions.fetch_add(1, Ordering::SeqCst);
         }
 
         Ok(())
@@ -282,7 +324,9 @@ impl Default for Config {
 fn open(root: &Path, mapper: Arc<dyn Mapper>, config: Config) -> Result<KvStore> {
     let root = root.to_path_buf();
     let log_path = root.join(LOG_FILE);
-    let restore_log = log_path.exists();
+    // the log is left empty once the memtable has been flushed, and an empty log can't
+    //  be memory-mapped
+    let restore_log = log_path.exists() && fs::metadata(&log_path)?.len() > 0;
 
     if !root.exists() {
         fs::create_dir(&root)?;
@@ -317,6 +361,7 @@ fn open(root: &Path, mapper: Arc<dyn Mapper>, config: Config) -> Result<KvStore>
         sender: Mutex::new(sender),
         receiver: Mutex::new(receiver),
         compactor_handle,
+        pending_compactions: AtomicUsize::new(0),
     })
 }
 
@@ -343,18 +388,33 @@ fn query_compactor(
     tables: &mut Vec<BTreeMap<Key, SSTable>>,
     receiver: &mut Receiver<compactor::Resp>,
     sender: &mut Sender<compactor::Req>,
+    pending_compactions: &AtomicUsize,
 ) -> Result<()> {
     match receiver.try_recv() {
-        Ok(compactor::Resp::Done(new_tables)) => {
+        Ok(resp) => apply_compaction(root, mapper, tables, sender, pending_compactions, resp),
+        // Nothing available, do nothing
+        _ => Ok(()),
+    }
+}
+
+fn apply_compaction(
+    root: &Path,
+    mapper: &dyn Mapper,
+    tables: &mut Vec<BTreeMap<Key, SSTable>>,
+    sender: &mut Sender<compactor::Req>,
+    pending_compactions: &AtomicUsize,
+    resp: compactor::Resp,
+) -> Result<()> {
+    pending_compactions.fetch_sub(1, Ordering::SeqCst);
+    match resp {
+        compactor::Resp::Done(new_tables) => {
             std::mem::replace(tables, new_tables);
             dump_tables(root, mapper)?;
             sender.send(compactor::Req::Gc).unwrap();
         }
-        Ok(compactor::Resp::Failed(e)) => {
+        compactor::Resp::Failed(e) => {
             return Err(e);
         }
-        // Nothing available, do nothing
-        _ => {}
     }
 
     Ok(())
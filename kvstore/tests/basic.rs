@@ -125,6 +125,48 @@ fn test_delete_many() {
     teardown(&path);
 }
 
+#[test]
+fn test_compact() {
+    let path = setup("test_compact");
+
+    let cfg = Config {
+        max_mem: 64 * KB,
+        max_tables: 5,
+        page_size: 64 * KB,
+        ..Config::default()
+    };
+    let lsm = KvStore::open(&path, cfg).unwrap();
+
+    let mut pairs: Vec<_> = gen::pairs(HALF_KB).take(64 * 6).collect();
+    pairs.sort_unstable_by_key(|(k, _)| *k);
+
+    for (k, i) in pairs.iter() {
+        lsm.put(k, i).expect("put fail");
+    }
+
+    for (k, _) in pairs.drain(64..128) {
+        lsm.delete(&k).expect("delete fail");
+    }
+
+    lsm.compact().expect("compact fail");
+    // nothing left to compact
+    lsm.compact().expect("compact fail");
+
+    let retrieved: Vec<(Key, Vec<u8>)> =
+        lsm.range(Key::ALL_INCLUSIVE).expect("range fail").collect();
+    assert_eq!(pairs, retrieved);
+
+    // compacted tables survive a re-open
+    drop(lsm);
+    let lsm = KvStore::open(&path, cfg).unwrap();
+
+    let retrieved: Vec<(Key, Vec<u8>)> =
+        lsm.range(Key::ALL_INCLUSIVE).expect("range fail").collect();
+    assert_eq!(pairs, retrieved);
+
+    teardown(&path);
+}
+
 #[test]
 fn test_close_reopen() {
     let path = setup("test_close_reopen");
@@ -1,14 +1,28 @@
-use clap::{crate_description, crate_name, crate_version, value_t_or_exit, App, Arg, SubCommand};
+use clap::{
+    crate_description, crate_name, crate_version, value_t, value_t_or_exit, values_t, App, Arg,
+    SubCommand,
+};
+use solana::bank_forks::SnapshotConfig;
 use solana::blocktree::Blocktree;
-use solana::blocktree_processor::process_blocktree;
+use solana::blocktree_processor::{process_blocktree, process_blocktree_with_callback};
+use solana::ledger_archive;
+use solana::snapshot_package::SnapshotPackagerService;
+use solana::snapshot_utils;
+use solana_runtime::accounts_db::AccountsDB;
+use solana_runtime::bank::Bank;
+use solana_sdk::account::Account;
 use solana_sdk::genesis_block::GenesisBlock;
+use solana_sdk::pubkey::Pubkey;
 use solana_sdk::timing::Slot;
 use std::collections::BTreeMap;
-use std::fs::File;
-use std::io::{stdout, Write};
-use std::path::PathBuf;
+use std::fs::{self, File};
+use std::io::{stdout, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
 use std::process::exit;
 use std::str::FromStr;
+use transaction_decoder::{decode_entry, EntryInfo};
+
+mod transaction_decoder;
 
 #[derive(PartialEq)]
 enum LedgerOutputMethod {
@@ -16,6 +30,39 @@ enum LedgerOutputMethod {
     Json,
 }
 
+fn output_entry(entry_info: &EntryInfo) {
+    println!(
+        "Entry {{ num_hashes: {}, hash: {}, transactions: {} }}",
+        entry_info.num_hashes,
+        entry_info.hash,
+        entry_info.transactions.len()
+    );
+    for transaction_info in &entry_info.transactions {
+        println!("  Transaction {{");
+        for signature in &transaction_info.signatures {
+            println!("    signature: {}", signature);
+        }
+        for (i, account_key) in transaction_info.account_keys.iter().enumerate() {
+            println!("    account {}: {}", i, account_key);
+        }
+        println!(
+            "    recent_blockhash: {}",
+            transaction_info.recent_blockhash
+        );
+        for (i, instruction_info) in transaction_info.instructions.iter().enumerate() {
+            println!(
+                "    instruction {}: program: {}",
+                i, instruction_info.program_id
+            );
+            for account in &instruction_info.accounts {
+                println!("      account: {}", account);
+            }
+            println!("      {:?}", instruction_info.instruction);
+        }
+        println!("  }}");
+    }
+}
+
 fn output_slot(blocktree: &Blocktree, slot: u64, method: &LedgerOutputMethod) {
     let entries = blocktree
         .get_slot_entries(slot, 0, None)
@@ -25,10 +72,11 @@ fn output_slot(blocktree: &Blocktree, slot: u64, method: &LedgerOutputMethod) {
         });
 
     for entry in entries {
+        let entry_info = decode_entry(&entry);
         match method {
-            LedgerOutputMethod::Print => println!("{:?}", entry),
+            LedgerOutputMethod::Print => output_entry(&entry_info),
             LedgerOutputMethod::Json => {
-                serde_json::to_writer(stdout(), &entry).expect("serialize entry");
+                serde_json::to_writer(stdout(), &entry_info).expect("serialize entry");
                 stdout().write_all(b",\n").expect("newline");
             }
         }
@@ -67,6 +115,126 @@ fn output_ledger(blocktree: Blocktree, starting_slot: u64, method: LedgerOutputM
     }
 }
 
+// Return an error if a pubkey cannot be parsed.
+fn is_pubkey(string: String) -> Result<(), String> {
+    match string.parse::<Pubkey>() {
+        Ok(_) => Ok(()),
+        Err(err) => Err(format!("{:?}", err)),
+    }
+}
+
+fn output_replayed_bank(bank: &Bank, print_accounts: bool) {
+    println!(
+        "Slot {} parent: {} hash: {} transaction_count: {} capitalization: {}",
+        bank.slot(),
+        bank.parent()
+            .map(|parent| parent.slot().to_string())
+            .unwrap_or_else(|| "-".to_string()),
+        bank.hash(),
+        bank.transaction_count(),
+        bank.capitalization(),
+    );
+
+    if print_accounts {
+        for (pubkey, account) in bank.get_accounts_modified_since_parent() {
+            output_account(&pubkey, &account);
+        }
+    }
+}
+
+fn output_account(pubkey: &Pubkey, account: &Account) {
+    println!(
+        "  {}: lamports: {} owner: {} executable: {} data_len: {} hash: {}",
+        pubkey,
+        account.lamports,
+        account.owner,
+        account.executable,
+        account.data.len(),
+        AccountsDB::hash_account(pubkey, account),
+    );
+}
+
+fn create_snapshot(
+    genesis_block: &GenesisBlock,
+    blocktree: &Blocktree,
+    slot: Slot,
+    output_dir: &Path,
+) -> Result<PathBuf, String> {
+    let (bank_forks, _bank_forks_info, _leader_schedule_cache) =
+        process_blocktree(genesis_block, blocktree, None, true, Some(slot))
+            .map_err(|err| format!("Ledger replay failed: {:?}", err))?;
+    let bank = bank_forks
+        .get(slot)
+        .cloned()
+        .ok_or_else(|| format!("Slot {} was not replayed, is it a full slot?", slot))?;
+    bank.squash();
+
+    let snapshot_path = output_dir.join("snapshot");
+    fs::create_dir_all(&snapshot_path).map_err(|err| err.to_string())?;
+    let status_cache_slots: Vec<Slot> = bank.src.roots().into_iter().collect();
+    snapshot_utils::add_snapshot(&snapshot_path, &bank, &status_cache_slots)
+        .map_err(|err| format!("Unable to add snapshot: {:?}", err))?;
+
+    let tar_output_file = snapshot_utils::get_snapshot_tar_path(output_dir);
+    let snapshot_package = snapshot_utils::package_snapshot(
+        &bank,
+        &snapshot_utils::get_snapshot_paths(&snapshot_path),
+        &tar_output_file,
+        &snapshot_path,
+    )
+    .map_err(|err| format!("Unable to package snapshot: {:?}", err))?;
+    SnapshotPackagerService::package_snapshots(&snapshot_package)
+        .map_err(|err| format!("Unable to write snapshot archive: {:?}", err))?;
+    Ok(tar_output_file)
+}
+
+fn output_snapshot(snapshot_tar: &Path, pubkeys: &[Pubkey], owner: Option<Pubkey>) {
+    let work_dir = tempfile::tempdir().unwrap_or_else(|err| {
+        eprintln!("Unable to create a temporary directory: {}", err);
+        exit(1);
+    });
+    let snapshot_path = work_dir.path().join("snapshot");
+    let accounts_path = work_dir.path().join("accounts");
+    fs::create_dir_all(&snapshot_path).expect("create snapshot directory");
+    fs::create_dir_all(&accounts_path).expect("create accounts directory");
+    let snapshot_config = SnapshotConfig::new(snapshot_path, PathBuf::new(), 0, 0);
+
+    let bank = snapshot_utils::bank_from_archive(
+        accounts_path.to_str().unwrap().to_string(),
+        &snapshot_config,
+        snapshot_tar,
+        None,
+    )
+    .unwrap_or_else(|err| {
+        eprintln!("Failed to load snapshot {:?}: {:?}", snapshot_tar, err);
+        exit(1);
+    });
+
+    let accounts = bank.get_all_accounts();
+    let total_lamports: u64 = accounts.iter().map(|(_, account)| account.lamports).sum();
+    println!("Slot: {}", bank.slot());
+    println!("Bank hash: {}", bank.hash());
+    println!("Accounts hash: {}", bank.get_accounts_hash());
+    println!("Accounts: {}", accounts.len());
+    println!("Total lamports: {}", total_lamports);
+    println!("Capitalization: {}", bank.capitalization());
+
+    for pubkey in pubkeys {
+        match bank.get_account(pubkey) {
+            Some(account) => output_account(pubkey, &account),
+            None => println!("  {}: not found", pubkey),
+        }
+    }
+    if let Some(owner) = owner {
+        for (pubkey, account) in accounts
+            .iter()
+            .filter(|(_, account)| account.owner == owner)
+        {
+            output_account(pubkey, account);
+        }
+    }
+}
+
 fn main() {
     const DEFAULT_ROOT_COUNT: &str = "1";
     solana_logger::setup();
@@ -101,6 +269,104 @@ fn main() {
         ))
         .subcommand(SubCommand::with_name("json").about("Print the ledger in JSON format").arg(&starting_slot_arg))
         .subcommand(SubCommand::with_name("verify").about("Verify the ledger's PoH"))
+        .subcommand(
+            SubCommand::with_name("replay")
+            .about("Replay the ledger, printing the bank hash, transaction count and capitalization of every slot")
+            .arg(
+                Arg::with_name("accounts")
+                    .long("accounts")
+                    .takes_value(false)
+                    .help("Also print the accounts modified in every slot"),
+            )
+            .arg(
+                Arg::with_name("halt_at_slot")
+                    .long("halt-at-slot")
+                    .value_name("SLOT")
+                    .takes_value(true)
+                    .help("Stop replaying once this slot has been processed"),
+            )
+        )
+        .subcommand(
+            SubCommand::with_name("export")
+            .about("Export a range of slots to a ledger archive")
+            .arg(&starting_slot_arg)
+            .arg(
+                Arg::with_name("ending_slot")
+                    .long("ending-slot")
+                    .value_name("NUM")
+                    .takes_value(true)
+                    .help("Stop after this slot [default: last slot in the ledger]"),
+            )
+            .arg(
+                Arg::with_name("archive")
+                    .index(1)
+                    .value_name("FILENAME")
+                    .takes_value(true)
+                    .required(true)
+                    .help("The ledger archive to write"),
+            )
+        )
+        .subcommand(
+            SubCommand::with_name("import")
+            .about("Import a ledger archive into the ledger, which need not have a genesis block yet")
+            .arg(
+                Arg::with_name("archive")
+                    .index(1)
+                    .value_name("FILENAME")
+                    .takes_value(true)
+                    .required(true)
+                    .help("The ledger archive to read"),
+            )
+        )
+        .subcommand(
+            SubCommand::with_name("create-snapshot")
+            .about("Replay the ledger up to a slot and write a snapshot archive of that slot")
+            .arg(
+                Arg::with_name("snapshot_slot")
+                    .index(1)
+                    .value_name("SLOT")
+                    .takes_value(true)
+                    .required(true)
+                    .help("Slot to snapshot"),
+            )
+            .arg(
+                Arg::with_name("output_directory")
+                    .index(2)
+                    .value_name("DIR")
+                    .takes_value(true)
+                    .required(true)
+                    .help("Output directory for the snapshot archive"),
+            )
+        )
+        .subcommand(
+            SubCommand::with_name("snapshot-info")
+            .about("Print the contents of a snapshot archive")
+            .arg(
+                Arg::with_name("snapshot_archive")
+                    .index(1)
+                    .value_name("FILENAME")
+                    .takes_value(true)
+                    .required(true)
+                    .help("The snapshot archive to inspect"),
+            )
+            .arg(
+                Arg::with_name("pubkey")
+                    .long("pubkey")
+                    .value_name("PUBKEY")
+                    .takes_value(true)
+                    .multiple(true)
+                    .validator(is_pubkey)
+                    .help("Print the account at this address"),
+            )
+            .arg(
+                Arg::with_name("owner")
+                    .long("owner")
+                    .value_name("PUBKEY")
+                    .takes_value(true)
+                    .validator(is_pubkey)
+                    .help("Print every account owned by this program"),
+            )
+        )
         .subcommand(SubCommand::with_name("prune").about("Prune the ledger at the block height").arg(
             Arg::with_name("slot_list")
                 .long("slot-list")
@@ -135,8 +401,38 @@ fn main() {
         ))
         .get_matches();
 
+    // Snapshot archives are inspected on their own, without a ledger
+    if let ("snapshot-info", Some(args_matches)) = matches.subcommand() {
+        let snapshot_tar = value_t_or_exit!(args_matches, "snapshot_archive", String);
+        let pubkeys = values_t!(args_matches, "pubkey", Pubkey).unwrap_or_default();
+        let owner = value_t!(args_matches, "owner", Pubkey).ok();
+        output_snapshot(Path::new(&snapshot_tar), &pubkeys, owner);
+        return;
+    }
+
     let ledger_path = PathBuf::from(value_t_or_exit!(matches, "ledger", String));
 
+    // Imports may populate a fresh ledger directory, so no genesis block is required
+    if let ("import", Some(args_matches)) = matches.subcommand() {
+        let archive_path = value_t_or_exit!(args_matches, "archive", String);
+        let archive = File::open(&archive_path).unwrap_or_else(|err| {
+            eprintln!("Failed to open {}: {}", archive_path, err);
+            exit(1);
+        });
+        let blocktree = Blocktree::open(&ledger_path).unwrap_or_else(|err| {
+            eprintln!("Failed to open ledger at {:?}: {:?}", ledger_path, err);
+            exit(1);
+        });
+        match ledger_archive::import_ledger(&blocktree, BufReader::new(archive)) {
+            Ok(num_slots) => println!("Imported {} slots", num_slots),
+            Err(err) => {
+                eprintln!("Failed to import {}: {:?}", archive_path, err);
+                exit(1);
+            }
+        }
+        return;
+    }
+
     let genesis_block = GenesisBlock::load(&ledger_path).unwrap_or_else(|err| {
         eprintln!(
             "Failed to open ledger genesis_block at {:?}: {}",
@@ -166,6 +462,27 @@ fn main() {
             let starting_slot = value_t_or_exit!(args_matches, "starting_slot", Slot);
             output_ledger(blocktree, starting_slot, LedgerOutputMethod::Json);
         }
+        ("export", Some(args_matches)) => {
+            let starting_slot = value_t_or_exit!(args_matches, "starting_slot", Slot);
+            let ending_slot = value_t!(args_matches, "ending_slot", Slot).unwrap_or(std::u64::MAX);
+            let archive_path = value_t_or_exit!(args_matches, "archive", String);
+            let archive = File::create(&archive_path).unwrap_or_else(|err| {
+                eprintln!("Failed to create {}: {}", archive_path, err);
+                exit(1);
+            });
+            match ledger_archive::export_ledger(
+                &blocktree,
+                starting_slot,
+                ending_slot,
+                BufWriter::new(archive),
+            ) {
+                Ok(num_slots) => println!("Exported {} slots", num_slots),
+                Err(err) => {
+                    eprintln!("Failed to export ledger: {:?}", err);
+                    exit(1);
+                }
+            }
+        }
         ("verify", _) => {
             println!("Verifying ledger...");
             match process_blocktree(&genesis_block, &blocktree, None, true, None) {
@@ -178,6 +495,36 @@ fn main() {
                 }
             }
         }
+        ("replay", Some(args_matches)) => {
+            let print_accounts = args_matches.is_present("accounts");
+            let halt_at_slot = value_t!(args_matches, "halt_at_slot", Slot).ok();
+            let output_bank = |bank: &Bank| output_replayed_bank(bank, print_accounts);
+            if let Err(err) = process_blocktree_with_callback(
+                &genesis_block,
+                &blocktree,
+                None,
+                true,
+                halt_at_slot,
+                Some(&output_bank),
+            ) {
+                eprintln!("Ledger replay failed: {:?}", err);
+                exit(1);
+            }
+        }
+        ("create-snapshot", Some(args_matches)) => {
+            let slot = value_t_or_exit!(args_matches, "snapshot_slot", Slot);
+            let output_dir =
+                PathBuf::from(value_t_or_exit!(args_matches, "output_directory", String));
+            match create_snapshot(&genesis_block, &blocktree, slot, &output_dir) {
+                Ok(snapshot_tar) => {
+                    println!("Created snapshot of slot {} at {:?}", slot, snapshot_tar);
+                }
+                Err(err) => {
+                    eprintln!("{}", err);
+                    exit(1);
+                }
+            }
+        }
         ("prune", Some(args_matches)) => {
             if let Some(prune_file_path) = args_matches.value_of("slot_list") {
                 let prune_file = File::open(prune_file_path.to_string()).unwrap();
@@ -0,0 +1,522 @@
+//! Decodes ledger entries and their transactions into signatures, account keys and the
+//! instructions of the built-in programs they invoke.
+
+use bincode::{deserialize, serialize};
+use serde::ser::{
+    Serialize, SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant, SerializeTuple,
+    SerializeTupleStruct, SerializeTupleVariant, Serializer,
+};
+use serde_derive::Serialize;
+use solana::entry::Entry;
+use solana_budget_api::budget_instruction::BudgetInstruction;
+use solana_config_api::{get_config_data, ConfigKeys};
+use solana_exchange_api::exchange_instruction::ExchangeInstruction;
+use solana_sdk::bpf_loader;
+use solana_sdk::hash::Hash;
+use solana_sdk::loader_instruction::LoaderInstruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::system_instruction::SystemInstruction;
+use solana_sdk::system_program;
+use solana_sdk::transaction::Transaction;
+use solana_stake_api::stake_instruction::StakeInstruction;
+use solana_storage_api::storage_instruction::StorageInstruction;
+use solana_token_api::token_state::TokenInstruction;
+use solana_vote_api::vote_instruction::VoteInstruction;
+
+/// An instruction decoded with the instruction enum of the program it invokes
+#[derive(Debug, Serialize)]
+pub enum DecodedInstruction {
+    System(SystemInstruction),
+    Vote(VoteInstruction),
+    Stake(StakeInstruction),
+    Storage(StorageInstruction),
+    Config {
+        keys: ConfigKeys,
+        data: Vec<u8>,
+    },
+    Budget(BudgetInstruction),
+    Token(TokenInstruction),
+    Exchange(ExchangeInstruction),
+    BpfLoader(LoaderInstruction),
+    /// Not a built-in program, or data its program could not have accepted
+    Unknown {
+        data: Vec<u8>,
+    },
+}
+
+#[derive(Debug, Serialize)]
+pub struct InstructionInfo {
+    pub program_id: String,
+    pub accounts: Vec<String>,
+    #[serde(serialize_with = "serialize_base58")]
+    pub instruction: DecodedInstruction,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TransactionInfo {
+    pub signatures: Vec<String>,
+    pub account_keys: Vec<String>,
+    pub recent_blockhash: String,
+    pub instructions: Vec<InstructionInfo>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EntryInfo {
+    pub num_hashes: u64,
+    pub hash: String,
+    pub transactions: Vec<TransactionInfo>,
+}
+
+pub fn decode_instruction(program_id: &Pubkey, data: &[u8]) -> DecodedInstruction {
+    let decoded = if system_program::check_id(program_id) {
+        deserialize(data).ok().map(DecodedInstruction::System)
+    } else if solana_vote_api::check_id(program_id) {
+        deserialize(data).ok().map(DecodedInstruction::Vote)
+    } else if solana_stake_api::check_id(program_id) {
+        deserialize(data).ok().map(DecodedInstruction::Stake)
+    } else if solana_storage_api::check_id(program_id) {
+        deserialize(data).ok().map(DecodedInstruction::Storage)
+    } else if solana_config_api::check_id(program_id) {
+        deserialize::<ConfigKeys>(data)
+            .and_then(|keys| Ok((keys, get_config_data(data)?)))
+            .ok()
+            .map(|(keys, config_data)| DecodedInstruction::Config {
+                keys,
+                data: config_data.to_vec(),
+            })
+    } else if solana_budget_api::check_id(program_id) {
+        deserialize(data).ok().map(DecodedInstruction::Budget)
+    } else if solana_token_api::check_id(program_id) {
+        deserialize(data).ok().map(DecodedInstruction::Token)
+    } else if solana_exchange_api::check_id(program_id) {
+        deserialize(data).ok().map(DecodedInstruction::Exchange)
+    } else if bpf_loader::check_id(program_id) {
+        deserialize(data).ok().map(DecodedInstruction::BpfLoader)
+    } else {
+        None
+    };
+
+    decoded.unwrap_or_else(|| DecodedInstruction::Unknown {
+        data: data.to_vec(),
+    })
+}
+
+pub fn decode_transaction(transaction: &Transaction) -> TransactionInfo {
+    let message = &transaction.message;
+    let key_at = |index: u8| {
+        message
+            .account_keys
+            .get(index as usize)
+            .map(|key| key.to_string())
+            .unwrap_or_else(|| format!("<invalid account index {}>", index))
+    };
+
+    let instructions = message
+        .instructions
+        .iter()
+        .map(|instruction| {
+            let decoded = match message
+                .account_keys
+                .get(instruction.program_id_index as usize)
+            {
+                Some(program_id) => decode_instruction(program_id, &instruction.data),
+                None => DecodedInstruction::Unknown {
+                    data: instruction.data.clone(),
+                },
+            };
+            InstructionInfo {
+                program_id: key_at(instruction.program_id_index),
+                accounts: instruction
+                    .accounts
+                    .iter()
+                    .map(|index| key_at(*index))
+                    .collect(),
+                instruction: decoded,
+            }
+        })
+        .collect();
+
+    TransactionInfo {
+        signatures: transaction
+            .signatures
+            .iter()
+            .map(|signature| signature.to_string())
+            .collect(),
+        account_keys: message
+            .account_keys
+            .iter()
+            .map(|key| key.to_string())
+            .collect(),
+        recent_blockhash: message.recent_blockhash.to_string(),
+        instructions,
+    }
+}
+
+pub fn decode_entry(entry: &Entry) -> EntryInfo {
+    EntryInfo {
+        num_hashes: entry.num_hashes,
+        hash: entry.hash.to_string(),
+        transactions: entry.transactions.iter().map(decode_transaction).collect(),
+    }
+}
+
+// Serializes `value` with every Pubkey and Hash in it as a base58 string, like the rest of
+// the decoded output, instead of as an array of bytes
+fn serialize_base58<T: Serialize, S: Serializer>(
+    value: &T,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    value.serialize(Base58Serializer(serializer))
+}
+
+struct Base58<'a, T: ?Sized>(&'a T);
+
+impl<'a, T: Serialize + ?Sized> Serialize for Base58<'a, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(Base58Serializer(serializer))
+    }
+}
+
+/// Passes everything through to the inner serializer, except for the Pubkey and Hash
+/// newtypes, which are written as base58 strings
+struct Base58Serializer<S>(S);
+
+/// Passes the elements of a sequence, map or struct back through a Base58Serializer
+struct Base58Compound<C>(C);
+
+impl<S: Serializer> Serializer for Base58Serializer<S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+    type SerializeSeq = Base58Compound<S::SerializeSeq>;
+    type SerializeTuple = Base58Compound<S::SerializeTuple>;
+    type SerializeTupleStruct = Base58Compound<S::SerializeTupleStruct>;
+    type SerializeTupleVariant = Base58Compound<S::SerializeTupleVariant>;
+    type SerializeMap = Base58Compound<S::SerializeMap>;
+    type SerializeStruct = Base58Compound<S::SerializeStruct>;
+    type SerializeStructVariant = Base58Compound<S::SerializeStructVariant>;
+
+    fn serialize_bool(self, v: bool) -> Result<S::Ok, S::Error> {
+        self.0.serialize_bool(v)
+    }
+    fn serialize_i8(self, v: i8) -> Result<S::Ok, S::Error> {
+        self.0.serialize_i8(v)
+    }
+    fn serialize_i16(self, v: i16) -> Result<S::Ok, S::Error> {
+        self.0.serialize_i16(v)
+    }
+    fn serialize_i32(self, v: i32) -> Result<S::Ok, S::Error> {
+        self.0.serialize_i32(v)
+    }
+    fn serialize_i64(self, v: i64) -> Result<S::Ok, S::Error> {
+        self.0.serialize_i64(v)
+    }
+    fn serialize_i128(self, v: i128) -> Result<S::Ok, S::Error> {
+        self.0.serialize_i128(v)
+    }
+    fn serialize_u8(self, v: u8) -> Result<S::Ok, S::Error> {
+        self.0.serialize_u8(v)
+    }
+    fn serialize_u16(self, v: u16) -> Result<S::Ok, S::Error> {
+        self.0.serialize_u16(v)
+    }
+    fn serialize_u32(self, v: u32) -> Result<S::Ok, S::Error> {
+        self.0.serialize_u32(v)
+    }
+    fn serialize_u64(self, v: u64) -> Result<S::Ok, S::Error> {
+        self.0.serialize_u64(v)
+    }
+    fn serialize_u128(self, v: u128) -> Result<S::Ok, S::Error> {
+        self.0.serialize_u128(v)
+    }
+    fn serialize_f32(self, v: f32) -> Result<S::Ok, S::Error> {
+        self.0.serialize_f32(v)
+    }
+    fn serialize_f64(self, v: f64) -> Result<S::Ok, S::Error> {
+        self.0.serialize_f64(v)
+    }
+    fn serialize_char(self, v: char) -> Result<S::Ok, S::Error> {
+        self.0.serialize_char(v)
+    }
+    fn serialize_str(self, v: &str) -> Result<S::Ok, S::Error> {
+        self.0.serialize_str(v)
+    }
+    fn serialize_bytes(self, v: &[u8]) -> Result<S::Ok, S::Error> {
+        self.0.serialize_bytes(v)
+    }
+    fn serialize_none(self) -> Result<S::Ok, S::Error> {
+        self.0.serialize_none()
+    }
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<S::Ok, S::Error> {
+        self.0.serialize_some(&Base58(value))
+    }
+    fn serialize_unit(self) -> Result<S::Ok, S::Error> {
+        self.0.serialize_unit()
+    }
+    fn serialize_unit_struct(self, name: &'static str) -> Result<S::Ok, S::Error> {
+        self.0.serialize_unit_struct(name)
+    }
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> Result<S::Ok, S::Error> {
+        self.0.serialize_unit_variant(name, variant_index, variant)
+    }
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<S::Ok, S::Error> {
+        // Both wrap a [u8; 32], which bincode writes out as just its bytes
+        let base58 = match (name, serialize(value)) {
+            ("Pubkey", Ok(bytes)) => Some(Pubkey::new(&bytes).to_string()),
+            ("Hash", Ok(bytes)) => Some(Hash::new(&bytes).to_string()),
+            _ => None,
+        };
+        match base58 {
+            Some(base58) => self.0.serialize_str(&base58),
+            None => self.0.serialize_newtype_struct(name, &Base58(value)),
+        }
+    }
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<S::Ok, S::Error> {
+        self.0
+            .serialize_newtype_variant(name, variant_index, variant, &Base58(value))
+    }
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, S::Error> {
+        self.0.serialize_seq(len).map(Base58Compound)
+    }
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, S::Error> {
+        self.0.serialize_tuple(len).map(Base58Compound)
+    }
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, S::Error> {
+        self.0.serialize_tuple_struct(name, len).map(Base58Compound)
+    }
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, S::Error> {
+        self.0
+            .serialize_tuple_variant(name, variant_index, variant, len)
+            .map(Base58Compound)
+    }
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, S::Error> {
+        self.0.serialize_map(len).map(Base58Compound)
+    }
+    fn serialize_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, S::Error> {
+        self.0.serialize_struct(name, len).map(Base58Compound)
+    }
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant, S::Error> {
+        self.0
+            .serialize_struct_variant(name, variant_index, variant, len)
+            .map(Base58Compound)
+    }
+    fn is_human_readable(&self) -> bool {
+        self.0.is_human_readable()
+    }
+}
+
+impl<C: SerializeSeq> SerializeSeq for Base58Compound<C> {
+    type Ok = C::Ok;
+    type Error = C::Error;
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), C::Error> {
+        self.0.serialize_element(&Base58(value))
+    }
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.0.end()
+    }
+}
+
+impl<C: SerializeTuple> SerializeTuple for Base58Compound<C> {
+    type Ok = C::Ok;
+    type Error = C::Error;
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), C::Error> {
+        self.0.serialize_element(&Base58(value))
+    }
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.0.end()
+    }
+}
+
+impl<C: SerializeTupleStruct> SerializeTupleStruct for Base58Compound<C> {
+    type Ok = C::Ok;
+    type Error = C::Error;
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), C::Error> {
+        self.0.serialize_field(&Base58(value))
+    }
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.0.end()
+    }
+}
+
+impl<C: SerializeTupleVariant> SerializeTupleVariant for Base58Compound<C> {
+    type Ok = C::Ok;
+    type Error = C::Error;
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), C::Error> {
+        self.0.serialize_field(&Base58(value))
+    }
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.0.end()
+    }
+}
+
+impl<C: SerializeMap> SerializeMap for Base58Compound<C> {
+    type Ok = C::Ok;
+    type Error = C::Error;
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), C::Error> {
+        self.0.serialize_key(&Base58(key))
+    }
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), C::Error> {
+        self.0.serialize_value(&Base58(value))
+    }
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.0.end()
+    }
+}
+
+impl<C: SerializeStruct> SerializeStruct for Base58Compound<C> {
+    type Ok = C::Ok;
+    type Error = C::Error;
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), C::Error> {
+        self.0.serialize_field(key, &Base58(value))
+    }
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.0.end()
+    }
+}
+
+impl<C: SerializeStructVariant> SerializeStructVariant for Base58Compound<C> {
+    type Ok = C::Ok;
+    type Error = C::Error;
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), C::Error> {
+        self.0.serialize_field(key, &Base58(value))
+    }
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.0.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::hash::hash;
+    use solana_sdk::signature::{Keypair, KeypairUtil};
+    use solana_sdk::system_instruction;
+    use solana_sdk::system_transaction;
+    use solana_vote_api::vote_instruction;
+    use solana_vote_api::vote_state::Vote;
+
+    #[test]
+    fn test_decode_system_transfer() {
+        let from = Keypair::new();
+        let to = Pubkey::new_rand();
+        let tx = system_transaction::transfer(&from, &to, 42, Hash::default());
+
+        let info = decode_transaction(&tx);
+        assert_eq!(info.signatures, vec![tx.signatures[0].to_string()]);
+        assert_eq!(info.instructions.len(), 1);
+        assert_eq!(
+            info.instructions[0].program_id,
+            system_program::id().to_string()
+        );
+        assert_eq!(
+            info.instructions[0].accounts,
+            vec![from.pubkey().to_string(), to.to_string()]
+        );
+        match &info.instructions[0].instruction {
+            DecodedInstruction::System(SystemInstruction::Transfer { lamports }) => {
+                assert_eq!(*lamports, 42)
+            }
+            instruction => panic!("unexpected instruction: {:?}", instruction),
+        }
+    }
+
+    #[test]
+    fn test_decode_base58() {
+        let from = Keypair::new();
+        let program_id = Pubkey::new_rand();
+        let tx = Transaction::new_signed_instructions(
+            &[&from],
+            vec![system_instruction::assign(&from.pubkey(), &program_id)],
+            Hash::default(),
+        );
+
+        let json = serde_json::to_string(&decode_transaction(&tx).instructions[0]).unwrap();
+        assert!(json.contains(&format!("{{\"program_id\":\"{}\"}}", program_id)));
+
+        let hash = hash(&[1, 2, 3]);
+        let instruction = vote_instruction::vote(
+            &Pubkey::new_rand(),
+            &Pubkey::new_rand(),
+            vec![Vote::new(1, hash)],
+        );
+        let json = serde_json::to_string(&InstructionInfo {
+            program_id: instruction.program_id.to_string(),
+            accounts: vec![],
+            instruction: decode_instruction(&instruction.program_id, &instruction.data),
+        })
+        .unwrap();
+        assert!(json.contains(&format!("\"hash\":\"{}\"", hash)));
+    }
+
+    #[test]
+    fn test_decode_vote() {
+        let vote_pubkey = Pubkey::new_rand();
+        let instruction = vote_instruction::vote(
+            &vote_pubkey,
+            &Pubkey::new_rand(),
+            vec![Vote::new(1, Hash::default())],
+        );
+        match decode_instruction(&instruction.program_id, &instruction.data) {
+            DecodedInstruction::Vote(VoteInstruction::Vote(votes)) => assert_eq!(votes.len(), 1),
+            instruction => panic!("unexpected instruction: {:?}", instruction),
+        }
+    }
+
+    #[test]
+    fn test_decode_unknown() {
+        let data = vec![1, 2, 3];
+        match decode_instruction(&Pubkey::new_rand(), &data) {
+            DecodedInstruction::Unknown { data: unknown } => assert_eq!(unknown, data),
+            instruction => panic!("unexpected instruction: {:?}", instruction),
+        }
+
+        // Data the system program couldn't accept isn't decoded either
+        assert!(
+            serde_json::to_string(&decode_instruction(&system_program::id(), &data))
+                .unwrap()
+                .starts_with("{\"Unknown\"")
+        );
+    }
+}
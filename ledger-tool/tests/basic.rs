@@ -48,4 +48,35 @@ fn nominal() {
     let output = run_ledger_tool(&["-l", &ledger_path, "print"]);
     assert!(output.status.success());
     assert_eq!(count_newlines(&output.stdout), ticks + 1);
+
+    // Replay prints one line for the only slot
+    let output = run_ledger_tool(&["-l", &ledger_path, "replay"]);
+    assert!(output.status.success());
+    assert_eq!(count_newlines(&output.stdout), 1);
+
+    // ...followed by the genesis accounts when asked to
+    let output = run_ledger_tool(&["-l", &ledger_path, "replay", "--accounts"]);
+    assert!(output.status.success());
+    assert!(count_newlines(&output.stdout) > 1);
+
+    // Snapshot the only slot and look inside the archive
+    let output_dir = tempfile::tempdir().unwrap();
+    let output_dir = output_dir.path().to_str().unwrap();
+    let output = run_ledger_tool(&["-l", &ledger_path, "create-snapshot", "0", output_dir]);
+    assert!(output.status.success());
+
+    let snapshot_tar = format!("{}/snapshot.tar.bz2", output_dir);
+    let output = run_ledger_tool(&["snapshot-info", &snapshot_tar]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("Slot: 0\n"));
+
+    // Round trip the ledger through an archive
+    let archive = format!("{}/ledger.archive", output_dir);
+    let output = run_ledger_tool(&["-l", &ledger_path, "export", &archive]);
+    assert!(output.status.success());
+    let import_dir = tempfile::tempdir().unwrap();
+    let import_dir = import_dir.path().to_str().unwrap();
+    let output = run_ledger_tool(&["-l", import_dir, "import", &archive]);
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"Imported 1 slots\n");
 }
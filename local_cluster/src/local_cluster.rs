@@ -191,7 +191,7 @@ impl LocalCluster {
             &leader_keypair,
             &leader_ledger_path,
             &leader_voting_keypair.pubkey(),
-            &leader_voting_keypair,
+            &[leader_voting_keypair.clone()],
             &leader_storage_keypair,
             None,
             true,
@@ -323,7 +323,7 @@ impl LocalCluster {
             &validator_keypair,
             &ledger_path,
             &voting_keypair.pubkey(),
-            &voting_keypair,
+            &[voting_keypair.clone()],
             &storage_keypair,
             Some(&self.entry_point_info),
             true,
@@ -603,7 +603,7 @@ impl Cluster for LocalCluster {
             &fullnode_info.keypair,
             &fullnode_info.ledger_path,
             &fullnode_info.voting_keypair.pubkey(),
-            &fullnode_info.voting_keypair,
+            &[fullnode_info.voting_keypair.clone()],
             &fullnode_info.storage_keypair,
             None,
             true,
@@ -4,7 +4,8 @@ use log::*;
 use serial_test_derive::serial;
 use solana::{
     blocktree::Blocktree, broadcast_stage::BroadcastStageType, cluster::Cluster,
-    gossip_service::discover_cluster, validator::ValidatorConfig,
+    gossip_service::discover_cluster, ledger_cleanup_service::LedgerCleanupConfig,
+    validator::ValidatorConfig,
 };
 use solana_local_cluster::{
     cluster_tests,
@@ -21,7 +22,10 @@ fn test_ledger_cleanup_service() {
     error!("test_ledger_cleanup_service");
     let num_nodes = 3;
     let mut validator_config = ValidatorConfig::default();
-    validator_config.max_ledger_slots = Some(100);
+    validator_config.ledger_cleanup_config = Some(LedgerCleanupConfig {
+        max_ledger_slots: Some(100),
+        ..LedgerCleanupConfig::default()
+    });
     let config = ClusterConfig {
         cluster_lamports: 10_000,
         poh_config: PohConfig::new_sleep(Duration::from_millis(50)),
@@ -1,5 +1,6 @@
 pub mod config;
 pub mod rewards_pools;
+pub mod slashing_sink;
 pub mod stake_instruction;
 pub mod stake_state;
 
@@ -19,5 +20,5 @@ pub fn genesis(mut builder: Builder) -> Builder {
     for (pubkey, account) in crate::rewards_pools::genesis().iter() {
         builder = builder.rewards_pool(*pubkey, account.clone());
     }
-    builder.accounts(&[crate::config::genesis()])
+    builder.accounts(&[crate::config::genesis(), crate::slashing_sink::genesis()])
 }
@@ -0,0 +1,19 @@
+//! slashing sink
+//! * initialize genesis with the account that slashed stake is sent to
+//! * nobody can sign for it and the stake program never debits it, so what lands
+//!   here is out of circulation for good
+use solana_sdk::{account::Account, pubkey::Pubkey};
+
+// slashing sink ID
+const ID: [u8; 32] = [
+    6, 161, 216, 23, 187, 36, 23, 166, 200, 156, 108, 153, 230, 97, 103, 186, 92, 67, 176, 12, 147,
+    166, 196, 62, 166, 68, 120, 141, 64, 0, 0, 0,
+];
+
+solana_sdk::solana_name_id!(ID, "StakeS1ashing111111111111111111111111111111");
+
+pub fn genesis() -> (Pubkey, Account) {
+    // owned by the stake program so the system program can't hand it to anyone else,
+    //  and without any StakeState so the stake program has nothing to debit it for
+    (id(), Account::new(1, 0, &crate::id()))
+}
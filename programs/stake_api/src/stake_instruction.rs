@@ -10,6 +10,7 @@ use solana_sdk::{
     instruction::{AccountMeta, Instruction, InstructionError},
     pubkey::Pubkey,
     system_instruction, sysvar,
+    transaction::Transaction,
 };
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
@@ -56,6 +57,28 @@ pub enum StakeInstruction {
     ///    1 - VoteAccount to which the Stake is delegated
     ///    2 - Syscall Account that carries epoch
     Deactivate,
+
+    /// Split lamports, along with a proportional share of any delegated stake,
+    ///    off into a new stake account
+    ///
+    /// Expects 2 Accounts:
+    ///    0 - StakeAccount to be split <= must have this signature
+    ///    1 - Uninitialized StakeAccount that will receive the split
+    ///
+    /// The u64 is the portion of the Stake account balance to be split off,
+    ///    must be <= StakeAccount.lamports
+    Split(u64),
+
+    /// Slash a stake whose vote account voted for two different hashes at the
+    ///    same slot, the transactions carry the conflicting votes and must both be
+    ///    signed by the vote account's authorized voter
+    ///
+    /// Expects 4 Accounts:
+    ///    0 - Delegate StakeAccount to be slashed
+    ///    1 - VoteAccount to which the Stake is delegated
+    ///    2 - SlashingSink Account that receives the slashed lamports
+    ///    3 - Clock sysvar Account that carries clock bank epoch
+    SlashDoubleVote(Transaction, Transaction),
 }
 
 pub fn create_stake_account(
@@ -123,6 +146,33 @@ pub fn deactivate_stake(stake_pubkey: &Pubkey, vote_pubkey: &Pubkey) -> Instruct
     Instruction::new(id(), &StakeInstruction::Deactivate, account_metas)
 }
 
+pub fn split(stake_pubkey: &Pubkey, lamports: u64, split_stake_pubkey: &Pubkey) -> Instruction {
+    let account_metas = vec![
+        AccountMeta::new(*stake_pubkey, true),
+        AccountMeta::new(*split_stake_pubkey, false),
+    ];
+    Instruction::new(id(), &StakeInstruction::Split(lamports), account_metas)
+}
+
+pub fn slash_double_vote(
+    stake_pubkey: &Pubkey,
+    vote_pubkey: &Pubkey,
+    first_vote: Transaction,
+    second_vote: Transaction,
+) -> Instruction {
+    let account_metas = vec![
+        AccountMeta::new(*stake_pubkey, false),
+        AccountMeta::new_credit_only(*vote_pubkey, false),
+        AccountMeta::new(crate::slashing_sink::id(), false),
+        AccountMeta::new_credit_only(sysvar::clock::id(), false),
+    ];
+    Instruction::new(
+        id(),
+        &StakeInstruction::SlashDoubleVote(first_vote, second_vote),
+        account_metas,
+    )
+}
+
 pub fn process_instruction(
     _program_id: &Pubkey,
     keyed_accounts: &mut [KeyedAccount],
@@ -195,6 +245,31 @@ pub fn process_instruction(
 
             me.deactivate_stake(vote, &sysvar::clock::from_keyed_account(&clock)?)
         }
+        StakeInstruction::Split(lamports) => {
+            if rest.len() != 1 {
+                Err(InstructionError::InvalidInstructionData)?;
+            }
+            let split_stake = &mut rest[0];
+
+            me.split(lamports, split_stake)
+        }
+        StakeInstruction::SlashDoubleVote(first_vote, second_vote) => {
+            if rest.len() != 3 {
+                Err(InstructionError::InvalidInstructionData)?;
+            }
+            let (vote, rest) = rest.split_at_mut(1);
+            let vote = &vote[0];
+            let (slashing_sink, rest) = rest.split_at_mut(1);
+            let slashing_sink = &mut slashing_sink[0];
+
+            me.slash_double_vote(
+                vote,
+                slashing_sink,
+                &first_vote,
+                &second_vote,
+                &sysvar::clock::from_keyed_account(&rest[0])?,
+            )
+        }
     }
 }
 
@@ -252,6 +327,19 @@ mod tests {
             process_instruction(&deactivate_stake(&Pubkey::default(), &Pubkey::default())),
             Err(InstructionError::InvalidAccountData),
         );
+        assert_eq!(
+            process_instruction(&split(&Pubkey::default(), 100, &Pubkey::new_rand())),
+            Err(InstructionError::InvalidAccountData),
+        );
+        assert_eq!(
+            process_instruction(&slash_double_vote(
+                &Pubkey::default(),
+                &Pubkey::default(),
+                Transaction::new_unsigned_instructions(vec![]),
+                Transaction::new_unsigned_instructions(vec![]),
+            )),
+            Err(InstructionError::InvalidAccountData),
+        );
     }
 
     #[test]
@@ -12,11 +12,13 @@ use solana_sdk::{
     pubkey::Pubkey,
     sysvar::{
         self,
+        slot_hashes::MAX_SLOT_HASHES,
         stake_history::{StakeHistory, StakeHistoryEntry},
     },
-    timing::Epoch,
+    timing::{Epoch, Slot},
+    transaction::Transaction,
 };
-use solana_vote_api::vote_state::VoteState;
+use solana_vote_api::{vote_instruction::parse_vote_transaction, vote_state::VoteState};
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 pub enum StakeState {
@@ -57,6 +59,7 @@ pub struct Stake {
     pub activation_epoch: Epoch, // epoch the stake was activated, std::Epoch::MAX if is a bootstrap stake
     pub deactivation_epoch: Epoch, // epoch the stake was deactivated, std::Epoch::MAX if not deactivated
     pub config: Config,
+    pub slashed_slot: Option<Slot>, // latest slot of a double vote the stake was slashed for
 }
 
 impl Default for Stake {
@@ -68,6 +71,7 @@ impl Default for Stake {
             activation_epoch: 0,
             deactivation_epoch: std::u64::MAX,
             config: Config::default(),
+            slashed_slot: None,
         }
     }
 }
@@ -81,7 +85,8 @@ impl Stake {
         self.stake_activating_and_deactivating(epoch, history).0
     }
 
-    fn stake_activating_and_deactivating(
+    /// Returns the (effective, activating, deactivating) portions of this stake at `epoch`
+    pub fn stake_activating_and_deactivating(
         &self,
         epoch: Epoch,
         history: Option<&StakeHistory>,
@@ -308,6 +313,54 @@ pub trait StakeAccount {
         clock: &sysvar::clock::Clock,
         stake_history: &sysvar::stake_history::StakeHistory,
     ) -> Result<(), InstructionError>;
+    fn split(
+        &mut self,
+        lamports: u64,
+        split_stake: &mut KeyedAccount,
+    ) -> Result<(), InstructionError>;
+    fn slash_double_vote(
+        &mut self,
+        vote_account: &KeyedAccount,
+        slashing_sink: &mut KeyedAccount,
+        first_vote: &Transaction,
+        second_vote: &Transaction,
+        clock: &sysvar::clock::Clock,
+    ) -> Result<(), InstructionError>;
+}
+
+/// Returns the slot for which both vote transactions, signed by the vote account's
+///  authorized voter, voted for different hashes
+fn find_double_vote(
+    vote_pubkey: &Pubkey,
+    vote_state: &VoteState,
+    first_vote: &Transaction,
+    second_vote: &Transaction,
+    clock: &sysvar::clock::Clock,
+) -> Option<Slot> {
+    let authorized_voter_pubkey = vote_state.authorized_voter(clock.epoch)?;
+    let (first_vote_pubkey, first_voter_pubkey, first_votes) = parse_vote_transaction(first_vote)?;
+    let (second_vote_pubkey, second_voter_pubkey, second_votes) =
+        parse_vote_transaction(second_vote)?;
+
+    if first_vote_pubkey != *vote_pubkey
+        || second_vote_pubkey != *vote_pubkey
+        || first_voter_pubkey != *authorized_voter_pubkey
+        || second_voter_pubkey != *authorized_voter_pubkey
+    {
+        return None;
+    }
+
+    first_votes
+        .iter()
+        // only recent votes are slashable, older ones may have been cast by a
+        //  previously authorized voter
+        .filter(|first| clock.slot.saturating_sub(first.slot) <= MAX_SLOT_HASHES as u64)
+        .find(|first| {
+            second_votes
+                .iter()
+                .any(|second| second.slot == first.slot && second.hash != first.hash)
+        })
+        .map(|vote| vote.slot)
 }
 
 impl<'a> StakeAccount for KeyedAccount<'a> {
@@ -440,6 +493,98 @@ impl<'a> StakeAccount for KeyedAccount<'a> {
             _ => Err(InstructionError::InvalidAccountData),
         }
     }
+    fn split(
+        &mut self,
+        lamports: u64,
+        split_stake: &mut KeyedAccount,
+    ) -> Result<(), InstructionError> {
+        if self.signer_key().is_none() {
+            return Err(InstructionError::MissingRequiredSignature);
+        }
+        match split_stake.state()? {
+            StakeState::Uninitialized => (),
+            _ => return Err(InstructionError::InvalidAccountData),
+        }
+        if lamports > self.account.lamports {
+            return Err(InstructionError::InsufficientFunds);
+        }
+
+        match self.state()? {
+            StakeState::Stake(mut stake) => {
+                // the split takes its proportional share of the delegated stake
+                let split_stake_amount = (u128::from(stake.stake) * u128::from(lamports)
+                    / u128::from(self.account.lamports))
+                    as u64;
+
+                stake.stake -= split_stake_amount;
+                self.set_state(&StakeState::Stake(stake.clone()))?;
+                split_stake.set_state(&StakeState::Stake(Stake {
+                    stake: split_stake_amount,
+                    ..stake
+                }))?;
+            }
+            StakeState::Uninitialized => (),
+            _ => return Err(InstructionError::InvalidAccountData),
+        }
+
+        self.account.lamports -= lamports;
+        split_stake.account.lamports += lamports;
+        Ok(())
+    }
+    fn slash_double_vote(
+        &mut self,
+        vote_account: &KeyedAccount,
+        slashing_sink: &mut KeyedAccount,
+        first_vote: &Transaction,
+        second_vote: &Transaction,
+        clock: &sysvar::clock::Clock,
+    ) -> Result<(), InstructionError> {
+        if !crate::slashing_sink::check_id(slashing_sink.unsigned_key()) {
+            return Err(InstructionError::InvalidArgument);
+        }
+        if let StakeState::Stake(mut stake) = self.state()? {
+            if stake.voter_pubkey != *vote_account.unsigned_key() {
+                return Err(InstructionError::InvalidArgument);
+            }
+            let vote_state: VoteState = vote_account.state()?;
+
+            let slot = find_double_vote(
+                vote_account.unsigned_key(),
+                &vote_state,
+                first_vote,
+                second_vote,
+                clock,
+            )
+            .ok_or(InstructionError::InvalidArgument)?;
+
+            // each double vote is only punished once
+            if stake
+                .slashed_slot
+                .map_or(false, |slashed_slot| slot <= slashed_slot)
+            {
+                return Err(InstructionError::InvalidArgument);
+            }
+
+            let penalty = ((u128::from(stake.stake) * u128::from(stake.config.slash_penalty)
+                / u128::from(std::u8::MAX)) as u64)
+                .min(self.account.lamports);
+
+            // lamports can't be burned, so the penalty is locked away in the sink
+            slashing_sink.account.lamports = slashing_sink
+                .account
+                .lamports
+                .checked_add(penalty)
+                .ok_or(InstructionError::InvalidArgument)?;
+            self.account.lamports -= penalty;
+
+            stake.stake -= penalty;
+            stake.slashed_slot = Some(slot);
+
+            self.set_state(&StakeState::Stake(stake))
+        } else {
+            Err(InstructionError::InvalidAccountData)
+        }
+    }
 }
 
 // utility function, used by runtime::Stakes, tests
@@ -489,10 +634,14 @@ mod tests {
     use super::*;
     use crate::id;
     use solana_sdk::account::Account;
+    use solana_sdk::hash::Hash;
     use solana_sdk::pubkey::Pubkey;
     use solana_sdk::signature::{Keypair, KeypairUtil};
     use solana_sdk::system_program;
-    use solana_vote_api::vote_state;
+    use solana_vote_api::{
+        vote_instruction,
+        vote_state::{self, Vote},
+    };
 
     #[test]
     fn test_stake_state_stake_from_fail() {
@@ -583,7 +732,8 @@ mod tests {
                 stake: stake_lamports,
                 activation_epoch: clock.epoch,
                 deactivation_epoch: std::u64::MAX,
-                config: Config::default()
+                config: Config::default(),
+                slashed_slot: None,
             })
         );
         // verify that delegate_stake can't be called twice StakeState::default()
@@ -1332,4 +1482,202 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_split_stake() {
+        let clock = sysvar::clock::Clock::default();
+
+        let stake_pubkey = Pubkey::new_rand();
+        let stake_lamports = 100;
+        let mut stake_account =
+            Account::new(stake_lamports, std::mem::size_of::<StakeState>(), &id());
+
+        let split_stake_pubkey = Pubkey::new_rand();
+        let mut split_stake_account = Account::new(0, std::mem::size_of::<StakeState>(), &id());
+        let mut split_stake_keyed_account =
+            KeyedAccount::new(&split_stake_pubkey, false, &mut split_stake_account);
+
+        // unsigned keyed account
+        let mut stake_keyed_account = KeyedAccount::new(&stake_pubkey, false, &mut stake_account);
+        assert_eq!(
+            stake_keyed_account.split(stake_lamports / 2, &mut split_stake_keyed_account),
+            Err(InstructionError::MissingRequiredSignature)
+        );
+
+        let mut stake_keyed_account = KeyedAccount::new(&stake_pubkey, true, &mut stake_account);
+
+        // can't split more than the account holds
+        assert_eq!(
+            stake_keyed_account.split(stake_lamports + 1, &mut split_stake_keyed_account),
+            Err(InstructionError::InsufficientFunds)
+        );
+
+        let vote_pubkey = Pubkey::new_rand();
+        let mut vote_account =
+            vote_state::create_account(&vote_pubkey, &Pubkey::new_rand(), 0, 100);
+        let vote_keyed_account = KeyedAccount::new(&vote_pubkey, false, &mut vote_account);
+        stake_keyed_account
+            .delegate_stake(
+                &vote_keyed_account,
+                stake_lamports / 2,
+                &clock,
+                &Config::default(),
+            )
+            .unwrap();
+
+        // split off a quarter of the lamports, along with a quarter of the stake
+        assert_eq!(
+            stake_keyed_account.split(stake_lamports / 4, &mut split_stake_keyed_account),
+            Ok(())
+        );
+        assert_eq!(stake_keyed_account.account.lamports, stake_lamports * 3 / 4);
+        assert_eq!(
+            split_stake_keyed_account.account.lamports,
+            stake_lamports / 4
+        );
+
+        let stake = StakeState::stake_from(&stake_keyed_account.account).unwrap();
+        let split_stake = StakeState::stake_from(&split_stake_keyed_account.account).unwrap();
+        assert_eq!(stake.stake + split_stake.stake, stake_lamports / 2);
+        assert_eq!(split_stake.stake, stake_lamports / 8);
+        assert_eq!(split_stake.voter_pubkey, vote_pubkey);
+        assert_eq!(split_stake.activation_epoch, stake.activation_epoch);
+
+        // split target must be uninitialized
+        assert_eq!(
+            stake_keyed_account.split(stake_lamports / 4, &mut split_stake_keyed_account),
+            Err(InstructionError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    fn test_slash_double_vote() {
+        let clock = sysvar::clock::Clock {
+            slot: 10,
+            ..sysvar::clock::Clock::default()
+        };
+
+        let vote_keypair = Keypair::new();
+        let vote_pubkey = vote_keypair.pubkey();
+        let mut vote_account =
+            vote_state::create_account(&vote_pubkey, &Pubkey::new_rand(), 0, 100);
+        let vote_keyed_account = KeyedAccount::new(&vote_pubkey, false, &mut vote_account);
+
+        let vote_transaction = |keypair: &Keypair, slot: Slot, hash: Hash| {
+            Transaction::new_signed_instructions(
+                &[keypair],
+                vec![vote_instruction::vote(
+                    &vote_pubkey,
+                    &keypair.pubkey(),
+                    vec![Vote::new(slot, hash)],
+                )],
+                Hash::default(),
+            )
+        };
+        let first_vote = vote_transaction(&vote_keypair, 5, Hash::new(&[1; 32]));
+        let second_vote = vote_transaction(&vote_keypair, 5, Hash::new(&[2; 32]));
+
+        let stake_pubkey = Pubkey::new_rand();
+        let stake_lamports = 1_000_000;
+        let mut stake_account =
+            Account::new(stake_lamports, std::mem::size_of::<StakeState>(), &id());
+        let mut stake_keyed_account = KeyedAccount::new(&stake_pubkey, true, &mut stake_account);
+
+        let (slashing_sink_pubkey, mut slashing_sink_account) = crate::slashing_sink::genesis();
+        let mut slashing_sink_keyed_account =
+            KeyedAccount::new(&slashing_sink_pubkey, false, &mut slashing_sink_account);
+
+        // only delegated stakes can be slashed
+        assert_eq!(
+            stake_keyed_account.slash_double_vote(
+                &vote_keyed_account,
+                &mut slashing_sink_keyed_account,
+                &first_vote,
+                &second_vote,
+                &clock,
+            ),
+            Err(InstructionError::InvalidAccountData)
+        );
+
+        stake_keyed_account
+            .delegate_stake(
+                &vote_keyed_account,
+                stake_lamports,
+                &clock,
+                &Config::default(),
+            )
+            .unwrap();
+
+        // votes for the same hash aren't a double vote
+        assert_eq!(
+            stake_keyed_account.slash_double_vote(
+                &vote_keyed_account,
+                &mut slashing_sink_keyed_account,
+                &first_vote,
+                &first_vote,
+                &clock,
+            ),
+            Err(InstructionError::InvalidArgument)
+        );
+
+        // votes must be signed by the authorized voter
+        let other_voter_keypair = Keypair::new();
+        let other_vote = vote_transaction(&other_voter_keypair, 5, Hash::new(&[2; 32]));
+        assert_eq!(
+            stake_keyed_account.slash_double_vote(
+                &vote_keyed_account,
+                &mut slashing_sink_keyed_account,
+                &first_vote,
+                &other_vote,
+                &clock,
+            ),
+            Err(InstructionError::InvalidArgument)
+        );
+
+        // the penalty can only go to the slashing sink, not to a (full) rewards pool
+        let (rewards_pool_pubkey, mut rewards_pool_account) =
+            crate::rewards_pools::genesis().remove(0);
+        assert_eq!(
+            stake_keyed_account.slash_double_vote(
+                &vote_keyed_account,
+                &mut KeyedAccount::new(&rewards_pool_pubkey, false, &mut rewards_pool_account),
+                &first_vote,
+                &second_vote,
+                &clock,
+            ),
+            Err(InstructionError::InvalidArgument)
+        );
+
+        assert_eq!(
+            stake_keyed_account.slash_double_vote(
+                &vote_keyed_account,
+                &mut slashing_sink_keyed_account,
+                &first_vote,
+                &second_vote,
+                &clock,
+            ),
+            Ok(())
+        );
+        let penalty =
+            stake_lamports * u64::from(Config::default().slash_penalty) / u64::from(std::u8::MAX);
+        assert_eq!(slashing_sink_keyed_account.account.lamports, 1 + penalty);
+        assert_eq!(
+            stake_keyed_account.account.lamports,
+            stake_lamports - penalty
+        );
+        let stake = StakeState::stake_from(&stake_keyed_account.account).unwrap();
+        assert_eq!(stake.stake, stake_lamports - penalty);
+        assert_eq!(stake.slashed_slot, Some(5));
+
+        // the same double vote can't be punished twice
+        assert_eq!(
+            stake_keyed_account.slash_double_vote(
+                &vote_keyed_account,
+                &mut slashing_sink_keyed_account,
+                &second_vote,
+                &first_vote,
+                &clock,
+            ),
+            Err(InstructionError::InvalidArgument)
+        );
+    }
 }
@@ -0,0 +1,12 @@
+pub mod stake_pool_instruction;
+pub mod stake_pool_state;
+
+const STAKE_POOL_PROGRAM_ID: [u8; 32] = [
+    6, 161, 216, 23, 183, 114, 147, 166, 37, 105, 121, 210, 37, 19, 179, 215, 234, 37, 210, 134,
+    107, 245, 133, 7, 70, 39, 205, 144, 0, 0, 0, 0,
+];
+
+solana_sdk::solana_name_id!(
+    STAKE_POOL_PROGRAM_ID,
+    "StakePoo11111111111111111111111111111111111"
+);
@@ -0,0 +1,581 @@
+use crate::{
+    id,
+    stake_pool_state::{StakePoolAccount, StakePoolState},
+};
+use bincode::deserialize;
+use log::*;
+use serde_derive::{Deserialize, Serialize};
+use solana_sdk::{
+    account::KeyedAccount,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    pubkey::Pubkey,
+    system_instruction,
+};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub enum StakePoolInstruction {
+    /// Initialize a StakePool, the Pubkey is the pool's manager
+    ///
+    /// Expects 1 Account:
+    ///    0 - Uninitialized StakePool account
+    InitializePool(Pubkey),
+
+    /// Initialize a PoolTokenAccount, the Pubkey is the account's owner
+    ///
+    /// Expects 2 Accounts:
+    ///    0 - Uninitialized PoolTokenAccount
+    ///    1 - StakePool whose tokens the account will hold
+    InitializeTokenAccount(Pubkey),
+
+    /// Add a stake account to the pool
+    ///
+    /// Expects 3 Accounts:
+    ///    0 - StakePool
+    ///    1 - StakeAccount to be added <= must have this signature
+    ///    2 - Manager <= must have this signature
+    AddStake,
+
+    /// Remove an emptied stake account from the pool
+    ///
+    /// Expects 3 Accounts:
+    ///    0 - StakePool
+    ///    1 - StakeAccount to be removed, must hold no lamports
+    ///    2 - Manager <= must have this signature
+    RemoveStake,
+
+    /// Move lamports from the pool's reserve into one of its stake accounts,
+    ///    where the manager can delegate them, the reserve can't be emptied
+    ///
+    /// Expects 3 Accounts:
+    ///    0 - StakePool
+    ///    1 - StakeAccount of the pool
+    ///    2 - Manager <= must have this signature
+    FundStake(u64),
+
+    /// Deposit lamports from a PoolTokenAccount into the pool's reserve and mint
+    ///    pool tokens in proportion to the pool's value, the PoolTokenAccount
+    ///    can't be emptied
+    ///
+    /// Expects 3 + N Accounts:
+    ///    0 - StakePool
+    ///    1 - PoolTokenAccount holding the lamports to be deposited
+    ///    2..2+N - every StakeAccount of the pool, in the pool's order
+    ///    2+N - Owner of the PoolTokenAccount <= must have this signature
+    Deposit(u64),
+
+    /// Burn pool tokens in exchange for a stake account split from one of the
+    ///    pool's stakes earlier in the same transaction
+    ///
+    /// Expects 5 + N Accounts:
+    ///    0 - StakePool
+    ///    1 - PoolTokenAccount holding the tokens to be burned
+    ///    2 - StakeAccount split from the pool, worth no more than the tokens
+    ///    3..3+N - every StakeAccount of the pool, in the pool's order
+    ///    3+N - Owner of the PoolTokenAccount <= must have this signature
+    ///    4+N - Manager <= must have this signature
+    Redeem(u64),
+
+    /// Burn pool tokens in exchange for lamports out of the pool's reserve,
+    ///    the reserve can't be emptied
+    ///
+    /// Expects 4 + N Accounts:
+    ///    0 - StakePool
+    ///    1 - PoolTokenAccount holding the tokens to be burned
+    ///    2 - Account to credit
+    ///    3..3+N - every StakeAccount of the pool, in the pool's order
+    ///    3+N - Owner of the PoolTokenAccount <= must have this signature
+    Withdraw(u64),
+
+    /// Transfer pool tokens
+    ///
+    /// Expects 3 Accounts:
+    ///    0 - PoolTokenAccount to debit
+    ///    1 - PoolTokenAccount to credit
+    ///    2 - Owner of the debited PoolTokenAccount <= must have this signature
+    Transfer(u64),
+}
+
+pub fn create_pool(
+    from_pubkey: &Pubkey,
+    pool_pubkey: &Pubkey,
+    manager_pubkey: &Pubkey,
+    lamports: u64,
+) -> Vec<Instruction> {
+    vec![
+        system_instruction::create_account(
+            from_pubkey,
+            pool_pubkey,
+            lamports,
+            StakePoolState::size_of() as u64,
+            &id(),
+        ),
+        Instruction::new(
+            id(),
+            &StakePoolInstruction::InitializePool(*manager_pubkey),
+            vec![AccountMeta::new(*pool_pubkey, false)],
+        ),
+    ]
+}
+
+pub fn create_token_account(
+    from_pubkey: &Pubkey,
+    token_account_pubkey: &Pubkey,
+    pool_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    lamports: u64,
+) -> Vec<Instruction> {
+    vec![
+        system_instruction::create_account(
+            from_pubkey,
+            token_account_pubkey,
+            lamports,
+            StakePoolState::size_of() as u64,
+            &id(),
+        ),
+        Instruction::new(
+            id(),
+            &StakePoolInstruction::InitializeTokenAccount(*owner_pubkey),
+            vec![
+                AccountMeta::new(*token_account_pubkey, false),
+                AccountMeta::new_credit_only(*pool_pubkey, false),
+            ],
+        ),
+    ]
+}
+
+pub fn add_stake(
+    pool_pubkey: &Pubkey,
+    manager_pubkey: &Pubkey,
+    stake_pubkey: &Pubkey,
+) -> Instruction {
+    let account_metas = vec![
+        AccountMeta::new(*pool_pubkey, false),
+        AccountMeta::new_credit_only(*stake_pubkey, true),
+        AccountMeta::new_credit_only(*manager_pubkey, true),
+    ];
+    Instruction::new(id(), &StakePoolInstruction::AddStake, account_metas)
+}
+
+pub fn remove_stake(
+    pool_pubkey: &Pubkey,
+    manager_pubkey: &Pubkey,
+    stake_pubkey: &Pubkey,
+) -> Instruction {
+    let account_metas = vec![
+        AccountMeta::new(*pool_pubkey, false),
+        AccountMeta::new_credit_only(*stake_pubkey, false),
+        AccountMeta::new_credit_only(*manager_pubkey, true),
+    ];
+    Instruction::new(id(), &StakePoolInstruction::RemoveStake, account_metas)
+}
+
+pub fn fund_stake(
+    pool_pubkey: &Pubkey,
+    manager_pubkey: &Pubkey,
+    stake_pubkey: &Pubkey,
+    lamports: u64,
+) -> Instruction {
+    let account_metas = vec![
+        AccountMeta::new(*pool_pubkey, false),
+        AccountMeta::new_credit_only(*stake_pubkey, false),
+        AccountMeta::new_credit_only(*manager_pubkey, true),
+    ];
+    Instruction::new(
+        id(),
+        &StakePoolInstruction::FundStake(lamports),
+        account_metas,
+    )
+}
+
+/// Transfers `lamports` from the owner into the token account, then deposits them
+pub fn deposit(
+    pool_pubkey: &Pubkey,
+    token_account_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    stake_pubkeys: &[Pubkey],
+    lamports: u64,
+) -> Vec<Instruction> {
+    let mut account_metas = vec![
+        AccountMeta::new(*pool_pubkey, false),
+        AccountMeta::new(*token_account_pubkey, false),
+    ];
+    for stake_pubkey in stake_pubkeys {
+        account_metas.push(AccountMeta::new_credit_only(*stake_pubkey, false));
+    }
+    account_metas.push(AccountMeta::new_credit_only(*owner_pubkey, true));
+
+    vec![
+        system_instruction::transfer(owner_pubkey, token_account_pubkey, lamports),
+        Instruction::new(
+            id(),
+            &StakePoolInstruction::Deposit(lamports),
+            account_metas,
+        ),
+    ]
+}
+
+/// The stake account to redeem into is expected to have been split from one of the
+///  pool's stakes by earlier instructions in the same transaction, see
+///  solana_stake_api::stake_instruction::split(), which the manager signs for
+pub fn redeem(
+    pool_pubkey: &Pubkey,
+    token_account_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    manager_pubkey: &Pubkey,
+    split_stake_pubkey: &Pubkey,
+    stake_pubkeys: &[Pubkey],
+    tokens: u64,
+) -> Instruction {
+    let mut account_metas = vec![
+        AccountMeta::new(*pool_pubkey, false),
+        AccountMeta::new(*token_account_pubkey, false),
+        AccountMeta::new_credit_only(*split_stake_pubkey, false),
+    ];
+    for stake_pubkey in stake_pubkeys {
+        account_metas.push(AccountMeta::new_credit_only(*stake_pubkey, false));
+    }
+    account_metas.push(AccountMeta::new_credit_only(*owner_pubkey, true));
+    account_metas.push(AccountMeta::new_credit_only(*manager_pubkey, true));
+
+    Instruction::new(id(), &StakePoolInstruction::Redeem(tokens), account_metas)
+}
+
+pub fn withdraw(
+    pool_pubkey: &Pubkey,
+    token_account_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    to_pubkey: &Pubkey,
+    stake_pubkeys: &[Pubkey],
+    tokens: u64,
+) -> Instruction {
+    let mut account_metas = vec![
+        AccountMeta::new(*pool_pubkey, false),
+        AccountMeta::new(*token_account_pubkey, false),
+        AccountMeta::new_credit_only(*to_pubkey, false),
+    ];
+    for stake_pubkey in stake_pubkeys {
+        account_metas.push(AccountMeta::new_credit_only(*stake_pubkey, false));
+    }
+    account_metas.push(AccountMeta::new_credit_only(*owner_pubkey, true));
+
+    Instruction::new(id(), &StakePoolInstruction::Withdraw(tokens), account_metas)
+}
+
+pub fn transfer(
+    from_token_account_pubkey: &Pubkey,
+    to_token_account_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    tokens: u64,
+) -> Instruction {
+    let account_metas = vec![
+        AccountMeta::new(*from_token_account_pubkey, false),
+        AccountMeta::new(*to_token_account_pubkey, false),
+        AccountMeta::new_credit_only(*owner_pubkey, true),
+    ];
+    Instruction::new(id(), &StakePoolInstruction::Transfer(tokens), account_metas)
+}
+
+// number of stake accounts listed by the pool at keyed_accounts[0]
+fn pool_stakes_len(pool: &KeyedAccount) -> Result<usize, InstructionError> {
+    StakePoolState::pool_from(pool.account)
+        .map(|pool| pool.stake_pubkeys.len())
+        .ok_or(InstructionError::InvalidAccountData)
+}
+
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    keyed_accounts: &mut [KeyedAccount],
+    data: &[u8],
+) -> Result<(), InstructionError> {
+    solana_logger::setup();
+
+    trace!("process_instruction: {:?}", data);
+    trace!("keyed_accounts: {:?}", keyed_accounts);
+
+    if keyed_accounts.is_empty() {
+        Err(InstructionError::InvalidInstructionData)?;
+    }
+
+    let (me, rest) = &mut keyed_accounts.split_at_mut(1);
+    let me = &mut me[0];
+
+    // TODO: data-driven unpack and dispatch of KeyedAccounts
+    match deserialize(data).map_err(|_| InstructionError::InvalidInstructionData)? {
+        StakePoolInstruction::InitializePool(manager_pubkey) => me.initialize_pool(&manager_pubkey),
+        StakePoolInstruction::InitializeTokenAccount(owner_pubkey) => {
+            if rest.len() != 1 {
+                Err(InstructionError::InvalidInstructionData)?;
+            }
+            me.initialize_token_account(&rest[0], &owner_pubkey)
+        }
+        StakePoolInstruction::AddStake => {
+            if rest.is_empty() {
+                Err(InstructionError::InvalidInstructionData)?;
+            }
+            let (stake, signers) = rest.split_at_mut(1);
+            me.add_stake(&stake[0], signers)
+        }
+        StakePoolInstruction::RemoveStake => {
+            if rest.is_empty() {
+                Err(InstructionError::InvalidInstructionData)?;
+            }
+            let (stake, signers) = rest.split_at_mut(1);
+            me.remove_stake(&stake[0], signers)
+        }
+        StakePoolInstruction::FundStake(lamports) => {
+            if rest.is_empty() {
+                Err(InstructionError::InvalidInstructionData)?;
+            }
+            let (stake, signers) = rest.split_at_mut(1);
+            me.fund_stake(lamports, &mut stake[0], signers)
+        }
+        StakePoolInstruction::Deposit(lamports) => {
+            let stakes_len = pool_stakes_len(me)?;
+            if rest.len() < 1 + stakes_len {
+                Err(InstructionError::InvalidInstructionData)?;
+            }
+            let (token_account, rest) = rest.split_at_mut(1);
+            let (stakes, signers) = rest.split_at_mut(stakes_len);
+            me.deposit(lamports, &mut token_account[0], stakes, signers)
+        }
+        StakePoolInstruction::Redeem(tokens) => {
+            let stakes_len = pool_stakes_len(me)?;
+            if rest.len() < 2 + stakes_len {
+                Err(InstructionError::InvalidInstructionData)?;
+            }
+            let (token_account, rest) = rest.split_at_mut(1);
+            let (split_stake, rest) = rest.split_at_mut(1);
+            let (stakes, signers) = rest.split_at_mut(stakes_len);
+            me.redeem(
+                tokens,
+                &mut token_account[0],
+                &split_stake[0],
+                stakes,
+                signers,
+            )
+        }
+        StakePoolInstruction::Withdraw(tokens) => {
+            let stakes_len = pool_stakes_len(me)?;
+            if rest.len() < 2 + stakes_len {
+                Err(InstructionError::InvalidInstructionData)?;
+            }
+            let (token_account, rest) = rest.split_at_mut(1);
+            let (to, rest) = rest.split_at_mut(1);
+            let (stakes, signers) = rest.split_at_mut(stakes_len);
+            me.withdraw(tokens, &mut token_account[0], &mut to[0], stakes, signers)
+        }
+        StakePoolInstruction::Transfer(tokens) => {
+            if rest.is_empty() {
+                Err(InstructionError::InvalidInstructionData)?;
+            }
+            let (to, signers) = rest.split_at_mut(1);
+            me.transfer(tokens, &mut to[0], signers)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_runtime::bank::Bank;
+    use solana_runtime::bank_client::BankClient;
+    use solana_runtime::genesis_utils::{create_genesis_block_with_leader, GenesisBlockInfo};
+    use solana_sdk::client::SyncClient;
+    use solana_sdk::message::Message;
+    use solana_sdk::signature::{Keypair, KeypairUtil};
+    use solana_stake_api::{stake_instruction, stake_state::StakeState};
+
+    fn token_amount(bank_client: &BankClient, token_account_pubkey: &Pubkey) -> u64 {
+        let account = bank_client
+            .get_account(token_account_pubkey)
+            .unwrap()
+            .unwrap();
+        StakePoolState::token_account_from(&account).unwrap().amount
+    }
+
+    #[test]
+    fn test_stake_pool_deposit_delegate_redeem() {
+        let GenesisBlockInfo {
+            genesis_block,
+            mint_keypair,
+            voting_keypair,
+        } = create_genesis_block_with_leader(1_000_000, &Pubkey::new_rand(), 1);
+        let mut bank = Bank::new(&genesis_block);
+        bank.add_instruction_processor(id(), process_instruction);
+        bank.add_instruction_processor(
+            solana_stake_api::id(),
+            stake_instruction::process_instruction,
+        );
+        let bank_client = BankClient::new(bank);
+        let mint_pubkey = mint_keypair.pubkey();
+        let vote_pubkey = voting_keypair.pubkey();
+
+        // the manager sets up a pool
+        let manager_keypair = Keypair::new();
+        let manager_pubkey = manager_keypair.pubkey();
+        let pool_pubkey = Pubkey::new_rand();
+        bank_client
+            .send_message(
+                &[&mint_keypair],
+                Message::new(create_pool(&mint_pubkey, &pool_pubkey, &manager_pubkey, 1)),
+            )
+            .unwrap();
+
+        // a depositor buys in
+        let owner_keypair = Keypair::new();
+        let owner_pubkey = owner_keypair.pubkey();
+        let token_account_pubkey = Pubkey::new_rand();
+        bank_client
+            .transfer(1_000, &mint_keypair, &owner_pubkey)
+            .unwrap();
+        bank_client
+            .send_message(
+                &[&owner_keypair],
+                Message::new(create_token_account(
+                    &owner_pubkey,
+                    &token_account_pubkey,
+                    &pool_pubkey,
+                    &owner_pubkey,
+                    1,
+                )),
+            )
+            .unwrap();
+        bank_client
+            .send_message(
+                &[&owner_keypair],
+                Message::new(deposit(
+                    &pool_pubkey,
+                    &token_account_pubkey,
+                    &owner_pubkey,
+                    &[],
+                    500,
+                )),
+            )
+            .unwrap();
+        assert_eq!(token_amount(&bank_client, &token_account_pubkey), 500);
+        assert_eq!(bank_client.get_balance(&pool_pubkey).unwrap(), 501);
+
+        // the manager delegates the reserve through a new stake account
+        let stake_keypair = Keypair::new();
+        let stake_pubkey = stake_keypair.pubkey();
+        let mut instructions =
+            stake_instruction::create_stake_account(&mint_pubkey, &stake_pubkey, 0);
+        instructions.push(add_stake(&pool_pubkey, &manager_pubkey, &stake_pubkey));
+        instructions.push(fund_stake(
+            &pool_pubkey,
+            &manager_pubkey,
+            &stake_pubkey,
+            500,
+        ));
+        instructions.push(stake_instruction::delegate_stake(
+            &stake_pubkey,
+            &vote_pubkey,
+            500,
+        ));
+        bank_client
+            .send_message(
+                &[&mint_keypair, &stake_keypair, &manager_keypair],
+                Message::new(instructions),
+            )
+            .unwrap();
+        assert_eq!(bank_client.get_balance(&pool_pubkey).unwrap(), 1);
+        assert_eq!(bank_client.get_balance(&stake_pubkey).unwrap(), 500);
+
+        // rewards double the pool's value
+        bank_client
+            .transfer(500, &mint_keypair, &stake_pubkey)
+            .unwrap();
+
+        // 250 tokens are now worth 500 of the pool's 1001 lamports, redeemed as a stake
+        //  the manager splits from the pool's
+        let split_stake_keypair = Keypair::new();
+        let split_stake_pubkey = split_stake_keypair.pubkey();
+        let mut instructions =
+            stake_instruction::create_stake_account(&owner_pubkey, &split_stake_pubkey, 0);
+        instructions.push(stake_instruction::split(
+            &stake_pubkey,
+            500,
+            &split_stake_pubkey,
+        ));
+        instructions.push(redeem(
+            &pool_pubkey,
+            &token_account_pubkey,
+            &owner_pubkey,
+            &manager_pubkey,
+            &split_stake_pubkey,
+            &[stake_pubkey],
+            250,
+        ));
+        bank_client
+            .send_message(
+                &[&owner_keypair, &stake_keypair, &manager_keypair],
+                Message::new(instructions),
+            )
+            .unwrap();
+
+        assert_eq!(token_amount(&bank_client, &token_account_pubkey), 250);
+        let split_stake_account = bank_client
+            .get_account(&split_stake_pubkey)
+            .unwrap()
+            .unwrap();
+        assert_eq!(split_stake_account.lamports, 500);
+        assert_eq!(
+            StakeState::stake_from(&split_stake_account)
+                .unwrap()
+                .voter_pubkey,
+            vote_pubkey
+        );
+        assert_eq!(
+            StakePoolState::pool_from(&bank_client.get_account(&pool_pubkey).unwrap().unwrap())
+                .unwrap()
+                .token_supply,
+            250
+        );
+
+        // the redeemed stake is the depositor's
+        bank_client
+            .send_message(
+                &[&owner_keypair, &split_stake_keypair],
+                Message::new(vec![stake_instruction::deactivate_stake(
+                    &split_stake_pubkey,
+                    &vote_pubkey,
+                )]),
+            )
+            .unwrap();
+
+        // tokens can also be withdrawn out of the reserve without the manager, 200 more
+        //  lamports buy 99 tokens and fill the reserve back up
+        bank_client
+            .send_message(
+                &[&owner_keypair],
+                Message::new(deposit(
+                    &pool_pubkey,
+                    &token_account_pubkey,
+                    &owner_pubkey,
+                    &[stake_pubkey],
+                    200,
+                )),
+            )
+            .unwrap();
+        assert_eq!(token_amount(&bank_client, &token_account_pubkey), 349);
+        assert_eq!(bank_client.get_balance(&pool_pubkey).unwrap(), 201);
+
+        let to_pubkey = Pubkey::new_rand();
+        bank_client
+            .send_message(
+                &[&owner_keypair],
+                Message::new(vec![withdraw(
+                    &pool_pubkey,
+                    &token_account_pubkey,
+                    &owner_pubkey,
+                    &to_pubkey,
+                    &[stake_pubkey],
+                    50,
+                )]),
+            )
+            .unwrap();
+        assert_eq!(token_amount(&bank_client, &token_account_pubkey), 299);
+        assert_eq!(bank_client.get_balance(&to_pubkey).unwrap(), 100);
+        assert_eq!(bank_client.get_balance(&pool_pubkey).unwrap(), 101);
+    }
+}
@@ -0,0 +1,964 @@
+//! Stake pool state
+//! * pool depositors' lamports and delegate them through a manager's stake accounts
+//! * keep the pool token ledger, tokens are minted on deposit and burned on redemption
+//!
+//! The pool program only ever writes to the accounts it owns, the pool and its token
+//!  accounts.  The pool's stake accounts belong to the stake program and are run by
+//!  the manager, who holds their keypairs, the pool only reads their balances.
+
+use crate::id;
+use serde_derive::{Deserialize, Serialize};
+use solana_sdk::{
+    account::{Account, KeyedAccount},
+    account_utils::State,
+    instruction::InstructionError,
+    pubkey::Pubkey,
+};
+use solana_stake_api::stake_state::StakeState;
+
+// Maximum number of stake accounts a pool can delegate through
+pub const MAX_POOL_STAKES: usize = 32;
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub enum StakePoolState {
+    Uninitialized,
+    Pool(StakePool),
+    TokenAccount(PoolTokenAccount),
+}
+
+impl Default for StakePoolState {
+    fn default() -> Self {
+        StakePoolState::Uninitialized
+    }
+}
+
+impl StakePoolState {
+    /// Upper limit on the size of a pool or pool token account
+    pub fn size_of() -> usize {
+        let pool = StakePoolState::Pool(StakePool {
+            stake_pubkeys: vec![Pubkey::default(); MAX_POOL_STAKES],
+            ..StakePool::default()
+        });
+        bincode::serialized_size(&pool).unwrap() as usize
+    }
+
+    // utility function, used by clients, tests
+    pub fn from(account: &Account) -> Option<StakePoolState> {
+        if account.owner != id() {
+            return None;
+        }
+        account.state().ok()
+    }
+
+    pub fn pool_from(account: &Account) -> Option<StakePool> {
+        match Self::from(account) {
+            Some(StakePoolState::Pool(pool)) => Some(pool),
+            _ => None,
+        }
+    }
+
+    pub fn token_account_from(account: &Account) -> Option<PoolTokenAccount> {
+        match Self::from(account) {
+            Some(StakePoolState::TokenAccount(token_account)) => Some(token_account),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Clone)]
+pub struct StakePool {
+    /// The manager may add, fund and remove the pool's stake accounts
+    pub manager_pubkey: Pubkey,
+
+    /// Pool tokens outstanding
+    pub token_supply: u64,
+
+    /// Stake accounts the pool's lamports are delegated through, the pool's
+    ///  own balance is its undelegated reserve
+    pub stake_pubkeys: Vec<Pubkey>,
+}
+
+impl StakePool {
+    /// Pool tokens minted for a deposit of `lamports` into a pool worth `pool_lamports`
+    pub fn tokens_for_deposit(&self, lamports: u64, pool_lamports: u64) -> u64 {
+        if self.token_supply == 0 || pool_lamports == 0 {
+            lamports
+        } else {
+            (u128::from(lamports) * u128::from(self.token_supply) / u128::from(pool_lamports))
+                as u64
+        }
+    }
+
+    /// Lamports owed for redeeming `tokens` from a pool worth `pool_lamports`
+    pub fn lamports_for_tokens(&self, tokens: u64, pool_lamports: u64) -> u64 {
+        if self.token_supply == 0 {
+            0
+        } else {
+            (u128::from(tokens) * u128::from(pool_lamports) / u128::from(self.token_supply)) as u64
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Clone)]
+pub struct PoolTokenAccount {
+    /// The pool whose tokens this account holds
+    pub pool_pubkey: Pubkey,
+
+    /// Owner of this account, must sign deposits, redemptions and transfers
+    pub owner_pubkey: Pubkey,
+
+    /// Amount of pool tokens this account holds
+    pub amount: u64,
+}
+
+fn verify_signer(authorized: &Pubkey, signers: &[KeyedAccount]) -> Result<(), InstructionError> {
+    if signers
+        .iter()
+        .any(|account| account.signer_key() == Some(authorized))
+    {
+        Ok(())
+    } else {
+        Err(InstructionError::MissingRequiredSignature)
+    }
+}
+
+fn pool_state(pool: &KeyedAccount) -> Result<StakePool, InstructionError> {
+    match pool.state()? {
+        StakePoolState::Pool(pool) => Ok(pool),
+        _ => Err(InstructionError::InvalidAccountData),
+    }
+}
+
+// the pool's reserve plus the balances of all of its stake accounts, which
+//  must be given in the order the pool lists them
+fn pool_lamports(
+    pool_account: &KeyedAccount,
+    pool: &StakePool,
+    stakes: &[KeyedAccount],
+) -> Result<u64, InstructionError> {
+    if stakes.len() != pool.stake_pubkeys.len()
+        || stakes
+            .iter()
+            .zip(pool.stake_pubkeys.iter())
+            .any(|(stake, stake_pubkey)| stake.unsigned_key() != stake_pubkey)
+    {
+        return Err(InstructionError::InvalidArgument);
+    }
+    Ok(stakes
+        .iter()
+        .fold(pool_account.account.lamports, |sum, stake| {
+            sum + stake.account.lamports
+        }))
+}
+
+fn pool_token_account(
+    token_account: &KeyedAccount,
+    pool_pubkey: &Pubkey,
+    signers: &[KeyedAccount],
+) -> Result<PoolTokenAccount, InstructionError> {
+    if let StakePoolState::TokenAccount(token_account) = token_account.state()? {
+        if token_account.pool_pubkey != *pool_pubkey {
+            return Err(InstructionError::InvalidArgument);
+        }
+        verify_signer(&token_account.owner_pubkey, signers)?;
+        Ok(token_account)
+    } else {
+        Err(InstructionError::InvalidAccountData)
+    }
+}
+
+pub trait StakePoolAccount {
+    fn initialize_pool(&mut self, manager_pubkey: &Pubkey) -> Result<(), InstructionError>;
+    fn initialize_token_account(
+        &mut self,
+        pool: &KeyedAccount,
+        owner_pubkey: &Pubkey,
+    ) -> Result<(), InstructionError>;
+    fn add_stake(
+        &mut self,
+        stake: &KeyedAccount,
+        signers: &[KeyedAccount],
+    ) -> Result<(), InstructionError>;
+    fn remove_stake(
+        &mut self,
+        stake: &KeyedAccount,
+        signers: &[KeyedAccount],
+    ) -> Result<(), InstructionError>;
+    fn fund_stake(
+        &mut self,
+        lamports: u64,
+        stake: &mut KeyedAccount,
+        signers: &[KeyedAccount],
+    ) -> Result<(), InstructionError>;
+    fn deposit(
+        &mut self,
+        lamports: u64,
+        token_account: &mut KeyedAccount,
+        stakes: &[KeyedAccount],
+        signers: &[KeyedAccount],
+    ) -> Result<(), InstructionError>;
+    fn redeem(
+        &mut self,
+        tokens: u64,
+        token_account: &mut KeyedAccount,
+        split_stake: &KeyedAccount,
+        stakes: &[KeyedAccount],
+        signers: &[KeyedAccount],
+    ) -> Result<(), InstructionError>;
+    fn withdraw(
+        &mut self,
+        tokens: u64,
+        token_account: &mut KeyedAccount,
+        to: &mut KeyedAccount,
+        stakes: &[KeyedAccount],
+        signers: &[KeyedAccount],
+    ) -> Result<(), InstructionError>;
+    fn transfer(
+        &mut self,
+        tokens: u64,
+        to: &mut KeyedAccount,
+        signers: &[KeyedAccount],
+    ) -> Result<(), InstructionError>;
+}
+
+impl<'a> StakePoolAccount for KeyedAccount<'a> {
+    fn initialize_pool(&mut self, manager_pubkey: &Pubkey) -> Result<(), InstructionError> {
+        if let StakePoolState::Uninitialized = self.state()? {
+            self.set_state(&StakePoolState::Pool(StakePool {
+                manager_pubkey: *manager_pubkey,
+                ..StakePool::default()
+            }))
+        } else {
+            Err(InstructionError::AccountAlreadyInitialized)
+        }
+    }
+
+    fn initialize_token_account(
+        &mut self,
+        pool: &KeyedAccount,
+        owner_pubkey: &Pubkey,
+    ) -> Result<(), InstructionError> {
+        pool_state(pool)?;
+
+        if let StakePoolState::Uninitialized = self.state()? {
+            self.set_state(&StakePoolState::TokenAccount(PoolTokenAccount {
+                pool_pubkey: *pool.unsigned_key(),
+                owner_pubkey: *owner_pubkey,
+                amount: 0,
+            }))
+        } else {
+            Err(InstructionError::AccountAlreadyInitialized)
+        }
+    }
+
+    fn add_stake(
+        &mut self,
+        stake: &KeyedAccount,
+        signers: &[KeyedAccount],
+    ) -> Result<(), InstructionError> {
+        let mut pool = pool_state(self)?;
+        verify_signer(&pool.manager_pubkey, signers)?;
+
+        // the stake account signs too, so the manager can only count stakes it runs
+        //  toward the pool's value
+        if stake.signer_key().is_none() {
+            return Err(InstructionError::MissingRequiredSignature);
+        }
+        if stake.account.owner != solana_stake_api::id()
+            || pool.stake_pubkeys.contains(stake.unsigned_key())
+        {
+            return Err(InstructionError::InvalidArgument);
+        }
+        if pool.stake_pubkeys.len() >= MAX_POOL_STAKES {
+            return Err(InstructionError::InvalidArgument);
+        }
+        pool.stake_pubkeys.push(*stake.unsigned_key());
+
+        self.set_state(&StakePoolState::Pool(pool))
+    }
+
+    fn remove_stake(
+        &mut self,
+        stake: &KeyedAccount,
+        signers: &[KeyedAccount],
+    ) -> Result<(), InstructionError> {
+        let mut pool = pool_state(self)?;
+        verify_signer(&pool.manager_pubkey, signers)?;
+
+        // only emptied stake accounts leave the pool, so the pool's value is unchanged
+        if stake.account.lamports != 0 {
+            return Err(InstructionError::InvalidArgument);
+        }
+        let len = pool.stake_pubkeys.len();
+        pool.stake_pubkeys
+            .retain(|stake_pubkey| stake_pubkey != stake.unsigned_key());
+        if pool.stake_pubkeys.len() == len {
+            return Err(InstructionError::InvalidArgument);
+        }
+
+        self.set_state(&StakePoolState::Pool(pool))
+    }
+
+    fn fund_stake(
+        &mut self,
+        lamports: u64,
+        stake: &mut KeyedAccount,
+        signers: &[KeyedAccount],
+    ) -> Result<(), InstructionError> {
+        let pool = pool_state(self)?;
+        verify_signer(&pool.manager_pubkey, signers)?;
+
+        if !pool.stake_pubkeys.contains(stake.unsigned_key()) {
+            return Err(InstructionError::InvalidArgument);
+        }
+        // the pool can't be emptied, an account without lamports is dropped
+        if lamports >= self.account.lamports {
+            return Err(InstructionError::InsufficientFunds);
+        }
+        self.account.lamports -= lamports;
+        stake.account.lamports += lamports;
+        Ok(())
+    }
+
+    fn deposit(
+        &mut self,
+        lamports: u64,
+        token_account: &mut KeyedAccount,
+        stakes: &[KeyedAccount],
+        signers: &[KeyedAccount],
+    ) -> Result<(), InstructionError> {
+        let mut pool = pool_state(self)?;
+        let mut pool_token_account =
+            pool_token_account(token_account, self.unsigned_key(), signers)?;
+
+        // the token account can't be emptied, it would be dropped along with its tokens
+        if lamports >= token_account.account.lamports {
+            return Err(InstructionError::InsufficientFunds);
+        }
+        let tokens = pool.tokens_for_deposit(lamports, pool_lamports(self, &pool, stakes)?);
+        if tokens == 0 {
+            // too small to be worth a pool token
+            return Err(InstructionError::InvalidArgument);
+        }
+
+        token_account.account.lamports -= lamports;
+        self.account.lamports += lamports;
+
+        pool_token_account.amount += tokens;
+        pool.token_supply += tokens;
+
+        token_account.set_state(&StakePoolState::TokenAccount(pool_token_account))?;
+        self.set_state(&StakePoolState::Pool(pool))
+    }
+
+    fn redeem(
+        &mut self,
+        tokens: u64,
+        token_account: &mut KeyedAccount,
+        split_stake: &KeyedAccount,
+        stakes: &[KeyedAccount],
+        signers: &[KeyedAccount],
+    ) -> Result<(), InstructionError> {
+        let mut pool = pool_state(self)?;
+        let mut pool_token_account =
+            pool_token_account(token_account, self.unsigned_key(), signers)?;
+        // the split is signed for by the manager, who holds the keypairs of the
+        //  pool's stakes, so the manager vouches for where it came from
+        verify_signer(&pool.manager_pubkey, signers)?;
+
+        if tokens > pool_token_account.amount {
+            return Err(InstructionError::InsufficientFunds);
+        }
+
+        // the split stake has to be delegated to one of the pool's voters
+        //  and can't be one of the pool's stakes
+        if split_stake.account.owner != solana_stake_api::id()
+            || pool.stake_pubkeys.contains(split_stake.unsigned_key())
+        {
+            return Err(InstructionError::InvalidArgument);
+        }
+        let split_voter_pubkey = StakeState::stake_from(&split_stake.account)
+            .ok_or(InstructionError::InvalidAccountData)?
+            .voter_pubkey;
+        let is_pool_voter = stakes.iter().any(|stake| {
+            StakeState::stake_from(&stake.account)
+                .map(|stake| stake.voter_pubkey == split_voter_pubkey)
+                .unwrap_or(false)
+        });
+        if !is_pool_voter {
+            return Err(InstructionError::InvalidArgument);
+        }
+
+        // the split was taken out of the pool's stakes earlier in this transaction,
+        //  so count it toward what the pool was worth
+        let pool_lamports = pool_lamports(self, &pool, stakes)? + split_stake.account.lamports;
+        if split_stake.account.lamports > pool.lamports_for_tokens(tokens, pool_lamports) {
+            return Err(InstructionError::InsufficientFunds);
+        }
+
+        pool_token_account.amount -= tokens;
+        pool.token_supply -= tokens;
+
+        token_account.set_state(&StakePoolState::TokenAccount(pool_token_account))?;
+        self.set_state(&StakePoolState::Pool(pool))
+    }
+
+    fn withdraw(
+        &mut self,
+        tokens: u64,
+        token_account: &mut KeyedAccount,
+        to: &mut KeyedAccount,
+        stakes: &[KeyedAccount],
+        signers: &[KeyedAccount],
+    ) -> Result<(), InstructionError> {
+        let mut pool = pool_state(self)?;
+        let mut pool_token_account =
+            pool_token_account(token_account, self.unsigned_key(), signers)?;
+
+        if tokens > pool_token_account.amount {
+            return Err(InstructionError::InsufficientFunds);
+        }
+        let lamports = pool.lamports_for_tokens(tokens, pool_lamports(self, &pool, stakes)?);
+        if lamports == 0 {
+            // too small to be worth a lamport
+            return Err(InstructionError::InvalidArgument);
+        }
+        // paid out of the reserve, which can't be emptied
+        if lamports >= self.account.lamports {
+            return Err(InstructionError::InsufficientFunds);
+        }
+
+        self.account.lamports -= lamports;
+        to.account.lamports += lamports;
+
+        pool_token_account.amount -= tokens;
+        pool.token_supply -= tokens;
+
+        token_account.set_state(&StakePoolState::TokenAccount(pool_token_account))?;
+        self.set_state(&StakePoolState::Pool(pool))
+    }
+
+    fn transfer(
+        &mut self,
+        tokens: u64,
+        to: &mut KeyedAccount,
+        signers: &[KeyedAccount],
+    ) -> Result<(), InstructionError> {
+        let mut from_token_account = match self.state()? {
+            StakePoolState::TokenAccount(token_account) => token_account,
+            _ => return Err(InstructionError::InvalidAccountData),
+        };
+        verify_signer(&from_token_account.owner_pubkey, signers)?;
+
+        let mut to_token_account = match to.state()? {
+            StakePoolState::TokenAccount(token_account) => token_account,
+            _ => return Err(InstructionError::InvalidAccountData),
+        };
+        if from_token_account.pool_pubkey != to_token_account.pool_pubkey {
+            return Err(InstructionError::InvalidArgument);
+        }
+        if tokens > from_token_account.amount {
+            return Err(InstructionError::InsufficientFunds);
+        }
+
+        from_token_account.amount -= tokens;
+        to_token_account.amount += tokens;
+
+        self.set_state(&StakePoolState::TokenAccount(from_token_account))?;
+        to.set_state(&StakePoolState::TokenAccount(to_token_account))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::account::Account;
+    use solana_stake_api::stake_state::Stake;
+
+    fn new_pool_account(lamports: u64, pool: StakePool) -> Account {
+        let mut account = Account::new(lamports, StakePoolState::size_of(), &id());
+        account.set_state(&StakePoolState::Pool(pool)).unwrap();
+        account
+    }
+
+    fn new_token_account(lamports: u64, token_account: PoolTokenAccount) -> Account {
+        let mut account = Account::new(lamports, StakePoolState::size_of(), &id());
+        account
+            .set_state(&StakePoolState::TokenAccount(token_account))
+            .unwrap();
+        account
+    }
+
+    fn new_stake_account(lamports: u64, voter_pubkey: &Pubkey) -> Account {
+        let mut account = Account::new(
+            lamports,
+            std::mem::size_of::<StakeState>(),
+            &solana_stake_api::id(),
+        );
+        account
+            .set_state(&StakeState::Stake(Stake {
+                voter_pubkey: *voter_pubkey,
+                stake: lamports,
+                ..Stake::default()
+            }))
+            .unwrap();
+        account
+    }
+
+    #[test]
+    fn test_stake_pool_token_math() {
+        let pool = StakePool::default();
+        // first deposit mints 1 token per lamport
+        assert_eq!(pool.tokens_for_deposit(100, 0), 100);
+        assert_eq!(pool.tokens_for_deposit(100, 42), 100);
+        assert_eq!(pool.lamports_for_tokens(100, 42), 0);
+
+        let pool = StakePool {
+            token_supply: 100,
+            ..StakePool::default()
+        };
+        // the pool has doubled in value
+        assert_eq!(pool.tokens_for_deposit(100, 200), 50);
+        assert_eq!(pool.lamports_for_tokens(50, 200), 100);
+        // no overflow on large balances
+        assert_eq!(pool.lamports_for_tokens(100, std::u64::MAX), std::u64::MAX);
+    }
+
+    #[test]
+    fn test_stake_pool_initialize() {
+        let pool_pubkey = Pubkey::new_rand();
+        let mut pool_account = Account::new(0, StakePoolState::size_of(), &id());
+        let mut pool_keyed_account = KeyedAccount::new(&pool_pubkey, false, &mut pool_account);
+
+        let manager_pubkey = Pubkey::new_rand();
+        assert_eq!(pool_keyed_account.initialize_pool(&manager_pubkey), Ok(()));
+        assert_eq!(
+            pool_keyed_account.initialize_pool(&manager_pubkey),
+            Err(InstructionError::AccountAlreadyInitialized)
+        );
+
+        let token_pubkey = Pubkey::new_rand();
+        let mut token_account = Account::new(0, StakePoolState::size_of(), &id());
+        let mut token_keyed_account = KeyedAccount::new(&token_pubkey, false, &mut token_account);
+
+        // not a pool
+        let owner_pubkey = Pubkey::new_rand();
+        let mut not_pool_account = Account::new(0, StakePoolState::size_of(), &id());
+        let not_pool_keyed_account = KeyedAccount::new(&token_pubkey, false, &mut not_pool_account);
+        assert_eq!(
+            token_keyed_account.initialize_token_account(&not_pool_keyed_account, &owner_pubkey),
+            Err(InstructionError::InvalidAccountData)
+        );
+
+        assert_eq!(
+            token_keyed_account.initialize_token_account(&pool_keyed_account, &owner_pubkey),
+            Ok(())
+        );
+        assert_eq!(
+            StakePoolState::token_account_from(&token_account),
+            Some(PoolTokenAccount {
+                pool_pubkey,
+                owner_pubkey,
+                amount: 0,
+            })
+        );
+        assert_eq!(
+            StakePoolState::pool_from(&pool_account),
+            Some(StakePool {
+                manager_pubkey,
+                ..StakePool::default()
+            })
+        );
+    }
+
+    #[test]
+    fn test_stake_pool_manage_stakes() {
+        let manager_pubkey = Pubkey::new_rand();
+        let pool_pubkey = Pubkey::new_rand();
+        let mut pool_account = new_pool_account(
+            100,
+            StakePool {
+                manager_pubkey,
+                ..StakePool::default()
+            },
+        );
+        let mut pool_keyed_account = KeyedAccount::new(&pool_pubkey, false, &mut pool_account);
+
+        let stake_pubkey = Pubkey::new_rand();
+        let mut stake_account = Account::new(0, 0, &solana_stake_api::id());
+        let mut stake_keyed_account = KeyedAccount::new(&stake_pubkey, true, &mut stake_account);
+
+        let mut manager_account = Account::default();
+        let manager_keyed_account = KeyedAccount::new(&manager_pubkey, true, &mut manager_account);
+        let signers = vec![manager_keyed_account];
+
+        // manager must sign
+        assert_eq!(
+            pool_keyed_account.add_stake(&stake_keyed_account, &[]),
+            Err(InstructionError::MissingRequiredSignature)
+        );
+        // and so must the stake
+        let mut unsigned_stake_account = Account::new(0, 0, &solana_stake_api::id());
+        assert_eq!(
+            pool_keyed_account.add_stake(
+                &KeyedAccount::new(&stake_pubkey, false, &mut unsigned_stake_account),
+                &signers
+            ),
+            Err(InstructionError::MissingRequiredSignature)
+        );
+        // only stakes already in the pool can be funded
+        assert_eq!(
+            pool_keyed_account.fund_stake(10, &mut stake_keyed_account, &signers),
+            Err(InstructionError::InvalidArgument)
+        );
+        assert_eq!(
+            pool_keyed_account.add_stake(&stake_keyed_account, &signers),
+            Ok(())
+        );
+        // no duplicates
+        assert_eq!(
+            pool_keyed_account.add_stake(&stake_keyed_account, &signers),
+            Err(InstructionError::InvalidArgument)
+        );
+
+        assert_eq!(
+            pool_keyed_account.fund_stake(101, &mut stake_keyed_account, &signers),
+            Err(InstructionError::InsufficientFunds)
+        );
+        // the pool can't be emptied
+        assert_eq!(
+            pool_keyed_account.fund_stake(100, &mut stake_keyed_account, &signers),
+            Err(InstructionError::InsufficientFunds)
+        );
+        assert_eq!(
+            pool_keyed_account.fund_stake(99, &mut stake_keyed_account, &signers),
+            Ok(())
+        );
+        assert_eq!(pool_keyed_account.account.lamports, 1);
+        assert_eq!(stake_keyed_account.account.lamports, 99);
+
+        // only empty stakes can be removed
+        assert_eq!(
+            pool_keyed_account.remove_stake(&stake_keyed_account, &signers),
+            Err(InstructionError::InvalidArgument)
+        );
+        stake_keyed_account.account.lamports = 0;
+        assert_eq!(
+            pool_keyed_account.remove_stake(&stake_keyed_account, &signers),
+            Ok(())
+        );
+        assert_eq!(
+            pool_keyed_account.remove_stake(&stake_keyed_account, &signers),
+            Err(InstructionError::InvalidArgument)
+        );
+    }
+
+    #[test]
+    fn test_stake_pool_deposit_and_redeem() {
+        let voter_pubkey = Pubkey::new_rand();
+        let stake_pubkey = Pubkey::new_rand();
+        let mut stake_account = new_stake_account(100, &voter_pubkey);
+
+        let manager_pubkey = Pubkey::new_rand();
+        let pool_pubkey = Pubkey::new_rand();
+        let mut pool_account = new_pool_account(
+            0,
+            StakePool {
+                manager_pubkey,
+                token_supply: 50,
+                stake_pubkeys: vec![stake_pubkey],
+                ..StakePool::default()
+            },
+        );
+        let mut pool_keyed_account = KeyedAccount::new(&pool_pubkey, false, &mut pool_account);
+
+        let owner_pubkey = Pubkey::new_rand();
+        let token_pubkey = Pubkey::new_rand();
+        let mut token_account = new_token_account(
+            101,
+            PoolTokenAccount {
+                pool_pubkey,
+                owner_pubkey,
+                amount: 0,
+            },
+        );
+        let mut token_keyed_account = KeyedAccount::new(&token_pubkey, false, &mut token_account);
+
+        let mut owner_account = Account::default();
+        let mut manager_account = Account::default();
+        let owner_keyed_account = KeyedAccount::new(&owner_pubkey, true, &mut owner_account);
+        let mut signers = vec![owner_keyed_account];
+
+        {
+            let stakes = vec![KeyedAccount::new(&stake_pubkey, false, &mut stake_account)];
+
+            // owner must sign
+            assert_eq!(
+                pool_keyed_account.deposit(100, &mut token_keyed_account, &stakes, &[]),
+                Err(InstructionError::MissingRequiredSignature)
+            );
+            // all of the pool's stakes must be counted
+            assert_eq!(
+                pool_keyed_account.deposit(100, &mut token_keyed_account, &[], &signers),
+                Err(InstructionError::InvalidArgument)
+            );
+            assert_eq!(
+                pool_keyed_account.deposit(102, &mut token_keyed_account, &stakes, &signers),
+                Err(InstructionError::InsufficientFunds)
+            );
+            // the token account can't be emptied
+            assert_eq!(
+                pool_keyed_account.deposit(101, &mut token_keyed_account, &stakes, &signers),
+                Err(InstructionError::InsufficientFunds)
+            );
+
+            // 50 tokens are worth 100 lamports, 100 lamports buy 50 more
+            assert_eq!(
+                pool_keyed_account.deposit(100, &mut token_keyed_account, &stakes, &signers),
+                Ok(())
+            );
+        }
+        assert_eq!(pool_keyed_account.account.lamports, 100);
+        assert_eq!(token_keyed_account.account.lamports, 1);
+        assert_eq!(
+            StakePoolState::token_account_from(&token_keyed_account.account)
+                .unwrap()
+                .amount,
+            50
+        );
+
+        // split 40 lamports out of the pool's stake
+        stake_account.lamports -= 40;
+        let split_stake_pubkey = Pubkey::new_rand();
+        let mut split_stake_account = new_stake_account(40, &voter_pubkey);
+        let split_stake_keyed_account =
+            KeyedAccount::new(&split_stake_pubkey, false, &mut split_stake_account);
+        let stakes = vec![KeyedAccount::new(&stake_pubkey, false, &mut stake_account)];
+
+        // the manager has to vouch for the split
+        assert_eq!(
+            pool_keyed_account.redeem(
+                20,
+                &mut token_keyed_account,
+                &split_stake_keyed_account,
+                &stakes,
+                &signers
+            ),
+            Err(InstructionError::MissingRequiredSignature)
+        );
+        signers.push(KeyedAccount::new(
+            &manager_pubkey,
+            true,
+            &mut manager_account,
+        ));
+
+        // 10 tokens are only worth 20 lamports
+        assert_eq!(
+            pool_keyed_account.redeem(
+                10,
+                &mut token_keyed_account,
+                &split_stake_keyed_account,
+                &stakes,
+                &signers
+            ),
+            Err(InstructionError::InsufficientFunds)
+        );
+        assert_eq!(
+            pool_keyed_account.redeem(
+                51,
+                &mut token_keyed_account,
+                &split_stake_keyed_account,
+                &stakes,
+                &signers
+            ),
+            Err(InstructionError::InsufficientFunds)
+        );
+        assert_eq!(
+            pool_keyed_account.redeem(
+                20,
+                &mut token_keyed_account,
+                &split_stake_keyed_account,
+                &stakes,
+                &signers
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            StakePoolState::token_account_from(&token_keyed_account.account)
+                .unwrap()
+                .amount,
+            30
+        );
+        assert_eq!(
+            StakePoolState::pool_from(&pool_keyed_account.account)
+                .unwrap()
+                .token_supply,
+            80
+        );
+
+        // a stake delegated elsewhere can't be redeemed into
+        let mut other_stake_account = new_stake_account(40, &Pubkey::new_rand());
+        let other_stake_keyed_account =
+            KeyedAccount::new(&split_stake_pubkey, false, &mut other_stake_account);
+        assert_eq!(
+            pool_keyed_account.redeem(
+                20,
+                &mut token_keyed_account,
+                &other_stake_keyed_account,
+                &stakes,
+                &signers
+            ),
+            Err(InstructionError::InvalidArgument)
+        );
+    }
+
+    #[test]
+    fn test_stake_pool_withdraw() {
+        let stake_pubkey = Pubkey::new_rand();
+        let mut stake_account = new_stake_account(100, &Pubkey::new_rand());
+
+        let pool_pubkey = Pubkey::new_rand();
+        let mut pool_account = new_pool_account(
+            100,
+            StakePool {
+                token_supply: 100,
+                stake_pubkeys: vec![stake_pubkey],
+                ..StakePool::default()
+            },
+        );
+        let mut pool_keyed_account = KeyedAccount::new(&pool_pubkey, false, &mut pool_account);
+
+        let owner_pubkey = Pubkey::new_rand();
+        let token_pubkey = Pubkey::new_rand();
+        let mut token_account = new_token_account(
+            1,
+            PoolTokenAccount {
+                pool_pubkey,
+                owner_pubkey,
+                amount: 60,
+            },
+        );
+        let mut token_keyed_account = KeyedAccount::new(&token_pubkey, false, &mut token_account);
+
+        let to_pubkey = Pubkey::new_rand();
+        let mut to_account = Account::default();
+        let mut to_keyed_account = KeyedAccount::new(&to_pubkey, false, &mut to_account);
+
+        let mut owner_account = Account::default();
+        let signers = vec![KeyedAccount::new(&owner_pubkey, true, &mut owner_account)];
+        let stakes = vec![KeyedAccount::new(&stake_pubkey, false, &mut stake_account)];
+
+        // owner must sign, the manager doesn't have to
+        assert_eq!(
+            pool_keyed_account.withdraw(
+                10,
+                &mut token_keyed_account,
+                &mut to_keyed_account,
+                &stakes,
+                &[]
+            ),
+            Err(InstructionError::MissingRequiredSignature)
+        );
+        assert_eq!(
+            pool_keyed_account.withdraw(
+                61,
+                &mut token_keyed_account,
+                &mut to_keyed_account,
+                &stakes,
+                &signers
+            ),
+            Err(InstructionError::InsufficientFunds)
+        );
+        // 50 tokens are worth all 100 lamports of the reserve, which can't be emptied
+        assert_eq!(
+            pool_keyed_account.withdraw(
+                50,
+                &mut token_keyed_account,
+                &mut to_keyed_account,
+                &stakes,
+                &signers
+            ),
+            Err(InstructionError::InsufficientFunds)
+        );
+        assert_eq!(
+            pool_keyed_account.withdraw(
+                10,
+                &mut token_keyed_account,
+                &mut to_keyed_account,
+                &stakes,
+                &signers
+            ),
+            Ok(())
+        );
+        assert_eq!(pool_keyed_account.account.lamports, 80);
+        assert_eq!(to_keyed_account.account.lamports, 20);
+        assert_eq!(
+            StakePoolState::token_account_from(&token_keyed_account.account)
+                .unwrap()
+                .amount,
+            50
+        );
+        assert_eq!(
+            StakePoolState::pool_from(&pool_keyed_account.account)
+                .unwrap()
+                .token_supply,
+            90
+        );
+    }
+
+    #[test]
+    fn test_stake_pool_transfer() {
+        let pool_pubkey = Pubkey::new_rand();
+        let owner_pubkey = Pubkey::new_rand();
+        let from_pubkey = Pubkey::new_rand();
+        let mut from_account = new_token_account(
+            0,
+            PoolTokenAccount {
+                pool_pubkey,
+                owner_pubkey,
+                amount: 10,
+            },
+        );
+        let mut from_keyed_account = KeyedAccount::new(&from_pubkey, false, &mut from_account);
+
+        let to_pubkey = Pubkey::new_rand();
+        let mut to_account = new_token_account(
+            0,
+            PoolTokenAccount {
+                pool_pubkey,
+                ..PoolTokenAccount::default()
+            },
+        );
+        let mut to_keyed_account = KeyedAccount::new(&to_pubkey, false, &mut to_account);
+
+        let mut owner_account = Account::default();
+        let signers = vec![KeyedAccount::new(&owner_pubkey, true, &mut owner_account)];
+
+        assert_eq!(
+            from_keyed_account.transfer(5, &mut to_keyed_account, &[]),
+            Err(InstructionError::MissingRequiredSignature)
+        );
+        assert_eq!(
+            from_keyed_account.transfer(11, &mut to_keyed_account, &signers),
+            Err(InstructionError::InsufficientFunds)
+        );
+        assert_eq!(
+            from_keyed_account.transfer(5, &mut to_keyed_account, &signers),
+            Ok(())
+        );
+        assert_eq!(
+            StakePoolState::token_account_from(&to_account)
+                .unwrap()
+                .amount,
+            5
+        );
+
+        // tokens of another pool
+        let mut other_account = new_token_account(0, PoolTokenAccount::default());
+        let mut other_keyed_account = KeyedAccount::new(&to_pubkey, false, &mut other_account);
+        assert_eq!(
+            from_keyed_account.transfer(5, &mut other_keyed_account, &signers),
+            Err(InstructionError::InvalidArgument)
+        );
+    }
+}
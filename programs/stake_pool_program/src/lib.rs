@@ -0,0 +1,12 @@
+#[macro_export]
+macro_rules! solana_stake_pool_program {
+    () => {
+        (
+            "solana_stake_pool_program".to_string(),
+            solana_stake_pool_api::id(),
+        )
+    };
+}
+
+use solana_stake_pool_api::stake_pool_instruction::process_instruction;
+solana_sdk::solana_entrypoint!(process_instruction);
@@ -12,6 +12,7 @@ use solana_sdk::instruction::{AccountMeta, Instruction, InstructionError};
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::system_instruction;
 use solana_sdk::sysvar;
+use solana_sdk::transaction::Transaction;
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 pub enum VoteInstruction {
@@ -19,14 +20,20 @@ pub enum VoteInstruction {
     /// takes a node_pubkey and commission
     InitializeAccount(Pubkey, u8),
 
-    /// Authorize a voter to send signed votes.
+    /// Authorize a voter to send signed votes, effective at the start of the next epoch
     AuthorizeVoter(Pubkey),
 
     /// A Vote instruction with recent votes
     Vote(Vec<Vote>),
 
-    /// Withdraw some amount of funds
+    /// Withdraw some amount of funds, requires the authorized withdrawer's signature
     Withdraw(u64),
+
+    /// Authorize a new withdrawer, requires the current authorized withdrawer's signature
+    AuthorizeWithdrawer(Pubkey),
+
+    /// Update the node_pubkey, requires the authorized withdrawer's signature
+    UpdateNode(Pubkey),
 }
 
 fn initialize_account(vote_pubkey: &Pubkey, node_pubkey: &Pubkey, commission: u8) -> Instruction {
@@ -60,10 +67,10 @@ pub fn create_account(
 
 fn metas_for_authorized_signer(
     vote_pubkey: &Pubkey,
-    authorized_voter_pubkey: &Pubkey, // currently authorized
+    authorized_signer: &Pubkey, // currently authorized
     other_params: &[AccountMeta],
 ) -> Vec<AccountMeta> {
-    let is_own_signer = authorized_voter_pubkey == vote_pubkey;
+    let is_own_signer = authorized_signer == vote_pubkey;
 
     // vote account
     let mut account_metas = vec![AccountMeta::new(*vote_pubkey, is_own_signer)];
@@ -74,7 +81,7 @@ fn metas_for_authorized_signer(
 
     // append signer at the end
     if !is_own_signer {
-        account_metas.push(AccountMeta::new_credit_only(*authorized_voter_pubkey, true)) // signer
+        account_metas.push(AccountMeta::new_credit_only(*authorized_signer, true)) // signer
     }
 
     account_metas
@@ -85,7 +92,14 @@ pub fn authorize_voter(
     authorized_voter_pubkey: &Pubkey, // currently authorized
     new_authorized_voter_pubkey: &Pubkey,
 ) -> Instruction {
-    let account_metas = metas_for_authorized_signer(vote_pubkey, authorized_voter_pubkey, &[]);
+    let account_metas = metas_for_authorized_signer(
+        vote_pubkey,
+        authorized_voter_pubkey,
+        &[
+            // request clock sysvar account after vote_pubkey
+            AccountMeta::new_credit_only(sysvar::clock::id(), false),
+        ],
+    );
 
     Instruction::new(
         id(),
@@ -113,15 +127,88 @@ pub fn vote(
     Instruction::new(id(), &VoteInstruction::Vote(recent_votes), account_metas)
 }
 
-pub fn withdraw(vote_pubkey: &Pubkey, lamports: u64, to_pubkey: &Pubkey) -> Instruction {
-    let account_metas = vec![
-        AccountMeta::new(*vote_pubkey, true),
-        AccountMeta::new_credit_only(*to_pubkey, false),
-    ];
+pub fn authorize_withdrawer(
+    vote_pubkey: &Pubkey,
+    authorized_withdrawer_pubkey: &Pubkey, // currently authorized
+    new_authorized_withdrawer_pubkey: &Pubkey,
+) -> Instruction {
+    let account_metas = metas_for_authorized_signer(vote_pubkey, authorized_withdrawer_pubkey, &[]);
+
+    Instruction::new(
+        id(),
+        &VoteInstruction::AuthorizeWithdrawer(*new_authorized_withdrawer_pubkey),
+        account_metas,
+    )
+}
+
+pub fn update_node(
+    vote_pubkey: &Pubkey,
+    authorized_withdrawer_pubkey: &Pubkey,
+    node_pubkey: &Pubkey,
+) -> Instruction {
+    let account_metas = metas_for_authorized_signer(vote_pubkey, authorized_withdrawer_pubkey, &[]);
+
+    Instruction::new(
+        id(),
+        &VoteInstruction::UpdateNode(*node_pubkey),
+        account_metas,
+    )
+}
+
+pub fn withdraw(
+    vote_pubkey: &Pubkey,
+    authorized_withdrawer_pubkey: &Pubkey,
+    lamports: u64,
+    to_pubkey: &Pubkey,
+) -> Instruction {
+    let account_metas = metas_for_authorized_signer(
+        vote_pubkey,
+        authorized_withdrawer_pubkey,
+        &[AccountMeta::new_credit_only(*to_pubkey, false)],
+    );
 
     Instruction::new(id(), &VoteInstruction::Withdraw(lamports), account_metas)
 }
 
+/// Returns the vote account, the authorized voter that signed, and the votes of the
+///  first Vote instruction in `transaction`, if all of the transaction's signatures verify
+pub fn parse_vote_transaction(transaction: &Transaction) -> Option<(Pubkey, Pubkey, Vec<Vote>)> {
+    let message = transaction.message();
+    let message_data = transaction.message_data();
+    if transaction.signatures.len() != message.header.num_required_signatures as usize
+        || !transaction.verify_refs()
+        || transaction
+            .signatures
+            .iter()
+            .zip(message.account_keys.iter())
+            .any(|(signature, pubkey)| !signature.verify(pubkey.as_ref(), &message_data))
+    {
+        return None;
+    }
+
+    message
+        .instructions
+        .iter()
+        .enumerate()
+        .find_map(|(i, instruction)| {
+            if *instruction.program_id(&message.account_keys) != id() {
+                return None;
+            }
+            match deserialize(&instruction.data).ok()? {
+                VoteInstruction::Vote(votes) => {
+                    let vote_pubkey = transaction.key(i, 0)?;
+                    // the authorized voter signs after the sysvars, unless it's the
+                    //  vote account itself
+                    let voter_pubkey = transaction
+                        .signer_key(i, 3)
+                        .or_else(|| transaction.signer_key(i, 0))?;
+                    Some((*vote_pubkey, *voter_pubkey, votes))
+                }
+                _ => None,
+            }
+        })
+}
+
 pub fn process_instruction(
     _program_id: &Pubkey,
     keyed_accounts: &mut [KeyedAccount],
@@ -146,7 +233,17 @@ pub fn process_instruction(
             vote_state::initialize_account(me, &node_pubkey, commission)
         }
         VoteInstruction::AuthorizeVoter(voter_pubkey) => {
-            vote_state::authorize_voter(me, rest, &voter_pubkey)
+            if rest.is_empty() {
+                Err(InstructionError::InvalidInstructionData)?;
+            }
+            let (clock, other_signers) = rest.split_at_mut(1);
+
+            vote_state::authorize_voter(
+                me,
+                other_signers,
+                &voter_pubkey,
+                &sysvar::clock::from_keyed_account(&clock[0])?,
+            )
         }
         VoteInstruction::Vote(votes) => {
             datapoint_warn!("vote-native", ("count", 1, i64));
@@ -167,8 +264,13 @@ pub fn process_instruction(
             if rest.is_empty() {
                 Err(InstructionError::InvalidInstructionData)?;
             }
-            vote_state::withdraw(me, lamports, &mut rest[0])
+            let (to, other_signers) = rest.split_at_mut(1);
+            vote_state::withdraw(me, other_signers, lamports, &mut to[0])
+        }
+        VoteInstruction::AuthorizeWithdrawer(withdrawer_pubkey) => {
+            vote_state::authorize_withdrawer(me, rest, &withdrawer_pubkey)
         }
+        VoteInstruction::UpdateNode(node_pubkey) => vote_state::update_node(me, rest, &node_pubkey),
     }
 }
 
@@ -176,6 +278,8 @@ pub fn process_instruction(
 mod tests {
     use super::*;
     use solana_sdk::account::Account;
+    use solana_sdk::hash::Hash;
+    use solana_sdk::signature::{Keypair, KeypairUtil};
 
     // these are for 100% coverage in this file
     #[test]
@@ -244,6 +348,82 @@ mod tests {
             )),
             Err(InstructionError::InvalidAccountData),
         );
+        assert_eq!(
+            process_instruction(&authorize_withdrawer(
+                &Pubkey::default(),
+                &Pubkey::default(),
+                &Pubkey::default(),
+            )),
+            Err(InstructionError::InvalidAccountData),
+        );
+        assert_eq!(
+            process_instruction(&update_node(
+                &Pubkey::default(),
+                &Pubkey::default(),
+                &Pubkey::default(),
+            )),
+            Err(InstructionError::InvalidAccountData),
+        );
+        assert_eq!(
+            process_instruction(&withdraw(
+                &Pubkey::default(),
+                &Pubkey::default(),
+                0,
+                &Pubkey::default(),
+            )),
+            Err(InstructionError::InvalidAccountData),
+        );
+    }
+
+    #[test]
+    fn test_parse_vote_transaction() {
+        let vote_pubkey = Pubkey::new_rand();
+        let voter_keypair = Keypair::new();
+        let votes = vec![Vote::new(1, Hash::default())];
+
+        let mut transaction = Transaction::new_signed_instructions(
+            &[&voter_keypair],
+            vec![vote(&vote_pubkey, &voter_keypair.pubkey(), votes.clone())],
+            Hash::default(),
+        );
+        assert_eq!(
+            parse_vote_transaction(&transaction),
+            Some((vote_pubkey, voter_keypair.pubkey(), votes.clone()))
+        );
+
+        // the vote account can be its own voter
+        let vote_keypair = Keypair::new();
+        let own_transaction = Transaction::new_signed_instructions(
+            &[&vote_keypair],
+            vec![vote(
+                &vote_keypair.pubkey(),
+                &vote_keypair.pubkey(),
+                votes.clone(),
+            )],
+            Hash::default(),
+        );
+        assert_eq!(
+            parse_vote_transaction(&own_transaction),
+            Some((vote_keypair.pubkey(), vote_keypair.pubkey(), votes))
+        );
+
+        // tampered votes fail signature verification
+        transaction.message.instructions[0].data =
+            bincode::serialize(&VoteInstruction::Vote(vec![Vote::new(2, Hash::default())]))
+                .unwrap();
+        assert_eq!(parse_vote_transaction(&transaction), None);
+
+        // not a vote
+        let transaction = Transaction::new_signed_instructions(
+            &[&voter_keypair],
+            vec![authorize_voter(
+                &vote_pubkey,
+                &voter_keypair.pubkey(),
+                &Pubkey::new_rand(),
+            )],
+            Hash::default(),
+        );
+        assert_eq!(parse_vote_transaction(&transaction), None);
     }
 
     #[test]
@@ -21,6 +21,9 @@ pub const INITIAL_LOCKOUT: usize = 2;
 //  smaller numbers makes
 pub const MAX_EPOCH_CREDITS_HISTORY: usize = 64;
 
+// Maximum number of authorized voter changes to keep around
+pub const MAX_AUTHORIZED_VOTER_HISTORY: usize = 8;
+
 #[derive(Serialize, Default, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
 pub struct Vote {
     /// A vote for height slot
@@ -68,7 +71,13 @@ impl Lockout {
 pub struct VoteState {
     pub votes: VecDeque<Lockout>,
     pub node_pubkey: Pubkey,
-    pub authorized_voter_pubkey: Pubkey,
+
+    /// history of authorized voters, ordered by epoch
+    ///  each tuple is (Epoch, Pubkey), Pubkey may sign votes from Epoch onward
+    authorized_voters: Vec<(Epoch, Pubkey)>,
+
+    /// the signer for withdrawals and node identity changes
+    pub authorized_withdrawer_pubkey: Pubkey,
     /// fraction of std::u8::MAX that represents what part of a rewards
     ///  payout should be given to this VoteAccount
     pub commission: u8,
@@ -91,7 +100,8 @@ impl VoteState {
     pub fn new(vote_pubkey: &Pubkey, node_pubkey: &Pubkey, commission: u8) -> Self {
         Self {
             node_pubkey: *node_pubkey,
-            authorized_voter_pubkey: *vote_pubkey,
+            authorized_voters: vec![(0, *vote_pubkey)],
+            authorized_withdrawer_pubkey: *vote_pubkey,
             commission,
             ..VoteState::default()
         }
@@ -104,6 +114,7 @@ impl VoteState {
         vote_state.votes = VecDeque::from(vec![Lockout::default(); MAX_LOCKOUT_HISTORY]);
         vote_state.root_slot = Some(std::u64::MAX);
         vote_state.epoch_credits = vec![(0, 0, 0); MAX_EPOCH_CREDITS_HISTORY];
+        vote_state.authorized_voters = vec![(0, Pubkey::default()); MAX_AUTHORIZED_VOTER_HISTORY];
         serialized_size(&vote_state).unwrap() as usize
     }
 
@@ -148,6 +159,37 @@ impl VoteState {
         }
     }
 
+    /// The voter authorized to sign votes in the given epoch
+    pub fn authorized_voter(&self, epoch: Epoch) -> Option<&Pubkey> {
+        self.authorized_voters
+            .iter()
+            .rev()
+            .find(|(start_epoch, _)| *start_epoch <= epoch)
+            .map(|(_, authorized_voter)| authorized_voter)
+    }
+
+    /// History of authorized voters, including any change scheduled for a future epoch.
+    /// Each tuple of (Epoch, Pubkey) is the voter authorized from that Epoch onward
+    pub fn authorized_voters(&self) -> impl Iterator<Item = &(Epoch, Pubkey)> {
+        self.authorized_voters.iter()
+    }
+
+    pub fn is_uninitialized(&self) -> bool {
+        self.authorized_voters.is_empty()
+    }
+
+    /// Schedule authorized_voter to take over at target_epoch, replacing any change
+    ///  already scheduled for that epoch or later
+    fn set_authorized_voter(&mut self, authorized_voter: &Pubkey, target_epoch: Epoch) {
+        self.authorized_voters
+            .retain(|(start_epoch, _)| *start_epoch < target_epoch);
+        self.authorized_voters
+            .push((target_epoch, *authorized_voter));
+        if self.authorized_voters.len() > MAX_AUTHORIZED_VOTER_HISTORY {
+            self.authorized_voters.remove(0);
+        }
+    }
+
     pub fn process_votes(&mut self, votes: &[Vote], slot_hashes: &[(Slot, Hash)], epoch: Epoch) {
         votes
             .iter()
@@ -276,18 +318,13 @@ impl VoteState {
     }
 }
 
-/// Authorize the given pubkey to sign votes. This may be called multiple times,
-/// but will implicitly withdraw authorization from the previously authorized
-/// voter. The default voter is the owner of the vote account's pubkey.
-pub fn authorize_voter(
-    vote_account: &mut KeyedAccount,
+// check that the vote account or one of the other signers is the authorized key
+fn verify_authorized_signer(
+    authorized: &Pubkey,
+    vote_account: &KeyedAccount,
     other_signers: &[KeyedAccount],
-    authorized_voter_pubkey: &Pubkey,
 ) -> Result<(), InstructionError> {
-    let mut vote_state: VoteState = vote_account.state()?;
-
-    // clock authorized signer must say "yay"
-    let authorized = Some(&vote_state.authorized_voter_pubkey);
+    let authorized = Some(authorized);
     if vote_account.signer_key() != authorized
         && other_signers
             .iter()
@@ -295,20 +332,85 @@ pub fn authorize_voter(
     {
         return Err(InstructionError::MissingRequiredSignature);
     }
+    Ok(())
+}
 
-    vote_state.authorized_voter_pubkey = *authorized_voter_pubkey;
+/// Authorize the given pubkey to sign votes starting at the next epoch. This may be
+/// called multiple times, but will implicitly withdraw authorization from the previously
+/// authorized voter at the epoch boundary, which gives the validator time to pick up
+/// the new keypair. The default voter is the owner of the vote account's pubkey.
+pub fn authorize_voter(
+    vote_account: &mut KeyedAccount,
+    other_signers: &[KeyedAccount],
+    authorized_voter_pubkey: &Pubkey,
+    clock: &Clock,
+) -> Result<(), InstructionError> {
+    let mut vote_state: VoteState = vote_account.state()?;
+
+    // current authorized signer must say "yay"
+    let authorized = vote_state
+        .authorized_voter(clock.epoch)
+        .cloned()
+        .ok_or(InstructionError::UninitializedAccount)?;
+    verify_authorized_signer(&authorized, vote_account, other_signers)?;
+
+    vote_state.set_authorized_voter(authorized_voter_pubkey, clock.epoch + 1);
     vote_account.set_state(&vote_state)
 }
 
-/// Withdraw funds from the vote account
+/// Authorize the given pubkey to withdraw funds and update the node identity.
+/// Like authorize_voter(), this implicitly removes authorization from the
+/// previously authorized withdrawer, which defaults to the vote account's pubkey.
+pub fn authorize_withdrawer(
+    vote_account: &mut KeyedAccount,
+    other_signers: &[KeyedAccount],
+    authorized_withdrawer_pubkey: &Pubkey,
+) -> Result<(), InstructionError> {
+    let mut vote_state: VoteState = vote_account.state()?;
+
+    verify_authorized_signer(
+        &vote_state.authorized_withdrawer_pubkey,
+        vote_account,
+        other_signers,
+    )?;
+
+    vote_state.authorized_withdrawer_pubkey = *authorized_withdrawer_pubkey;
+    vote_account.set_state(&vote_state)
+}
+
+/// Update the node_pubkey, requires signature of the authorized withdrawer
+pub fn update_node(
+    vote_account: &mut KeyedAccount,
+    other_signers: &[KeyedAccount],
+    node_pubkey: &Pubkey,
+) -> Result<(), InstructionError> {
+    let mut vote_state: VoteState = vote_account.state()?;
+
+    verify_authorized_signer(
+        &vote_state.authorized_withdrawer_pubkey,
+        vote_account,
+        other_signers,
+    )?;
+
+    vote_state.node_pubkey = *node_pubkey;
+    vote_account.set_state(&vote_state)
+}
+
+/// Withdraw funds from the vote account, requires signature of the authorized withdrawer
 pub fn withdraw(
     vote_account: &mut KeyedAccount,
+    other_signers: &[KeyedAccount],
     lamports: u64,
     to_account: &mut KeyedAccount,
 ) -> Result<(), InstructionError> {
-    if vote_account.signer_key().is_none() {
-        return Err(InstructionError::MissingRequiredSignature);
-    }
+    let vote_state: VoteState = vote_account.state()?;
+
+    verify_authorized_signer(
+        &vote_state.authorized_withdrawer_pubkey,
+        vote_account,
+        other_signers,
+    )?;
+
     if vote_account.account.lamports < lamports {
         return Err(InstructionError::InsufficientFunds);
     }
@@ -327,7 +429,7 @@ pub fn initialize_account(
 ) -> Result<(), InstructionError> {
     let vote_state: VoteState = vote_account.state()?;
 
-    if vote_state.authorized_voter_pubkey != Pubkey::default() {
+    if !vote_state.is_uninitialized() {
         return Err(InstructionError::AccountAlreadyInitialized);
     }
     vote_account.set_state(&VoteState::new(
@@ -346,19 +448,12 @@ pub fn process_votes(
 ) -> Result<(), InstructionError> {
     let mut vote_state: VoteState = vote_account.state()?;
 
-    if vote_state.authorized_voter_pubkey == Pubkey::default() {
-        return Err(InstructionError::UninitializedAccount);
-    }
-
-    let authorized = Some(&vote_state.authorized_voter_pubkey);
-    // find a signer that matches the authorized_voter_pubkey
-    if vote_account.signer_key() != authorized
-        && other_signers
-            .iter()
-            .all(|account| account.signer_key() != authorized)
-    {
-        return Err(InstructionError::MissingRequiredSignature);
-    }
+    // find a signer that matches the voter authorized for this epoch
+    let authorized = vote_state
+        .authorized_voter(clock.epoch)
+        .cloned()
+        .ok_or(InstructionError::UninitializedAccount)?;
+    verify_authorized_signer(&authorized, vote_account, other_signers)?;
 
     vote_state.process_votes(&votes, slot_hashes, clock.epoch);
     vote_account.set_state(&vote_state)
@@ -467,7 +562,7 @@ mod tests {
         let (vote_pubkey, vote_account) = create_test_account();
 
         let vote_state: VoteState = vote_account.state().unwrap();
-        assert_eq!(vote_state.authorized_voter_pubkey, vote_pubkey);
+        assert_eq!(vote_state.authorized_voter(0), Some(&vote_pubkey));
         assert!(vote_state.votes.is_empty());
     }
 
@@ -537,12 +632,13 @@ mod tests {
         );
         assert_eq!(res, Ok(()));
 
-        // another voter
+        // another voter, takes over next epoch
         let authorized_voter_pubkey = Pubkey::new_rand();
         let res = authorize_voter(
             &mut KeyedAccount::new(&vote_pubkey, false, &mut vote_account),
             &[],
             &authorized_voter_pubkey,
+            &Clock::default(),
         );
         assert_eq!(res, Err(InstructionError::MissingRequiredSignature));
 
@@ -550,8 +646,25 @@ mod tests {
             &mut KeyedAccount::new(&vote_pubkey, true, &mut vote_account),
             &[],
             &authorized_voter_pubkey,
+            &Clock::default(),
+        );
+        assert_eq!(res, Ok(()));
+
+        // still signed by the vote account for the rest of this epoch
+        let vote = Vote::new(2, Hash::default());
+        let res = process_votes(
+            &mut KeyedAccount::new(&vote_pubkey, true, &mut vote_account),
+            &[(vote.slot, vote.hash)],
+            &Clock::default(),
+            &[],
+            &[vote],
         );
         assert_eq!(res, Ok(()));
+
+        let clock = Clock {
+            epoch: 1,
+            ..Clock::default()
+        };
         // verify authorized_voter_pubkey can authorize authorized_voter_pubkey ;)
         let res = authorize_voter(
             &mut KeyedAccount::new(&vote_pubkey, false, &mut vote_account),
@@ -561,26 +674,27 @@ mod tests {
                 &mut Account::default(),
             )],
             &authorized_voter_pubkey,
+            &clock,
         );
         assert_eq!(res, Ok(()));
 
         // not signed by authorized voter
-        let vote = Vote::new(2, Hash::default());
+        let vote = Vote::new(3, Hash::default());
         let res = process_votes(
             &mut KeyedAccount::new(&vote_pubkey, true, &mut vote_account),
             &[(vote.slot, vote.hash)],
-            &Clock::default(),
+            &clock,
             &[],
             &[vote],
         );
         assert_eq!(res, Err(InstructionError::MissingRequiredSignature));
 
         // signed by authorized voter
-        let vote = Vote::new(2, Hash::default());
+        let vote = Vote::new(3, Hash::default());
         let res = process_votes(
             &mut KeyedAccount::new(&vote_pubkey, false, &mut vote_account),
             &[(vote.slot, vote.hash)],
-            &Clock::default(),
+            &clock,
             &[KeyedAccount::new(
                 &authorized_voter_pubkey,
                 true,
@@ -591,6 +705,39 @@ mod tests {
         assert_eq!(res, Ok(()));
     }
 
+    #[test]
+    fn test_vote_state_authorized_voter_history() {
+        let voter_pubkey = Pubkey::new_rand();
+        let mut vote_state = VoteState::new(&voter_pubkey, &Pubkey::new_rand(), 0);
+        assert_eq!(vote_state.authorized_voter(0), Some(&voter_pubkey));
+        assert_eq!(vote_state.authorized_voter(42), Some(&voter_pubkey));
+
+        let new_voter_pubkey = Pubkey::new_rand();
+        vote_state.set_authorized_voter(&new_voter_pubkey, 2);
+        assert_eq!(vote_state.authorized_voter(1), Some(&voter_pubkey));
+        assert_eq!(vote_state.authorized_voter(2), Some(&new_voter_pubkey));
+
+        // a second change for the same epoch replaces the pending one
+        let newer_voter_pubkey = Pubkey::new_rand();
+        vote_state.set_authorized_voter(&newer_voter_pubkey, 2);
+        assert_eq!(vote_state.authorized_voter(2), Some(&newer_voter_pubkey));
+        assert_eq!(vote_state.authorized_voters().count(), 2);
+
+        // only the most recent changes are kept
+        for epoch in 3..(MAX_AUTHORIZED_VOTER_HISTORY as u64 + 10) {
+            vote_state.set_authorized_voter(&Pubkey::new_rand(), epoch);
+        }
+        assert_eq!(
+            vote_state.authorized_voters().count(),
+            MAX_AUTHORIZED_VOTER_HISTORY
+        );
+        assert_eq!(vote_state.authorized_voter(0), None);
+
+        // history fits in the account
+        let mut vote_account = Account::new(1, VoteState::size_of(), &id());
+        assert!(vote_state.to(&mut vote_account).is_some());
+    }
+
     #[test]
     fn test_vote_without_initialization() {
         let vote_pubkey = Pubkey::new_rand();
@@ -806,6 +953,7 @@ mod tests {
         // unsigned
         let res = withdraw(
             &mut KeyedAccount::new(&vote_pubkey, false, &mut vote_account),
+            &[],
             0,
             &mut KeyedAccount::new(&Pubkey::new_rand(), false, &mut Account::default()),
         );
@@ -814,6 +962,7 @@ mod tests {
         // insufficient funds
         let res = withdraw(
             &mut KeyedAccount::new(&vote_pubkey, true, &mut vote_account),
+            &[],
             101,
             &mut KeyedAccount::new(&Pubkey::new_rand(), false, &mut Account::default()),
         );
@@ -824,6 +973,7 @@ mod tests {
         let lamports = vote_account.lamports;
         let res = withdraw(
             &mut KeyedAccount::new(&vote_pubkey, true, &mut vote_account),
+            &[],
             lamports,
             &mut KeyedAccount::new(&Pubkey::new_rand(), false, &mut to_account),
         );
@@ -832,6 +982,114 @@ mod tests {
         assert_eq!(to_account.lamports, lamports);
     }
 
+    #[test]
+    fn test_vote_state_authorize_withdrawer() {
+        let (vote_pubkey, mut vote_account) = create_test_account();
+        let authorized_withdrawer_pubkey = Pubkey::new_rand();
+
+        // unsigned
+        let res = authorize_withdrawer(
+            &mut KeyedAccount::new(&vote_pubkey, false, &mut vote_account),
+            &[],
+            &authorized_withdrawer_pubkey,
+        );
+        assert_eq!(res, Err(InstructionError::MissingRequiredSignature));
+
+        // the authorized voter can't authorize a withdrawer
+        let authorized_voter_pubkey = Pubkey::new_rand();
+        authorize_voter(
+            &mut KeyedAccount::new(&vote_pubkey, true, &mut vote_account),
+            &[],
+            &authorized_voter_pubkey,
+            &Clock::default(),
+        )
+        .unwrap();
+        let res = authorize_withdrawer(
+            &mut KeyedAccount::new(&vote_pubkey, false, &mut vote_account),
+            &[KeyedAccount::new(
+                &authorized_voter_pubkey,
+                true,
+                &mut Account::default(),
+            )],
+            &authorized_withdrawer_pubkey,
+        );
+        assert_eq!(res, Err(InstructionError::MissingRequiredSignature));
+
+        // signed by the vote account, the default withdrawer
+        let res = authorize_withdrawer(
+            &mut KeyedAccount::new(&vote_pubkey, true, &mut vote_account),
+            &[],
+            &authorized_withdrawer_pubkey,
+        );
+        assert_eq!(res, Ok(()));
+        let vote_state: VoteState = vote_account.state().unwrap();
+        assert_eq!(
+            vote_state.authorized_withdrawer_pubkey,
+            authorized_withdrawer_pubkey
+        );
+
+        // the vote account key no longer withdraws
+        let res = withdraw(
+            &mut KeyedAccount::new(&vote_pubkey, true, &mut vote_account),
+            &[],
+            1,
+            &mut KeyedAccount::new(&Pubkey::new_rand(), false, &mut Account::default()),
+        );
+        assert_eq!(res, Err(InstructionError::MissingRequiredSignature));
+
+        // but the authorized withdrawer does
+        let mut to_account = Account::default();
+        let res = withdraw(
+            &mut KeyedAccount::new(&vote_pubkey, false, &mut vote_account),
+            &[KeyedAccount::new(
+                &authorized_withdrawer_pubkey,
+                true,
+                &mut Account::default(),
+            )],
+            1,
+            &mut KeyedAccount::new(&Pubkey::new_rand(), false, &mut to_account),
+        );
+        assert_eq!(res, Ok(()));
+        assert_eq!(to_account.lamports, 1);
+    }
+
+    #[test]
+    fn test_vote_state_update_node() {
+        let (vote_pubkey, mut vote_account) = create_test_account();
+        let node_pubkey = Pubkey::new_rand();
+
+        // unsigned
+        let res = update_node(
+            &mut KeyedAccount::new(&vote_pubkey, false, &mut vote_account),
+            &[],
+            &node_pubkey,
+        );
+        assert_eq!(res, Err(InstructionError::MissingRequiredSignature));
+        let vote_state: VoteState = vote_account.state().unwrap();
+        assert!(vote_state.node_pubkey != node_pubkey);
+
+        // signed by the authorized withdrawer
+        let authorized_withdrawer_pubkey = Pubkey::new_rand();
+        authorize_withdrawer(
+            &mut KeyedAccount::new(&vote_pubkey, true, &mut vote_account),
+            &[],
+            &authorized_withdrawer_pubkey,
+        )
+        .unwrap();
+        let res = update_node(
+            &mut KeyedAccount::new(&vote_pubkey, false, &mut vote_account),
+            &[KeyedAccount::new(
+                &authorized_withdrawer_pubkey,
+                true,
+                &mut Account::default(),
+            )],
+            &node_pubkey,
+        );
+        assert_eq!(res, Ok(()));
+        let vote_state: VoteState = vote_account.state().unwrap();
+        assert_eq!(vote_state.node_pubkey, node_pubkey);
+    }
+
     #[test]
     fn test_vote_state_epoch_credits() {
         let mut vote_state = VoteState::default();
@@ -6,7 +6,6 @@ use crate::accounts_index::{AccountsIndex, Fork};
 use crate::append_vec::StoredAccount;
 use crate::blockhash_queue::BlockhashQueue;
 use crate::message_processor::has_duplicates;
-use bincode::serialize;
 use log::*;
 use rayon::slice::ParallelSliceMut;
 use solana_metrics::inc_new_counter_error;
@@ -285,6 +284,12 @@ impl Accounts {
             .collect()
     }
 
+    pub fn load_by_fork(&self, fork: Fork) -> Vec<(Pubkey, Account)> {
+        self.scan_fork(fork, |stored_account| {
+            Some((stored_account.meta.pubkey, stored_account.clone_account()))
+        })
+    }
+
     pub fn load_by_program_fork(&self, fork: Fork, program_id: &Pubkey) -> Vec<(Pubkey, Account)> {
         self.scan_fork(fork, |stored_account| {
             if stored_account.balance.owner == *program_id {
@@ -313,6 +318,23 @@ impl Accounts {
         )
     }
 
+    /// Returns every account with lamports visible from `ancestors`, ordered by pubkey
+    pub fn load_all(&self, ancestors: &HashMap<Fork, usize>) -> Vec<(Pubkey, Account)> {
+        let mut accounts = self.accounts_db.scan_accounts(
+            ancestors,
+            |collector: &mut Vec<(Pubkey, Account)>, option| {
+                if let Some(data) = option
+                    .filter(|(_, account, _)| account.lamports != 0)
+                    .map(|(pubkey, account, _fork)| (*pubkey, account))
+                {
+                    collector.push(data)
+                }
+            },
+        );
+        accounts.sort_by_key(|(pubkey, _)| *pubkey);
+        accounts
+    }
+
     /// Slow because lock is held for 1 operation instead of many
     pub fn store_slow(&self, fork: Fork, pubkey: &Pubkey, account: &Account) {
         self.accounts_db.store(fork, &[(pubkey, account)]);
@@ -428,17 +450,10 @@ impl Accounts {
         }
     }
 
-    fn hash_account(stored_account: &StoredAccount) -> Hash {
-        let mut hasher = Hasher::default();
-        hasher.hash(&serialize(&stored_account.balance).unwrap());
-        hasher.hash(stored_account.data);
-        hasher.result()
-    }
-
     pub fn hash_internal_state(&self, fork_id: Fork) -> Option<Hash> {
         let account_hashes = self.scan_fork(fork_id, |stored_account| {
             if !sysvar::check_id(&stored_account.balance.owner) {
-                Some(Self::hash_account(stored_account))
+                Some(AccountsDB::hash_stored_account(stored_account))
             } else {
                 None
             }
@@ -455,6 +470,10 @@ impl Accounts {
         }
     }
 
+    pub fn accounts_hash(&self, ancestors: &HashMap<Fork, usize>) -> Hash {
+        self.accounts_db.get_accounts_hash(ancestors)
+    }
+
     /// This function will prevent multiple threads from modifying the same account state at the
     /// same time
     #[must_use]
@@ -1103,6 +1122,28 @@ mod tests {
         assert_eq!(loaded, vec![]);
     }
 
+    #[test]
+    fn test_load_all() {
+        let accounts = Accounts::new(None);
+        let ancestors = vec![(0, 0), (1, 1)].into_iter().collect();
+
+        let pubkey0 = Pubkey::new_rand();
+        let account0 = Account::new(1, 0, &Pubkey::default());
+        accounts.store_slow(0, &pubkey0, &account0);
+        let pubkey1 = Pubkey::new_rand();
+        accounts.store_slow(0, &pubkey1, &account0);
+        let account1 = Account::new(2, 0, &Pubkey::default());
+        accounts.store_slow(1, &pubkey1, &account1);
+        // zero-lamport accounts are skipped
+        let zero_account = Account::new(0, 0, &Pubkey::default());
+        accounts.store_slow(1, &Pubkey::new_rand(), &zero_account);
+
+        let mut expected = vec![(pubkey0, account0), (pubkey1, account1)];
+        expected.sort_by_key(|(pubkey, _)| *pubkey);
+        assert_eq!(accounts.load_all(&ancestors), expected);
+        assert_eq!(accounts.load_by_fork(1).len(), 2);
+    }
+
     #[test]
     fn test_accounts_account_not_found() {
         let accounts = Accounts::new(None);
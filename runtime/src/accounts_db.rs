@@ -20,7 +20,7 @@
 
 use crate::accounts_index::{AccountsIndex, Fork};
 use crate::append_vec::{AppendVec, StorageMeta, StoredAccount};
-use bincode::{deserialize_from, serialize_into};
+use bincode::{deserialize_from, serialize, serialize_into};
 use fs_extra::dir::CopyOptions;
 use log::*;
 use rand::{thread_rng, Rng};
@@ -31,7 +31,9 @@ use serde::ser::{SerializeMap, Serializer};
 use serde::{Deserialize, Serialize};
 use solana_measure::measure::Measure;
 use solana_sdk::account::{Account, LamportCredit};
+use solana_sdk::hash::{Hash, Hasher};
 use solana_sdk::pubkey::Pubkey;
+use solana_sdk::sysvar;
 use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::io::{BufReader, Cursor, Error as IOError, ErrorKind, Read, Result as IOResult};
@@ -851,6 +853,64 @@ impl AccountsDB {
         self.accounts_index.write().unwrap().add_root(fork)
     }
 
+    fn hash_account_data(
+        pubkey: &Pubkey,
+        lamports: u64,
+        owner: &Pubkey,
+        executable: bool,
+        data: &[u8],
+    ) -> Hash {
+        let mut hasher = Hasher::default();
+        hasher.hash(pubkey.as_ref());
+        hasher.hash(&serialize(&lamports).unwrap());
+        hasher.hash(owner.as_ref());
+        hasher.hash(&serialize(&executable).unwrap());
+        hasher.hash(data);
+        hasher.result()
+    }
+
+    pub fn hash_stored_account(stored_account: &StoredAccount) -> Hash {
+        Self::hash_account_data(
+            &stored_account.meta.pubkey,
+            stored_account.balance.lamports,
+            &stored_account.balance.owner,
+            stored_account.balance.executable,
+            stored_account.data,
+        )
+    }
+
+    pub fn hash_account(pubkey: &Pubkey, account: &Account) -> Hash {
+        Self::hash_account_data(
+            pubkey,
+            account.lamports,
+            &account.owner,
+            account.executable,
+            &account.data,
+        )
+    }
+
+    /// Hash over every account visible from `ancestors`, in pubkey order. Sysvars and
+    /// zero-lamport accounts are left out since a snapshot need not carry them.
+    pub fn get_accounts_hash(&self, ancestors: &HashMap<Fork, usize>) -> Hash {
+        let mut account_hashes: Vec<(Pubkey, Hash)> =
+            self.scan_accounts(ancestors, |collector: &mut Vec<(Pubkey, Hash)>, option| {
+                if let Some((pubkey, account, _fork)) = option {
+                    if account.lamports != 0 && !sysvar::check_id(&account.owner) {
+                        collector.push((*pubkey, Self::hash_account(pubkey, &account)));
+                    }
+                }
+            });
+        self.thread_pool.install(|| {
+            account_hashes.par_sort_by_key(|(pubkey, _)| *pubkey);
+        });
+
+        let mut hasher = Hasher::default();
+        for (_, hash) in account_hashes {
+            hasher.hash(hash.as_ref());
+        }
+        hasher.result()
+    }
+
     pub fn get_storage_entries(&self) -> Vec<Arc<AccountStorageEntry>> {
         let r_storage = self.storage.read().unwrap();
         r_storage
@@ -1116,6 +1176,33 @@ pub mod tests {
         assert_eq!(db0.load_slow(&ancestors, &key), Some((account0, 0)));
     }
 
+    #[test]
+    fn test_accounts_hash() {
+        let key = Pubkey::default();
+        let key0 = Pubkey::new_rand();
+        let key1 = Pubkey::new_rand();
+        let account0 = Account::new(1, 0, &key);
+        let account1 = Account::new(2, 0, &key);
+        let ancestors = vec![(0, 0), (1, 1)].into_iter().collect();
+
+        // the hash doesn't depend on the order accounts were stored in
+        let db0 = AccountsDB::new(None);
+        db0.store(0, &[(&key0, &account0), (&key1, &account1)]);
+        let db1 = AccountsDB::new(None);
+        db1.store(0, &[(&key1, &account1)]);
+        db1.store(1, &[(&key0, &account0)]);
+        let hash = db0.get_accounts_hash(&ancestors);
+        assert_eq!(hash, db1.get_accounts_hash(&ancestors));
+
+        // zero-lamport accounts are left out
+        db1.store(1, &[(&Pubkey::new_rand(), &Account::new(0, 0, &key))]);
+        assert_eq!(hash, db1.get_accounts_hash(&ancestors));
+
+        // but any other change to the visible accounts is not
+        db1.store(1, &[(&key1, &account0)]);
+        assert_ne!(hash, db1.get_accounts_hash(&ancestors));
+    }
+
     fn create_account(
         accounts: &AccountsDB,
         pubkeys: &mut Vec<Pubkey>,
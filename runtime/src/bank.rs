@@ -165,6 +165,9 @@ pub struct Bank {
     /// Hash of this Bank's parent's state
     parent_hash: Hash,
 
+    /// Hash of all accounts visible from this Bank, computed when it is snapshotted
+    accounts_hash: RwLock<Hash>,
+
     /// The number of transactions processed without error
     #[serde(serialize_with = "serialize_atomicusize")]
     #[serde(deserialize_with = "deserialize_atomicusize")]
@@ -305,6 +308,7 @@ impl Bank {
             stakes: RwLock::new(parent.stakes.read().unwrap().clone_with_epoch(0)),
             storage_accounts: RwLock::new(parent.storage_accounts.read().unwrap().clone()),
             parent_hash: parent.hash(),
+            accounts_hash: RwLock::new(Hash::default()),
             collector_id: *collector_id,
             collector_fees: AtomicUsize::new(0),
             ancestors: HashMap::new(),
@@ -1211,6 +1215,11 @@ impl Bank {
             .map(|(account, _)| account)
     }
 
+    /// Return every account visible from this bank, ordered by pubkey
+    pub fn get_all_accounts(&self) -> Vec<(Pubkey, Account)> {
+        self.rc.accounts.load_all(&self.ancestors)
+    }
+
     pub fn get_program_accounts(&self, program_id: &Pubkey) -> Vec<(Pubkey, Account)> {
         self.rc
             .accounts
@@ -1226,6 +1235,11 @@ impl Bank {
             .load_by_program_fork(self.slot(), program_id)
     }
 
+    /// Return every account stored in this bank's fork, ordered by pubkey
+    pub fn get_accounts_modified_since_parent(&self) -> Vec<(Pubkey, Account)> {
+        self.rc.accounts.load_by_fork(self.slot())
+    }
+
     pub fn get_account_modified_since_parent(&self, pubkey: &Pubkey) -> Option<(Account, Fork)> {
         let just_self: HashMap<u64, usize> = vec![(self.slot(), 0)].into_iter().collect();
         self.rc.accounts.load_slow(&just_self, pubkey)
@@ -1274,7 +1288,6 @@ impl Bank {
         if let Some(accounts_delta_hash) = self.rc.accounts.hash_internal_state(self.slot()) {
             let mut signature_count_buf = [0u8; 8];
             LittleEndian::write_u64(&mut signature_count_buf[..], self.signature_count() as u64);
-
             hashv(&[
                 &self.parent_hash.as_ref(),
                 &accounts_delta_hash.as_ref(),
@@ -1285,6 +1298,54 @@ impl Bank {
         }
     }
 
+    /// Compute the hash of all accounts visible from this Bank and keep it with the Bank,
+    ///  so a snapshot of the Bank can later be checked against it
+    pub fn update_accounts_hash(&self) -> Hash {
+        let accounts_hash = self.rc.accounts.accounts_hash(&self.ancestors);
+        *self.accounts_hash.write().unwrap() = accounts_hash;
+        accounts_hash
+    }
+
+    pub fn get_accounts_hash(&self) -> Hash {
+        *self.accounts_hash.read().unwrap()
+    }
+
+    /// Recompute the hash of a Bank restored from a snapshot from its accounts, and
+    ///  check it against `expected_hash`, the hash the cluster voted on for the slot.
+    ///  The full accounts hash the Bank was snapshotted with is recomputed as well
+    pub fn verify_snapshot_bank(&self, expected_hash: &Hash) -> bool {
+        let hash = self.hash_internal_state();
+        if hash != *expected_hash {
+            warn!(
+                "bank hash mismatch for slot {}: {} expected: {}",
+                self.slot(),
+                hash,
+                expected_hash
+            );
+            return false;
+        }
+        if hash != self.hash() {
+            warn!(
+                "bank hash mismatch for slot {}: {} frozen: {}",
+                self.slot(),
+                hash,
+                self.hash()
+            );
+            return false;
+        }
+        let accounts_hash = self.rc.accounts.accounts_hash(&self.ancestors);
+        if accounts_hash != self.get_accounts_hash() {
+            warn!(
+                "accounts hash mismatch for slot {}: {} snapshotted: {}",
+                self.slot(),
+                accounts_hash,
+                self.get_accounts_hash()
+            );
+            return false;
+        }
+        true
+    }
+
     /// Return the number of ticks per slot
     pub fn ticks_per_slot(&self) -> u64 {
         self.ticks_per_slot
@@ -1427,6 +1488,7 @@ impl Bank {
         let bh = self.hash.read().unwrap();
         let dbh = dbank.hash.read().unwrap();
         assert_eq!(*bh, *dbh);
+        assert_eq!(self.get_accounts_hash(), dbank.get_accounts_hash());
 
         let bhq = self.blockhash_queue.read().unwrap();
         let dbhq = dbank.blockhash_queue.read().unwrap();
@@ -2769,6 +2831,8 @@ mod tests {
         let key = Keypair::new();
         bank.deposit(&key.pubkey(), 10);
         assert_eq!(bank.get_balance(&key.pubkey()), 10);
+        bank.freeze();
+        bank.update_accounts_hash();
 
         let len = serialized_size(&bank).unwrap() + serialized_size(&bank.rc).unwrap();
         let mut buf = vec![0u8; len as usize];
@@ -2794,9 +2858,39 @@ mod tests {
             .accounts_from_stream(&mut reader, dbank_paths, copied_accounts.path())
             .unwrap();
         assert_eq!(dbank.get_balance(&key.pubkey()), 10);
+        assert!(dbank.verify_snapshot_bank(&bank.hash()));
         bank.compare_bank(&dbank);
     }
 
+    #[test]
+    fn test_bank_verify_snapshot_bank() {
+        let (genesis_block, _) = create_genesis_block(500);
+        let bank0 = Arc::new(Bank::new(&genesis_block));
+        let bank = new_from_parent(&bank0);
+
+        let key = Keypair::new();
+        bank.deposit(&key.pubkey(), 10);
+        bank.freeze();
+        // freezing only hashes the accounts stored in the slot
+        assert_eq!(bank.get_accounts_hash(), Hash::default());
+        let accounts_hash = bank.update_accounts_hash();
+        assert_eq!(bank.get_accounts_hash(), accounts_hash);
+        assert!(bank.verify_snapshot_bank(&bank.hash()));
+
+        // the hash given by the cluster has the last word
+        assert!(!bank.verify_snapshot_bank(&Hash::default()));
+
+        // a snapshot whose accounts don't add up to its accounts hash is caught
+        *bank.accounts_hash.write().unwrap() = Hash::default();
+        assert!(!bank.verify_snapshot_bank(&bank.hash()));
+        bank.update_accounts_hash();
+
+        // as is a change to the accounts of the slot
+        let hash = bank.hash();
+        bank.store_account(&key.pubkey(), &Account::new(11, 0, &Pubkey::default()));
+        assert!(!bank.verify_snapshot_bank(&hash));
+    }
+
     #[test]
     fn test_check_point_values() {
         let (genesis_block, _) = create_genesis_block(500);
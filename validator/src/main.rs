@@ -1,16 +1,18 @@
 use bzip2::bufread::BzDecoder;
-use clap::{crate_description, crate_name, crate_version, value_t, App, Arg};
+use clap::{crate_description, crate_name, crate_version, value_t, value_t_or_exit, App, Arg};
 use log::*;
 use solana::bank_forks::SnapshotConfig;
 use solana::cluster_info::{Node, FULLNODE_PORT_RANGE};
 use solana::contact_info::ContactInfo;
 use solana::gossip_service::discover;
-use solana::ledger_cleanup_service::DEFAULT_MAX_LEDGER_SLOTS;
+use solana::ledger_cleanup_service::LedgerCleanupConfig;
 use solana::local_vote_signer_service::LocalVoteSignerService;
+use solana::quic_streamer::QuicConfig;
 use solana::service::Service;
 use solana::socketaddr;
 use solana::validator::{Validator, ValidatorConfig};
 use solana_netutil::parse_port_range;
+use solana_sdk::hash::Hash;
 use solana_sdk::signature::{read_keypair, Keypair, KeypairUtil};
 use solana_sdk::timing::Slot;
 use std::fs::{self, File};
@@ -28,6 +30,12 @@ fn port_range_validator(port_range: String) -> Result<(), String> {
     }
 }
 
+fn hash_validator(hash: String) -> Result<(), String> {
+    hash.parse::<Hash>()
+        .map(|_| ())
+        .map_err(|err| format!("Invalid hash: {:?}", err))
+}
+
 fn download_archive(
     rpc_addr: &SocketAddr,
     archive_name: &str,
@@ -49,6 +57,9 @@ fn download_archive(
     let download_start = Instant::now();
 
     let mut response = reqwest::get(&url).map_err(|err| format!("Unable to get: {:?}", err))?;
+    if !response.status().is_success() {
+        return Err(format!("Unable to get {}: {}", url, response.status()));
+    }
     let mut file = File::create(&temp_archive_path)
         .map_err(|err| format!("Unable to create {:?}: {:?}", temp_archive_path, err))?;
     std::io::copy(&mut response, &mut file)
@@ -115,8 +126,23 @@ fn initialize_ledger_path(
     download_archive(&rpc_addr, "genesis.tar.bz2", ledger_path, true)?;
     if !no_snapshot_fetch {
         let _ = fs::remove_file(ledger_path.join("snapshot.tar.bz2"));
-        download_archive(&rpc_addr, "snapshot.tar.bz2", ledger_path, false)
-            .unwrap_or_else(|err| eprintln!("Warning: Unable to fetch snapshot: {:?}", err));
+        let _ = fs::remove_file(ledger_path.join("incremental-snapshot.tar.bz2"));
+        match download_archive(&rpc_addr, "snapshot.tar.bz2", ledger_path, false) {
+            // The fetched snapshot supersedes the bank snapshots left by a previous run
+            Ok(()) => {
+                let _ = fs::remove_dir_all(ledger_path.join("snapshot"));
+            }
+            Err(err) => eprintln!("Warning: Unable to fetch snapshot: {:?}", err),
+        }
+        download_archive(
+            &rpc_addr,
+            "incremental-snapshot.tar.bz2",
+            ledger_path,
+            false,
+        )
+        .unwrap_or_else(|err| {
+            eprintln!("Warning: Unable to fetch incremental snapshot: {:?}", err)
+        });
     }
 
     Ok(())
@@ -153,6 +179,14 @@ fn main() {
                 .takes_value(true)
                 .help("File containing the authorized voting keypair.  Default is an ephemeral keypair"),
         )
+        .arg(
+            Arg::with_name("authorized_voter_keypairs")
+                .long("authorized-voter")
+                .value_name("PATH")
+                .takes_value(true)
+                .multiple(true)
+                .help("Include an additional authorized voter keypair.  May be specified multiple times to hold keypairs for scheduled authorized voter changes"),
+        )
         .arg(
             Arg::with_name("vote_account")
                 .long("vote-account")
@@ -198,6 +232,12 @@ fn main() {
                 .requires("entrypoint")
                 .help("Do not attempt to fetch a snapshot from the cluster entrypoint"),
         )
+        .arg(
+            Arg::with_name("full_replay")
+                .long("full-replay")
+                .takes_value(false)
+                .help("Replay the ledger from genesis instead of starting from the newest local snapshot"),
+        )
         .arg(
             Arg::with_name("no_voting")
                 .long("no-voting")
@@ -230,6 +270,32 @@ fn main() {
                 .takes_value(false)
                 .help("Enable the JSON RPC 'fullnodeExit' API.  Only enable in a debug environment"),
         )
+        .arg(
+            Arg::with_name("enable_rpc_fork_tree")
+                .long("enable-rpc-fork-tree")
+                .takes_value(false)
+                .help("Enable the JSON RPC 'getForkTree' and 'dumpForkTree' APIs, used to debug fork choice"),
+        )
+        .arg(
+            Arg::with_name("enable_tpu_quic")
+                .long("enable-tpu-quic")
+                .takes_value(false)
+                .help("Also accept transactions over QUIC, on the port advertised in gossip"),
+        )
+        .arg(
+            Arg::with_name("max_unstaked_packets_per_second")
+                .long("max-unstaked-packets-per-second")
+                .value_name("PACKETS")
+                .takes_value(true)
+                .help("Cap the TPU packets accepted each second from an unstaked IP address"),
+        )
+        .arg(
+            Arg::with_name("max_staked_packets_per_second")
+                .long("max-staked-packets-per-second")
+                .value_name("PACKETS")
+                .takes_value(true)
+                .help("Cap the TPU packets accepted each second from a staked node's IP address"),
+        )
         .arg(
             Arg::with_name("rpc_drone_addr")
                 .long("rpc-drone-address")
@@ -274,6 +340,23 @@ fn main() {
                 .takes_value(true)
                 .help("Number of slots between generating snapshots"),
         )
+        .arg(
+            clap::Arg::with_name("full_snapshot_interval_slots")
+                .long("full-snapshot-interval-slots")
+                .value_name("FULL_SNAPSHOT_INTERVAL_SLOTS")
+                .takes_value(true)
+                .requires("snapshot_interval_slots")
+                .help("Number of slots between generating full snapshots, snapshots in between only contain the accounts changed since the last full snapshot [default: --snapshot-interval-slots]"),
+        )
+        .arg(
+            clap::Arg::with_name("expected_bank_hash")
+                .long("expected-bank-hash")
+                .value_name("HASH")
+                .takes_value(true)
+                .requires("snapshot_interval_slots")
+                .validator(hash_validator)
+                .help("The cluster's bank hash for the slot of the snapshot to start from, the snapshot is refused if its accounts don't add up to it. Snapshots are neither fetched nor loaded without it"),
+        )
         .arg(
             clap::Arg::with_name("limit_ledger_size")
                 .long("limit-ledger-size")
@@ -281,6 +364,29 @@ fn main() {
                 .requires("snapshot_path")
                 .help("drop older slots in the ledger"),
         )
+        .arg(
+            clap::Arg::with_name("max_ledger_bytes")
+                .long("max-ledger-bytes")
+                .value_name("BYTES")
+                .takes_value(true)
+                .requires("limit_ledger_size")
+                .help("Drop the oldest slots while the ledger uses more than this much disk space"),
+        )
+        .arg(
+            clap::Arg::with_name("min_root_slots")
+                .long("min-root-slots")
+                .value_name("SLOTS")
+                .takes_value(true)
+                .requires("limit_ledger_size")
+                .help("Number of slots below the latest root to always keep in the ledger"),
+        )
+        .arg(
+            clap::Arg::with_name("long_term_storage_path")
+                .long("long-term-storage")
+                .value_name("DIR")
+                .takes_value(true)
+                .help("Upload rooted blocks to this directory and serve them over RPC once they are dropped from the ledger"),
+        )
         .arg(
             clap::Arg::with_name("skip_ledger_verify")
                 .long("skip-ledger-verify")
@@ -306,6 +412,15 @@ fn main() {
     } else {
         Keypair::new()
     };
+    let mut authorized_voter_keypairs = vec![];
+    if let Some(paths) = matches.values_of("authorized_voter_keypairs") {
+        for path in paths {
+            authorized_voter_keypairs.push(Arc::new(read_keypair(path).unwrap_or_else(|err| {
+                eprintln!("{}: Unable to open keypair file: {}", err, path);
+                exit(1);
+            })));
+        }
+    }
     let storage_keypair = if let Some(storage_keypair) = matches.value_of("storage_keypair") {
         read_keypair(storage_keypair).unwrap_or_else(|err| {
             eprintln!("{}: Unable to open keypair file: {}", err, storage_keypair);
@@ -325,10 +440,26 @@ fn main() {
 
     validator_config.dev_sigverify_disabled = matches.is_present("dev_no_sigverify");
     validator_config.dev_halt_at_slot = value_t!(matches, "dev_halt_at_slot", Slot).ok();
+    validator_config.full_replay = matches.is_present("full_replay");
 
     validator_config.voting_disabled = matches.is_present("no_voting");
 
+    if matches.is_present("enable_tpu_quic") {
+        validator_config.tpu_quic_config = Some(QuicConfig::default());
+    }
+
+    validator_config
+        .packet_qos_config
+        .max_unstaked_packets_per_second =
+        value_t!(matches, "max_unstaked_packets_per_second", u64).unwrap_or(0);
+    validator_config
+        .packet_qos_config
+        .max_staked_packets_per_second =
+        value_t!(matches, "max_staked_packets_per_second", u64).unwrap_or(0);
+
     validator_config.rpc_config.enable_fullnode_exit = matches.is_present("enable_rpc_exit");
+    validator_config.rpc_config.enable_fork_tree_export =
+        matches.is_present("enable_rpc_fork_tree");
 
     validator_config.rpc_config.drone_addr = matches.value_of("rpc_drone_addr").map(|address| {
         solana_netutil::parse_host_port(address).expect("failed to parse drone address")
@@ -356,16 +487,32 @@ fn main() {
     validator_config.snapshot_config = matches.value_of("snapshot_interval_slots").map(|s| {
         let snapshots_dir = ledger_path.clone().join("snapshot");
         fs::create_dir_all(&snapshots_dir).expect("Failed to create snapshots directory");
-        SnapshotConfig::new(
+        let snapshot_interval_slots = s.parse::<usize>().unwrap();
+        let mut snapshot_config = SnapshotConfig::new(
             snapshots_dir,
             ledger_path.clone(),
-            s.parse::<usize>().unwrap(),
-        )
+            snapshot_interval_slots,
+            value_t!(matches, "full_snapshot_interval_slots", usize)
+                .unwrap_or(snapshot_interval_slots),
+        );
+        snapshot_config.set_expected_bank_hash(value_t!(matches, "expected_bank_hash", Hash).ok());
+        snapshot_config
     });
 
     if matches.is_present("limit_ledger_size") {
-        validator_config.max_ledger_slots = Some(DEFAULT_MAX_LEDGER_SLOTS);
+        let mut ledger_cleanup_config = LedgerCleanupConfig::default();
+        if matches.is_present("max_ledger_bytes") {
+            ledger_cleanup_config.max_ledger_bytes =
+                Some(value_t_or_exit!(matches, "max_ledger_bytes", u64));
+        }
+        if matches.is_present("min_root_slots") {
+            ledger_cleanup_config.min_root_slots = value_t_or_exit!(matches, "min_root_slots", u64);
+        }
+        validator_config.ledger_cleanup_config = Some(ledger_cleanup_config);
     }
+    validator_config.long_term_storage_path = matches
+        .value_of("long_term_storage_path")
+        .map(PathBuf::from);
     let cluster_entrypoint = matches.value_of("entrypoint").map(|entrypoint| {
         let entrypoint_addr = solana_netutil::parse_host_port(entrypoint)
             .expect("failed to parse entrypoint address");
@@ -393,11 +540,17 @@ fn main() {
         .map(PathBuf::from);
 
     if let Some(ref entrypoint_addr) = cluster_entrypoint {
+        // A fetched snapshot can only be loaded once it checks out against the cluster's hash
+        let no_snapshot_fetch =
+            matches.is_present("no_snapshot_fetch") || !matches.is_present("expected_bank_hash");
+        if !matches.is_present("no_snapshot_fetch") && no_snapshot_fetch {
+            eprintln!("Warning: Not fetching a snapshot without --expected-bank-hash to verify it");
+        }
         initialize_ledger_path(
             entrypoint_addr,
             &gossip_addr,
             &ledger_path,
-            matches.is_present("no_snapshot_fetch"),
+            no_snapshot_fetch,
         )
         .unwrap_or_else(|err| {
             eprintln!("Failed to download ledger: {}", err);
@@ -427,12 +580,14 @@ fn main() {
 
     let verify_ledger = !matches.is_present("skip_ledger_verify");
 
+    authorized_voter_keypairs.insert(0, Arc::new(voting_keypair));
+
     let validator = Validator::new(
         node,
         &Arc::new(keypair),
         &ledger_path,
         &vote_account,
-        &Arc::new(voting_keypair),
+        &authorized_voter_keypairs,
         &Arc::new(storage_keypair),
         cluster_entrypoint.as_ref(),
         verify_ledger,
@@ -28,8 +28,11 @@ use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::{read_keypair, Keypair, KeypairUtil, Signature};
 use solana_sdk::system_instruction::SystemError;
 use solana_sdk::system_transaction;
+use solana_sdk::sysvar::{self, stake_history::StakeHistory};
+use solana_sdk::timing::Epoch;
 use solana_sdk::transaction::{Transaction, TransactionError};
 use solana_stake_api::stake_instruction;
+use solana_stake_api::stake_state::{Stake, StakeState};
 use solana_storage_api::storage_instruction;
 use solana_vote_api::vote_instruction;
 use solana_vote_api::vote_state::VoteState;
@@ -64,6 +67,8 @@ pub enum WalletCommand {
     DeactivateStake(Keypair, Pubkey),
     RedeemVoteCredits(Pubkey, Pubkey),
     ShowStakeAccount(Pubkey),
+    ShowDelegatedStakes(Pubkey),
+    ShowStakeHistory,
     CreateReplicatorStorageAccount(Pubkey, Pubkey),
     CreateValidatorStorageAccount(Pubkey, Pubkey),
     ClaimStorageReward(Pubkey, Pubkey),
@@ -309,6 +314,11 @@ pub fn parse_command(
             let stake_account_pubkey = pubkey_of(matches, "stake_account_pubkey").unwrap();
             Ok(WalletCommand::ShowStakeAccount(stake_account_pubkey))
         }
+        ("show-delegated-stakes", Some(matches)) => {
+            let vote_account_pubkey = pubkey_of(matches, "vote_account_pubkey").unwrap();
+            Ok(WalletCommand::ShowDelegatedStakes(vote_account_pubkey))
+        }
+        ("show-stake-history", Some(_matches)) => Ok(WalletCommand::ShowStakeHistory),
         ("create-replicator-storage-account", Some(matches)) => {
             let account_owner = pubkey_of(matches, "storage_account_owner").unwrap();
             let storage_account_pubkey = pubkey_of(matches, "storage_account_pubkey").unwrap();
@@ -650,9 +660,16 @@ fn process_show_vote_account(
 
     println!("account lamports: {}", vote_account.lamports);
     println!("node id: {}", vote_state.node_pubkey);
+    println!("authorized voters:");
+    for (epoch, authorized_voter_pubkey) in vote_state.authorized_voters() {
+        println!(
+            "- epoch: {}\n  authorized voter: {}",
+            epoch, authorized_voter_pubkey
+        );
+    }
     println!(
-        "authorized voter pubkey: {}",
-        vote_state.authorized_voter_pubkey
+        "authorized withdrawer pubkey: {}",
+        vote_state.authorized_withdrawer_pubkey
     );
     println!("credits: {}", vote_state.credits());
     println!(
@@ -849,7 +866,6 @@ fn process_show_stake_account(
     _config: &WalletConfig,
     stake_account_pubkey: &Pubkey,
 ) -> ProcessResult {
-    use solana_stake_api::stake_state::StakeState;
     let stake_account = rpc_client.get_account(stake_account_pubkey)?;
     if stake_account.owner != solana_stake_api::id() {
         Err(WalletError::RpcRequestError(
@@ -874,6 +890,8 @@ fn process_show_stake_account(
                     stake.deactivation_epoch
                 );
             }
+            let (epoch, stake_history) = get_epoch_and_stake_history(rpc_client)?;
+            println_stake_progress(&stake, epoch, &stake_history);
             Ok("".to_string())
         }
         Ok(StakeState::RewardsPool) => Ok("Stake account is a rewards pool".to_string()),
@@ -885,6 +903,119 @@ fn process_show_stake_account(
     }
 }
 
+fn get_epoch_and_stake_history(
+    rpc_client: &RpcClient,
+) -> Result<(Epoch, StakeHistory), Box<dyn error::Error>> {
+    let clock_account = rpc_client.get_account(&sysvar::clock::id())?;
+    let clock = sysvar::clock::Clock::from(&clock_account).ok_or_else(|| {
+        WalletError::RpcRequestError("Clock sysvar could not be deserialized".to_string())
+    })?;
+    let stake_history_account = rpc_client.get_account(&sysvar::stake_history::id())?;
+    let stake_history = StakeHistory::from(&stake_history_account).ok_or_else(|| {
+        WalletError::RpcRequestError("StakeHistory sysvar could not be deserialized".to_string())
+    })?;
+    Ok((clock.epoch, stake_history))
+}
+
+fn println_stake_progress(stake: &Stake, epoch: Epoch, stake_history: &StakeHistory) {
+    let (effective, activating, deactivating) =
+        stake.stake_activating_and_deactivating(epoch, Some(stake_history));
+    println!("effective stake at epoch {}: {}", epoch, effective);
+    if activating > 0 {
+        println!(
+            "activating stake: {} ({:.2}% warmed up)",
+            activating,
+            100f64 * effective as f64 / stake.stake as f64
+        );
+    }
+    if deactivating > 0 {
+        println!(
+            "deactivating stake: {} ({:.2}% cooled down)",
+            deactivating,
+            100f64 * (stake.stake - deactivating) as f64 / stake.stake as f64
+        );
+    }
+}
+
+fn process_show_delegated_stakes(
+    rpc_client: &RpcClient,
+    _config: &WalletConfig,
+    vote_account_pubkey: &Pubkey,
+) -> ProcessResult {
+    let vote_account = rpc_client.get_account(vote_account_pubkey)?;
+    if vote_account.owner != solana_vote_api::id() {
+        Err(WalletError::RpcRequestError(
+            format!("{:?} is not a vote account", vote_account_pubkey).to_string(),
+        ))?;
+    }
+    let vote_state = VoteState::deserialize(&vote_account.data).map_err(|_| {
+        WalletError::RpcRequestError(
+            "Account data could not be deserialized to vote state".to_string(),
+        )
+    })?;
+
+    let (epoch, stake_history) = get_epoch_and_stake_history(rpc_client)?;
+
+    let stakes: Vec<_> = rpc_client
+        .get_program_accounts(&solana_stake_api::id())?
+        .into_iter()
+        .filter_map(
+            |(stake_pubkey, stake_account)| match stake_account.state() {
+                Ok(StakeState::Stake(stake)) if stake.voter_pubkey == *vote_account_pubkey => {
+                    Some((stake_pubkey, stake_account.lamports, stake))
+                }
+                _ => None,
+            },
+        )
+        .collect();
+
+    let (mut total_effective, mut total_activating, mut total_deactivating) = (0, 0, 0);
+    println!("stake accounts delegated to {}:", vote_account_pubkey);
+    for (stake_pubkey, lamports, stake) in &stakes {
+        let (effective, activating, deactivating) =
+            stake.stake_activating_and_deactivating(epoch, Some(&stake_history));
+        println!(
+            "- stake account: {}\n  lamports: {}\n  delegated stake: {}\n  effective: {}\n  activating: {}\n  deactivating: {}",
+            stake_pubkey, lamports, stake.stake, effective, activating, deactivating,
+        );
+        total_effective += effective;
+        total_activating += activating;
+        total_deactivating += deactivating;
+    }
+    println!(
+        "total effective stake at epoch {}: {}",
+        epoch, total_effective
+    );
+    println!("total activating stake: {}", total_activating);
+    println!("total deactivating stake: {}", total_deactivating);
+
+    println!("epoch credits:");
+    for (epoch, credits, prev_credits) in vote_state.epoch_credits() {
+        println!(
+            "- epoch: {}\n  credits earned: {}\n  total credits: {}",
+            epoch,
+            credits - prev_credits,
+            credits,
+        );
+    }
+    Ok("".to_string())
+}
+
+fn process_show_stake_history(rpc_client: &RpcClient, _config: &WalletConfig) -> ProcessResult {
+    let (epoch, stake_history) = get_epoch_and_stake_history(rpc_client)?;
+    println!("current epoch: {}", epoch);
+
+    let mut entries: Vec<_> = stake_history.iter().collect();
+    entries.sort_by(|(a, _), (b, _)| b.cmp(a));
+    for (epoch, entry) in entries {
+        println!(
+            "- epoch: {}\n  effective: {}\n  activating: {}\n  deactivating: {}",
+            epoch, entry.effective, entry.activating, entry.deactivating,
+        );
+    }
+    Ok("".to_string())
+}
+
 fn process_create_replicator_storage_account(
     rpc_client: &RpcClient,
     config: &WalletConfig,
@@ -1463,6 +1594,12 @@ pub fn process_command(config: &WalletConfig) -> ProcessResult {
             process_show_stake_account(&rpc_client, config, &stake_account_pubkey)
         }
 
+        WalletCommand::ShowDelegatedStakes(vote_account_pubkey) => {
+            process_show_delegated_stakes(&rpc_client, config, &vote_account_pubkey)
+        }
+
+        WalletCommand::ShowStakeHistory => process_show_stake_history(&rpc_client, config),
+
         WalletCommand::CreateReplicatorStorageAccount(
             storage_account_owner,
             storage_account_pubkey,
@@ -1706,7 +1843,7 @@ pub fn app<'ab, 'v>(name: &str, about: &'ab str, version: &'v str) -> App<'ab, '
         )
         .subcommand(
             SubCommand::with_name("authorize-voter")
-                .about("Authorize a new vote signing keypair for the given vote account")
+                .about("Authorize a new vote signing keypair for the given vote account, effective at the next epoch")
                 .arg(
                     Arg::with_name("vote_account_pubkey")
                         .index(1)
@@ -1926,6 +2063,23 @@ pub fn app<'ab, 'v>(name: &str, about: &'ab str, version: &'v str) -> App<'ab, '
                         .help("Stake account pubkey"),
                 )
         )
+        .subcommand(
+            SubCommand::with_name("show-delegated-stakes")
+                .about("Show the stake accounts delegated to a vote account and their activation progress")
+                .arg(
+                    Arg::with_name("vote_account_pubkey")
+                        .index(1)
+                        .value_name("VOTE ACCOUNT PUBKEY")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_pubkey_or_keypair)
+                        .help("Vote account pubkey"),
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("show-stake-history")
+                .about("Show the cluster's stake activation and deactivation history"),
+        )
         .subcommand(
             SubCommand::with_name("create-storage-mining-pool-account")
                 .about("Create mining pool account")
@@ -2401,6 +2555,26 @@ mod tests {
             WalletCommand::DeactivateStake(keypair, pubkey)
         );
 
+        // Test ShowDelegatedStakes Subcommand
+        let test_show_delegated_stakes = test_commands.clone().get_matches_from(vec![
+            "test",
+            "show-delegated-stakes",
+            &pubkey_string,
+        ]);
+        assert_eq!(
+            parse_command(&pubkey, &test_show_delegated_stakes).unwrap(),
+            WalletCommand::ShowDelegatedStakes(pubkey)
+        );
+
+        // Test ShowStakeHistory Subcommand
+        let test_show_stake_history = test_commands
+            .clone()
+            .get_matches_from(vec!["test", "show-stake-history"]);
+        assert_eq!(
+            parse_command(&pubkey, &test_show_stake_history).unwrap(),
+            WalletCommand::ShowStakeHistory
+        );
+
         // Test Deploy Subcommand
         let test_deploy =
             test_commands
@@ -2694,6 +2868,12 @@ mod tests {
         config.command = WalletCommand::AuthorizeVoter(bob_pubkey, Keypair::new(), bob_pubkey);
         assert!(process_command(&config).is_err());
 
+        config.command = WalletCommand::ShowDelegatedStakes(bob_pubkey);
+        assert!(process_command(&config).is_err());
+
+        config.command = WalletCommand::ShowStakeHistory;
+        assert!(process_command(&config).is_err());
+
         config.command = WalletCommand::GetSlot;
         assert!(process_command(&config).is_err());
 